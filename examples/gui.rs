@@ -0,0 +1,77 @@
+/**
+ * The smallest graphical client: a Painter that draws sprites onto a grid of characters, as
+ * a toolkit would onto its canvas, and clicks turned back into board positions. An egui or
+ * other toolkit client implements Painter with its own shapes in the same way.
+ *
+ *   cargo run --features gui --example gui
+ */
+use ironclad::board::{Board, Terrain};
+use ironclad::game::PlayerId;
+use ironclad::gui::{paint, Geometry, Painter, Piece, Point, Rect, Sprite};
+use ironclad::overlay::Overlay;
+use ironclad::vec::Vec2;
+
+// pixels in each character of the canvas
+const CELL: f32 = 4.0;
+
+/* Canvas is a character grid standing in for a toolkit's drawing surface. */
+struct Canvas {
+    columns: usize,
+    rows: Vec<Vec<char>>,
+}
+
+impl Canvas {
+    fn new(columns: usize, rows: usize) -> Canvas {
+        Canvas { columns, rows: vec![vec![' '; columns]; rows] }
+    }
+
+    /* Helper function filling the characters covering @rect with @glyph. */
+    fn fill(&mut self, rect: Rect, glyph: char) {
+        let (left, right) = ((rect.min.x / CELL) as usize, ((rect.max.x / CELL) as usize).min(self.columns));
+        let (top, bottom) = ((rect.min.y / CELL) as usize, ((rect.max.y / CELL) as usize).min(self.rows.len()));
+        for row in self.rows[top..bottom].iter_mut() {
+            row[left..right].fill(glyph);
+        }
+    }
+}
+
+impl Painter for Canvas {
+    fn paint_sprite(&mut self, sprite: &Sprite) {
+        let glyph = match sprite.piece {
+            Piece::Square(Terrain::Plain) if (sprite.at.x + sprite.at.y) % 2 == 0 => '.',
+            Piece::Square(Terrain::Plain) => ' ',
+            Piece::Square(Terrain::Mud) => '~',
+            Piece::Square(Terrain::Hill) => '^',
+            Piece::Checker { owner: PlayerId::A, height } => char::from_digit(height as u32, 10).unwrap_or('A'),
+            Piece::Checker { owner: PlayerId::B, .. } => 'b',
+            Piece::Stone { owner: PlayerId::A } => 'o',
+            Piece::Stone { owner: PlayerId::B } => 'x',
+        };
+        // Checkers are drawn inset, so the square's colour shows around them
+        let rect = match sprite.piece {
+            Piece::Checker { .. } => Rect {
+                min: Point::new(sprite.rect.min.x + CELL, sprite.rect.min.y + CELL),
+                max: Point::new(sprite.rect.max.x - CELL, sprite.rect.max.y - CELL),
+            },
+            _ => sprite.rect,
+        };
+        self.fill(rect, glyph);
+    }
+}
+
+fn main() {
+    let board = Board::new();
+    let (columns, rows) = (board.width() * 5, board.height() * 3);
+    let area = Rect { min: Point::new(0.0, 0.0), max: Point::new(columns as f32 * CELL, rows as f32 * CELL) };
+    let geometry = Geometry::fit(&board, area);
+    let mut canvas = Canvas::new(columns, rows);
+    paint(&board, Some(&Overlay::new(&board)), &geometry, &mut canvas);
+    for row in canvas.rows.iter() {
+        println!("{}", row.iter().collect::<String>());
+    }
+
+    for (x, y) in [(geometry.square * 1.5, geometry.square * 0.5), (geometry.square, geometry.square), (-geometry.square, 0.0)] {
+        println!("click at {},{} lands on {:?}", x, y, geometry.hit_test(Point::new(geometry.origin.x + x, geometry.origin.y + y)));
+    }
+    println!("corner 1,1 is drawn at {:?}", geometry.corner_point(Vec2::new(1, 1)));
+}
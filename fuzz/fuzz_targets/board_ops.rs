@@ -0,0 +1,52 @@
+/**
+ * Drives the board directly from fuzzer input, with no rules of play in between. Every
+ * four bytes are one operation and its coordinates, which may fall off the board. Bad
+ * operations must come back as errors, never panics, and leave the board consistent.
+ */
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use ironclad::board::{Board, Direction, Terrain};
+use ironclad::game::{Checker, CheckerHeight, PlayerId, Stone};
+use ironclad::vec::Vec2;
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+fuzz_target!(|data: &[u8]| {
+    let mut board = Board::from_seed([0; 32]);
+    // a margin of one square around the board, so the bounds checks are exercised too
+    let (columns, rows) = (board.width() as i32 + 3, board.height() as i32 + 3);
+    for op in data.chunks_exact(4) {
+        let pos = Vec2::new(op[1] as i32 % columns - 1, op[2] as i32 % rows - 1);
+        let player = if op[3] % 2 == 0 { PlayerId::A } else { PlayerId::B };
+        match op[0] % 7 {
+            0 => {
+                let to = Vec2::new(pos.x + (op[3] % 3) as i32 - 1, pos.y + (op[3] / 3 % 3) as i32 - 1);
+                let _ = board.move_checker(pos, to);
+            },
+            1 => {
+                let _ = board.fire_checker_at(pos);
+            },
+            2 => {
+                let _ = board.place_stone_at(pos, Stone::new(player));
+            },
+            3 => {
+                let _ = board.slide_stone(pos, DIRECTIONS[op[3] as usize % 4]);
+            },
+            4 => {
+                let height = CheckerHeight::new(op[3] as usize % CheckerHeight::MAX.get() + 1).unwrap();
+                let _ = board.place_checker_at(pos, Checker::new(height, player));
+            },
+            5 => {
+                let terrain = [Terrain::Plain, Terrain::Mud, Terrain::Hill][op[3] as usize % 3];
+                let _ = board.set_terrain(pos, terrain);
+            },
+            _ => {
+                let _ = board.stone_chain_winner();
+                let _ = board.can_fire_checker_at(pos);
+            },
+        }
+        board.check_invariants().unwrap();
+    }
+});
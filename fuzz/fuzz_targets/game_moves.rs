@@ -0,0 +1,55 @@
+/**
+ * Plays games from fuzzer input. Each byte either picks one of the legal moves or makes up
+ * an arbitrary move, which must be refused exactly when it is not on offer. The board is
+ * checked after every move, and nothing may panic.
+ */
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use ironclad::game::{Decide, Game, Intent, MoveRequest, Player, PlayerId, STARTING_STONES};
+use ironclad::test_support::random_intent;
+
+/* Decider for a game that is driven by the fuzzer and never asked for a move. */
+struct Driven;
+
+impl Decide for Driven {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        *request.all_moves().next().expect("no moves offered")
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 8 {
+        return;
+    }
+    let (seed, choices) = data.split_at(8);
+    let mut rng = StdRng::seed_from_u64(u64::from_le_bytes(seed.try_into().unwrap()));
+    let (driven_a, driven_b) = (Driven, Driven);
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &driven_a);
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &driven_b);
+    let mut game = Game::new(&mut player_a, &mut player_b);
+    game.set_silent(true);
+    game.board.reseed([seed[0]; 32]);
+
+    for choice in choices {
+        if game.check_for_outcome().is_some() {
+            break;
+        }
+        let player = game.side_to_move();
+        let offered: Vec<Intent> = game.move_request(player).all_moves().copied().collect();
+        if choice % 2 == 0 {
+            if offered.is_empty() {
+                break;
+            }
+            let intent = offered[(choice / 2) as usize % offered.len()];
+            game.try_apply_move(player, intent).expect("offered move was refused");
+        } else {
+            let intent = random_intent(&mut rng, &game.board);
+            assert_eq!(game.try_apply_move(player, intent).is_ok(), offered.contains(&intent));
+        }
+        game.board.check_invariants().unwrap();
+    }
+});
@@ -0,0 +1,254 @@
+/**
+ * Admin controls for long-running servers: list the active games, abort one, adjudicate
+ * its result, or kick a player. Commands arrive one per line over an admin connection or
+ * a local control socket, and act on games through the Game API.
+ *
+ *   list
+ *   abort <game id>
+ *   adjudicate <game id> a|b|draw
+ *   kick <player name>
+ *
+ * Commands must only be read from connections the server trusts with them.
+ */
+use crate::game::{Game, PlayerId};
+use crate::scenario::{arg, number, player};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum AdminError {
+    // The line is not a command
+    #[error("{0}")]
+    Parse(String),
+    // No active game has the id
+    #[error("No active game with id {0}")]
+    NoSuchGame(u64),
+    // The game is already over or aborted
+    #[error("Game {0} is already over")]
+    GameOver(u64),
+    // Nobody of that name is connected
+    #[error("{0} is not connected")]
+    NotConnected(String),
+}
+
+/**
+ * AdminCommand is one privileged command.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdminCommand {
+    // Describe every active game
+    List,
+    // Stop a game without a result
+    Abort(u64),
+    // End a game with a win for a player, or a draw for None
+    Adjudicate(u64, Option<PlayerId>),
+    // Disconnect a player from the server
+    Kick(String),
+}
+
+impl AdminCommand {
+    /**
+     * parse
+     * Read a command line.
+     * line - Line received, with or without its line ending.
+     * ret - The command, or AdminError::Parse saying what is wrong with the line.
+     */
+    pub fn parse(line: &str) -> Result<AdminCommand, AdminError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let args = words.get(1..).unwrap_or_default();
+        let command = match words.first().copied() {
+            Some("list") => AdminCommand::List,
+            Some("abort") => AdminCommand::Abort(number(arg(args, 0)?)?),
+            Some("adjudicate") => {
+                let winner = match arg(args, 1)? {
+                    "draw" => None,
+                    side => Some(player(side)?),
+                };
+                AdminCommand::Adjudicate(number(arg(args, 0)?)?, winner)
+            },
+            Some("kick") if !args.is_empty() => AdminCommand::Kick(args.join(" ")),
+            Some("kick") => return Err(AdminError::Parse(String::from("missing value"))),
+            Some(other) => return Err(AdminError::Parse(format!("unknown command '{}'", other))),
+            None => return Err(AdminError::Parse(String::from("empty command"))),
+        };
+        Ok(command)
+    }
+}
+
+impl From<String> for AdminError {
+    fn from(message: String) -> AdminError {
+        AdminError::Parse(message)
+    }
+}
+
+/**
+ * ActiveGame describes one game being played on the server.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveGame {
+    pub id: u64,
+    // names of the players of side A and side B, in that order
+    pub players: [String; 2],
+    // moves played so far
+    pub moves: usize,
+    pub to_move: PlayerId,
+}
+
+/**
+ * Administered is what a server offers its admin controls.
+ */
+pub trait Administered {
+    /**
+     * active_games
+     * ret - Every game being played, by id.
+     */
+    fn active_games(&self) -> Vec<ActiveGame>;
+
+    /**
+     * with_game
+     * Run an action on an active game.
+     * id - Id of the game.
+     * action - What to do with it.
+     * ret - False if no active game has the id.
+     */
+    fn with_game(&mut self, id: u64, action: &mut dyn FnMut(&mut Game)) -> bool;
+
+    /**
+     * kick
+     * Disconnect a player, leaving their games to be aborted or adjudicated.
+     * name - Name of the player's profile.
+     * ret - False if nobody of that name is connected.
+     */
+    fn kick(&mut self, name: &str) -> bool;
+}
+
+/**
+ * execute
+ * Carry out a command on a server.
+ * command - What to do.
+ * server - Server to do it on.
+ * ret - Lines to send back to the admin, or an AdminError saying why nothing was done.
+ */
+pub fn execute(command: &AdminCommand, server: &mut dyn Administered) -> Result<Vec<String>, AdminError> {
+    match command {
+        AdminCommand::List => {
+            let games = server.active_games();
+            let mut lines = vec![format!("{} active games", games.len())];
+            lines.extend(games.iter().map(|game| format!(
+                "{}: {} (A) against {} (B), {} moves, Player {} to move",
+                game.id, game.players[0], game.players[1], game.moves, game.to_move,
+            )));
+            Ok(lines)
+        },
+        AdminCommand::Abort(id) => {
+            in_game(server, *id, &mut |game| game.abort())?;
+            Ok(vec![format!("Game {} aborted", id)])
+        },
+        AdminCommand::Adjudicate(id, winner) => {
+            let mut result = String::new();
+            in_game(server, *id, &mut |game| result = game.adjudicate(*winner).to_string())?;
+            Ok(vec![format!("Game {}: {}", id, result)])
+        },
+        AdminCommand::Kick(name) => {
+            if !server.kick(name) {
+                return Err(AdminError::NotConnected(name.clone()));
+            }
+            Ok(vec![format!("{} was kicked", name)])
+        },
+    }
+}
+
+/*
+ * Helper function running @action on game @id, unless it is already over or aborted.
+ */
+fn in_game(server: &mut dyn Administered, id: u64, action: &mut dyn FnMut(&mut Game)) -> Result<(), AdminError> {
+    let mut over = false;
+    let found = server.with_game(id, &mut |game| {
+        over = game.aborted() || game.check_for_outcome().is_some();
+        if !over {
+            action(game);
+        }
+    });
+    match (found, over) {
+        (false, _) => Err(AdminError::NoSuchGame(id)),
+        (true, true) => Err(AdminError::GameOver(id)),
+        (true, false) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Decide, GameError, Intent, MoveRequest, Player, STARTING_STONES};
+
+    struct FirstOffered;
+
+    impl Decide for FirstOffered {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            *request.all_moves().next().unwrap()
+        }
+    }
+
+    /* Server with one game, between Ada and Grace Hopper. */
+    struct OneGame<'a> {
+        game: Game<'a>,
+        connected: Vec<String>,
+    }
+
+    impl Administered for OneGame<'_> {
+        fn active_games(&self) -> Vec<ActiveGame> {
+            vec![ActiveGame {
+                id: 7,
+                players: [String::from("Ada"), String::from("Grace Hopper")],
+                moves: self.game.history().len(),
+                to_move: self.game.side_to_move(),
+            }]
+        }
+
+        fn with_game(&mut self, id: u64, action: &mut dyn FnMut(&mut Game)) -> bool {
+            if id == 7 {
+                action(&mut self.game);
+            }
+            id == 7
+        }
+
+        fn kick(&mut self, name: &str) -> bool {
+            let before = self.connected.len();
+            self.connected.retain(|connected| connected != name);
+            self.connected.len() < before
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(AdminCommand::parse("list\n"), Ok(AdminCommand::List));
+        assert_eq!(AdminCommand::parse("adjudicate 7 draw"), Ok(AdminCommand::Adjudicate(7, None)));
+        assert_eq!(AdminCommand::parse("adjudicate 7 b"), Ok(AdminCommand::Adjudicate(7, Some(PlayerId::B))));
+        assert_eq!(AdminCommand::parse("kick Grace Hopper"), Ok(AdminCommand::Kick(String::from("Grace Hopper"))));
+        assert!(matches!(AdminCommand::parse("abort seven"), Err(AdminError::Parse(_))));
+        assert!(matches!(AdminCommand::parse("adjudicate 7 c"), Err(AdminError::Parse(_))));
+        assert!(matches!(AdminCommand::parse("kick"), Err(AdminError::Parse(_))));
+        assert!(matches!(AdminCommand::parse("shutdown"), Err(AdminError::Parse(_))));
+    }
+
+    #[test]
+    fn execute() {
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut server = OneGame { game: Game::new(&mut player_a, &mut player_b), connected: vec![String::from("Ada")] };
+        server.game.set_silent(true);
+        server.game.play_turn().unwrap();
+
+        let listed = super::execute(&AdminCommand::List, &mut server).unwrap();
+        assert_eq!(listed, ["1 active games", "7: Ada (A) against Grace Hopper (B), 1 moves, Player B to move"]);
+        assert_eq!(super::execute(&AdminCommand::Abort(8), &mut server), Err(AdminError::NoSuchGame(8)));
+        assert_eq!(super::execute(&AdminCommand::Kick(String::from("Alan")), &mut server), Err(AdminError::NotConnected(String::from("Alan"))));
+        super::execute(&AdminCommand::Kick(String::from("Ada")), &mut server).unwrap();
+
+        let ruled = super::execute(&AdminCommand::Adjudicate(7, Some(PlayerId::A)), &mut server).unwrap();
+        assert_eq!(ruled, ["Game 7: Player A wins by decision of the arbiter"]);
+        assert_eq!(super::execute(&AdminCommand::Abort(7), &mut server), Err(AdminError::GameOver(7)));
+
+        server.game.reset();
+        super::execute(&AdminCommand::Abort(7), &mut server).unwrap();
+        assert_eq!(server.game.play_turn(), Err(GameError::Aborted));
+    }
+}
@@ -0,0 +1,233 @@
+/**
+ * Analysis of a recorded game: every position of the main line is searched one move deep
+ * on material, and the results can be written back into the record as evaluations and
+ * variations, so the analysis can be saved and opened again in a review.
+ */
+use std::cmp::Reverse;
+use std::path::Path;
+
+use crate::game::{ApplyError, Decide, Game, GameView, Intent, MoveRequest, PlayerId};
+use crate::record::{notation, GameRecord, RecordError, RecordedMove};
+use crate::review::ReviewError;
+use crate::scoring;
+use crate::verify::with_replay;
+
+// number of dice seeds an attack is tried with, to score it on its average outcome
+const FIRE_SAMPLES: u8 = 8;
+
+/**
+ * MoveScore is a move with the material score of the position it leads to, from the
+ * point of view of the player making it.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MoveScore {
+    pub player: PlayerId,
+    pub intent: Intent,
+    pub score: i32,
+}
+
+/**
+ * PlyAnalysis is what analysis found for one move of the main line.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlyAnalysis {
+    // the move that was played
+    pub played: MoveScore,
+    // the best other moves, best first
+    pub alternatives: Vec<MoveScore>,
+}
+
+/**
+ * Analysis holds the results for every move of a record's main line, in order.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Analysis {
+    pub plies: Vec<PlyAnalysis>,
+}
+
+/* Decider for the replayed game, which is driven by the record and never asked for a move. */
+pub(crate) struct Replayed;
+
+impl Decide for Replayed {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        *request.all_moves().next().expect("no moves offered")
+    }
+}
+
+/**
+ * analyse
+ * Score every legal move at each position of the main line of a record.
+ * record - Game to analyse. Its variations are ignored.
+ * top - Number of alternatives to keep for each move.
+ * ret - The analysis, or ReviewError::Replay if a recorded move is not legal.
+ */
+pub fn analyse(record: &GameRecord, top: usize) -> Result<Analysis, ReviewError> {
+    with_replay(record, |game| analyse_game(record, game, top))
+}
+
+/* Helper function analysing the main line of @record by replaying it in @game. */
+fn analyse_game(record: &GameRecord, game: &mut Game, top: usize) -> Result<Analysis, ReviewError> {
+    let mut analysis = Analysis::default();
+    for (idx, recorded) in record.moves.iter().enumerate() {
+        let replay_error = |source| ReviewError::Replay { ply: idx + 1, notation: notation(recorded.intent), source };
+        // moves the board refuses are left out rather than scored
+        let mut scores: Vec<MoveScore> = game.move_request(recorded.player)
+            .all_moves()
+            .filter(|intent| **intent != recorded.intent)
+            .filter_map(|intent| Some(MoveScore { player: recorded.player, intent: *intent, score: score_move(game, recorded.player, *intent).ok()? }))
+            .collect();
+        scores.sort_by_key(|scored| Reverse(scored.score));
+        scores.truncate(top);
+        let score = score_move(game, recorded.player, recorded.intent).map_err(replay_error)?;
+        let played = MoveScore { player: recorded.player, intent: recorded.intent, score };
+        analysis.plies.push(PlyAnalysis { played, alternatives: scores });
+
+        game.try_apply_move(recorded.player, recorded.intent).map_err(replay_error)?;
+    }
+    Ok(analysis)
+}
+
+/**
+ * annotate
+ * Write an analysis into a copy of the record it was made from. Each main line move gets
+ * its evaluation, each alternative becomes a one-move variation with its own evaluation,
+ * and moves that were not the best are given a comment naming the best one. Comments and
+ * variations already in the record are kept.
+ * record - Record the analysis was made from.
+ * analysis - Results to write.
+ * ret - The annotated record.
+ */
+pub fn annotate(record: &GameRecord, analysis: &Analysis) -> GameRecord {
+    let mut annotated = record.clone();
+    for (recorded, ply) in annotated.moves.iter_mut().zip(analysis.plies.iter()) {
+        recorded.evaluation = Some(ply.played.score);
+        if let Some(best) = ply.alternatives.first().filter(|best| best.score > ply.played.score) {
+            let note = format!("best was {} eval {}", notation(best.intent), best.score);
+            recorded.comment = Some(match recorded.comment.take() {
+                Some(comment) if comment.contains(&note) => comment,
+                Some(comment) => format!("{} / {}", comment, note),
+                None => note,
+            });
+        }
+        for alternative in ply.alternatives.iter() {
+            let existing = recorded.variations.iter_mut()
+                .find(|variation| variation[0].player == alternative.player && variation[0].intent == alternative.intent);
+            match existing {
+                Some(variation) => variation[0].evaluation = Some(alternative.score),
+                None => {
+                    let mut first = RecordedMove::new(alternative.player, alternative.intent);
+                    first.evaluation = Some(alternative.score);
+                    recorded.variations.push(vec![first]);
+                },
+            }
+        }
+    }
+    annotated
+}
+
+/**
+ * export
+ * Write an annotated copy of a record to a file, ready to be opened in a review.
+ * record - Record the analysis was made from.
+ * analysis - Results to write.
+ * path - File to write.
+ * ret - Ok, or RecordError::Io if the file could not be written.
+ */
+pub fn export<P: AsRef<Path>>(record: &GameRecord, analysis: &Analysis, path: P) -> Result<(), RecordError> {
+    annotate(record, analysis).save(path)
+}
+
+/*
+ * Helper function scoring the position after @player makes @intent, averaging attacks over
+ * several fixed dice seeds rather than the dice the game will roll, so every move is scored
+ * alike. Returns the board's error if it refuses the move.
+ */
+fn score_move(game: &Game, player: PlayerId, intent: Intent) -> Result<i32, ApplyError> {
+    let view = game.view();
+    let mut stones = view.stones;
+    if matches!(intent, Intent::PlaceStone(_)) {
+        stones[player.index()] -= 1;
+    }
+    let samples = if matches!(intent, Intent::FireChecker(_)) { FIRE_SAMPLES } else { 1 };
+    let mut total = 0;
+    for seed in 0..samples {
+        let mut board = game.board.clone();
+        board.script_dice(&[]);
+        board.reseed([seed; 32]);
+        board.apply(player, intent)?;
+        total += scoring::score(&GameView { board: &board, stones, captures: view.captures, history: view.history, dice: view.dice, clocks: view.clocks }, player);
+    }
+    Ok(total / samples as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fought_record;
+    use crate::vec::Vec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn analyse() {
+        let record = GameRecord::parse("a move 6,2 5,2\nb place 4,0 ; quiet").unwrap();
+        let analysis = super::analyse(&record, 3).unwrap();
+        assert_eq!(analysis.plies.len(), 2);
+        let first = &analysis.plies[0];
+        assert_eq!(first.played.intent, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2)));
+        assert_eq!(first.alternatives.len(), 3);
+        assert!(first.alternatives.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(first.alternatives.iter().all(|scored| scored.intent != first.played.intent));
+        // Placing a stone moves one from the pool to the board, worth one point more
+        assert_eq!(analysis.plies[1].played.score, scoring::BOARD_STONE_VALUE - scoring::POOL_STONE_VALUE);
+
+        let record = GameRecord::parse("a move 6,2 5,2\na place 9,9").unwrap();
+        assert!(matches!(super::analyse(&record, 1), Err(ReviewError::Replay { ply: 2, .. })));
+    }
+
+    #[test]
+    fn fought() {
+        let record = fought_record(&mut StdRng::seed_from_u64(7), 12);
+        let analysis = super::analyse(&record, 2).unwrap();
+        // The last attack is scored on the fixed seeds, from the position the game reached
+        let fire = record.moves.iter().rposition(|recorded| matches!(recorded.intent, Intent::FireChecker(_))).unwrap();
+        let (before, fired) = (&record.moves[..fire], &record.moves[fire]);
+        let score = with_replay(&record, |game| {
+            for recorded in before {
+                game.try_apply_move(recorded.player, recorded.intent).unwrap();
+            }
+            score_move(game, fired.player, fired.intent).unwrap()
+        });
+        assert_eq!(analysis.plies[fire].played, MoveScore { player: fired.player, intent: fired.intent, score });
+        assert_eq!(super::analyse(&record, 2).unwrap(), analysis);
+    }
+
+    #[test]
+    fn annotate() {
+        let record = GameRecord::parse("a move 6,2 5,2\nb place 4,0 ; quiet").unwrap();
+        let better = MoveScore { player: PlayerId::B, intent: Intent::PlaceStone(Vec2::new(3, 0)), score: 5 };
+        let worse = MoveScore { player: PlayerId::B, intent: Intent::PlaceStone(Vec2::new(2, 0)), score: -1 };
+        let analysis = Analysis { plies: vec![
+            PlyAnalysis { played: MoveScore { player: PlayerId::A, intent: record.moves[0].intent, score: 0 }, alternatives: vec![] },
+            PlyAnalysis { played: MoveScore { player: PlayerId::B, intent: record.moves[1].intent, score: 1 }, alternatives: vec![better, worse] },
+        ] };
+        let annotated = super::annotate(&record, &analysis);
+        assert_eq!(annotated.moves[0].evaluation, Some(0));
+        assert_eq!(annotated.moves[0].comment, None);
+        let second = &annotated.moves[1];
+        assert_eq!(second.evaluation, Some(1));
+        assert_eq!(second.comment.as_deref(), Some("quiet / best was place 3,0 eval 5"));
+        assert_eq!(second.variations.len(), 2);
+        assert_eq!((second.variations[0][0].intent, second.variations[0][0].evaluation), (better.intent, Some(5)));
+
+        // Annotating again updates the variations rather than adding more
+        assert_eq!(super::annotate(&annotated, &analysis), annotated);
+
+        // The export opens in a review
+        let path = std::env::temp_dir().join("ironclad_analysis_export.txt");
+        export(&record, &analysis, &path).unwrap();
+        let review = crate::review::Review::load(&path).unwrap();
+        assert_eq!(review.record(), &annotated);
+        std::fs::remove_file(path).unwrap();
+    }
+}
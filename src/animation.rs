@@ -0,0 +1,146 @@
+/**
+ * Export of a recorded game as a sequence of SVG frames, the start position and then the
+ * board after each move of the main line with that move highlighted, for putting together
+ * into a GIF or video of the game with other tools.
+ */
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::board::Board;
+use crate::game::Intent;
+use crate::record::{notation, GameRecord};
+use crate::render::svg::{self, Highlight};
+use crate::review::{start_board, ReviewError};
+
+// fewest digits in a frame's number, so frames sort by name up to this many
+const FRAME_DIGITS: usize = 3;
+
+#[derive(Debug, Error)]
+pub enum AnimationError {
+    #[error(transparent)]
+    Review(#[from] ReviewError),
+    // A frame could not be written
+    #[error("Could not write frame: {0}")]
+    Io(#[from] io::Error),
+}
+
+/**
+ * highlight
+ * Find the squares and corners a move touched: the squares a checker moved between, the
+ * square attacked, or the corner a stone was placed on, or slid from and to.
+ * before - Board before the move.
+ * intent - Move made.
+ * ret - What to highlight, empty for moves that touch no square, such as swapping sides.
+ */
+pub fn highlight(before: &Board, intent: Intent) -> Highlight {
+    match intent {
+        Intent::MoveChecker(from, to) => Highlight { squares: vec![from, to], corners: Vec::new() },
+        Intent::FireChecker(at) | Intent::PlaceChecker(at, _) => Highlight { squares: vec![at], corners: Vec::new() },
+        Intent::PlaceStone(at) => Highlight { squares: Vec::new(), corners: vec![at] },
+        Intent::SlideStone(from, direction) => {
+            let mut corners = vec![from];
+            corners.extend(before.slide_stone_result(from, direction).ok());
+            Highlight { squares: Vec::new(), corners }
+        },
+        Intent::SwapSides => Highlight::default(),
+    }
+}
+
+/**
+ * frames
+ * Draw the main line of a record, one frame a ply, rolling the dice the record kept so
+ * every attack goes as it did in the game.
+ * record - Game to draw.
+ * ret - The start position, then the board after each move with it highlighted, or
+ *       ReviewError::Replay if a move could not be replayed.
+ */
+pub fn frames(record: &GameRecord) -> Result<Vec<String>, ReviewError> {
+    let mut board = start_board(record);
+    let mut frames = vec![svg::render(&board, &Highlight::default())];
+    for (idx, recorded) in record.moves.iter().enumerate() {
+        let marked = highlight(&board, recorded.intent);
+        board.apply(recorded.player, recorded.intent).map_err(|source| ReviewError::Replay {
+            ply: idx + 1,
+            notation: notation(recorded.intent),
+            source,
+        })?;
+        frames.push(svg::render(&board, &marked));
+    }
+    Ok(frames)
+}
+
+/**
+ * export
+ * Write the frames of a record to a directory, as frame-000.svg, frame-001.svg and so on,
+ * creating the directory if it is missing.
+ * record - Game to draw.
+ * dir - Directory to write to.
+ * ret - Number of frames written, or an AnimationError.
+ */
+pub fn export<P: AsRef<Path>>(record: &GameRecord, dir: P) -> Result<usize, AnimationError> {
+    let frames = frames(record)?;
+    let digits = FRAME_DIGITS.max(frames.len().to_string().len());
+    fs::create_dir_all(&dir)?;
+    for (idx, frame) in frames.iter().enumerate() {
+        fs::write(dir.as_ref().join(format!("frame-{:0width$}.svg", idx, width = digits)), frame)?;
+    }
+    Ok(frames.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    use crate::board::Direction;
+    use crate::config::Variant;
+    use crate::game::{PlayerId, Stone};
+    use crate::record::RecordedMove;
+    use crate::test_support::fought_record;
+    use crate::vec::Vec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn highlight() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::B)).unwrap();
+        let slide = super::highlight(&board, Intent::SlideStone(Vec2::new(4, 0), Direction::Down));
+        let to = board.slide_stone_result(Vec2::new(4, 0), Direction::Down).unwrap();
+        assert_eq!(slide.corners, [Vec2::new(4, 0), to]);
+        assert_eq!(super::highlight(&board, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2))).squares.len(), 2);
+        assert_eq!(super::highlight(&board, Intent::SwapSides), Highlight::default());
+    }
+
+    #[test]
+    fn fought() {
+        let record = fought_record(&mut StdRng::seed_from_u64(7), 12);
+        // Every move of the game is drawn, attacks and all, each frame showing a change
+        let frames = super::frames(&record).unwrap();
+        assert_eq!(frames.len(), record.moves.len() + 1);
+        assert_eq!(frames[0], svg::render(&start_board(&record), &Highlight::default()));
+        assert!(frames.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn export() {
+        let mut record = GameRecord::new(Variant::Standard);
+        record.moves.push(RecordedMove::new(PlayerId::A, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2))));
+        record.moves.push(RecordedMove::new(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 0))));
+
+        let frames = super::frames(&record).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_ne!(frames[0], frames[1]);
+
+        let dir = env::temp_dir().join(format!("ironclad-frames-{}", std::process::id()));
+        assert_eq!(super::export(&record, &dir).unwrap(), 3);
+        let mut names: Vec<String> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+        names.sort();
+        assert_eq!(names, ["frame-000.svg", "frame-001.svg", "frame-002.svg"]);
+        assert_eq!(fs::read_to_string(dir.join("frame-002.svg")).unwrap(), frames[2]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+/**
+ * Token authentication for network players: clients present a token when they connect,
+ * tokens map to player profiles, and only the two profiles seated in a game may submit
+ * moves for it, each for their own side.
+ *
+ * Token files hold one token per line, followed by the name of its profile, # starts a
+ * comment line:
+ *
+ *   <token> <profile name>
+ */
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::PlayerId;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    // The token file could not be read
+    #[error("Could not read tokens: {0}")]
+    Io(#[from] io::Error),
+    // A line could not be understood, with its line number counting from 1
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    // The token is not known
+    #[error("Unknown token")]
+    UnknownToken,
+    // Someone else already sits on the side
+    #[error("Player {0} is already taken")]
+    SeatTaken(PlayerId),
+    // The profile is not one of the game's two participants
+    #[error("{0} is not playing this game")]
+    NotParticipant(String),
+    // The profile plays the game, but not the side the move is for
+    #[error("{name} does not play side {player}")]
+    WrongSide { name: String, player: PlayerId },
+}
+
+/**
+ * Profile is who a token belongs to.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+}
+
+/**
+ * Tokens maps each token a client may present to its profile.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tokens {
+    profiles: HashMap<String, Profile>,
+}
+
+impl Tokens {
+    /**
+     * load
+     * Read and parse a token file.
+     * path - File to read.
+     * ret - The tokens, or an AuthError if the file could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Tokens, AuthError> {
+        Tokens::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * parse
+     * Parse the text of a token file.
+     * text - Contents of the file.
+     * ret - The tokens, or AuthError::Parse describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<Tokens, AuthError> {
+        let mut tokens = Tokens::default();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: &str| AuthError::Parse { line: idx + 1, message: String::from(message) };
+            let Some((token, name)) = line.split_once(char::is_whitespace) else {
+                return Err(err("missing profile name"));
+            };
+            if tokens.profiles.contains_key(token) {
+                return Err(err("token is listed twice"));
+            }
+            tokens.insert(token, name.trim());
+        }
+        Ok(tokens)
+    }
+
+    /**
+     * insert
+     * Let a token sign in as a profile, replacing whoever it signed in as before.
+     * token - Token the client will present.
+     * name - Name of the profile.
+     */
+    pub fn insert(&mut self, token: &str, name: &str) {
+        self.profiles.insert(String::from(token), Profile { name: String::from(name) });
+    }
+
+    /**
+     * authenticate
+     * Find who a client is from the token they presented on connecting.
+     * token - Token presented.
+     * ret - Their profile, or AuthError::UnknownToken.
+     */
+    pub fn authenticate(&self, token: &str) -> Result<&Profile, AuthError> {
+        self.profiles.get(token).ok_or(AuthError::UnknownToken)
+    }
+}
+
+/**
+ * Seats records which profile plays each side of one game.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Seats {
+    // names of the profiles playing side A and side B, in that order
+    names: [Option<String>; 2],
+}
+
+impl Seats {
+    /**
+     * sit
+     * Seat a profile on a side. A profile may take both sides, to play itself.
+     * profile - Authenticated profile.
+     * player - Side to play.
+     * ret - Ok, or AuthError::SeatTaken if another profile sits there.
+     */
+    pub fn sit(&mut self, profile: &Profile, player: PlayerId) -> Result<(), AuthError> {
+        let seat = &mut self.names[player.index()];
+        match seat {
+            Some(name) if *name != profile.name => Err(AuthError::SeatTaken(player)),
+            _ => {
+                *seat = Some(profile.name.clone());
+                Ok(())
+            },
+        }
+    }
+
+    /**
+     * player
+     * player - Side to look up.
+     * ret - Name of the profile playing the side, or None if it is free.
+     */
+    pub fn player(&self, player: PlayerId) -> Option<&str> {
+        self.names[player.index()].as_deref()
+    }
+
+    /**
+     * check_move
+     * Check a profile may submit a move for a side, before the move is applied.
+     * profile - Authenticated profile submitting the move.
+     * player - Side the move is for.
+     * ret - Ok, AuthError::NotParticipant if the profile does not play the game, or
+     * AuthError::WrongSide if it plays the other side.
+     */
+    pub fn check_move(&self, profile: &Profile, player: PlayerId) -> Result<(), AuthError> {
+        if self.player(player) == Some(profile.name.as_str()) {
+            Ok(())
+        } else if self.player(player.other()) == Some(profile.name.as_str()) {
+            Err(AuthError::WrongSide { name: profile.name.clone(), player })
+        } else {
+            Err(AuthError::NotParticipant(profile.name.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authenticate() {
+        let tokens = Tokens::parse("# players\n5f2a Ada\n9c41 Grace Hopper\n").unwrap();
+        assert_eq!(tokens.authenticate("9c41").unwrap().name, "Grace Hopper");
+        assert!(matches!(tokens.authenticate("0000"), Err(AuthError::UnknownToken)));
+        assert!(matches!(Tokens::parse("5f2a"), Err(AuthError::Parse { line: 1, .. })));
+        assert!(matches!(Tokens::parse("5f2a Ada\n5f2a Alan"), Err(AuthError::Parse { line: 2, .. })));
+    }
+
+    #[test]
+    fn seats() {
+        let ada = Profile { name: String::from("Ada") };
+        let grace = Profile { name: String::from("Grace Hopper") };
+        let alan = Profile { name: String::from("Alan") };
+        let mut seats = Seats::default();
+        seats.sit(&ada, PlayerId::A).unwrap();
+        seats.sit(&grace, PlayerId::B).unwrap();
+        assert!(matches!(seats.sit(&alan, PlayerId::A), Err(AuthError::SeatTaken(PlayerId::A))));
+
+        // Only the two participants may move, and each only for their own side
+        seats.check_move(&ada, PlayerId::A).unwrap();
+        seats.check_move(&grace, PlayerId::B).unwrap();
+        assert!(matches!(seats.check_move(&ada, PlayerId::B), Err(AuthError::WrongSide { player: PlayerId::B, .. })));
+        assert!(matches!(seats.check_move(&alan, PlayerId::A), Err(AuthError::NotParticipant(_))));
+    }
+}
@@ -2,53 +2,137 @@
  * Stores board and related classes that track the state of the board,
  * and can answer questions about it.
  */
-use std::fmt::{Debug, Display, Error, Formatter};
+use std::collections::VecDeque;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 use std::vec::Vec;
 
 use rand::rngs::StdRng;
 use rand::{RngCore, SeedableRng};
 
-use crate::game::{Checker, Stone, PLAYER_A_ID, PLAYER_B_ID, EMPTY_PLAYER_ID};
+use crate::chains::StoneChains;
+use crate::grid::Grid;
+use crate::overlay::Overlay;
+use crate::config::{GlyphTheme, Handicap, RulesConfig, StartPosition, Variant};
+use crate::game::{ApplyError, Checker, Intent, PlayerId, Stone};
 use crate::vec::{Vec2, UP, LEFT, RIGHT, DOWN};
 
+// Dimensions of the standard board
 pub const BOARD_WIDTH: usize = 8;
 pub const BOARD_HEIGHT: usize = 6;
 
-const PLAYER_A_CHECK: [char; 4] = ['.', 'A', 'B', 'C'];
-const PLAYER_A_STONE: char = 'a';
+/* Zobrist keys are split into ranges of slots, one range per kind of key. */
+const ZOBRIST_CHECKER_SLOTS: u64 = 0;
+const ZOBRIST_STONE_SLOTS: u64 = 1 << 32;
+pub const ZOBRIST_SIDE_SLOTS: u64 = 2 << 32;
 
-const PLAYER_B_CHECK: [char; 4] = ['.', '1', '2', '3'];
-const PLAYER_B_STONE: char = 'b';
-
-const EMPTY_STONE: char = '.';
-const EMPTY_CHECKER: char = '_';
+/**
+ * zobrist_key
+ * Get the Zobrist key for a slot. Keys are derived from the slot number with splitmix64
+ * rather than stored in a table, so they are the same in every run and for every board size.
+ * @slot Slot to get the key for.
+ * @ret Pseudo-random 64 bit key.
+ */
+pub fn zobrist_key(slot: u64) -> u64 {
+    let mut z = slot.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
 pub enum MoveError {
     // Thrown when move index is out of bounds.
+    #[error("{0}")]
     IndexError(String),
     // Thrown when trying to place a piece on an occupied square.
+    #[error("Space is already occupied")]
     OccupiedError,
     // Thrown when Rule of Negation is broken ie trying to place a stone on a square with checker.
+    #[error("Cannot place a stone on a square with a checker.")]
     NegationError,
+    // Thrown when a checker is moved to a square that is not one of its neighbours.
+    #[error("Checker cannot move that far")]
+    UnreachableError,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
 pub enum FireError {
     // Move was out not a valid board index
+    #[error("Square is not on the board")]
     IndexError,
     // No attacker pieces are in range
+    #[error("No attackers are in range")]
     NoAttackersError,
 }
 
-#[derive(Clone, Debug)]
+/**
+ * Combat is how one fire was resolved, so the dice can be shown and checked.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Combat {
+    // checkers that attacked, by column and then row
+    pub attackers: Vec<Vec2>,
+    pub target: Vec2,
+    // one die per attacker, in the order of attackers; empty under deterministic combat
+    pub rolls: Vec<u32>,
+    // lowest roll that does damage, from the target's hill and neighbouring stones
+    pub terrain_bonus: u32,
+    // levels of damage done, which may be more than the target had
+    pub damage: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
 pub enum SlideError {
     // Thrown when move index is out of bounds.
+    #[error("Stone is not on the board")]
     IndexError,
     // Thrown when slide is blocked
+    #[error("Stone is blocked")]
     BlockedError
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+pub enum PositionError {
+    // The position is not one of the board's checker squares
+    #[error("{0} is not a checker square on the board")]
+    NotASquare(Vec2),
+    // The position is not one of the board's stone corners
+    #[error("{0} is not a stone corner on the board")]
+    NotACorner(Vec2),
+}
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum InvariantError {
+    // A checker with an owner has no height, or a checker with height has no owner
+    #[error("Checker at {0} has a height that does not match its owner")]
+    CheckerHeight(Vec2),
+    // The occupancy masks disagree with the checkers on the board
+    #[error("Occupancy of player {0} does not match their checkers")]
+    Occupancy(PlayerId),
+    // The stone lists disagree with the stones on the board
+    #[error("Stone list of player {0} does not match their stones")]
+    StoneList(PlayerId),
+    // The stone chains disagree with a fresh rebuild from the stones on the board
+    #[error("Stone chains of player {0} do not match their stones")]
+    Chains(PlayerId),
+}
+
+/**
+ * Terrain is the ground under a checker square. Boards are all Plain unless a
+ * scenario lays out terrain.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Terrain {
+    #[default]
+    Plain,
+    // Slows movement: checkers can only step into or out of mud orthogonally
+    Mud,
+    // Adds one to the defence bonus of a checker standing on it
+    Hill,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Direction {
     Up,
@@ -89,20 +173,48 @@ impl Direction {
     }
 }
 
-impl Display for MoveError {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        match self {
-            MoveError::IndexError(msg) => write!(f, "{}", msg),
-            MoveError::OccupiedError => write!(f, "{}", "Space is already occupied"),
-            MoveError::NegationError => write!(f, "{}", "Cannot place a stone on a square with a checker.")
-        }
-    }
-}
-
+/**
+ * Board holds the pieces and terrain of one position.
+ *
+ * Cloning is cheap enough for search: it copies the checker, stone, and terrain grids
+ * and the stone chains, a few small allocations sized to the board, and nothing else.
+ * The dice are not copied. A clone shares its random number generator with the board
+ * it came from through a handle, so clones do not all roll the same numbers; call
+ * reseed on a clone to give it a generator of its own. Scripted dice are copied.
+ *
+ * Boards compare and hash as positions: two boards are equal when they have the same
+ * size, pieces, terrain and rules, whatever their dice, and hash to their Zobrist hash.
+ */
+#[derive(Clone)]
 pub struct Board {
-    checker_board: [Checker; BOARD_WIDTH * BOARD_HEIGHT],
-    stone_board: [Stone; (BOARD_WIDTH + 1) * (BOARD_HEIGHT + 1)],
-    rng: StdRng
+    variant: Variant,
+    // number of checker columns and rows; stones sit on the (width + 1) x (height + 1) corners
+    width: usize,
+    height: usize,
+    checker_board: Vec<Checker>,
+    stone_board: Vec<Stone>,
+    // one per checker square, kept across resets
+    terrain: Vec<Terrain>,
+    // connectivity of each player's stones, for the stone-chain win
+    chains: StoneChains,
+    // set when stones may have been changed through mut_stone_at, behind the chains' back
+    // and the stone lists' back
+    chains_stale: bool,
+    // positions of player A's stones, and of player B's, in no particular order
+    stone_lists: [Vec<Vec2>; 2],
+    rules: RulesConfig,
+    // characters as_string draws the board with
+    glyphs: GlyphTheme,
+    // per checker square, a bit set for every square a checker could attack it from
+    attack_masks: Vec<u128>,
+    // bit set for every square holding a checker of player A, and of player B
+    occupancy: [u128; 2],
+    // set when checkers may have been changed through mut_checker_at, behind occupancy's back
+    occupancy_stale: bool,
+    // shared between a board and its clones
+    rng: Arc<Mutex<StdRng>>,
+    // rolls the next attacks take instead of the random dice, the next one last
+    scripted_dice: Vec<u32>,
 }
 
 impl Board {
@@ -111,13 +223,7 @@ impl Board {
      * @ret New board, in Ironclad's start state.
      */
     pub fn new() -> Board {
-        let mut board = Board {
-            checker_board: [Checker{height: 0, owner: EMPTY_PLAYER_ID}; BOARD_WIDTH * BOARD_HEIGHT],
-            stone_board: [Stone{owner: EMPTY_PLAYER_ID}; (BOARD_WIDTH + 1) * (BOARD_HEIGHT + 1)],
-            rng: StdRng::from_entropy() 
-        };
-        board.place_start_pieces();
-        board
+        Board::for_variant(Variant::Standard)
     }
 
     /**
@@ -125,15 +231,181 @@ impl Board {
      * @seed Array of 32 u8's as a seed.
      */
     pub fn from_seed(seed: [u8; 32]) -> Board {
+        Board::build(Variant::Standard, StdRng::from_seed(seed))
+    }
+
+    /**
+     * for_variant creates a new Board sized and laid out for @variant.
+     * @variant Board preset to use.
+     * @ret New board, in the variant's start state.
+     */
+    pub fn for_variant(variant: Variant) -> Board {
+        Board::build(variant, StdRng::from_entropy())
+    }
+
+    /**
+     * Create a new Board for @variant with the given seed for the random number generator.
+     * @variant Board preset to use.
+     * @seed Array of 32 u8's as a seed.
+     */
+    pub fn for_variant_from_seed(variant: Variant, seed: [u8; 32]) -> Board {
+        Board::build(variant, StdRng::from_seed(seed))
+    }
+
+    fn build(variant: Variant, rng: StdRng) -> Board {
+        let (width, height) = (variant.width(), variant.height());
         let mut board = Board {
-            checker_board: [Checker{height: 0, owner: EMPTY_PLAYER_ID}; BOARD_WIDTH * BOARD_HEIGHT],
-            stone_board: [Stone{owner: EMPTY_PLAYER_ID}; (BOARD_WIDTH + 1) * (BOARD_HEIGHT + 1)],
-            rng: StdRng::from_seed(seed) 
+            variant,
+            width,
+            height,
+            checker_board: vec![Checker::empty(); width * height],
+            stone_board: vec![Stone::empty(); (width + 1) * (height + 1)],
+            terrain: vec![Terrain::Plain; width * height],
+            chains: StoneChains::new(width, height),
+            chains_stale: false,
+            stone_lists: [Vec::new(), Vec::new()],
+            rules: RulesConfig::default(),
+            glyphs: GlyphTheme::default(),
+            attack_masks: Vec::new(),
+            occupancy: [0; 2],
+            occupancy_stale: false,
+            rng: Arc::new(Mutex::new(rng)),
+            scripted_dice: Vec::new(),
         };
+        assert!(width * height <= u128::BITS as usize, "board too large for occupancy masks");
+        board.compute_attack_masks();
         board.place_start_pieces();
         board
     }
 
+    /**
+     * width
+     * @ret Number of checker columns. Stones have one more column than checkers.
+     */
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /**
+     * height
+     * @ret Number of checker rows. Stones have one more row than checkers.
+     */
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /**
+     * variant
+     * @ret Preset the board was created for.
+     */
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /**
+     * reseed gives this board its own random number generator, no longer shared with
+     * the board it was cloned from or its other clones.
+     * @seed Array of 32 u8's as a seed.
+     */
+    pub fn reseed(&mut self, seed: [u8; 32]) {
+        self.rng = Arc::new(Mutex::new(StdRng::from_seed(seed)));
+    }
+
+    /**
+     * script_dice has the next attacks roll @rolls, in order, before the random dice take
+     * over again, so a recorded game's combat can be replayed exactly. Replaces any rolls
+     * still scripted.
+     * @rolls Die rolls, each from 1 to 6.
+     */
+    pub fn script_dice(&mut self, rolls: &[u32]) {
+        self.scripted_dice = rolls.iter().rev().copied().collect();
+    }
+
+    /**
+     * scripted_dice_left
+     * @ret Number of scripted rolls not yet used.
+     */
+    pub fn scripted_dice_left(&self) -> usize {
+        self.scripted_dice.len()
+    }
+
+    /**
+     * rules
+     * @ret Rule switches the board enforces.
+     */
+    pub fn rules(&self) -> RulesConfig {
+        self.rules
+    }
+
+    /**
+     * terrain_at
+     * @pos Checker square to look at.
+     * @ret Terrain of the square, or Plain if @pos is off the board.
+     */
+    pub fn terrain_at(&self, pos: Vec2) -> Terrain {
+        if !self.is_checker_vec_valid(pos) {
+            return Terrain::Plain;
+        }
+        self.terrain[self.vec_to_checker_idx(pos)]
+    }
+
+    /**
+     * set_terrain changes the ground under a checker square.
+     * @pos Checker square to change.
+     * @terrain New terrain.
+     * @ret Ok, or an IndexError if @pos is off the board.
+     */
+    pub fn set_terrain(&mut self, pos: Vec2, terrain: Terrain) -> Result<(), MoveError> {
+        if !self.is_checker_vec_valid(pos) {
+            return Err(MoveError::IndexError(format!("{pos} is not a valid checker position")));
+        }
+        let idx = self.vec_to_checker_idx(pos);
+        self.terrain[idx] = terrain;
+        Ok(())
+    }
+
+    /**
+     * checker_can_step
+     * Determine if a checker could step from @from to @to on an empty board, ie @to is a
+     * neighbour of @from and the step does not cross into or out of mud diagonally.
+     * @from Position of the checker.
+     * @to Square to step to.
+     * @ret True if the step is allowed.
+     */
+    pub fn checker_can_step(&self, from: Vec2, to: Vec2) -> bool {
+        if !self.checker_neighbours(from).any(|neighbour| neighbour == to) {
+            return false;
+        }
+        let diagonal = from.x != to.x && from.y != to.y;
+        let muddy = self.terrain_at(from) == Terrain::Mud || self.terrain_at(to) == Terrain::Mud;
+        !(diagonal && muddy)
+    }
+
+    /**
+     * glyphs
+     * @ret Characters the board is drawn with.
+     */
+    pub fn glyphs(&self) -> GlyphTheme {
+        self.glyphs
+    }
+
+    /**
+     * set_glyphs changes the characters the board is drawn with. It has no effect on play.
+     * @glyphs New glyphs.
+     */
+    pub fn set_glyphs(&mut self, glyphs: GlyphTheme) {
+        self.glyphs = glyphs;
+    }
+
+    /**
+     * set_rules changes the rule switches the board enforces from now on.
+     * @rules New rules.
+     */
+    pub fn set_rules(&mut self, rules: RulesConfig) {
+        self.rules = rules;
+        self.compute_attack_masks();
+    }
+
     /**
      * reset the board to the game's initial state.
      */
@@ -143,85 +415,296 @@ impl Board {
     }
 
     fn place_start_pieces(&mut self) {
-        self.place_checker_at(Vec2::new(6, 2), Checker::new(1, PLAYER_A_ID)).unwrap();
-        self.place_checker_at(Vec2::new(6, 3), Checker::new(1, PLAYER_A_ID)).unwrap();
-        self.place_checker_at(Vec2::new(7, 1), Checker::new(2, PLAYER_A_ID)).unwrap();
-        self.place_checker_at(Vec2::new(7, 4), Checker::new(2, PLAYER_A_ID)).unwrap();
-        self.place_checker_at(Vec2::new(7, 3), Checker::new(3, PLAYER_A_ID)).unwrap();
-        self.place_checker_at(Vec2::new(7, 2), Checker::new(3, PLAYER_A_ID)).unwrap();
+        let mirror = self.width as i32 - 1;
+        for (pos, height) in self.variant.starting_checkers() {
+            self.place_checker_at(pos, Checker::new(height, PlayerId::A)).unwrap();
+            self.place_checker_at(Vec2::new(mirror - pos.x, pos.y), Checker::new(height, PlayerId::B)).unwrap();
+        }
+    }
+
+    /**
+     * place_handicap puts the pieces of a handicap on the board for @player.
+     * Stone pool handicaps are not board pieces, and are ignored.
+     * @player Id of player receiving the handicap.
+     * @handicap Head start to place.
+     * @ret Ok if every piece was placed, or the MoveError of the first piece that could not be.
+     */
+    pub fn place_handicap(&mut self, player: PlayerId, handicap: &Handicap) -> Result<(), MoveError> {
+        match *handicap {
+            Handicap::ExtraStones(_) => Ok(()),
+            Handicap::ExtraChecker(pos, height) => self.place_checker_at(pos, Checker::new(height, player)),
+            Handicap::StoneLine { start, direction, length } => {
+                for step in 0..length as i32 {
+                    self.place_stone_at(start + direction.as_vec().scale(step), Stone::new(player))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /**
+     * clear_checkers removes every checker from the board, leaving stones in place.
+     */
+    pub fn clear_checkers(&mut self) {
+        self.checker_board.fill(Checker::empty());
+        self.occupancy = [0; 2];
+        self.occupancy_stale = false;
+        self.strict_check();
+    }
+
+    /**
+     * stone_chain_winner
+     * Find a player whose stones connect the top and bottom edges of the board.
+     * O(log n) using the chains kept up to date as stones move.
+     * @ret Id of the connected player, checking player A first, or None.
+     */
+    pub fn stone_chain_winner(&self) -> Option<PlayerId> {
+        let rebuilt;
+        let chains = if self.chains_stale {
+            let mut fresh = StoneChains::new(self.width, self.height);
+            fresh.rebuild(&self.stone_board, PlayerId::A);
+            fresh.rebuild(&self.stone_board, PlayerId::B);
+            rebuilt = fresh;
+            &rebuilt
+        } else {
+            &self.chains
+        };
+        [PlayerId::A, PlayerId::B].into_iter().find(|player| chains.connects(*player))
+    }
+
+    /**
+     * stone_influence
+     * Map how close @player is to a stone chain through each stone corner: the fewest stones
+     * they would still have to place for a top-to-bottom chain passing through it. Corners on
+     * the player's best chains have the lowest gaps, so the map shows which stones matter.
+     * @player Id of player to measure for.
+     * @ret Gap through each corner, or None where the other player's stones cut it off.
+     */
+    pub fn stone_influence(&self, player: PlayerId) -> Grid<Option<u32>> {
+        self.chains.gaps(&self.stone_board, player)
+    }
+
+    /**
+     * chain_gap
+     * Count the fewest stones @player would still have to place to connect the top and bottom
+     * edges, ignoring what the other player does meanwhile.
+     * @player Id of player to measure for.
+     * @ret 0 if the player is connected, or None if the other player's stones cut every path.
+     */
+    pub fn chain_gap(&self, player: PlayerId) -> Option<u32> {
+        self.stone_influence(player).iter().filter_map(|(_, gap)| *gap).min()
+    }
+
+    /**
+     * dead_stones
+     * Find @player's stones that can no longer be part of any chain of theirs, because the
+     * other player's stones cut off every route from them to the top edge or to the bottom.
+     * Stones only come back to life if the stones cutting them off are slid away.
+     * @player Id of player whose stones to look at.
+     * @ret Positions of the dead stones, by column and then row.
+     */
+    pub fn dead_stones(&self, player: PlayerId) -> Vec<Vec2> {
+        let influence = self.stone_influence(player);
+        self.stones_for_player(player)
+            .into_iter()
+            .filter(|pos| influence.get(*pos).is_some_and(|gap| gap.is_none()))
+            .collect()
+    }
+
+    /**
+     * shortest_path_to_goal
+     * Find the fewest steps a checker at @pos needs to reach the far column, stepping only onto
+     * empty squares as move_checker allows, with every other checker where it stands: player A
+     * heads for column 0 and player B for the last column.
+     * @pos Square the checker starts from.
+     * @player Id of player the checker belongs to.
+     * @ret Number of steps and the squares stepped onto, ending in the far column, or None if
+     * every route is blocked or @pos is off the board. A checker already there needs 0 steps.
+     */
+    pub fn shortest_path_to_goal(&self, pos: Vec2, player: PlayerId) -> Option<(u32, Vec<Vec2>)> {
+        if !self.is_checker_vec_valid(pos) {
+            return None;
+        }
+        let goal = match player {
+            PlayerId::A => 0,
+            PlayerId::B => self.width as i32 - 1,
+        };
+        // breadth first, remembering the square each square was first reached from
+        let mut came_from: Grid<Option<Vec2>> = Grid::new(self.width, self.height, None);
+        *came_from.get_mut(pos).unwrap() = Some(pos);
+        let mut frontier = VecDeque::from([pos]);
+        while let Some(current) = frontier.pop_front() {
+            if current.x == goal {
+                let mut path = Vec::new();
+                let mut step = current;
+                while step != pos {
+                    path.push(step);
+                    step = came_from.get(step).unwrap().unwrap();
+                }
+                path.reverse();
+                return Some((path.len() as u32, path));
+            }
+            for next in self.checker_neighbours(current) {
+                let unvisited = came_from.get(next).unwrap().is_none();
+                if unvisited && self.checker_at_unsafe(next).owner.is_none() && self.checker_can_step(current, next) {
+                    *came_from.get_mut(next).unwrap() = Some(current);
+                    frontier.push_back(next);
+                }
+            }
+        }
+        None
+    }
 
-        self.place_checker_at(Vec2::new(1, 2), Checker::new(1, PLAYER_B_ID)).unwrap();
-        self.place_checker_at(Vec2::new(1, 3), Checker::new(1, PLAYER_B_ID)).unwrap();
-        self.place_checker_at(Vec2::new(0, 1), Checker::new(2, PLAYER_B_ID)).unwrap();
-        self.place_checker_at(Vec2::new(0, 4), Checker::new(2, PLAYER_B_ID)).unwrap();
-        self.place_checker_at(Vec2::new(0, 3), Checker::new(3, PLAYER_B_ID)).unwrap();
-        self.place_checker_at(Vec2::new(0, 2), Checker::new(3, PLAYER_B_ID)).unwrap();
+    /*
+     * Helper function bringing the chains up to date after @owner's stones changed in a way
+     * union-find cannot follow, or after outside changes made them stale.
+     */
+    fn refresh_chains(&mut self, owner: Option<PlayerId>) {
+        if self.chains_stale {
+            self.chains.rebuild(&self.stone_board, PlayerId::A);
+            self.chains.rebuild(&self.stone_board, PlayerId::B);
+            self.stone_lists = [PlayerId::A, PlayerId::B].map(|player| self.scan_stones(Some(player)));
+            self.chains_stale = false;
+        } else if let Some(owner) = owner {
+            self.chains.rebuild(&self.stone_board, owner);
+        }
+    }
+
+    /**
+     * set_position clears the board and sets out the pieces in @position instead.
+     * @position Pieces to put on the board.
+     * @ret Ok, or the MoveError of the first piece that could not be placed.
+     */
+    pub fn set_position(&mut self, position: &StartPosition) -> Result<(), MoveError> {
+        self.clear_board();
+        for (owner, pos, height) in position.checkers.iter() {
+            self.place_checker_at(*pos, Checker::new(*height, *owner))?;
+        }
+        for (owner, pos) in position.stones.iter() {
+            self.place_stone_at(*pos, Stone::new(*owner))?;
+        }
+        Ok(())
+    }
+
+    /**
+     * restore_position clears the board and sets out the pieces of a position reached in
+     * play. Checkers can move up to stones, so unlike set_position, stones on the corners of
+     * a checker's square are allowed.
+     * @position Pieces to put on the board.
+     * @ret Ok, or the MoveError of the first piece that could not be placed.
+     */
+    pub fn restore_position(&mut self, position: &StartPosition) -> Result<(), MoveError> {
+        self.clear_board();
+        // Stones go first, before any checker they touch is there to trip the rule of negation
+        for (owner, pos) in position.stones.iter() {
+            self.place_stone_at(*pos, Stone::new(*owner))?;
+        }
+        for (owner, pos, height) in position.checkers.iter() {
+            self.place_checker_at(*pos, Checker::new(*height, *owner))?;
+        }
+        Ok(())
     }
 
     fn clear_board(&mut self) {
-        self.checker_board.fill(Checker::new(0, EMPTY_PLAYER_ID));
-        self.stone_board.fill(Stone::new(EMPTY_PLAYER_ID))
+        self.clear_checkers();
+        self.stone_board.fill(Stone::empty());
+        self.chains.clear();
+        self.stone_lists.iter_mut().for_each(Vec::clear);
+        self.chains_stale = false;
+    }
+
+    /**
+     * apply makes @intent on the board for @player, the way Game::apply_move does but with
+     * no turns, stone pools, or setup to keep track of. Stones and checkers placed come from
+     * nowhere, and swapping sides leaves the board as it is.
+     * Meant for tools that work on positions alone, like search and perft.
+     * @player Id of player making the move.
+     * @intent Move to make.
+     * @ret Ok if the move was made, IllegalMove if it moves or attacks the wrong player's
+     * piece, or the board's error if it breaks a rule.
+     */
+    pub fn apply(&mut self, player: PlayerId, intent: Intent) -> Result<(), ApplyError> {
+        let illegal = ApplyError::IllegalMove { player, intent };
+        match intent {
+            Intent::MoveChecker(from, to) => {
+                if self.checker_at(from).is_ok_and(|checker| checker.owner != Some(player)) {
+                    return Err(illegal);
+                }
+                self.move_checker(from, to)?;
+            },
+            Intent::FireChecker(at) => {
+                self.apply_fire(player, at)?;
+            },
+            Intent::PlaceStone(at) => self.place_stone_at(at, Stone::new(player))?,
+            Intent::SlideStone(from, direction) => {
+                if self.stone_at(from).is_ok_and(|stone| stone.owner != Some(player)) {
+                    return Err(illegal);
+                }
+                self.slide_stone(from, direction)?;
+            },
+            Intent::PlaceChecker(at, height) => {
+                if height.is_zero() {
+                    return Err(illegal);
+                }
+                self.place_checker_at(at, Checker::new(height, player))?;
+            },
+            Intent::SwapSides => (),
+        }
+        Ok(())
     }
 
     /**
      * move_checker from @from to @to, returning Ok if the move is accepted, and an error otherwise.
+     * The step must pass checker_can_step, so diagonal steps are refused under orthogonal-only rules and across mud.
      * @from Position of checker to move.
      * @to Position to move checker to.
      * @ret Ok if move is legal, or a MoveError if something went wrong.
      */
     pub fn move_checker(&mut self, from: Vec2, to: Vec2) -> Result<(), MoveError> {
-        for vec in vec![from, to].iter() {
-            if !Board::is_checker_vec_valid(*vec) {
-                return Err(MoveError::IndexError(String::from("{vec} is not a valid checker position")));
+        for vec in [from, to].iter() {
+            if !self.is_checker_vec_valid(*vec) {
+                return Err(MoveError::IndexError(format!("{vec} is not a valid checker position")));
             }
         }
+        if !self.checker_can_step(from, to) {
+            return Err(MoveError::UnreachableError);
+        }
         let to_checker = self.checker_at_unsafe(to);
-        if to_checker.owner != EMPTY_PLAYER_ID {
+        if to_checker.owner.is_some() {
             return Err(MoveError::OccupiedError);
         }
 
-        let to_idx = Board::vec_to_checker_idx(to);
-        let from_idx = Board::vec_to_checker_idx(from);
+        let to_idx = self.vec_to_checker_idx(to);
+        let from_idx = self.vec_to_checker_idx(from);
 
         self.checker_board.swap(to_idx, from_idx);
+        self.sync_occupancy(to_idx);
+        self.sync_occupancy(from_idx);
+        self.strict_check();
 
         Ok(())
     }
 
     /**
      * slide_stone
-     * Slide a stone in a Direction as many squares as possible.
+     * Slide a stone in a Direction as many squares as possible, up to the rules' slide limit.
      * @from Position of stone to slide.
      * @dir Direction to move stone in.
      * @ret Ok if slide is legal, or SlideError if something went wrong.
      */
     pub fn slide_stone(&mut self, from: Vec2, dir: Direction) -> Result<Vec2, SlideError> {
-        if !Board::is_stone_vec_valid(from) {
-            return Err(SlideError::IndexError)
-        }
-        let target = from + dir.as_vec();
-        if !Board::is_stone_vec_valid(target) {
-            return Err(SlideError::BlockedError)
-        }
-        let target_idx = Board::vec_to_stone_idx(target);
-        if self.stone_board[target_idx].owner != EMPTY_PLAYER_ID {
-            return Err(SlideError::BlockedError)
-        }
-        let mut last_free_position = target;
-        loop {
-            let next_target = last_free_position + dir.as_vec();
-            if !Board::is_stone_vec_valid(next_target) {
-                break;
-            }
-            let idx = Board::vec_to_stone_idx(next_target);
-            if self.stone_board[idx].owner != EMPTY_PLAYER_ID {
-                break;
+        let last_free_position = self.slide_stone_result(from, dir)?;
+        let new_idx = self.vec_to_stone_idx(last_free_position);
+        let old_idx  = self.vec_to_stone_idx(from);
+        self.stone_board.swap(new_idx, old_idx);
+        let owner = self.stone_board[new_idx].owner;
+        if let Some(player) = owner {
+            if let Some(listed) = self.stone_lists[player.index()].iter_mut().find(|listed| **listed == from) {
+                *listed = last_free_position;
             }
-            last_free_position = next_target;
         }
-        let new_idx = Board::vec_to_stone_idx(last_free_position);
-        let old_idx  = Board::vec_to_stone_idx(from);
-        self.stone_board.swap(new_idx, old_idx);
+        self.refresh_chains(owner);
+        self.strict_check();
 
         Ok(last_free_position)
     }
@@ -235,33 +718,53 @@ impl Board {
      * @ret Ok if slide is legal, with location stone ends at, or SlideError if something went wrong.
      */
     pub fn slide_stone_result(&self, from: Vec2, dir: Direction) -> Result<Vec2, SlideError> {
-        if !Board::is_stone_vec_valid(from) {
+        if !self.is_stone_vec_valid(from) {
             return Err(SlideError::IndexError)
         }
         let target = from + dir.as_vec();
-        if !Board::is_stone_vec_valid(target) {
+        if !self.is_stone_vec_valid(target) {
             return Err(SlideError::BlockedError)
         }
-        let target_idx = Board::vec_to_stone_idx(target);
-        if self.stone_board[target_idx].owner != EMPTY_PLAYER_ID {
+        let target_idx = self.vec_to_stone_idx(target);
+        if self.stone_board[target_idx].owner.is_some() {
             return Err(SlideError::BlockedError)
         }
         let mut last_free_position = target;
+        let mut distance = 1;
         loop {
+            if self.rules.slide_limit.is_some_and(|limit| distance >= limit) {
+                break;
+            }
             let next_target = last_free_position + dir.as_vec();
-            if !Board::is_stone_vec_valid(next_target) {
+            if !self.is_stone_vec_valid(next_target) {
                 break;
             }
-            let idx = Board::vec_to_stone_idx(next_target);
-            if self.stone_board[idx].owner != EMPTY_PLAYER_ID {
+            let idx = self.vec_to_stone_idx(next_target);
+            if self.stone_board[idx].owner.is_some() {
                 break;
             }
             last_free_position = next_target;
+            distance += 1;
         }
 
         Ok(last_free_position)
     }
 
+    /**
+     * apply_fire
+     * @player attacks the checker at @at, as apply does for Intent::FireChecker.
+     * @player Id of attacking player.
+     * @at Square to attack.
+     * @ret How the attack was resolved, or ApplyError::IllegalMove if @at holds one of
+     *      @player's own checkers, or the FireError if it could not be attacked.
+     */
+    pub fn apply_fire(&mut self, player: PlayerId, at: Vec2) -> Result<Combat, ApplyError> {
+        if self.checker_at(at).is_ok_and(|checker| checker.owner == Some(player)) {
+            return Err(ApplyError::IllegalMove { player, intent: Intent::FireChecker(at) });
+        }
+        Ok(self.resolve_fire(at)?)
+    }
+
     /**
      * fire_checker_at
      * Player of id @player attacks the checker at @pos, with all possible pieces in range, or errors.
@@ -270,58 +773,110 @@ impl Board {
      * @return Ok if no error, or one of the error types if something went wrong.
      */
     pub fn fire_checker_at(&mut self, pos: Vec2) -> Result<(), FireError> {
-        if !Board::is_checker_vec_valid(pos) {
+        self.resolve_fire(pos).map(|_| ())
+    }
+
+    /**
+     * resolve_fire
+     * Attack the checker at @pos with all possible pieces in range, as fire_checker_at does.
+     * @pos Square to attack.
+     * @ret The attackers, their dice and the damage done, or a FireError.
+     */
+    pub fn resolve_fire(&mut self, pos: Vec2) -> Result<Combat, FireError> {
+        if !self.is_checker_vec_valid(pos) {
             return Err(FireError::IndexError);
         }
-        let checker_idx = Board::vec_to_checker_idx(pos);
+        let checker_idx = self.vec_to_checker_idx(pos);
         let checker = self.checker_board[checker_idx];
-
-        // Check neighbourhood for attackers
-        let mut attackers = 0;
-        let dirs = vec![UP, DOWN, LEFT, RIGHT, UP + LEFT, UP + RIGHT, DOWN + LEFT, DOWN + RIGHT];
-        for dir in dirs.iter() {
-            for scale_factor in 1..3 {
-                let offset = dir.scale(scale_factor);
-                let neighbour_pos = pos + offset;
-                if !Board::is_checker_vec_valid(neighbour_pos) {
-                    continue;
-                }
-                let neighbour_idx = Board::vec_to_checker_idx(neighbour_pos);
-                let neigh = self.checker_board[neighbour_idx];
-                if neigh.owner != checker.owner && neigh.owner != EMPTY_PLAYER_ID {
-                    attackers += 1;
-                }
-            }
-        }
-        if attackers == 0 {
-            return Err(FireError::NoAttackersError)
-        }
-        // Get terrain bonus
-        let mut terrain_bonus = 0;
-        for stone_pos in Board::stone_neighbours_of_checker(pos).iter() {
-            let idx = Board::vec_to_stone_idx(*stone_pos);
-            if let Some(s) = self.stone_board.get(idx) {
-                if s.owner != EMPTY_PLAYER_ID {
-                    terrain_bonus += 1;
+        let attackers = self.can_fire_checker_at(pos)?;
+        let terrain_bonus = self.defence_bonus(pos);
+        let mut rolls = Vec::new();
+        let dmg = if self.rules.deterministic_combat {
+            self.expected_damage(pos, attackers) as usize
+        } else {
+            // For each attack, roll a die
+            let mut dmg = 0;
+            for _ in 0..attackers {
+                // If die >= terrain bonus, checker takes 1 damage
+                let roll = match self.scripted_dice.pop() {
+                    Some(roll) => roll,
+                    None => self.rng.lock().unwrap().next_u32() % 6 + 1,
+                };
+                if roll >= terrain_bonus {
+                    dmg += 1;
                 }
+                rolls.push(roll);
             }
-        }
-        // For each attack, roll a die
-        let mut dmg = 0;
-        for _ in 0..attackers {
-            // If die > terrain bonus, checker takes 1 damage
-            let roll = self.rng.next_u32() % 6 + 1;
-            if roll >= terrain_bonus {
-                dmg += 1;
-            }
-        }
-        let new_height = checker.height.checked_sub(dmg).unwrap_or(0);
-        if new_height == 0 {
-            self.checker_board[checker_idx] = Checker::new(0, EMPTY_PLAYER_ID);
+            dmg
+        };
+        let combat = Combat { attackers: self.attackers_of(pos), target: pos, rolls, terrain_bonus, damage: dmg };
+        let new_height = checker.height.saturating_sub(dmg);
+        if new_height.is_zero() {
+            self.checker_board[checker_idx] = Checker::empty();
         } else {
-            self.checker_board[checker_idx] = Checker::new(new_height, checker.owner);
+            self.checker_board[checker_idx] = Checker { height: new_height, owner: checker.owner };
         }
-        Ok(())
+        self.sync_occupancy(checker_idx);
+        self.strict_check();
+        Ok(combat)
+    }
+
+    /**
+     * defence_bonus
+     * Get the bonus a checker at @pos gets against attacks: one for a hill, and one for each
+     * stone, of either player, on the square's corners. Each attacker's die must roll at
+     * least the bonus to do damage.
+     * @pos Checker square to look at.
+     * @ret The bonus, 0 if @pos is off the board.
+     */
+    pub fn defence_bonus(&self, pos: Vec2) -> u32 {
+        if !self.is_checker_vec_valid(pos) {
+            return 0;
+        }
+        let hill = u32::from(self.terrain_at(pos) == Terrain::Hill);
+        let stones = self.stone_neighbours_of_checker(pos)
+            .filter(|stone_pos| self.stone_at_unsafe(*stone_pos).owner.is_some())
+            .count() as u32;
+        hill + stones
+    }
+
+    /**
+     * hit_chance
+     * Get the chance that one attacker's die damages a checker at @pos.
+     * @pos Checker square to look at.
+     * @ret Probability from 0 to 1.
+     */
+    pub fn hit_chance(&self, pos: Vec2) -> f32 {
+        // the die must roll at least the bonus, and any roll beats a bonus of 0 or 1
+        let hits = 7 - self.defence_bonus(pos).clamp(1, 7);
+        hits as f32 / 6.0
+    }
+
+    /**
+     * expected_damage
+     * Get the damage @attackers attackers do to a checker at @pos under deterministic
+     * combat: the average damage of a roll, rounded to nearest with halves rounded up.
+     * @pos Checker square being attacked.
+     * @attackers Number of attackers.
+     * @ret Damage done.
+     */
+    pub fn expected_damage(&self, pos: Vec2, attackers: u32) -> u32 {
+        let hits = 7 - self.defence_bonus(pos).clamp(1, 7);
+        (2 * attackers * hits + 6) / 12
+    }
+
+    /**
+     * attackers_by
+     * Count @player's checkers within fire range of @pos.
+     * @pos Checker square to look at.
+     * @player Id of attacking player.
+     * @ret Number of @player's checkers that could attack @pos, 0 if @pos is off the board.
+     */
+    pub fn attackers_by(&self, pos: Vec2, player: PlayerId) -> u32 {
+        if !self.is_checker_vec_valid(pos) {
+            return 0;
+        }
+        (self.attack_masks[self.vec_to_checker_idx(pos)] & self.occupancy()[player.index()]).count_ones()
     }
 
     /**
@@ -333,199 +888,261 @@ impl Board {
      * @return Ok if and number of attackers, or one of the error types if something went wrong.
      */
     pub fn can_fire_checker_at(&self, pos: Vec2) -> Result<u32, FireError> {
-        if !Board::is_checker_vec_valid(pos) {
+        if !self.is_checker_vec_valid(pos) {
             return Err(FireError::IndexError);
         }
-        let checker_idx = Board::vec_to_checker_idx(pos);
+        let checker_idx = self.vec_to_checker_idx(pos);
         let checker = self.checker_board[checker_idx];
 
-        // Check neighbourhood for attackers
-        let mut attackers = 0;
-        let dirs = vec![UP, DOWN, LEFT, RIGHT, UP + LEFT, UP + RIGHT, DOWN + LEFT, DOWN + RIGHT];
-        for dir in dirs.iter() {
-            for scale_factor in 1..3 {
-                let offset = dir.scale(scale_factor);
-                let neighbour_pos = pos + offset;
-                if !Board::is_checker_vec_valid(neighbour_pos) {
-                    continue;
-                }
-                let neighbour_idx = Board::vec_to_checker_idx(neighbour_pos);
-                let neigh = self.checker_board[neighbour_idx];
-                if neigh.owner != checker.owner && neigh.owner != EMPTY_PLAYER_ID {
-                    attackers += 1;
-                }
-            }
-        }
+        // Attackers are the other player's checkers under this square's attack mask
+        let occupancy = self.occupancy();
+        let enemies = match checker.owner {
+            Some(player) => occupancy[player.other().index()],
+            None => occupancy[0] | occupancy[1]
+        };
+        let attackers = (self.attack_masks[checker_idx] & enemies).count_ones();
         if attackers == 0 {
             return Err(FireError::NoAttackersError)
         }
         Ok(attackers)
     }
 
+    /**
+     * attackers_of
+     * List the checkers that could attack @pos, the ones can_fire_checker_at counts: the
+     * other player's checkers within fire range, or either player's if @pos is empty.
+     * @pos Checker square to look at.
+     * @ret Positions of the attackers by column and then row, empty if @pos is off the board.
+     */
+    pub fn attackers_of(&self, pos: Vec2) -> Vec<Vec2> {
+        if !self.is_checker_vec_valid(pos) {
+            return Vec::new();
+        }
+        let checker_idx = self.vec_to_checker_idx(pos);
+        let occupancy = self.occupancy();
+        let enemies = match self.checker_board[checker_idx].owner {
+            Some(player) => occupancy[player.other().index()],
+            None => occupancy[0] | occupancy[1]
+        };
+        self.checker_squares(self.attack_masks[checker_idx] & enemies)
+    }
+
+    /*
+     * Helper function returning the occupancy masks, recounted if they may be out of date.
+     */
+    fn occupancy(&self) -> [u128; 2] {
+        if !self.occupancy_stale {
+            return self.occupancy;
+        }
+        let mut occupancy = [0; 2];
+        for (idx, checker) in self.checker_board.iter().enumerate() {
+            if let Some(player) = checker.owner {
+                occupancy[player.index()] |= 1 << idx;
+            }
+        }
+        occupancy
+    }
+
+    /*
+     * Helper function updating the occupancy bits of the checker square at @idx after it changed.
+     */
+    fn sync_occupancy(&mut self, idx: usize) {
+        if self.occupancy_stale {
+            self.occupancy = self.occupancy();
+            self.occupancy_stale = false;
+            return;
+        }
+        let bit = 1u128 << idx;
+        self.occupancy[0] &= !bit;
+        self.occupancy[1] &= !bit;
+        if let Some(player) = self.checker_board[idx].owner {
+            self.occupancy[player.index()] |= bit;
+        }
+    }
+
+    /*
+     * Helper function precomputing, for each square, the squares within fire range of it
+     * along a row, column, or diagonal.
+     */
+    fn compute_attack_masks(&mut self) {
+        let dirs = [UP, DOWN, LEFT, RIGHT, UP + LEFT, UP + RIGHT, DOWN + LEFT, DOWN + RIGHT];
+        let mut masks = vec![0u128; self.width * self.height];
+        for (idx, mask) in masks.iter_mut().enumerate() {
+            let pos = Vec2::new((idx % self.width) as i32, (idx / self.width) as i32);
+            for dir in dirs.iter() {
+                for scale_factor in 1..=self.rules.fire_range {
+                    let neighbour_pos = pos + dir.scale(scale_factor);
+                    if self.is_checker_vec_valid(neighbour_pos) {
+                        *mask |= 1 << self.vec_to_checker_idx(neighbour_pos);
+                    }
+                }
+            }
+        }
+        self.attack_masks = masks;
+    }
+
+    /**
+     * in_fire_range
+     * Determine if a checker at @from could take part in an attack on @target, ie they
+     * share a row, column, or diagonal and are at most the rules' fire range apart.
+     * @from Position of the attacking checker.
+     * @target Position of the checker under attack.
+     * @ret True if @target is in range of @from.
+     */
+    pub fn in_fire_range(&self, from: Vec2, target: Vec2) -> bool {
+        let offset = target - from;
+        let (dx, dy) = (offset.x.abs(), offset.y.abs());
+        let in_line = dx == 0 || dy == 0 || dx == dy;
+        let range = self.rules.fire_range;
+        in_line && dx <= range && dy <= range && (dx, dy) != (0, 0)
+    }
+
     /**
      * place_checker_at places a checker at a position, or returns a MoveError if a rule is
      * violated.
      * 
-     * @pos - Position to place the checker at, should be within [[0,0], [BOARD_WIDTH,BOARD_HEIGHT])
+     * @pos - Position to place the checker at, should be within [[0,0], [width,height])
      * @checker - Checker to put at the position.
      * @return - Ok if checker was placed, or one of the MoveError types if the checker was not placed.
      */
     pub fn place_checker_at(&mut self, pos: Vec2, checker: Checker) -> Result<(), MoveError> {
         // Check index of move is ok
-        if !Board::is_checker_vec_valid(pos) {
-            return Err(MoveError::IndexError(String::from("{pos} not within 0,0 and {BOARD_WIDTH-1},{BOARD_HEIGHT-1}")))
+        if !self.is_checker_vec_valid(pos) {
+            return Err(MoveError::IndexError(format!("{pos} not within 0,0 and {},{}", self.width - 1, self.height - 1)))
         }
-        let idx = Board::vec_to_checker_idx(pos);
+        let idx = self.vec_to_checker_idx(pos);
         let current_piece = &self.checker_board[idx];
 
         // Do not allow to placing a non-empty piece in a non-empty slot
-        if current_piece.owner != EMPTY_PLAYER_ID && checker.owner != EMPTY_PLAYER_ID {
+        if current_piece.owner.is_some() && checker.owner.is_some() {
             return Err(MoveError::OccupiedError);
         }
         self.checker_board[idx] = checker;
+        self.sync_occupancy(idx);
+        self.strict_check();
         Ok(())
     }
 
     /**
      * place_stone_at places a stone at a position, or returns a MoveError if a rule is broken.
-     * @pos - Position to place the checker at, should be within [0, 0], [BOARD_WIDTH+1, BOARD_HEIGHT+1]
+     * @pos - Position to place the checker at, should be within [0, 0], [width+1, height+1]
      * @stone - Stone to put at the position.
      * @return - Ok if checker was placed, or one of the MoveError types if the stone was not placed.
      */
     pub fn place_stone_at(&mut self, pos: Vec2, stone: Stone) -> Result<(), MoveError> {
-        if !Board::is_stone_vec_valid(pos) {
-            return Err(MoveError::IndexError(String::from("{pos} not within 0,0 and {BOARD_WIDTH},{BOARD_HEIGHT}")))  
+        if !self.is_stone_vec_valid(pos) {
+            return Err(MoveError::IndexError(format!("{pos} not within 0,0 and {},{}", self.width, self.height)))
         }
-        let idx = Board::vec_to_stone_idx(pos);
+        let idx = self.vec_to_stone_idx(pos);
         let current_piece: Stone = self.stone_board[idx];
-        if current_piece.owner != EMPTY_PLAYER_ID {
+        if current_piece.owner.is_some() {
             return Err(MoveError::OccupiedError);
         }
         // Check for rule of negation
-        for neighbour in Board::checker_neigbours_of_stone(pos) {
-            let checker = self.checker_at_unsafe(neighbour);
-            if checker.owner != EMPTY_PLAYER_ID {
-                return Err(MoveError::NegationError)
+        if self.rules.negation {
+            for neighbour in self.checker_neigbours_of_stone(pos) {
+                let checker = self.checker_at_unsafe(neighbour);
+                if checker.owner.is_some() {
+                    return Err(MoveError::NegationError)
+                }
             }
-        } 
+        }
 
         self.stone_board[idx] = stone;
+        if let Some(player) = stone.owner {
+            self.stone_lists[player.index()].push(pos);
+        }
+        if self.chains_stale {
+            self.refresh_chains(stone.owner);
+        } else {
+            self.chains.add(&self.stone_board, pos);
+        }
+        self.strict_check();
         Ok(())
     }
 
     /**
      * checker_neighbours_of_stone
-     * Returns a list of all valid checker neighbours of the stone at @pos.
+     * Returns an iterator over all valid checker neighbours of the stone at @pos.
      * Since stones surround check squares, for a vector pos, the possible
      * valid neighbours are {(pos), (pos.x - 1, pos.y), (pos.x, pos.y - 1), (pos.x - 1, pos.y - 1)}
      * Neighbours that do not represent a valid board position are filtered out.
-     * 
+     * Nothing is allocated, since this is called throughout move generation.
      */
-    pub fn checker_neigbours_of_stone(pos: Vec2) -> Vec<Vec2>  {
-        let mut neighbours: Vec<Vec2> = Vec::new();
-        let mut out: Vec<Vec2> = Vec::new();
-        neighbours.push(pos.clone());
-        neighbours.push(pos.left().up());
-        neighbours.push(pos.left());
-        neighbours.push(pos.up());
-
-        for pos in neighbours.iter().rev() {
-            if Board::is_checker_vec_valid(*pos) {
-                out.push(*pos);
-            }
-        }
-
-        out
+    pub fn checker_neigbours_of_stone(&self, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        [pos.up(), pos.left(), pos.left().up(), pos]
+            .into_iter()
+            .filter(move |pos| self.is_checker_vec_valid(*pos))
     }
 
     /**
      * stone_neighbours_of_checker
-     * Returns a list of all valid stone neighbours of the checker at @pos.
+     * Returns an iterator over all valid stone neighbours of the checker at @pos.
      * Since stones surround check squares, for a vector pos, the possible
      * valid neighbours are {(pos), (pos.x + 1, pos.y), (pos.x, pos.y + 1), (pos.x + 1, pos.y + 1)}
      * Neighbours that do not represent a valid board position are filtered out.
      */
-    pub fn stone_neighbours_of_checker(pos: Vec2) -> Vec<Vec2> {
-        let mut neighbours: Vec<Vec2> = Vec::new();
-        let mut out: Vec<Vec2> = Vec::new();
-        neighbours.push(pos.clone());
-        neighbours.push(pos.right().down());
-        neighbours.push(pos.right());
-        neighbours.push(pos.down());
-
-        for pos in neighbours.iter() {
-            if Board::is_stone_vec_valid(*pos) {
-                out.push(*pos);
-            }
-        }
-
-        out
+    pub fn stone_neighbours_of_checker(&self, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        [pos, pos.right().down(), pos.right(), pos.down()]
+            .into_iter()
+            .filter(move |pos| self.is_stone_vec_valid(*pos))
     }
 
     /**
      * checker_neighbours
-     * Given the checker position @pos, return up to 8 neighbours of the square.
-     */
-    pub fn checker_neighbours(pos: Vec2) -> Vec<Vec2> {
-        let mut neighbours: Vec<Vec2> = Vec::new();
-        neighbours.push(pos.down().left());
-        neighbours.push(pos.down().right());
-        neighbours.push(pos.left());
-        neighbours.push(pos.right());
-        neighbours.push(pos.up().left());
-        neighbours.push(pos.up().right());
-        neighbours.push(pos.down());
-        neighbours.push(pos.up());
-
-        let mut out: Vec<Vec2> = Vec::new();
-        for pos in neighbours.iter() {
-            if Board::is_checker_vec_valid(*pos) {
-                out.push(*pos)
-            }
-        }
-        out
+     * Given the checker position @pos, iterate over up to 8 neighbours of the square.
+     */
+    pub fn checker_neighbours(&self, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        let neighbours = [
+            pos.left(),
+            pos.right(),
+            pos.down(),
+            pos.up(),
+            pos.down().left(),
+            pos.down().right(),
+            pos.up().left(),
+            pos.up().right(),
+        ];
+        // Diagonals are the last four
+        let count = if self.rules.orthogonal_checkers { 4 } else { neighbours.len() };
+
+        neighbours
+            .into_iter()
+            .take(count)
+            .filter(move |pos| self.is_checker_vec_valid(*pos))
     }
 
     /**
      * TODO: Test me
      * stone_neighbours
-     * Given the stone position @pos, return up to 4 neighbours of the square.
-     */
-    pub fn stone_neighbours(pos: Vec2) -> Vec<Vec2> {
-        let mut neighbours: Vec<Vec2> = Vec::new();
-        neighbours.push(pos.down());
-        neighbours.push(pos.up());
-        neighbours.push(pos.left());
-        neighbours.push(pos.right());
-
-        let mut out: Vec<Vec2> = Vec::new();
-        for pos in neighbours.iter() {
-            if Board::is_stone_vec_valid(*pos) {
-                out.push(*pos)
-            }
-        }
-        out
-    } 
-    
+     * Given the stone position @pos, iterate over up to 4 neighbours of the square.
+     */
+    pub fn stone_neighbours(&self, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        [pos.down(), pos.up(), pos.left(), pos.right()]
+            .into_iter()
+            .filter(move |pos| self.is_stone_vec_valid(*pos))
+    }
+
     /**
      * checker_at returns the Checker on the board at the provided position or an error.
-     * @pos Vec2 instance that should be between [0, 0] and [BOARD_WIDTH - 1, BOARD_HEIGHT - 1].
-     * @ret Ok containing the Checker, or an Err if position is not a valid checker index.
+     * @pos Vec2 instance that should be between [0, 0] and [width - 1, height - 1].
+     * @ret Ok containing the Checker, or PositionError::NotASquare if position is not a valid checker index.
      */
-    pub fn checker_at<'a>(&'a self, pos: Vec2) -> Result<&'a Checker, ()> {
-        if !Board::is_checker_vec_valid(pos) {
-            Err(())
+    pub fn checker_at(&self, pos: Vec2) -> Result<&Checker, PositionError> {
+        if !self.is_checker_vec_valid(pos) {
+            Err(PositionError::NotASquare(pos))
         } else {
-            let idx: usize = Board::vec_to_checker_idx(pos); 
+            let idx: usize = self.vec_to_checker_idx(pos); 
             Ok(&self.checker_board[idx])
         }
     }
 
-    pub fn mut_checker_at<'a>(&'a mut self, pos: Vec2) -> Result<&'a mut Checker, ()> {
-        if !Board::is_checker_vec_valid(pos) {
-            Err(())
+    pub fn mut_checker_at(&mut self, pos: Vec2) -> Result<&mut Checker, PositionError> {
+        if !self.is_checker_vec_valid(pos) {
+            Err(PositionError::NotASquare(pos))
         } else {
-            let idx: usize = Board::vec_to_checker_idx(pos); 
+            self.occupancy_stale = true;
+            let idx: usize = self.vec_to_checker_idx(pos); 
             Ok(&mut self.checker_board[idx])
         }
     }
@@ -533,36 +1150,37 @@ impl Board {
     /**
      * stone_at returns the Stone on the board at the provided position, or an error
      * if the position was not in range.
-     * @pos Vec2 instance that should be between [0, 0] and [BOARD_WIDTH, BOARD_HEIGHT] inclusive.
-     * @ret Ok containing the stone, or an Err if position is not a valid stone index. 
+     * @pos Vec2 instance that should be between [0, 0] and [width, height] inclusive.
+     * @ret Ok containing the stone, or PositionError::NotACorner if position is not a valid stone index.
      */
-    pub fn stone_at<'a>(&'a self, pos: Vec2) -> Result<&'a Stone, ()> {
-        if !Board::is_stone_vec_valid(pos) {
-            Err(())
+    pub fn stone_at(&self, pos: Vec2) -> Result<&Stone, PositionError> {
+        if !self.is_stone_vec_valid(pos) {
+            Err(PositionError::NotACorner(pos))
         } else {
-            let idx: usize = Board::vec_to_stone_idx(pos); 
+            let idx: usize = self.vec_to_stone_idx(pos); 
             Ok(&self.stone_board[idx])
         }
     }
 
-    pub fn mut_stone_at<'a>(&'a mut self, pos: Vec2) -> Result<&'a mut Stone, ()> {
-        if !Board::is_stone_vec_valid(pos) {
-            Err(())
+    pub fn mut_stone_at(&mut self, pos: Vec2) -> Result<&mut Stone, PositionError> {
+        if !self.is_stone_vec_valid(pos) {
+            Err(PositionError::NotACorner(pos))
         } else {
-            let idx: usize = Board::vec_to_stone_idx(pos); 
+            self.chains_stale = true;
+            let idx: usize = self.vec_to_stone_idx(pos); 
             Ok(&mut self.stone_board[idx])
         }
     }
 
     // Use when @pos has already been bounds-checked
-    fn checker_at_unsafe<'a>(&'a self, pos: Vec2) -> &'a Checker {
-        let idx: usize = Board::vec_to_checker_idx(pos); 
+    fn checker_at_unsafe(&self, pos: Vec2) -> &Checker {
+        let idx: usize = self.vec_to_checker_idx(pos); 
         &self.checker_board[idx]
     }
 
     // Use when @pos has already been bounds-checked
-    fn stone_at_unsafe<'a>(&'a self, pos: Vec2) -> &'a Stone {
-        let idx: usize = Board::vec_to_stone_idx(pos); 
+    fn stone_at_unsafe(&self, pos: Vec2) -> &Stone {
+        let idx: usize = self.vec_to_stone_idx(pos); 
         &self.stone_board[idx]
     }
 
@@ -571,18 +1189,49 @@ impl Board {
      * @pos - Position to convert into an index.
      * @return - Index into inner stone array.
      */
-    pub fn vec_to_stone_idx(pos: Vec2) -> usize {
+    pub fn vec_to_stone_idx(&self, pos: Vec2) -> usize {
         let x = pos.x as usize;
         let y = pos.y as usize;
-        x + y * (BOARD_WIDTH + 1)
+        x + y * (self.width + 1)
+    }
+
+    /**
+     * raw_checkers
+     * Read the checker squares directly, without the bounds checks of checker_at, for code
+     * that scans the whole board often. The square at x, y is at index x + y * width, the
+     * index vec_to_checker_idx gives; empty squares hold a checker with no owner.
+     * @ret Every checker square, row by row from row 0.
+     */
+    pub fn raw_checkers(&self) -> &[Checker] {
+        &self.checker_board
+    }
+
+    /**
+     * raw_stones
+     * Read the stone corners directly, without the bounds checks of stone_at. The corner at
+     * x, y is at index x + y * (width + 1), the index vec_to_stone_idx gives; empty corners
+     * hold a stone with no owner.
+     * @ret Every stone corner, row by row from row 0.
+     */
+    pub fn raw_stones(&self) -> &[Stone] {
+        &self.stone_board
+    }
+
+    /**
+     * raw_terrain
+     * Read the terrain of the checker squares directly, indexed as raw_checkers is.
+     * @ret Terrain of every checker square, row by row from row 0.
+     */
+    pub fn raw_terrain(&self) -> &[Terrain] {
+        &self.terrain
     }
 
-    fn is_checker_vec_valid(pos: Vec2) -> bool {
-        !(pos.x < 0 || pos.y < 0 || pos.x >= BOARD_WIDTH as i32 || pos.y >= BOARD_HEIGHT as i32)
+    fn is_checker_vec_valid(&self, pos: Vec2) -> bool {
+        !(pos.x < 0 || pos.y < 0 || pos.x >= self.width as i32 || pos.y >= self.height as i32)
     }
 
-    fn is_stone_vec_valid(pos: Vec2) -> bool {
-        !(pos.x < 0 || pos.y < 0 || pos.x >= (BOARD_WIDTH + 1) as i32 || pos.y >= (BOARD_HEIGHT + 1) as i32)
+    fn is_stone_vec_valid(&self, pos: Vec2) -> bool {
+        !(pos.x < 0 || pos.y < 0 || pos.x > self.width as i32 || pos.y > self.height as i32)
     }
 
     /**
@@ -590,24 +1239,38 @@ impl Board {
      * @pos - Position to convert into an index.
      * @return - Index into inner checker array.
      */
-    pub fn vec_to_checker_idx(pos: Vec2) -> usize {
+    pub fn vec_to_checker_idx(&self, pos: Vec2) -> usize {
         let x = pos.x as usize;
         let y = pos.y as usize;
-        x + y * BOARD_WIDTH
+        x + y * self.width
     }
 
     /**
      * stones_for_player returns the positions of all stones belonging
-     * to @player.
+     * to @player. O(stones) for either player, read from lists kept up to date
+     * as stones are placed and slid.
      * @player Player id to match against stone.owner.
-     * @ret Vec containing positions of stones owned by player.
+     * @ret Vec containing positions of stones owned by player, by column and then row.
+     */
+    pub fn stones_for_player(&self, player: PlayerId) -> Vec<Vec2> {
+        if self.chains_stale {
+            return self.scan_stones(Some(player));
+        }
+        let mut stones = self.stone_lists[player.index()].clone();
+        stones.sort_by_key(|pos| (pos.x, pos.y));
+        stones
+    }
+
+    /*
+     * Helper function finding the stones owned by @owner, or the empty corners for None,
+     * by looking at every corner.
      */
-    pub fn stones_for_player(&self, player: i32) -> Vec<Vec2> {
+    fn scan_stones(&self, owner: Option<PlayerId>) -> Vec<Vec2> {
         let mut stones: Vec<Vec2> = Vec::new();
-        for x in 0 ..=BOARD_WIDTH {
-            for y in 0..=BOARD_HEIGHT {
+        for x in 0..=self.width {
+            for y in 0..=self.height {
                 let pos = Vec2::new(x as i32, y as i32);
-                if self.stone_at_unsafe(pos).owner == player {
+                if self.stone_at_unsafe(pos).owner == owner {
                     stones.push(pos);
                 } 
             }
@@ -617,21 +1280,52 @@ impl Board {
 
     /**
      * checkers_for_player returns the positions of all checkers belonging
-     * to @player.
+     * to @player. O(checkers) for either player, read from the occupancy masks.
      * @player Player ID to match against checker.owner.
-     * @ret Vec containing positions of checkers owned by player.
+     * @ret Vec containing positions of checkers owned by player, by column and then row.
      */
-    pub fn checkers_for_player(&self, player: i32) -> Vec<Vec2> {
-        let mut checkers: Vec<Vec2> = Vec::new();
-        for x in 0 .. BOARD_WIDTH {
-            for y in 0 .. BOARD_HEIGHT {
-                let pos = Vec2::new(x as i32, y as i32);
-                if self.checker_at_unsafe(pos).owner == player {
-                    checkers.push(pos);
-                } 
-            }
-        }
-        checkers
+    pub fn checkers_for_player(&self, player: PlayerId) -> Vec<Vec2> {
+        self.checker_squares(self.occupancy()[player.index()])
+    }
+
+    /**
+     * checkers
+     * List every checker on the board with where it stands, for either player.
+     * @ret Position and checker of each, by column and then row.
+     */
+    pub fn checkers(&self) -> Vec<(Vec2, Checker)> {
+        let occupancy = self.occupancy();
+        self.checker_squares(occupancy[0] | occupancy[1])
+            .into_iter()
+            .map(|pos| (pos, *self.checker_at_unsafe(pos)))
+            .collect()
+    }
+
+    /**
+     * stones
+     * List every stone on the board with where it sits, for either player.
+     * @ret Position and stone of each, by column and then row.
+     */
+    pub fn stones(&self) -> Vec<(Vec2, Stone)> {
+        let mut stones = self.stones_for_player(PlayerId::A);
+        stones.extend(self.stones_for_player(PlayerId::B));
+        stones.sort_by_key(|pos| (pos.x, pos.y));
+        stones.into_iter().map(|pos| (pos, *self.stone_at_unsafe(pos))).collect()
+    }
+
+    /*
+     * Helper function listing the checker squares whose bits are set in @bits, by column and
+     * then row.
+     */
+    fn checker_squares(&self, mut bits: u128) -> Vec<Vec2> {
+        let mut squares: Vec<Vec2> = Vec::with_capacity(bits.count_ones() as usize);
+        while bits != 0 {
+            let idx = bits.trailing_zeros() as usize;
+            squares.push(Vec2::new((idx % self.width) as i32, (idx / self.width) as i32));
+            bits &= bits - 1;
+        }
+        squares.sort_by_key(|pos| (pos.x, pos.y));
+        squares
     }
 
     /**
@@ -640,7 +1334,163 @@ impl Board {
      * @ret Vec containing positions on stone board not containing a stone.
      */
     pub fn empty_stones(&self) -> Vec<Vec2> {
-        self.stones_for_player(EMPTY_PLAYER_ID)
+        self.scan_stones(None)
+    }
+
+    /**
+     * position_hash
+     * Zobrist hash of the pieces on the board. Equal positions hash equally however they
+     * were reached, and the random number generator is not part of the position.
+     * @ret 64 bit hash of the position.
+     */
+    pub fn position_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (idx, checker) in self.checker_board.iter().enumerate() {
+            if let Some(owner) = checker.owner {
+                let slot = (idx * 8 + owner.index() * 4 + checker.height.get()) as u64;
+                hash ^= zobrist_key(ZOBRIST_CHECKER_SLOTS + slot);
+            }
+        }
+        for (idx, stone) in self.stone_board.iter().enumerate() {
+            if let Some(owner) = stone.owner {
+                let slot = (idx * 2 + owner.index()) as u64;
+                hash ^= zobrist_key(ZOBRIST_STONE_SLOTS + slot);
+            }
+        }
+        hash
+    }
+
+    /**
+     * mirrored
+     * Reflect the board top to bottom. Both players still head for the same column, so the
+     * mirrored position plays exactly as this one does with its moves reflected.
+     * @ret Copy of the board with its rows in reverse order, sharing this board's dice.
+     */
+    pub fn mirrored(&self) -> Board {
+        self.transformed(false, true, false)
+    }
+
+    /**
+     * flipped
+     * Reflect the board left to right and swap the owner of every piece, so each player
+     * stands where the other stood. The flipped position with the other player to move
+     * plays exactly as this one does.
+     * @ret Copy of the board with its columns in reverse order and owners swapped, sharing
+     * this board's dice.
+     */
+    pub fn flipped(&self) -> Board {
+        self.transformed(true, false, true)
+    }
+
+    /**
+     * canonical
+     * Pick one board to stand for this position and its mirror image, so symmetric positions
+     * can be stored once. The same side must be to move in both: flipped positions need the
+     * other side to move, so they are not merged.
+     * @ret This board or its mirror image, whichever has the lower position_hash, and true if
+     * it is the mirror image.
+     */
+    pub fn canonical(&self) -> (Board, bool) {
+        let mirrored = self.mirrored();
+        if mirrored.position_hash() < self.position_hash() {
+            (mirrored, true)
+        } else {
+            (self.clone(), false)
+        }
+    }
+
+    /**
+     * canonical_hash
+     * @ret The position_hash of the canonical board, the same for a position and its mirror
+     * image.
+     */
+    pub fn canonical_hash(&self) -> u64 {
+        self.position_hash().min(self.mirrored().position_hash())
+    }
+
+    /*
+     * Helper function copying the board with its columns reversed if @reverse_columns, its
+     * rows reversed if @reverse_rows, and every piece's owner swapped if @swap_owners.
+     */
+    fn transformed(&self, reverse_columns: bool, reverse_rows: bool, swap_owners: bool) -> Board {
+        let mut board = self.clone();
+        // checker squares run 0 to width - 1 across and stone corners 0 to width
+        let reflect = |pos: Vec2, last_x: i32, last_y: i32| Vec2::new(
+            if reverse_columns { last_x - pos.x } else { pos.x },
+            if reverse_rows { last_y - pos.y } else { pos.y },
+        );
+        let owner = |owner: Option<PlayerId>| if swap_owners { owner.map(PlayerId::other) } else { owner };
+        for x in 0..self.width as i32 {
+            for y in 0..self.height as i32 {
+                let (from, to) = (Vec2::new(x, y), reflect(Vec2::new(x, y), self.width as i32 - 1, self.height as i32 - 1));
+                let (from_idx, to_idx) = (self.vec_to_checker_idx(from), self.vec_to_checker_idx(to));
+                let checker = self.checker_board[from_idx];
+                board.checker_board[to_idx] = Checker { owner: owner(checker.owner), ..checker };
+                board.terrain[to_idx] = self.terrain[from_idx];
+            }
+        }
+        for x in 0..=self.width as i32 {
+            for y in 0..=self.height as i32 {
+                let (from, to) = (Vec2::new(x, y), reflect(Vec2::new(x, y), self.width as i32, self.height as i32));
+                let stone = self.stone_board[self.vec_to_stone_idx(from)];
+                let to_idx = board.vec_to_stone_idx(to);
+                board.stone_board[to_idx] = Stone { owner: owner(stone.owner) };
+            }
+        }
+        board.occupancy_stale = true;
+        board.occupancy = board.occupancy();
+        board.occupancy_stale = false;
+        board.chains_stale = true;
+        board.refresh_chains(None);
+        board.strict_check();
+        board
+    }
+
+    /**
+     * check_invariants
+     * Check that the board is self-consistent: every checker's height agrees with its owner,
+     * and the occupancy masks, stone lists, and stone chains agree with the pieces on the
+     * board. O(n) in the size of the board, meant for tests and fuzzing.
+     * @ret Ok, or the first InvariantError found.
+     */
+    pub fn check_invariants(&self) -> Result<(), InvariantError> {
+        for (idx, checker) in self.checker_board.iter().enumerate() {
+            if checker.owner.is_none() != checker.height.is_zero() {
+                return Err(InvariantError::CheckerHeight(Vec2::new((idx % self.width) as i32, (idx / self.width) as i32)));
+            }
+        }
+        let mut fresh_chains = StoneChains::new(self.width, self.height);
+        for player in [PlayerId::A, PlayerId::B] {
+            let mut occupancy = 0u128;
+            for (idx, checker) in self.checker_board.iter().enumerate() {
+                if checker.owner == Some(player) {
+                    occupancy |= 1 << idx;
+                }
+            }
+            if self.occupancy()[player.index()] != occupancy {
+                return Err(InvariantError::Occupancy(player));
+            }
+            if self.chains_stale {
+                continue;
+            }
+            if self.stones_for_player(player) != self.scan_stones(Some(player)) {
+                return Err(InvariantError::StoneList(player));
+            }
+            fresh_chains.rebuild(&self.stone_board, player);
+            if self.chains.connects(player) != fresh_chains.connects(player) {
+                return Err(InvariantError::Chains(player));
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * Helper function checking the board's invariants after a change, in debug builds with
+     * the strict-checks feature. Compiles to nothing otherwise.
+     */
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        debug_assert_eq!(self.check_invariants(), Ok(()));
     }
 
     /**
@@ -649,41 +1499,155 @@ impl Board {
      * ret - String representation of pieces on the board. 
      */
     pub fn as_string(&self) -> String {
+        self.draw(None)
+    }
+
+    /**
+     * as_string_with
+     * Draw the board as as_string does, with the marks of @overlay beside their pieces: a
+     * marked checker's symbol before it, in place of the space, and a marked stone's after it.
+     * @overlay Marks to draw.
+     * @ret String representation of pieces on the board, with the marks.
+     */
+    pub fn as_string_with(&self, overlay: &Overlay) -> String {
+        self.draw(Some(overlay))
+    }
+
+    /*
+     * Helper function drawing the board for as_string and as_string_with.
+     */
+    fn draw(&self, overlay: Option<&Overlay>) -> String {
         let mut string = String::new();
-        for yi in 0..=BOARD_HEIGHT as i32 {
-            for xi in 0..=BOARD_WIDTH as i32 {
-                let idx = Board::vec_to_stone_idx(Vec2::new(xi, yi));
+        for yi in 0..=self.height as i32 {
+            for xi in 0..=self.width as i32 {
+                let idx = self.vec_to_stone_idx(Vec2::new(xi, yi));
                 let stone = self.stone_board[idx];
-                    let mut draw_char: char = EMPTY_STONE;
-                    if stone.owner == PLAYER_A_ID {
-                        draw_char = PLAYER_A_STONE;
-                    } else if stone.owner == PLAYER_B_ID {
-                        draw_char = PLAYER_B_STONE;
-                    }
+                    let draw_char = stone.owner.map_or(self.glyphs.empty_stone, |owner| self.glyphs.stone(owner));
                     string.push(draw_char);
-                    string.push(' ');
+                    let mark = overlay.and_then(|overlay| overlay.stone_mark(Vec2::new(xi, yi)));
+                    string.push(mark.map_or(' ', |mark| mark.symbol()));
                     // print!("{} ", draw_char);
             }
-            string.push_str("\n");
-            if yi >= BOARD_HEIGHT as i32 { continue; }
+            string.push('\n');
+            if yi >= self.height as i32 { continue; }
             
-            for xi in 0..BOARD_WIDTH as i32{
-                let idx = Board::vec_to_checker_idx(Vec2::new(xi, yi));
+            for xi in 0..self.width as i32{
+                let idx = self.vec_to_checker_idx(Vec2::new(xi, yi));
                 let checker = self.checker_board[idx];
-                let mut draw_char: char = EMPTY_CHECKER;
-                if checker.owner == PLAYER_A_ID {
-                    draw_char = PLAYER_A_CHECK[checker.height as usize];
-                } else if checker.owner == PLAYER_B_ID {
-                    draw_char = PLAYER_B_CHECK[checker.height as usize];
-                }
+                let draw_char = checker.owner.map_or(self.glyphs.empty_checker, |owner| self.glyphs.checker(owner, checker.height));
                 // print!(" {}", draw_char);
-                string.push(' ');
+                let mark = overlay.and_then(|overlay| overlay.checker_mark(Vec2::new(xi, yi)));
+                string.push(mark.map_or(' ', |mark| mark.symbol()));
                 string.push(draw_char);
             }
             string.push('\n');
         }
         string
     }
+
+    /**
+     * describe
+     * Describe the position in words, row by row from the top, for reading aloud by a
+     * screen reader. Squares are named by square_name.
+     * @ret A summary line, then one line per row of checkers, each after a line for any
+     * stones on the corners above it, and a line for the stones along the bottom edge.
+     */
+    pub fn describe(&self) -> String {
+        let mut lines = vec![format!("Board of {} columns and {} rows.", self.width, self.height)];
+        for player in [PlayerId::A, PlayerId::B] {
+            lines.push(format!(
+                "Player {} has {} checkers and {} stones on the board.",
+                player,
+                self.checkers_for_player(player).len(),
+                self.stones_for_player(player).len(),
+            ));
+        }
+        for yi in 0..=self.height as i32 {
+            let stones: Vec<String> = (0..=self.width as i32)
+                .map(|xi| Vec2::new(xi, yi))
+                .filter_map(|pos| {
+                    let owner = self.stone_board[self.vec_to_stone_idx(pos)].owner?;
+                    Some(format!("Player {} stone at corner {}", owner, square_name(pos)))
+                })
+                .collect();
+            if !stones.is_empty() {
+                lines.push(format!("Corners {}: {}.", yi + 1, stones.join(", ")));
+            }
+            if yi >= self.height as i32 {
+                continue;
+            }
+            let mut squares = Vec::new();
+            for xi in 0..self.width as i32 {
+                let pos = Vec2::new(xi, yi);
+                let checker = self.checker_board[self.vec_to_checker_idx(pos)];
+                if let Some(owner) = checker.owner {
+                    squares.push(format!("Player {} checker, height {}, at {}", owner, checker.height, square_name(pos)));
+                }
+                match self.terrain_at(pos) {
+                    Terrain::Plain => (),
+                    Terrain::Mud => squares.push(format!("mud at {}", square_name(pos))),
+                    Terrain::Hill => squares.push(format!("hill at {}", square_name(pos))),
+                }
+            }
+            if squares.is_empty() {
+                lines.push(format!("Row {}: empty.", yi + 1));
+            } else {
+                lines.push(format!("Row {}: {}.", yi + 1, squares.join(", ")));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/**
+ * square_name
+ * Name a checker square, or a stone corner, by column letter and row number, eg "c2" for
+ * Vec2::new(2, 1). Corner c2 is the top left corner of square c2.
+ * @pos Square or corner to name.
+ * @ret Name of the position.
+ */
+pub fn square_name(pos: Vec2) -> String {
+    let column = u8::try_from(pos.x).ok().filter(|x| *x < 26).map_or('?', |x| char::from(b'a' + x));
+    format!("{}{}", column, pos.y + 1)
+}
+
+/**
+ * parse_square_name
+ * Read a name written by square_name, in either case.
+ * @name Name of a square or corner, eg "c2".
+ * @ret The position, or None if it is not a name. It may still be off the board.
+ */
+pub fn parse_square_name(name: &str) -> Option<Vec2> {
+    let mut chars = name.chars();
+    let column = chars.next().filter(char::is_ascii_alphabetic)?.to_ascii_lowercase();
+    let row: i32 = chars.as_str().parse().ok().filter(|row| *row >= 1)?;
+    Some(Vec2::new((column as u8 - b'a') as i32, row - 1))
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.variant == other.variant
+            && self.width == other.width
+            && self.height == other.height
+            && self.checker_board == other.checker_board
+            && self.stone_board == other.stone_board
+            && self.terrain == other.terrain
+            && self.rules == other.rules
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.position_hash());
+    }
 }
 
 impl Display for Board {
@@ -701,21 +1665,24 @@ impl Debug for Board {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::game::Checker;
+    use crate::game::{Checker, CheckerHeight};
+    use crate::builder::BoardBuilder;
 
     #[test]
     fn vec_to_checker_idx() {
-        assert_eq!(Board::vec_to_checker_idx(Vec2::new(1, 1)), 9);
-        assert_eq!(Board::vec_to_checker_idx(Vec2::new(0, 1)), 8);
-        assert_eq!(Board::vec_to_checker_idx(Vec2::new(1, 0)), 1);
-        assert_eq!(Board::vec_to_checker_idx(Vec2::new(7, 5)), 47)
+        let board = Board::new();
+        assert_eq!(board.vec_to_checker_idx(Vec2::new(1, 1)), 9);
+        assert_eq!(board.vec_to_checker_idx(Vec2::new(0, 1)), 8);
+        assert_eq!(board.vec_to_checker_idx(Vec2::new(1, 0)), 1);
+        assert_eq!(board.vec_to_checker_idx(Vec2::new(7, 5)), 47)
     }
 
     #[test]
     fn vec_to_stone_idx() {
-        assert_eq!(Board::vec_to_stone_idx(Vec2::new(1, 1)), 10);
-        assert_eq!(Board::vec_to_stone_idx(Vec2::new(0, 5)), 45);
-        assert_eq!(Board::vec_to_stone_idx(Vec2::new(8, 6)), 62);
+        let board = Board::new();
+        assert_eq!(board.vec_to_stone_idx(Vec2::new(1, 1)), 10);
+        assert_eq!(board.vec_to_stone_idx(Vec2::new(0, 5)), 45);
+        assert_eq!(board.vec_to_stone_idx(Vec2::new(8, 6)), 62);
     }
 
     #[test]
@@ -724,11 +1691,70 @@ mod tests {
         check_start_state(&board)
     }
 
+    #[test]
+    fn for_variant() {
+        let variant = Variant::Grand10x8;
+        let board = Board::for_variant(variant);
+        assert_eq!(board.variant(), variant);
+        assert_eq!((board.width(), board.height()), (10, 8));
+        assert_eq!(board.empty_stones().len(), 11 * 9);
+        assert!(board.stone_at(Vec2::new(10, 8)).is_ok());
+        assert_eq!(board.stone_at(Vec2::new(11, 8)), Err(PositionError::NotACorner(Vec2::new(11, 8))));
+        assert!(board.checker_at(Vec2::new(9, 7)).is_ok());
+        assert_eq!(board.checker_at(Vec2::new(10, 7)), Err(PositionError::NotASquare(Vec2::new(10, 7))));
+        assert_eq!(board.vec_to_checker_idx(Vec2::new(1, 1)), 11);
+        assert_eq!(board.vec_to_stone_idx(Vec2::new(1, 1)), 12);
+
+        // Player B's checkers mirror player A's
+        for (pos, height) in variant.starting_checkers() {
+            assert_eq!(*board.checker_at(pos).unwrap(), Checker::new(height, PlayerId::A));
+            assert_eq!(*board.checker_at(Vec2::new(9 - pos.x, pos.y)).unwrap(), Checker::new(height, PlayerId::B));
+        }
+        assert_eq!(board.checkers_for_player(PlayerId::B).len(), variant.starting_checkers().len());
+        assert_eq!(board.as_string().lines().count(), 8 + 9);
+    }
+
+    #[test]
+    fn place_handicap() {
+        let mut board = Board::new();
+        board.place_handicap(PlayerId::B, &Handicap::ExtraChecker(Vec2::new(1, 1), CheckerHeight::TWO)).unwrap();
+        assert_eq!(*board.checker_at(Vec2::new(1, 1)).unwrap(), Checker::new(CheckerHeight::TWO, PlayerId::B));
+
+        let line = Handicap::StoneLine { start: Vec2::new(4, 0), direction: Direction::Down, length: 3 };
+        board.place_handicap(PlayerId::A, &line).unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::A).len(), 3);
+        assert_eq!(board.stone_at(Vec2::new(4, 2)).unwrap().owner, Some(PlayerId::A));
+
+        board.place_handicap(PlayerId::A, &Handicap::ExtraStones(5)).unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::A).len(), 3);
+
+        // Handicap pieces obey the placement rules
+        match board.place_handicap(PlayerId::A, &Handicap::ExtraChecker(Vec2::new(0, 1), CheckerHeight::ONE)) {
+            Err(MoveError::OccupiedError) => (),
+            _ => panic!("Expected an OccupiedError")
+        }
+        let blocked = Handicap::StoneLine { start: Vec2::new(1, 0), direction: Direction::Down, length: 3 };
+        match board.place_handicap(PlayerId::A, &blocked) {
+            Err(MoveError::NegationError) => (),
+            _ => panic!("Expected a NegationError")
+        }
+    }
+
+    #[test]
+    fn clear_checkers() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(4, 4), Stone::new(PlayerId::A)).unwrap();
+        board.clear_checkers();
+        assert!(board.checkers_for_player(PlayerId::A).is_empty());
+        assert!(board.checkers_for_player(PlayerId::B).is_empty());
+        assert_eq!(board.stones_for_player(PlayerId::A), vec![Vec2::new(4, 4)]);
+    }
+
     #[test]
     fn reset() {
         let mut board = Board::new();
-        board.place_stone_at(Vec2::new(0, 0), Stone::new(PLAYER_A_ID)).unwrap();
-        board.place_checker_at(Vec2::new(4, 4), Checker::new(2, PLAYER_A_ID)).unwrap();
+        board.place_stone_at(Vec2::new(0, 0), Stone::new(PlayerId::A)).unwrap();
+        board.place_checker_at(Vec2::new(4, 4), Checker::new(CheckerHeight::TWO, PlayerId::A)).unwrap();
         board.reset();
         check_start_state(&board)
     }
@@ -740,33 +1766,33 @@ mod tests {
                 let stone = board.stone_at(
                     Vec2::new(x as i32, y as i32)
                 );
-                assert_eq!(stone.unwrap().owner, EMPTY_PLAYER_ID)
+                assert_eq!(stone.unwrap().owner, None)
             }
         }
-        let empty_c = Checker::new(0, EMPTY_PLAYER_ID);
+        let empty_c = Checker::empty();
         // On the checkerboard,
         // [0, 1] and [0, 4] should have 2-stack player B pieces
-        let b2 = Checker::new(2, PLAYER_B_ID);
+        let b2 = Checker::new(CheckerHeight::TWO, PlayerId::B);
         assert_eq!(*board.checker_at(Vec2::new(0, 1)).unwrap(), b2);
         assert_eq!(*board.checker_at(Vec2::new(0, 4)).unwrap(), b2);
         // [0, 2] and [0, 3] should have 3-stack player B pieces
-        let b3 = Checker::new(3, PLAYER_B_ID);
+        let b3 = Checker::new(CheckerHeight::THREE, PlayerId::B);
         assert_eq!(*board.checker_at(Vec2::new(0, 2)).unwrap(), b3);
         assert_eq!(*board.checker_at(Vec2::new(0, 3)).unwrap(), b3);
         // [1, 2] and [1, 3] should have 1-stack player B pieces
-        let b1 = Checker::new(1, PLAYER_B_ID);
+        let b1 = Checker::new(CheckerHeight::ONE, PlayerId::B);
         assert_eq!(*board.checker_at(Vec2::new(1, 2)).unwrap(), b1);
         assert_eq!(*board.checker_at(Vec2::new(1, 3)).unwrap(), b1);
         // [7, 1] and [7, 4] should have 2-stack player A pieces
-        let a2 = Checker::new(2, PLAYER_A_ID);
+        let a2 = Checker::new(CheckerHeight::TWO, PlayerId::A);
         assert_eq!(*board.checker_at(Vec2::new(7, 1)).unwrap(), a2);
         assert_eq!(*board.checker_at(Vec2::new(7, 4)).unwrap(), a2);
         // [7, 2] and [7, 3] should have 3-stack player A pieces
-        let a3 = Checker::new(3, PLAYER_A_ID);
+        let a3 = Checker::new(CheckerHeight::THREE, PlayerId::A);
         assert_eq!(*board.checker_at(Vec2::new(7, 2)).unwrap(), a3);
         assert_eq!(*board.checker_at(Vec2::new(7, 3)).unwrap(), a3);
         // [6, 2] and [6, 3] should have 1-stack player A pieces
-        let a1 = Checker::new(1, PLAYER_A_ID);
+        let a1 = Checker::new(CheckerHeight::ONE, PlayerId::A);
         assert_eq!(*board.checker_at(Vec2::new(6, 2)).unwrap(), a1);
         assert_eq!(*board.checker_at(Vec2::new(6, 3)).unwrap(), a1);   
         
@@ -798,19 +1824,21 @@ mod tests {
 
     #[test]
     fn place_checker_at() {
-        let c1 = Checker::new(1, PLAYER_A_ID);
+        let c1 = Checker::new(CheckerHeight::ONE, PlayerId::A);
         let mut board = Board::new();
         assert_eq!(board.place_checker_at(Vec2::new(1, 1), c1).unwrap(), ());
         match board.place_checker_at(Vec2::new(-1, -1), c1) {
             Err(MoveError::IndexError(_)) => (),
             Err(MoveError::NegationError) => panic!("Expected an IndexError, got a NegationError"),
             Err(MoveError::OccupiedError) => panic!("Expected an IndexError, got an OccupiedError"),
+            Err(MoveError::UnreachableError) => panic!("Expected an IndexError, got an UnreachableError"),
             Ok(()) => panic!("Expected an IndexError, no error")
         }
         match board.place_checker_at(Vec2::new(1, 1), c1) {
             Err(MoveError::OccupiedError) => (),
             Err(MoveError::NegationError) => panic!("Expected an OccupiedError, got a NegationError"),
             Err(MoveError::IndexError(_)) => panic!("Expected an OccupiedError, got an IndexError"),
+            Err(MoveError::UnreachableError) => panic!("Expected an OccupiedError, got an UnreachableError"),
             Ok(()) => panic!("Expected an OccupiedError, no error")
         }
         assert_eq!(*board.checker_at(Vec2::new(1, 1)).unwrap(), c1);
@@ -818,8 +1846,8 @@ mod tests {
 
     #[test]
     fn place_stone_at() {
-        let c1 = Checker::new(1, PLAYER_A_ID);
-        let s1 = Stone::new(PLAYER_A_ID);
+        let c1 = Checker::new(CheckerHeight::ONE, PlayerId::A);
+        let s1 = Stone::new(PlayerId::A);
         let s1_pos = Vec2::new(3, 3);
         let mut board = Board::new();
         assert_eq!(board.place_stone_at(s1_pos, s1).unwrap(), ());
@@ -829,6 +1857,7 @@ mod tests {
             Err(MoveError::OccupiedError) => (),
             Err(MoveError::NegationError) => panic!("Expected an OccupiedError, got a NegationError"),
             Err(MoveError::IndexError(_)) => panic!("Expected an OccupiedError, got an IndexError"),
+            Err(MoveError::UnreachableError) => panic!("Expected an OccupiedError, got an UnreachableError"),
             Ok(()) => panic!("Expected an OccupiedError, got no error")
         }
         board.place_checker_at(Vec2::new(4, 2), c1).unwrap();
@@ -837,6 +1866,7 @@ mod tests {
             Err(MoveError::NegationError) => (),
             Err(MoveError::OccupiedError) => panic!("Expected a NegationError, got a OccupiedError"),
             Err(MoveError::IndexError(_)) => panic!("Expected a NegationError, got an IndexError"),
+            Err(MoveError::UnreachableError) => panic!("Expected an NegationError, got an UnreachableError"),
             Ok(()) => panic!("Expected an NegationError, got no error")
         }
         // index error
@@ -844,6 +1874,7 @@ mod tests {
             Err(MoveError::IndexError(_)) => (),
             Err(MoveError::OccupiedError) => panic!("Expected an IndexError, got an OccupiedError"),
             Err(MoveError::NegationError) => panic!("Expected an IndexError, got a NegationError"),
+            Err(MoveError::UnreachableError) => panic!("Expected an IndexError, got an UnreachableError"),
             Ok(()) => panic!("Expected an IndexError, got no error")
         }
 
@@ -851,41 +1882,43 @@ mod tests {
     
     #[test]
     fn checker_neighbours_of_stone() {
-        let neighbours = Board::checker_neigbours_of_stone(Vec2::new(1, 1));
+        let board = Board::new();
+        let neighbours: Vec<Vec2> = board.checker_neigbours_of_stone(Vec2::new(1, 1)).collect();
         assert!(neighbours.contains(&Vec2::new(0, 0)));
         assert!(neighbours.contains(&Vec2::new(0, 1)));
         assert!(neighbours.contains(&Vec2::new(1, 0)));
         assert!(neighbours.contains(&Vec2::new(1, 1)));
 
         // Top left corner
-        let nw_neighbours = Board::checker_neigbours_of_stone(Vec2::new(0, 0));
+        let nw_neighbours: Vec<Vec2> = board.checker_neigbours_of_stone(Vec2::new(0, 0)).collect();
         assert!(nw_neighbours.contains(&Vec2::new(0, 0)));
 
-        let ne_neighbours = Board::checker_neigbours_of_stone(Vec2::new(BOARD_WIDTH as i32, 0));
+        let ne_neighbours: Vec<Vec2> = board.checker_neigbours_of_stone(Vec2::new(BOARD_WIDTH as i32, 0)).collect();
         assert!(ne_neighbours.contains(&Vec2::new((BOARD_WIDTH - 1) as i32, 0)));
 
-        let sw_neighbours = Board::checker_neigbours_of_stone(Vec2::new(0, BOARD_HEIGHT as i32));
+        let sw_neighbours: Vec<Vec2> = board.checker_neigbours_of_stone(Vec2::new(0, BOARD_HEIGHT as i32)).collect();
         assert!(sw_neighbours.contains(&Vec2::new(0, (BOARD_HEIGHT - 1) as i32)));
 
-        let se_neighbours = Board::checker_neigbours_of_stone(Vec2::new(BOARD_WIDTH as i32, BOARD_HEIGHT as i32));
+        let se_neighbours: Vec<Vec2> = board.checker_neigbours_of_stone(Vec2::new(BOARD_WIDTH as i32, BOARD_HEIGHT as i32)).collect();
         assert!(se_neighbours.contains(&Vec2::new((BOARD_WIDTH - 1) as i32, (BOARD_HEIGHT - 1) as i32)));
 
         // Edge
-        let edge_neighbours = Board::checker_neigbours_of_stone(Vec2::new(0, 3));
+        let edge_neighbours: Vec<Vec2> = board.checker_neigbours_of_stone(Vec2::new(0, 3)).collect();
         assert!(edge_neighbours.contains(&Vec2::new(0, 2)));
         assert!(edge_neighbours.contains(&Vec2::new(0, 3)));
     }
 
     #[test]
     fn stone_neighbours_of_checker() {
-        let normal = Board::stone_neighbours_of_checker(Vec2::new(1, 1));
+        let board = Board::new();
+        let normal: Vec<Vec2> = board.stone_neighbours_of_checker(Vec2::new(1, 1)).collect();
         assert!(normal.contains(&Vec2::new(1, 1)));
         assert!(normal.contains(&Vec2::new(1, 2)));
         assert!(normal.contains(&Vec2::new(2, 1)));
         assert!(normal.contains(&Vec2::new(2, 2)));
 
         // lower right corner
-        let se_neighbours = Board::stone_neighbours_of_checker(Vec2::new((BOARD_WIDTH - 1) as i32, (BOARD_HEIGHT - 1) as i32));
+        let se_neighbours: Vec<Vec2> = board.stone_neighbours_of_checker(Vec2::new((BOARD_WIDTH - 1) as i32, (BOARD_HEIGHT - 1) as i32)).collect();
         assert!(se_neighbours.contains(&Vec2::new((BOARD_WIDTH - 1) as i32, (BOARD_HEIGHT - 1) as i32)));
         assert!(se_neighbours.contains(&Vec2::new(BOARD_WIDTH as i32, (BOARD_HEIGHT - 1) as i32)));
         assert!(se_neighbours.contains(&Vec2::new((BOARD_WIDTH - 1) as i32, BOARD_HEIGHT as i32)));
@@ -894,9 +1927,10 @@ mod tests {
 
     #[test]
     fn checker_neighbours() {
+        let board = Board::new();
         // Case 1: Middle of board, 8 neighbours
         let mid_v = Vec2::new(3, 3);
-        let middle = Board::checker_neighbours(mid_v);
+        let middle: Vec<Vec2> = board.checker_neighbours(mid_v).collect();
         assert!(middle.contains(&mid_v.left()));
         assert!(middle.contains(&mid_v.right()));
         assert!(middle.contains(&mid_v.left().up()));
@@ -907,13 +1941,13 @@ mod tests {
         assert!(middle.contains(&mid_v.down()));
         // Case 2: Corner, 3 neighbours
         let cor_v = Vec2::new(0, 0);
-        let corner = Board::checker_neighbours(cor_v);
+        let corner: Vec<Vec2> = board.checker_neighbours(cor_v).collect();
         assert!(corner.contains(&cor_v.down()));
         assert!(corner.contains(&cor_v.right()));
         assert!(corner.contains(&cor_v.down().right()));
         // Case 3: Edge, 5 neighbours
         let edge_v = Vec2::new(0, 3);
-        let edge = Board::checker_neighbours(edge_v);
+        let edge: Vec<Vec2> = board.checker_neighbours(edge_v).collect();
         assert!(edge.contains(&edge_v.down()));
         assert!(edge.contains(&edge_v.down().right()));
         assert!(edge.contains(&edge_v.right()));
@@ -921,23 +1955,37 @@ mod tests {
         assert!(edge.contains(&edge_v.up().right()));
     }
 
+    #[test]
+    fn orthogonal_checker_neighbours() {
+        let mut board = Board::new();
+        board.set_rules(RulesConfig { orthogonal_checkers: true, ..RulesConfig::default() });
+        let mid_v = Vec2::new(3, 3);
+        let middle: Vec<Vec2> = board.checker_neighbours(mid_v).collect();
+        assert_eq!(middle.len(), 4);
+        for neighbour in [mid_v.left(), mid_v.right(), mid_v.up(), mid_v.down()] {
+            assert!(middle.contains(&neighbour));
+        }
+        assert_eq!(board.checker_neighbours(Vec2::new(0, 0)).count(), 2);
+    }
+
     #[test]
     fn stone_neighbours() {
+        let board = Board::new();
         // Case 1: Middle of board, 4 neighbours
         let middle_vector = Vec2::new(3, 3);
-        let middle_neighbours = Board::stone_neighbours(middle_vector);
+        let middle_neighbours: Vec<Vec2> = board.stone_neighbours(middle_vector).collect();
         assert!(middle_neighbours.contains(&middle_vector.up()));
         assert!(middle_neighbours.contains(&middle_vector.down()));
         assert!(middle_neighbours.contains(&middle_vector.left()));
         assert!(middle_neighbours.contains(&middle_vector.right()));
         // Case 2: Corner of board, 2 neighbours
         let corner_vector = Vec2::new(0, 0);
-        let corner_neighbours = Board::stone_neighbours(corner_vector);
+        let corner_neighbours: Vec<Vec2> = board.stone_neighbours(corner_vector).collect();
         assert!(corner_neighbours.contains(&corner_vector.down()));
         assert!(corner_neighbours.contains(&corner_vector.right()));
         // Case 3: Edge of board, 3 neighbours
         let edge_vector = Vec2::new(0, 3);
-        let edge_neighbours = Board::stone_neighbours(edge_vector);
+        let edge_neighbours: Vec<Vec2> = board.stone_neighbours(edge_vector).collect();
         assert!(edge_neighbours.contains(&edge_vector.down()));
         assert!(edge_neighbours.contains(&edge_vector.right()));
         assert!(edge_neighbours.contains(&edge_vector.up()));
@@ -947,11 +1995,11 @@ mod tests {
     fn stones_for_player() {
         // Create board, place some stones, verify that list contains all placed stones
         let mut board = Board::new();
-        let positions = vec![Vec2::new(0, 0), Vec2::new(5, 5), Vec2::new(4, 2)];
+        let positions = [Vec2::new(0, 0), Vec2::new(5, 5), Vec2::new(4, 2)];
         for pos in positions.iter() {
-            board.place_stone_at(*pos, Stone::new(PLAYER_A_ID)).unwrap();
+            board.place_stone_at(*pos, Stone::new(PlayerId::A)).unwrap();
         }
-        let stones = board.stones_for_player(PLAYER_A_ID);
+        let stones = board.stones_for_player(PlayerId::A);
         for pos in positions.iter() {
             assert!(stones.contains(pos))
         }
@@ -961,17 +2009,46 @@ mod tests {
     #[test]
     fn checkers_for_player() {
         let board = Board::new();
-        let checkers_a = board.checkers_for_player(PLAYER_B_ID);
-        let positions = vec![
-            Vec2::new(0, 1), Vec2::new(0, 2), Vec2::new(0, 3), 
-            Vec2::new(0, 4), Vec2::new(1, 2), Vec2::new(1, 3)
-        ];
+        let checkers_a = board.checkers_for_player(PlayerId::B);
+        let positions = [Vec2::new(0, 1), Vec2::new(0, 2), Vec2::new(0, 3), 
+            Vec2::new(0, 4), Vec2::new(1, 2), Vec2::new(1, 3)];
         for pos in positions.iter() {
             assert!(checkers_a.contains(pos));
         }
         assert!(positions.len() == checkers_a.len())
     }
 
+    #[test]
+    fn piece_lists() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::A)).unwrap();
+        board.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::B)).unwrap();
+        board.slide_stone(Vec2::new(4, 0), Direction::Down).unwrap();
+        board.move_checker(Vec2::new(1, 2), Vec2::new(2, 2)).unwrap();
+        for player in [PlayerId::A, PlayerId::B] {
+            assert_eq!(board.stones_for_player(player), board.scan_stones(Some(player)));
+            let scanned: Vec<Vec2> = (0..BOARD_WIDTH as i32)
+                .flat_map(|x| (0..BOARD_HEIGHT as i32).map(move |y| Vec2::new(x, y)))
+                .filter(|pos| board.checker_at(*pos).unwrap().owner == Some(player))
+                .collect();
+            assert_eq!(board.checkers_for_player(player), scanned);
+        }
+        assert_eq!(board.stones_for_player(PlayerId::A), vec![Vec2::new(4, 6)]);
+
+        // Changes through the mut accessors are seen, and the lists recover after them
+        board.mut_stone_at(Vec2::new(3, 3)).unwrap().owner = Some(PlayerId::A);
+        *board.mut_checker_at(Vec2::new(0, 1)).unwrap() = Checker::empty();
+        assert_eq!(board.stones_for_player(PlayerId::A), vec![Vec2::new(3, 3), Vec2::new(4, 6)]);
+        assert!(!board.checkers_for_player(PlayerId::B).contains(&Vec2::new(0, 1)));
+        board.place_stone_at(Vec2::new(4, 3), Stone::new(PlayerId::B)).unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::B), vec![Vec2::new(4, 3)]);
+        assert_eq!(board.stones_for_player(PlayerId::A), vec![Vec2::new(3, 3), Vec2::new(4, 6)]);
+
+        board.reset();
+        assert!(board.stones_for_player(PlayerId::A).is_empty());
+        assert_eq!(board.checkers_for_player(PlayerId::B).len(), 6);
+    }
+
     #[test]
     fn empty_stones() {
         let board = Board::new();
@@ -995,11 +2072,11 @@ mod tests {
             Ok(_) => panic!("Expected an IndexError, got no error")
         }
 
-        // board.place_stone_at(Vec2::new(2, 2), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(4, 2), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(3, 1), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(3, 3), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(3, 2), Stone::new(PLAYER_B_ID)).unwrap();
+        // board.place_stone_at(Vec2::new(2, 2), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(4, 2), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 1), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 2), Stone::new(PlayerId::B)).unwrap();
         match board.slide_stone(Vec2::new(3, 2), Direction::Up) {
             Err(SlideError::BlockedError) => (),
             Err(SlideError::IndexError) => panic!("Expected a BlockedError, got an IndexError"),
@@ -1011,23 +2088,38 @@ mod tests {
         board.slide_stone(board_edge_pos, Direction::Up).unwrap();
         // Previous position should be empty
         match board.stone_at(board_edge_pos) {
-            Ok(stone) => assert_eq!(stone.owner, EMPTY_PLAYER_ID),
+            Ok(stone) => assert_eq!(stone.owner, None),
             Err(_) => panic!("Expected to slide stone at 2,2 upward, got error instead")
         }
         // Board edge position should be occupied
         match board.stone_at(Vec2::new(4, 0)) {
-            Ok(stone) => assert_eq!(stone.owner, PLAYER_B_ID),
+            Ok(stone) => assert_eq!(stone.owner, Some(PlayerId::B)),
             Err(_) => panic!("Expected to slide stone at 4,2 upward, got error instead")
         }
         // Normal case -- does it stop when hitting another stone?
         let board_hit_pos = Vec2::new(4, 4);
-        board.place_stone_at(board_hit_pos, Stone::new(PLAYER_B_ID)).unwrap();
+        board.place_stone_at(board_hit_pos, Stone::new(PlayerId::B)).unwrap();
         board.slide_stone(board_hit_pos, Direction::Up).unwrap();
         // (4, 4) should be empty
-        assert_eq!(board.stone_at(board_hit_pos).unwrap().owner, EMPTY_PLAYER_ID);
+        assert_eq!(board.stone_at(board_hit_pos).unwrap().owner, None);
         // Stone should slide to (4,1), because it is blocked by (4,0)
-        assert_eq!(board.stone_at(Vec2::new(4, 1)).unwrap().owner, PLAYER_B_ID);
+        assert_eq!(board.stone_at(Vec2::new(4, 1)).unwrap().owner, Some(PlayerId::B));
+
+    }
 
+    #[test]
+    fn slide_limit() {
+        let mut board = Board::new();
+        board.set_rules(RulesConfig { slide_limit: Some(2), ..RulesConfig::default() });
+        let start = Vec2::new(4, 0);
+        board.place_stone_at(start, Stone::new(PlayerId::A)).unwrap();
+        assert_eq!(board.slide_stone_result(start, Direction::Down).unwrap(), Vec2::new(4, 2));
+        assert_eq!(board.slide_stone(start, Direction::Down).unwrap(), Vec2::new(4, 2));
+        assert_eq!(board.stone_at(Vec2::new(4, 2)).unwrap().owner, Some(PlayerId::A));
+
+        // A blocker inside the limit still stops the stone early
+        board.place_stone_at(Vec2::new(4, 4), Stone::new(PlayerId::B)).unwrap();
+        assert_eq!(board.slide_stone_result(Vec2::new(4, 2), Direction::Down).unwrap(), Vec2::new(4, 3));
     }
 
     #[test]
@@ -1039,11 +2131,11 @@ mod tests {
             Ok(_) => panic!("Expected an IndexError, got no error")
         }
 
-        // board.place_stone_at(Vec2::new(2, 2), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(4, 2), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(3, 1), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(3, 3), Stone::new(PLAYER_B_ID)).unwrap();
-        board.place_stone_at(Vec2::new(3, 2), Stone::new(PLAYER_B_ID)).unwrap();
+        // board.place_stone_at(Vec2::new(2, 2), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(4, 2), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 1), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 2), Stone::new(PlayerId::B)).unwrap();
         match board.slide_stone_result(Vec2::new(3, 2), Direction::Up) {
             Err(SlideError::BlockedError) => (),
             Err(SlideError::IndexError) => panic!("Expected a BlockedError, got an IndexError"),
@@ -1060,7 +2152,7 @@ mod tests {
         
         // Normal case -- does it stop when hitting another stone?
         let board_hit_pos = Vec2::new(4, 4);
-        board.place_stone_at(board_hit_pos, Stone::new(PLAYER_B_ID)).unwrap();
+        board.place_stone_at(board_hit_pos, Stone::new(PlayerId::B)).unwrap();
         match board.slide_stone_result(board_hit_pos, Direction::Up) {
             Err(SlideError::BlockedError) => panic!("Expected to slide stone at 2,2 upward, got BlockedError instead"),
             Err(SlideError::IndexError) => panic!("Expected to slide stone at 2,2 upward, got IndexError instead"),
@@ -1074,7 +2166,7 @@ mod tests {
         // Seed me
         let mut board = Board::new();
         // Normal case, checker takes damage and dies
-        board.place_checker_at(Vec2::new(5, 2), Checker::new(3, PLAYER_B_ID)).unwrap();
+        board.place_checker_at(Vec2::new(5, 2), Checker::new(CheckerHeight::THREE, PlayerId::B)).unwrap();
         match board.can_fire_checker_at(Vec2::new(5, 2)) {
             Ok(num_attackers) => assert_eq!(num_attackers, 4),
             Err(_) => panic!("Got error when expecting no error")
@@ -1095,27 +2187,143 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pieces() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::A)).unwrap();
+        assert_eq!(board.stones(), vec![(Vec2::new(3, 3), Stone::new(PlayerId::A)), (Vec2::new(4, 0), Stone::new(PlayerId::B))]);
+
+        let checkers = board.checkers();
+        let count = |player| board.checkers_for_player(player).len();
+        assert_eq!(checkers.len(), count(PlayerId::A) + count(PlayerId::B));
+        assert!(checkers.iter().all(|(pos, checker)| board.checker_at(*pos).unwrap() == checker && checker.owner.is_some()));
+        assert!(checkers.windows(2).all(|pair| (pair[0].0.x, pair[0].0.y) < (pair[1].0.x, pair[1].0.y)));
+        assert!(Board::new().stones().is_empty());
+    }
+
+    #[test]
+    fn raw_views() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(8, 6), Stone::new(PlayerId::B)).unwrap();
+        board.set_terrain(Vec2::new(2, 3), Terrain::Mud).unwrap();
+        assert_eq!(board.raw_checkers().len(), board.width() * board.height());
+        assert_eq!(board.raw_stones().len(), (board.width() + 1) * (board.height() + 1));
+        for pos in [Vec2::new(0, 0), Vec2::new(6, 2), Vec2::new(7, 5)] {
+            assert_eq!(&board.raw_checkers()[board.vec_to_checker_idx(pos)], board.checker_at(pos).unwrap());
+        }
+        assert_eq!(board.raw_stones().last().unwrap().owner, Some(PlayerId::B));
+        assert_eq!(board.raw_terrain()[board.vec_to_checker_idx(Vec2::new(2, 3))], Terrain::Mud);
+        let owned = board.raw_checkers().iter().filter(|checker| checker.owner == Some(PlayerId::A)).count();
+        assert_eq!(owned, board.checkers_for_player(PlayerId::A).len());
+    }
+
+    #[test]
+    fn symmetry() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(0, 0), Stone::new(PlayerId::A)).unwrap();
+        board.place_checker_at(Vec2::new(1, 2), Checker::empty()).unwrap();
+        board.set_terrain(Vec2::new(3, 0), Terrain::Hill).unwrap();
+
+        let mirrored = board.mirrored();
+        assert_eq!(mirrored.stone_at(Vec2::new(0, 6)).unwrap().owner, Some(PlayerId::A));
+        assert_eq!(mirrored.checker_at(Vec2::new(1, 3)).unwrap().owner, None);
+        assert_eq!(mirrored.terrain_at(Vec2::new(3, 5)), Terrain::Hill);
+        assert_eq!(mirrored.mirrored(), board);
+        assert_eq!(mirrored.check_invariants(), Ok(()));
+
+        let flipped = board.flipped();
+        assert_eq!(flipped.stone_at(Vec2::new(8, 0)).unwrap().owner, Some(PlayerId::B));
+        assert_eq!(flipped.checker_at(Vec2::new(6, 2)).unwrap().owner, None);
+        assert_eq!(flipped.checkers_for_player(PlayerId::A).len(), board.checkers_for_player(PlayerId::B).len());
+        assert_eq!(flipped.flipped(), board);
+        assert_eq!(flipped.check_invariants(), Ok(()));
+
+        // A position and its mirror image share one canonical form
+        let (canonical, reflected) = board.canonical();
+        assert_eq!(mirrored.canonical().0, canonical);
+        assert_ne!(mirrored.canonical().1, reflected);
+        assert_eq!(board.canonical_hash(), mirrored.canonical_hash());
+        assert_eq!(board.canonical_hash(), canonical.position_hash());
+    }
+
+    #[test]
+    fn shortest_path_to_goal() {
+        let mut board = BoardBuilder::new().checker(3, 2, PlayerId::A, 1).build().unwrap();
+        let start = Vec2::new(3, 2);
+        let (steps, path) = board.shortest_path_to_goal(start, PlayerId::A).unwrap();
+        assert_eq!(steps, 3);
+        assert_eq!(path.len(), 3);
+        assert_eq!(path.last().unwrap().x, 0);
+        assert!(path.windows(2).all(|pair| board.checker_can_step(pair[0], pair[1])));
+        assert_eq!(board.shortest_path_to_goal(start, PlayerId::B).unwrap().0, 4);
+        assert_eq!(board.shortest_path_to_goal(Vec2::new(0, 4), PlayerId::A), Some((0, Vec::new())));
+
+        // A wall of checkers cuts every route, and a gap in it means a detour
+        for y in 1..board.height() as i32 {
+            board.place_checker_at(Vec2::new(1, y), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        }
+        assert_eq!(board.shortest_path_to_goal(start, PlayerId::A).unwrap().0, 3);
+        board.place_checker_at(Vec2::new(1, 0), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        assert_eq!(board.shortest_path_to_goal(start, PlayerId::A), None);
+        assert_eq!(board.shortest_path_to_goal(Vec2::new(-1, 0), PlayerId::A), None);
+    }
+
+    #[test]
+    fn attackers_of() {
+        let mut board = Board::new();
+        board.place_checker_at(Vec2::new(5, 2), Checker::new(CheckerHeight::THREE, PlayerId::B)).unwrap();
+        let attackers = board.attackers_of(Vec2::new(5, 2));
+        assert_eq!(attackers.len() as u32, board.can_fire_checker_at(Vec2::new(5, 2)).unwrap());
+        assert!(attackers.iter().all(|pos| board.checker_at(*pos).unwrap().owner == Some(PlayerId::A)));
+        assert!(attackers.iter().all(|pos| board.in_fire_range(*pos, Vec2::new(5, 2))));
+        assert!(board.attackers_of(Vec2::new(7, 1)).is_empty());
+        assert!(board.attackers_of(Vec2::new(-1, -1)).is_empty());
+    }
+
+    #[test]
+    fn in_fire_range() {
+        let from = Vec2::new(3, 3);
+        let mut board = Board::new();
+        assert!(board.in_fire_range(from, Vec2::new(5, 3)));
+        assert!(board.in_fire_range(from, Vec2::new(1, 1)));
+        assert!(board.in_fire_range(from, Vec2::new(3, 4)));
+        assert!(!board.in_fire_range(from, from));
+        assert!(!board.in_fire_range(from, Vec2::new(6, 3)));
+        assert!(!board.in_fire_range(from, Vec2::new(4, 5)));
+
+        board.set_rules(RulesConfig { fire_range: 1, ..RulesConfig::default() });
+        assert!(board.in_fire_range(from, Vec2::new(4, 3)));
+        assert!(!board.in_fire_range(from, Vec2::new(5, 3)));
+    }
+
     #[test]
     fn fire_checker_at() {
         // Seed me
         let mut board = Board::from_seed([0; 32]);
         // Normal case, checker takes damage and dies
-        board.place_checker_at(Vec2::new(5, 2), Checker::new(3, PLAYER_B_ID)).unwrap();
+        board.place_checker_at(Vec2::new(5, 2), Checker::new(CheckerHeight::THREE, PlayerId::B)).unwrap();
         board.fire_checker_at(Vec2::new(5, 2)).unwrap();
         let post_fire = board.checker_at(Vec2::new(5, 2)).unwrap();
-        assert_eq!(post_fire.height, 0);
-        assert_eq!(post_fire.owner, EMPTY_PLAYER_ID);
+        assert_eq!(post_fire.height, CheckerHeight::ZERO);
+        assert_eq!(post_fire.owner, None);
 
         // Place stones, normal case with terrain, expect a certain result based on RNG rolls
-        let stone_pos = vec![Vec2::new(4, 2), Vec2::new(4, 3), Vec2::new(5, 2), Vec2::new(5, 3)];
+        let stone_pos = [Vec2::new(4, 2), Vec2::new(4, 3), Vec2::new(5, 2), Vec2::new(5, 3)];
         for pos in stone_pos.iter() {
-            board.place_stone_at(*pos, Stone::new(PLAYER_B_ID)).unwrap();
+            board.place_stone_at(*pos, Stone::new(PlayerId::B)).unwrap();
         }
-        board.place_checker_at(Vec2::new(4, 2), Checker::new(3, PLAYER_B_ID)).unwrap();
-        board.fire_checker_at(Vec2::new(4, 2)).unwrap();
+        board.place_checker_at(Vec2::new(4, 2), Checker::new(CheckerHeight::THREE, PlayerId::B)).unwrap();
+        let attackers = board.attackers_of(Vec2::new(4, 2));
+        let combat = board.resolve_fire(Vec2::new(4, 2)).unwrap();
         let victim = board.checker_at(Vec2::new(4, 2)).unwrap();
-        assert_eq!(victim.height, 2); 
-        assert_eq!(victim.owner, PLAYER_B_ID);
+        assert_eq!(victim.height, CheckerHeight::TWO);
+        assert_eq!(victim.owner, Some(PlayerId::B));
+        // Every attacker rolled, and each roll reaching the bonus did one damage
+        assert_eq!((combat.attackers, combat.target, combat.terrain_bonus), (attackers, Vec2::new(4, 2), 4));
+        assert_eq!(combat.rolls.len(), 1);
+        assert_eq!(combat.damage, combat.rolls.iter().filter(|roll| **roll >= 4).count());
+        assert!(matches!(board.apply_fire(PlayerId::B, Vec2::new(4, 2)), Err(ApplyError::IllegalMove { .. })));
 
         // IndexError case
         match board.fire_checker_at(Vec2::new(-1, -1)) {
@@ -1133,16 +2341,41 @@ mod tests {
 
     }
 
+    #[test]
+    fn deterministic_combat() {
+        let mut board = Board::new();
+        board.set_rules(RulesConfig { deterministic_combat: true, ..RulesConfig::default() });
+        // An open square takes one damage per attacker, every time
+        let target = Vec2::new(5, 2);
+        board.place_checker_at(target, Checker::new(CheckerHeight::THREE, PlayerId::B)).unwrap();
+        let attackers = board.attackers_by(target, PlayerId::A);
+        assert_eq!(board.expected_damage(target, attackers), attackers);
+        let combat = board.resolve_fire(target).unwrap();
+        assert_eq!(board.checker_at(target).unwrap().height, CheckerHeight::THREE.saturating_sub(attackers as usize));
+        assert!(combat.rolls.is_empty() && combat.damage == attackers as usize);
+
+        // A hill with stones on every corner turns one die in three into a hit
+        let fort = Vec2::new(3, 3);
+        board.set_terrain(fort, Terrain::Hill).unwrap();
+        for corner in [Vec2::new(3, 3), Vec2::new(4, 3), Vec2::new(3, 4), Vec2::new(4, 4)] {
+            board.place_stone_at(corner, Stone::new(PlayerId::B)).unwrap();
+        }
+        assert_eq!(board.defence_bonus(fort), 5);
+        assert_eq!(board.expected_damage(fort, 1), 0);
+        assert_eq!(board.expected_damage(fort, 2), 1);
+        assert_eq!(board.expected_damage(fort, 3), 1);
+    }
+
     #[test]
     fn move_checker() {
         let mut board = Board::new();
         // Normal case
         let start = Vec2::new(1, 2);
         let end = Vec2::new(2, 2);
-        let start_checker = board.checker_at(start).unwrap().clone();
+        let start_checker = *board.checker_at(start).unwrap();
         board.move_checker(start, end).unwrap();
         // Start should not be occupied
-        assert!(board.checker_at(start).unwrap().owner == EMPTY_PLAYER_ID);
+        assert!(board.checker_at(start).unwrap().owner.is_none());
         // End should contain start
         assert!(*board.checker_at(end).unwrap() == start_checker);
 
@@ -1151,6 +2384,7 @@ mod tests {
             Ok(_) => panic!("Expected an OccupiedError, got no error"),
             Err(MoveError::IndexError(_)) => panic!("Expected an OccupiedError, got IndexError"),
             Err(MoveError::NegationError) => panic!("Expected am OccupiedError, got NegationError"),
+            Err(MoveError::UnreachableError) => panic!("Expected an OccupiedError, got UnreachableError"),
             Err(MoveError::OccupiedError) => ()
         }
         // Trying to move out of bounds
@@ -1158,15 +2392,242 @@ mod tests {
             Ok(_) => panic!("Expected an IndexError, got no error"),
             Err(MoveError::IndexError(_)) => (),
             Err(MoveError::NegationError) => panic!("Expected a IndexError, got an NegationError"),
+            Err(MoveError::UnreachableError) => panic!("Expected an IndexError, got an UnreachableError"),
             Err(MoveError::OccupiedError) => panic!("Expected an IndexError, got an OccupiedError")
         }
+        // Trying to move more than one square
+        assert!(matches!(board.move_checker(end, Vec2::new(4, 2)), Err(MoveError::UnreachableError)));
+
+        // Diagonal steps are refused when checkers move orthogonally
+        board.set_rules(RulesConfig { orthogonal_checkers: true, ..RulesConfig::default() });
+        assert!(matches!(board.move_checker(end, Vec2::new(3, 1)), Err(MoveError::UnreachableError)));
+        board.move_checker(end, Vec2::new(3, 2)).unwrap();
+    }
+
+    #[test]
+    fn set_position() {
+        let mut board = Board::new();
+        let position = StartPosition {
+            checkers: vec![(PlayerId::A, Vec2::new(3, 3), CheckerHeight::TWO)],
+            stones: vec![(PlayerId::B, Vec2::new(0, 0))],
+        };
+        board.set_position(&position).unwrap();
+        assert_eq!(board.checkers_for_player(PlayerId::A), vec![Vec2::new(3, 3)]);
+        assert!(board.checkers_for_player(PlayerId::B).is_empty());
+        assert_eq!(board.stones_for_player(PlayerId::B), vec![Vec2::new(0, 0)]);
+
+        let overlapping = StartPosition { checkers: vec![], stones: vec![(PlayerId::B, Vec2::new(3, 3))] };
+        let touching = StartPosition { checkers: position.checkers, ..overlapping };
+        assert!(board.set_position(&touching).is_err());
+
+        // A position reached in play can have stones touching checkers
+        board.restore_position(&touching).unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::B), vec![Vec2::new(3, 3)]);
+        assert_eq!(board.checkers_for_player(PlayerId::A), vec![Vec2::new(3, 3)]);
+    }
+
+    #[test]
+    fn dead_stones() {
+        // B walls off the corner at 0,3 on its left, above and below
+        let board = BoardBuilder::new()
+            .stone(0, 3, PlayerId::A)
+            .stone(0, 2, PlayerId::B)
+            .stone(1, 3, PlayerId::B)
+            .stone(0, 4, PlayerId::B)
+            .stone(5, 5, PlayerId::A)
+            .build()
+            .unwrap();
+        assert_eq!(board.dead_stones(PlayerId::A), vec![Vec2::new(0, 3)]);
+        assert!(board.dead_stones(PlayerId::B).is_empty());
+        assert!(Board::new().dead_stones(PlayerId::A).is_empty());
+    }
+
+    #[test]
+    fn stone_chain_winner() {
+        let mut board = Board::new();
+        for yi in 0..BOARD_HEIGHT as i32 {
+            board.place_stone_at(Vec2::new(4, yi), Stone::new(PlayerId::B)).unwrap();
+        }
+        assert_eq!(board.stone_chain_winner(), None);
+        // A stone slid into the gap completes the chain, and sliding it out breaks it again
+        let bottom = BOARD_HEIGHT as i32;
+        board.place_stone_at(Vec2::new(5, bottom), Stone::new(PlayerId::A)).unwrap();
+        board.place_stone_at(Vec2::new(3, bottom), Stone::new(PlayerId::B)).unwrap();
+        assert_eq!(board.stone_chain_winner(), None);
+        board.slide_stone(Vec2::new(3, bottom), Direction::Right).unwrap();
+        assert_eq!(board.stone_chain_winner(), Some(PlayerId::B));
+        board.slide_stone(Vec2::new(4, bottom), Direction::Left).unwrap();
+        assert_eq!(board.stone_at(Vec2::new(0, bottom)).unwrap().owner, Some(PlayerId::B));
+        assert_eq!(board.stone_chain_winner(), None);
+
+        // Changes made through mut_stone_at are still seen
+        board.mut_stone_at(Vec2::new(4, bottom)).unwrap().owner = Some(PlayerId::B);
+        assert_eq!(board.stone_chain_winner(), Some(PlayerId::B));
+        board.place_stone_at(Vec2::new(2, 0), Stone::new(PlayerId::A)).unwrap();
+        assert_eq!(board.stone_chain_winner(), Some(PlayerId::B));
+        board.reset();
+        assert_eq!(board.stone_chain_winner(), None);
+    }
+
+    #[test]
+    fn stone_influence() {
+        let mut board = Board::new();
+        let rows = BOARD_HEIGHT as u32 + 1;
+        assert_eq!(board.chain_gap(PlayerId::A), Some(rows));
+        for yi in 1..BOARD_HEIGHT as i32 {
+            board.place_stone_at(Vec2::new(4, yi), Stone::new(PlayerId::B)).unwrap();
+        }
+        // B needs the two end corners of the column, and paths through elsewhere cost more
+        assert_eq!(board.chain_gap(PlayerId::B), Some(2));
+        let influence = board.stone_influence(PlayerId::B);
+        assert_eq!(influence[Vec2::new(4, 0)], Some(2));
+        assert_eq!(influence[Vec2::new(4, 3)], Some(2));
+        assert_eq!(influence[Vec2::new(3, 0)], Some(3));
+        // A cannot pass through B's stones, but can go around them
+        assert_eq!(board.stone_influence(PlayerId::A)[Vec2::new(4, 3)], None);
+        assert_eq!(board.chain_gap(PlayerId::A), Some(rows));
+
+        board.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(4, BOARD_HEIGHT as i32), Stone::new(PlayerId::B)).unwrap();
+        assert_eq!(board.chain_gap(PlayerId::B), Some(0));
+        assert_eq!(board.chain_gap(PlayerId::A), Some(rows));
+    }
+
+    #[test]
+    fn clone() {
+        let mut board = Board::from_seed([3; 32]);
+        board.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::A)).unwrap();
+        let mut copy = board.clone();
+        assert_eq!(copy.as_string(), board.as_string());
+        assert_eq!(copy.position_hash(), board.position_hash());
+
+        // Pieces are independent
+        copy.move_checker(Vec2::new(1, 2), Vec2::new(2, 2)).unwrap();
+        assert_eq!(board.checker_at(Vec2::new(1, 2)).unwrap().owner, Some(PlayerId::B));
+        copy.place_stone_at(Vec2::new(4, 1), Stone::new(PlayerId::A)).unwrap();
+        assert_eq!(board.stone_at(Vec2::new(4, 1)).unwrap().owner, None);
+        assert_eq!(board.stone_chain_winner(), None);
+
+        // Clones share one generator, unless reseeded
+        let original = Board::from_seed([3; 32]);
+        let reference = Board::from_seed([3; 32]);
+        let mut reseeded = original.clone();
+        reseeded.reseed([9; 32]);
+        reseeded.rng.lock().unwrap().next_u32();
+        let shared = original.clone();
+        assert_eq!(shared.rng.lock().unwrap().next_u32(), reference.rng.lock().unwrap().next_u32());
+        // The shared clone's roll used up the original's first number
+        assert_eq!(original.rng.lock().unwrap().next_u32(), reference.rng.lock().unwrap().next_u32());
+    }
+
+    #[test]
+    fn attack_masks() {
+        let mut board = Board::new();
+        let target = Vec2::new(4, 2);
+        assert!(matches!(board.can_fire_checker_at(Vec2::new(-1, 0)), Err(FireError::IndexError)));
+        board.place_checker_at(target, Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        // Of player A's checkers, only the one at (6, 2) is in line and in range
+        assert_eq!(board.can_fire_checker_at(target).unwrap(), 1);
+        board.move_checker(Vec2::new(6, 3), Vec2::new(5, 3)).unwrap();
+        assert_eq!(board.can_fire_checker_at(target).unwrap(), 2);
+        // Friendly checkers do not count
+        board.move_checker(Vec2::new(1, 2), Vec2::new(2, 2)).unwrap();
+        assert_eq!(board.can_fire_checker_at(target).unwrap(), 2);
+        // Changes through mut_checker_at are seen
+        board.mut_checker_at(Vec2::new(2, 2)).unwrap().owner = Some(PlayerId::A);
+        assert_eq!(board.can_fire_checker_at(target).unwrap(), 3);
+        board.place_checker_at(Vec2::new(2, 2), Checker::empty()).unwrap();
+        assert_eq!(board.can_fire_checker_at(target).unwrap(), 2);
+
+        // Out of range once the range shrinks
+        board.set_rules(RulesConfig { fire_range: 1, ..RulesConfig::default() });
+        assert_eq!(board.can_fire_checker_at(target).unwrap(), 1);
+        board.clear_checkers();
+        board.place_checker_at(target, Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        assert!(matches!(board.can_fire_checker_at(target), Err(FireError::NoAttackersError)));
+    }
+
+    #[test]
+    fn terrain() {
+        let mut board = Board::new();
+        assert_eq!(board.terrain_at(Vec2::new(3, 3)), Terrain::Plain);
+        let off_board = format!("{} is not a valid checker position", Vec2::new(-1, 0));
+        assert_eq!(board.set_terrain(Vec2::new(-1, 0), Terrain::Mud), Err(MoveError::IndexError(off_board)));
+
+        // Mud can only be crossed orthogonally
+        board.set_terrain(Vec2::new(3, 3), Terrain::Mud).unwrap();
+        assert!(!board.checker_can_step(Vec2::new(2, 2), Vec2::new(3, 3)));
+        assert!(!board.checker_can_step(Vec2::new(3, 3), Vec2::new(4, 4)));
+        assert!(board.checker_can_step(Vec2::new(2, 3), Vec2::new(3, 3)));
+        assert!(board.checker_can_step(Vec2::new(2, 2), Vec2::new(3, 2)));
+        assert!(!board.checker_can_step(Vec2::new(2, 2), Vec2::new(4, 2)));
+        assert!(matches!(board.move_checker(Vec2::new(1, 2), Vec2::new(2, 3)), Ok(())));
+        assert!(matches!(board.move_checker(Vec2::new(2, 3), Vec2::new(3, 3)), Ok(())));
+        assert!(matches!(board.move_checker(Vec2::new(3, 3), Vec2::new(4, 2)), Err(MoveError::UnreachableError)));
+
+        // Terrain stays when the pieces are reset
+        board.reset();
+        assert_eq!(board.terrain_at(Vec2::new(3, 3)), Terrain::Mud);
+    }
+
+    #[test]
+    fn hill_defence() {
+        // One stone is a bonus of 1, which every roll beats. On a hill the bonus is 2,
+        // so over many attacks some rolls of 1 should miss.
+        let mut board = Board::from_seed([7; 32]);
+        let target = Vec2::new(4, 2);
+        board.place_stone_at(Vec2::new(5, 3), Stone::new(PlayerId::B)).unwrap();
+        board.set_terrain(target, Terrain::Hill).unwrap();
+        let mut misses = 0;
+        for _ in 0..60 {
+            board.place_checker_at(target, Checker::empty()).unwrap();
+            board.place_checker_at(target, Checker::new(CheckerHeight::THREE, PlayerId::B)).unwrap();
+            board.fire_checker_at(target).unwrap();
+            if board.checker_at(target).unwrap().height == CheckerHeight::THREE {
+                misses += 1;
+            }
+        }
+        assert!(misses > 0);
+    }
+
+    #[test]
+    fn position_hash() {
+        let mut board = Board::new();
+        let initial = board.position_hash();
+        assert_eq!(initial, Board::from_seed([1; 32]).position_hash());
+
+        // Moving a checker changes the hash, moving it back restores it
+        board.move_checker(Vec2::new(1, 2), Vec2::new(2, 2)).unwrap();
+        assert_ne!(board.position_hash(), initial);
+        board.move_checker(Vec2::new(2, 2), Vec2::new(1, 2)).unwrap();
+        assert_eq!(board.position_hash(), initial);
+
+        // The same stones placed in a different order give the same hash
+        let mut other = Board::new();
+        board.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::A)).unwrap();
+        board.place_stone_at(Vec2::new(4, 4), Stone::new(PlayerId::B)).unwrap();
+        other.place_stone_at(Vec2::new(4, 4), Stone::new(PlayerId::B)).unwrap();
+        other.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::A)).unwrap();
+        assert_eq!(board.position_hash(), other.position_hash());
+
+        // Owner and height are part of the hash
+        let mut swapped = Board::new();
+        swapped.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::B)).unwrap();
+        swapped.place_stone_at(Vec2::new(4, 4), Stone::new(PlayerId::A)).unwrap();
+        assert_ne!(board.position_hash(), swapped.position_hash());
+        let mut damaged = Board::new();
+        damaged.mut_checker_at(Vec2::new(0, 2)).unwrap().height = CheckerHeight::TWO;
+        assert_ne!(damaged.position_hash(), initial);
+
+        board.reset();
+        assert_eq!(board.position_hash(), initial);
     }
 
     #[test]
     fn as_string() {
         let mut board = Board::new();
-        board.place_stone_at(Vec2::new(0, 0), Stone::new(PLAYER_A_ID)).unwrap();
-        board.place_stone_at(Vec2::new(0,  6), Stone::new(PLAYER_B_ID)).unwrap();
+        board.place_stone_at(Vec2::new(0, 0), Stone::new(PlayerId::A)).unwrap();
+        board.place_stone_at(Vec2::new(0,  6), Stone::new(PlayerId::B)).unwrap();
         let expected = 
 "a . . . . . . . . 
  _ _ _ _ _ _ _ _
@@ -1184,5 +2645,105 @@ b . . . . . . . .
 ";
         let rep = board.as_string();
         assert_eq!(rep, expected);
+
+        // Glyphs change the drawing, and nothing else
+        let plain = board.clone();
+        board.set_glyphs(GlyphTheme::unicode());
+        assert_eq!(board, plain);
+        let rep = board.as_string();
+        assert!(rep.starts_with("○ · ·"));
+        assert!(rep.contains(" ❷ _ _ _ _ _ _ ②"));
+        assert_eq!(rep.lines().count(), expected.lines().count());
+    }
+
+    #[test]
+    fn describe() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(0, 0), Stone::new(PlayerId::A)).unwrap();
+        board.set_terrain(Vec2::new(3, 2), Terrain::Hill).unwrap();
+        let description = board.describe();
+        assert_eq!(description.lines().count(), 3 + BOARD_HEIGHT + 1);
+        assert!(description.contains("Player A has 6 checkers and 1 stones on the board."));
+        assert!(description.contains("Corners 1: Player A stone at corner a1.\nRow 1: empty."));
+        assert!(description.contains("Row 2: Player B checker, height 2, at a2, Player A checker, height 2, at h2."));
+        assert!(description.contains("Row 3: Player B checker, height 3, at a3, Player B checker, height 1, at b3, hill at d3,"));
+        assert_eq!(square_name(Vec2::new(2, 1)), "c2");
+        assert_eq!(parse_square_name("C2"), Some(Vec2::new(2, 1)));
+        assert_eq!(parse_square_name(&square_name(Vec2::new(7, 5))), Some(Vec2::new(7, 5)));
+        assert_eq!(parse_square_name("c0"), None);
+        assert_eq!(parse_square_name("3,1"), None);
+    }
+
+    #[test]
+    fn value_semantics() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let board = Board::from_seed([1; 32]);
+        let other = Board::from_seed([2; 32]);
+        assert_eq!(board, other);
+        assert_eq!(board, board.clone());
+
+        let mut moved = board.clone();
+        moved.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::A)).unwrap();
+        assert_ne!(board, moved);
+        let mut muddy = board.clone();
+        muddy.set_terrain(Vec2::new(3, 3), Terrain::Mud).unwrap();
+        assert_ne!(board, muddy);
+        assert_ne!(board, Board::for_variant(Variant::Grand10x8));
+
+        let hash = |board: &Board| {
+            let mut hasher = DefaultHasher::new();
+            board.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&board), hash(&other));
+        assert_ne!(hash(&board), hash(&moved));
+    }
+
+    #[test]
+    fn check_invariants() {
+        let mut board = Board::new();
+        assert_eq!(board.check_invariants(), Ok(()));
+        board.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::A)).unwrap();
+        board.slide_stone(Vec2::new(4, 0), Direction::Down).unwrap();
+        board.fire_checker_at(Vec2::new(1, 2)).ok();
+        assert_eq!(board.check_invariants(), Ok(()));
+
+        // Changes made through the mutable accessors are picked up rather than reported
+        board.mut_checker_at(Vec2::new(6, 2)).unwrap().owner = Some(PlayerId::B);
+        board.mut_stone_at(Vec2::new(0, 0)).unwrap().owner = Some(PlayerId::B);
+        assert_eq!(board.check_invariants(), Ok(()));
+
+        board.mut_checker_at(Vec2::new(3, 3)).unwrap().owner = Some(PlayerId::A);
+        assert_eq!(board.check_invariants(), Err(InvariantError::CheckerHeight(Vec2::new(3, 3))));
+    }
+
+    #[test]
+    fn apply() {
+        let mut board = Board::from_seed([0; 32]);
+        board.apply(PlayerId::B, Intent::MoveChecker(Vec2::new(1, 2), Vec2::new(2, 2))).unwrap();
+        assert_eq!(board.checker_at(Vec2::new(2, 2)).unwrap().owner, Some(PlayerId::B));
+        board.apply(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 0))).unwrap();
+        board.apply(PlayerId::A, Intent::SlideStone(Vec2::new(4, 0), Direction::Down)).unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::A), vec![Vec2::new(4, 6)]);
+        board.apply(PlayerId::B, Intent::SwapSides).unwrap();
+
+        // Only the player's own pieces can be moved or slid, and they cannot be attacked
+        let illegal = |intent| Err(ApplyError::IllegalMove { player: PlayerId::B, intent });
+        let intent = Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2));
+        assert_eq!(board.apply(PlayerId::B, intent), illegal(intent));
+        let intent = Intent::SlideStone(Vec2::new(4, 6), Direction::Up);
+        assert_eq!(board.apply(PlayerId::B, intent), illegal(intent));
+        let intent = Intent::FireChecker(Vec2::new(2, 2));
+        assert_eq!(board.apply(PlayerId::B, intent), illegal(intent));
+        let intent = Intent::SlideStone(Vec2::new(0, 0), Direction::Down);
+        assert_eq!(board.apply(PlayerId::B, intent), illegal(intent));
+        let intent = Intent::PlaceChecker(Vec2::new(3, 3), CheckerHeight::ZERO);
+        assert_eq!(board.apply(PlayerId::B, intent), illegal(intent));
+
+        // Rule and bounds errors come from the board
+        assert_eq!(board.apply(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 6))), Err(ApplyError::Move(MoveError::OccupiedError)));
+        assert_eq!(board.apply(PlayerId::B, Intent::FireChecker(Vec2::new(9, 9))), Err(ApplyError::Fire(FireError::IndexError)));
+        assert_eq!(board.apply(PlayerId::B, Intent::SlideStone(Vec2::new(-1, 0), Direction::Up)), Err(ApplyError::Slide(SlideError::IndexError)));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,277 @@
+/**
+ * A text command adapter for playing in chat, with no tie to any chat platform: a bot for
+ * one passes each message of a conversation to a TextBot and posts the Reply back, with
+ * the board in a monospaced block. Squares and corners are named as square_name writes
+ * them, or as x,y like records do, and moves are made for the side to move.
+ *
+ *   new                        a new game from the standard position
+ *   board                      the board and whose move it is
+ *   moves                      the moves the side to move can make
+ *   move c3 d3                 a move in record notation, eg fire d3, place c1,
+ *                              slide c1 down, setup a2 3, or swap
+ *   help                       these commands
+ *
+ * Against an engine, the engine answers each move at once.
+ */
+use crate::analysis::Replayed;
+use crate::board::{parse_square_name, square_name};
+use crate::config::GameConfig;
+use crate::engine::Engine;
+use crate::game::{Decide, Game, Intent, Player, PlayerId, STARTING_STONES};
+use crate::record::parse_intent;
+
+const HELP: &str = "Commands: new, board, moves, help, or a move such as \"move c3 d3\", \"fire d3\", \
+    \"place c1\", \"slide c1 down\", \"setup a2 3\" or \"swap\".";
+
+/**
+ * Reply is what the bot says back: text, and a board to show in a monospaced block.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reply {
+    pub text: String,
+    // the board and its status lines, drawn as the console does, or None
+    pub board: Option<String>,
+}
+
+impl Reply {
+    /* Helper function making a reply with only text. */
+    fn text(text: impl Into<String>) -> Reply {
+        Reply { text: text.into(), board: None }
+    }
+}
+
+/**
+ * TextBot is the game of one conversation: the position it started from, the moves made
+ * since, and the engine playing one side, if any.
+ */
+pub struct TextBot {
+    config: GameConfig,
+    moves: Vec<(PlayerId, Intent)>,
+    engine: Option<(Engine, PlayerId)>,
+}
+
+impl Default for TextBot {
+    fn default() -> TextBot {
+        TextBot::new(GameConfig::default())
+    }
+}
+
+impl TextBot {
+    /**
+     * new
+     * Start a game where everyone in the conversation plays both sides, taking turns.
+     * config - Position and rules to start from.
+     */
+    pub fn new(config: GameConfig) -> TextBot {
+        TextBot { config, moves: Vec::new(), engine: None }
+    }
+
+    /**
+     * against_engine
+     * Start a game where the conversation plays one side and an engine the other. If the
+     * engine moves first, it does so at once.
+     * config - Position and rules to start from.
+     * engine - Engine to play with.
+     * side - Side the engine plays.
+     */
+    pub fn against_engine(config: GameConfig, engine: Engine, side: PlayerId) -> TextBot {
+        let mut bot = TextBot { config, moves: Vec::new(), engine: Some((engine, side)) };
+        bot.engine_replies();
+        bot
+    }
+
+    /**
+     * moves
+     * ret - Every move made since the game started, in order.
+     */
+    pub fn moves(&self) -> &[(PlayerId, Intent)] {
+        &self.moves
+    }
+
+    /**
+     * handle
+     * Act on one message of the conversation.
+     * message - Text of the message.
+     * ret - What to reply.
+     */
+    pub fn handle(&mut self, message: &str) -> Reply {
+        let words: Vec<&str> = message.split_whitespace().collect();
+        match words.first().map(|word| word.to_ascii_lowercase()).as_deref() {
+            None => Reply::text(HELP),
+            Some("help") => Reply::text(HELP),
+            Some("new") => {
+                self.moves.clear();
+                let mut lines = vec![String::from("New game.")];
+                lines.extend(self.engine_replies());
+                self.show(lines)
+            },
+            Some("board") => self.show(Vec::new()),
+            Some("moves") => Reply::text(self.with_game(|game| {
+                let player = game.side_to_move();
+                let moves: Vec<String> = game.move_request(player).all_moves().map(|intent| bot_notation(*intent)).collect();
+                format!("Player {} can play: {}", player, moves.join(", "))
+            })),
+            Some(_) => match self.play(&words) {
+                Ok(lines) => self.show(lines),
+                Err(message) => Reply::text(message),
+            },
+        }
+    }
+
+    /* Helper function making the move in @words for the side to move, and any engine reply. */
+    fn play(&mut self, words: &[&str]) -> Result<Vec<String>, String> {
+        let translated: Vec<String> = words.iter()
+            .map(|word| match parse_square_name(word) {
+                Some(pos) => format!("{},{}", pos.x, pos.y),
+                None => word.to_ascii_lowercase(),
+            })
+            .collect();
+        let translated: Vec<&str> = translated.iter().map(String::as_str).collect();
+        let (intent, used) = parse_intent(&translated).map_err(|message| format!("{}. {}", capitalise(&message), HELP))?;
+        if used < translated.len() {
+            return Err(format!("Unexpected '{}' after the move.", words[used]));
+        }
+        let player = self.with_game(|game| {
+            if let Some(outcome) = game.check_for_outcome() {
+                return Err(format!("The game is over: {}. Send new to play again.", outcome));
+            }
+            let player = game.side_to_move();
+            game.try_apply_move(player, intent).map_err(|error| format!("{}.", error))?;
+            Ok(player)
+        })?;
+        if self.engine.as_ref().is_some_and(|(_, side)| *side == player) {
+            return Err(format!("It is the engine's move, not Player {}'s.", player));
+        }
+        self.moves.push((player, intent));
+        let mut lines = vec![format!("Player {} plays {}.", player, bot_notation(intent))];
+        lines.extend(self.engine_replies());
+        Ok(lines)
+    }
+
+    /* Helper function letting the engine move for as long as it is to move, describing its moves. */
+    fn engine_replies(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let Some((engine, side)) = &self.engine else {
+            return lines;
+        };
+        let side = *side;
+        loop {
+            let chosen = self.with_game(|game| {
+                if game.check_for_outcome().is_some() || game.side_to_move() != side {
+                    return None;
+                }
+                let request = game.move_request(side);
+                request.all_moves().next()?;
+                Some(engine.choose_move(&request))
+            });
+            let Some(intent) = chosen else {
+                return lines;
+            };
+            self.moves.push((side, intent));
+            lines.push(format!("Engine (Player {}) plays {}.", side, bot_notation(intent)));
+        }
+    }
+
+    /* Helper function replying with @lines, then the board and whose move it is or how the game ended. */
+    fn show(&self, mut lines: Vec<String>) -> Reply {
+        let (status, board) = self.with_game(|game| {
+            let status = match game.check_for_outcome() {
+                Some(outcome) => format!("{}. Send new to play again.", outcome),
+                None => format!("Player {} to move.", game.side_to_move()),
+            };
+            (status, game.view().to_string())
+        });
+        lines.push(status);
+        Reply { text: lines.join("\n"), board: Some(board) }
+    }
+
+    /*
+     * Helper function setting up a game at the starting position, replaying the moves made
+     * so far, and running @action on it. Every move was checked when it was made, so the
+     * replay can not fail.
+     */
+    fn with_game<T>(&self, action: impl FnOnce(&mut Game) -> T) -> T {
+        let (replayed_a, replayed_b) = (Replayed, Replayed);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &replayed_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &replayed_b);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, self.config.clone());
+        game.set_silent(true);
+        for (player, intent) in self.moves.iter() {
+            game.try_apply_move(*player, *intent).expect("checked move no longer replays");
+        }
+        action(&mut game)
+    }
+}
+
+/**
+ * bot_notation
+ * Write a move as the bot reads them, with squares and corners named.
+ * intent - Move to write.
+ * ret - eg "move c3 d3".
+ */
+pub fn bot_notation(intent: Intent) -> String {
+    match intent {
+        Intent::MoveChecker(from, to) => format!("move {} {}", square_name(from), square_name(to)),
+        Intent::FireChecker(at) => format!("fire {}", square_name(at)),
+        Intent::PlaceStone(at) => format!("place {}", square_name(at)),
+        Intent::SlideStone(from, direction) => format!("slide {} {}", square_name(from), direction),
+        Intent::PlaceChecker(at, height) => format!("setup {} {}", square_name(at), height),
+        Intent::SwapSides => String::from("swap"),
+    }
+}
+
+/* Helper function starting @message with a capital letter, as replies are written. */
+fn capitalise(message: &str) -> String {
+    let mut chars = message.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec::Vec2;
+
+    #[test]
+    fn play() {
+        let mut bot = TextBot::default();
+        assert_eq!(bot.handle("help").text, HELP);
+        let shown = bot.handle("board");
+        assert!(shown.text.ends_with("Player A to move."));
+        assert!(shown.board.unwrap().contains("Player B:"));
+
+        // Squares by name or by x,y, and any case
+        let reply = bot.handle("Move G3 F3");
+        assert_eq!(reply.text, "Player A plays move g3 f3.\nPlayer B to move.");
+        bot.handle("place 4,0");
+        assert_eq!(bot.moves(), [
+            (PlayerId::A, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2))),
+            (PlayerId::B, Intent::PlaceStone(Vec2::new(4, 0))),
+        ]);
+        assert!(bot.handle("moves").text.starts_with("Player A can play: "));
+
+        // Refused moves leave the game as it was
+        assert!(bot.handle("place e1").board.is_none());
+        assert!(bot.handle("place e1 now").text.starts_with("Unexpected 'now'"));
+        assert!(bot.handle("castle").text.ends_with(HELP));
+        assert_eq!(bot.moves().len(), 2);
+
+        bot.handle("new");
+        assert!(bot.moves().is_empty());
+    }
+
+    #[test]
+    fn engine() {
+        let mut bot = TextBot::against_engine(GameConfig::default(), Engine::default(), PlayerId::B);
+        let reply = bot.handle("move g3 f3");
+        let lines: Vec<&str> = reply.text.lines().collect();
+        assert_eq!(lines[0], "Player A plays move g3 f3.");
+        assert!(lines[1].starts_with("Engine (Player B) plays "));
+        assert_eq!(lines.last().copied(), Some("Player A to move."));
+        assert_eq!(bot.moves().len(), 2);
+
+        // An engine moving first does so as soon as the game starts
+        let bot = TextBot::against_engine(GameConfig::default(), Engine::default(), PlayerId::A);
+        assert_eq!(bot.moves().len(), 1);
+        assert_eq!(bot_notation(Intent::SlideStone(Vec2::new(2, 0), crate::board::Direction::Down)), "slide c1 down");
+    }
+}
@@ -0,0 +1,196 @@
+/**
+ * BoardBuilder sets out a position piece by piece and checks it once at the end, for tests
+ * and scenario setup that would otherwise place each piece and unwrap the result:
+ *
+ *   let board = BoardBuilder::new()
+ *       .checker(6, 2, PlayerId::A, 1)
+ *       .stone(3, 3, PlayerId::B)
+ *       .build()?;
+ *
+ * Builders start from an empty standard board. Checkers are placed before stones, so the
+ * rule of negation is checked against every checker whatever order they were added in.
+ */
+use crate::board::{Board, InvariantError, MoveError, Terrain};
+use crate::config::{RulesConfig, StartPosition, Variant};
+use crate::game::{Checker, CheckerHeight, PlayerId, Stone};
+use crate::vec::Vec2;
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum BuildError {
+    // A checker was given a height it can not have
+    #[error("Checker at {x},{y} can not have height {height}", x = pos.x, y = pos.y)]
+    Height { pos: Vec2, height: usize },
+    // A piece or terrain could not be put on the board
+    #[error("Square {x},{y}: {source}", x = pos.x, y = pos.y)]
+    Square { pos: Vec2, source: MoveError },
+    // The finished board is inconsistent
+    #[error(transparent)]
+    Invariant(#[from] InvariantError),
+}
+
+/**
+ * BoardBuilder is a position being set out.
+ */
+#[derive(Clone, Debug)]
+pub struct BoardBuilder {
+    variant: Variant,
+    rules: Option<RulesConfig>,
+    seed: Option<[u8; 32]>,
+    // (owner, position, height), with heights not yet checked
+    checkers: Vec<(PlayerId, Vec2, usize)>,
+    stones: Vec<(PlayerId, Vec2)>,
+    terrain: Vec<(Vec2, Terrain)>,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> BoardBuilder {
+        BoardBuilder {
+            variant: Variant::Standard,
+            rules: None,
+            seed: None,
+            checkers: Vec::new(),
+            stones: Vec::new(),
+            terrain: Vec::new(),
+        }
+    }
+}
+
+impl BoardBuilder {
+    /**
+     * new
+     * Start setting out an empty standard board.
+     */
+    pub fn new() -> BoardBuilder {
+        BoardBuilder::default()
+    }
+
+    /**
+     * variant
+     * variant - Board to set the pieces out on, in place of the standard board.
+     */
+    pub fn variant(mut self, variant: Variant) -> BoardBuilder {
+        self.variant = variant;
+        self
+    }
+
+    /**
+     * rules
+     * rules - Rules the board plays by, in place of the defaults. They apply while the
+     * pieces are placed, so negation is checked as these rules say.
+     */
+    pub fn rules(mut self, rules: RulesConfig) -> BoardBuilder {
+        self.rules = Some(rules);
+        self
+    }
+
+    /**
+     * seed
+     * seed - Seed for the board's dice, in place of a random one.
+     */
+    pub fn seed(mut self, seed: [u8; 32]) -> BoardBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /**
+     * checker
+     * Add a checker.
+     * x, y - Checker square to put it on.
+     * owner - Id of player it belongs to.
+     * height - Its height, from 1 to CheckerHeight::MAX.
+     */
+    pub fn checker(mut self, x: i32, y: i32, owner: PlayerId, height: usize) -> BoardBuilder {
+        self.checkers.push((owner, Vec2::new(x, y), height));
+        self
+    }
+
+    /**
+     * stone
+     * Add a stone.
+     * x, y - Stone corner to put it on.
+     * owner - Id of player it belongs to.
+     */
+    pub fn stone(mut self, x: i32, y: i32, owner: PlayerId) -> BoardBuilder {
+        self.stones.push((owner, Vec2::new(x, y)));
+        self
+    }
+
+    /**
+     * terrain
+     * Change the ground under a checker square.
+     * x, y - Checker square to change.
+     * terrain - New terrain.
+     */
+    pub fn terrain(mut self, x: i32, y: i32, terrain: Terrain) -> BoardBuilder {
+        self.terrain.push((Vec2::new(x, y), terrain));
+        self
+    }
+
+    /**
+     * build
+     * Set out the board.
+     * ret - The board, or a BuildError for the first piece that could not be placed.
+     */
+    pub fn build(&self) -> Result<Board, BuildError> {
+        let mut board = match self.seed {
+            Some(seed) => Board::for_variant_from_seed(self.variant, seed),
+            None => Board::for_variant(self.variant),
+        };
+        if let Some(rules) = self.rules {
+            board.set_rules(rules);
+        }
+        board.set_position(&StartPosition::default()).expect("an empty position always fits");
+        for (owner, pos, height) in self.checkers.iter() {
+            let checked = CheckerHeight::new(*height).filter(|checked| !checked.is_zero());
+            let checked = checked.ok_or(BuildError::Height { pos: *pos, height: *height })?;
+            board.place_checker_at(*pos, Checker::new(checked, *owner))
+                .map_err(|source| BuildError::Square { pos: *pos, source })?;
+        }
+        for (owner, pos) in self.stones.iter() {
+            board.place_stone_at(*pos, Stone::new(*owner)).map_err(|source| BuildError::Square { pos: *pos, source })?;
+        }
+        for (pos, terrain) in self.terrain.iter() {
+            board.set_terrain(*pos, *terrain).map_err(|source| BuildError::Square { pos: *pos, source })?;
+        }
+        board.check_invariants()?;
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let board = BoardBuilder::new()
+            .checker(6, 2, PlayerId::A, 1)
+            .stone(3, 3, PlayerId::B)
+            .terrain(4, 4, Terrain::Hill)
+            .build()
+            .unwrap();
+        assert_eq!(board.checkers_for_player(PlayerId::A), vec![Vec2::new(6, 2)]);
+        assert!(board.checkers_for_player(PlayerId::B).is_empty());
+        assert_eq!(board.stones_for_player(PlayerId::B), vec![Vec2::new(3, 3)]);
+        assert_eq!(board.terrain_at(Vec2::new(4, 4)), Terrain::Hill);
+
+        let builder = BoardBuilder::new().variant(Variant::Grand10x8).checker(9, 7, PlayerId::B, 3);
+        assert_eq!(builder.build().unwrap().width(), Variant::Grand10x8.width());
+        assert_eq!(builder.clone().seed([1; 32]).build().unwrap(), builder.build().unwrap());
+    }
+
+    #[test]
+    fn errors() {
+        let tall = BoardBuilder::new().checker(1, 1, PlayerId::A, 4).build();
+        assert_eq!(tall, Err(BuildError::Height { pos: Vec2::new(1, 1), height: 4 }));
+        assert!(matches!(BoardBuilder::new().checker(1, 1, PlayerId::A, 0).build(), Err(BuildError::Height { .. })));
+        let stacked = BoardBuilder::new().checker(2, 2, PlayerId::A, 1).checker(2, 2, PlayerId::B, 1).build();
+        assert_eq!(stacked, Err(BuildError::Square { pos: Vec2::new(2, 2), source: MoveError::OccupiedError }));
+        // Negation holds whichever was added first
+        let negated = BoardBuilder::new().stone(3, 3, PlayerId::B).checker(3, 3, PlayerId::A, 1).build();
+        assert_eq!(negated, Err(BuildError::Square { pos: Vec2::new(3, 3), source: MoveError::NegationError }));
+        assert!(matches!(BoardBuilder::new().stone(20, 0, PlayerId::A).build(), Err(BuildError::Square { .. })));
+        assert!(BoardBuilder::new().stone(3, 3, PlayerId::B).rules(RulesConfig { negation: false, ..RulesConfig::default() })
+            .checker(3, 3, PlayerId::A, 1).build().is_ok());
+    }
+}
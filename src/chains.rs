@@ -0,0 +1,278 @@
+/**
+ * Union-find over each player's stones, kept up to date as stones are placed so the
+ * stone-chain win can be checked without searching the whole board every turn.
+ *
+ * Stones are joined to their same-owner orthogonal neighbours. Two extra nodes stand
+ * for the top and bottom edges, so a player has a winning chain exactly when the
+ * two edge nodes are in the same set.
+ */
+use std::collections::VecDeque;
+
+use crate::game::{PlayerId, Stone};
+use crate::grid::Grid;
+use crate::vec::Vec2;
+
+#[derive(Clone, Debug)]
+pub struct StoneChains {
+    // number of stone columns and rows, one more than the checker board in each
+    columns: usize,
+    rows: usize,
+    // per player (A then B): parent of each node, stone corners first, then top, then bottom
+    parent: [Vec<usize>; 2],
+    rank: [Vec<u8>; 2],
+}
+
+impl StoneChains {
+    /**
+     * new
+     * Create empty chains for a board of checker squares.
+     * width - Checker columns on the board.
+     * height - Checker rows on the board.
+     */
+    pub fn new(width: usize, height: usize) -> StoneChains {
+        let (columns, rows) = (width + 1, height + 1);
+        let nodes = columns * rows + 2;
+        StoneChains {
+            columns,
+            rows,
+            parent: [(0..nodes).collect(), (0..nodes).collect()],
+            rank: [vec![0; nodes], vec![0; nodes]],
+        }
+    }
+
+    /**
+     * clear
+     * Forget every stone, as for an empty board.
+     */
+    pub fn clear(&mut self) {
+        for player in 0..2 {
+            self.clear_player(player);
+        }
+    }
+
+    /**
+     * add
+     * Join a newly placed stone to its neighbours. O(log n), using union by rank.
+     * stones - Stone board, indexed x + y * columns, with the new stone already on it.
+     * pos - Corner the stone was placed on.
+     */
+    pub fn add(&mut self, stones: &[Stone], pos: Vec2) {
+        let idx = self.index(pos);
+        let Some(player) = stones[idx].owner.map(PlayerId::index) else {
+            return;
+        };
+        if pos.y == 0 {
+            self.union(player, idx, self.top());
+        }
+        if pos.y == self.rows as i32 - 1 {
+            self.union(player, idx, self.bottom());
+        }
+        for neighbour in [pos.up(), pos.down(), pos.left(), pos.right()] {
+            if self.in_bounds(neighbour) {
+                let n_idx = self.index(neighbour);
+                if stones[n_idx].owner == stones[idx].owner {
+                    self.union(player, idx, n_idx);
+                }
+            }
+        }
+    }
+
+    /**
+     * rebuild
+     * Recompute one player's chains from scratch. Union-find cannot split a set, so this is
+     * needed when a stone leaves a corner, such as after a slide. O(n).
+     * stones - Stone board, indexed x + y * columns.
+     * owner - Id of player whose chains to rebuild.
+     */
+    pub fn rebuild(&mut self, stones: &[Stone], owner: PlayerId) {
+        self.clear_player(owner.index());
+        for (idx, stone) in stones.iter().enumerate() {
+            if stone.owner == Some(owner) {
+                self.add(stones, self.position(idx));
+            }
+        }
+    }
+
+    /**
+     * connects
+     * Determine if a player has a chain of stones from the top edge to the bottom edge.
+     * owner - Id of player to check.
+     * ret - True if the player's stones connect the edges.
+     */
+    pub fn connects(&self, owner: PlayerId) -> bool {
+        let player = owner.index();
+        self.find(player, self.top()) == self.find(player, self.bottom())
+    }
+
+    /**
+     * gaps
+     * Measure how far a player is from a chain through each corner: the fewest empty corners
+     * they would still have to fill for a top-to-bottom chain that passes through it. A
+     * breadth-first search is run from each edge, where the player's own stones cost nothing,
+     * empty corners cost one, and the other player's stones block the way. O(n).
+     * stones - Stone board, indexed x + y * columns.
+     * owner - Id of player to measure for.
+     * ret - Gap through each corner, or None where the other player's stones cut it off.
+     */
+    pub fn gaps(&self, stones: &[Stone], owner: PlayerId) -> Grid<Option<u32>> {
+        let from_top = self.distances(stones, owner, 0);
+        let from_bottom = self.distances(stones, owner, self.rows as i32 - 1);
+        let mut gaps = Grid::new(self.columns, self.rows, None);
+        for (idx, (top, bottom)) in from_top.iter().zip(from_bottom.iter()).enumerate() {
+            if let (Some(top), Some(bottom)) = (top, bottom) {
+                // both searches paid for the corner itself
+                let cost = u32::from(stones[idx].owner.is_none());
+                gaps[self.position(idx)] = Some(top + bottom - cost);
+            }
+        }
+        gaps
+    }
+
+    /*
+     * Helper function finding the cost of reaching each corner from the edge row @row,
+     * counting the corner itself, with a 0-1 breadth-first search.
+     */
+    fn distances(&self, stones: &[Stone], owner: PlayerId, row: i32) -> Vec<Option<u32>> {
+        let cost = |idx: usize| match stones[idx].owner {
+            None => Some(1),
+            Some(player) if player == owner => Some(0),
+            Some(_) => None,
+        };
+        let mut distances = vec![None; self.columns * self.rows];
+        let mut queue = VecDeque::new();
+        for x in 0..self.columns as i32 {
+            let idx = self.index(Vec2::new(x, row));
+            if let Some(cost) = cost(idx) {
+                distances[idx] = Some(cost);
+                queue.push_back(idx);
+            }
+        }
+        while let Some(idx) = queue.pop_front() {
+            let (pos, distance) = (self.position(idx), distances[idx].unwrap());
+            for neighbour in [pos.up(), pos.down(), pos.left(), pos.right()] {
+                if !self.in_bounds(neighbour) {
+                    continue;
+                }
+                let n_idx = self.index(neighbour);
+                let Some(step) = cost(n_idx) else {
+                    continue;
+                };
+                if distances[n_idx].is_none_or(|known| distance + step < known) {
+                    distances[n_idx] = Some(distance + step);
+                    // free steps go to the front, so corners leave the queue in order of distance
+                    if step == 0 {
+                        queue.push_front(n_idx);
+                    } else {
+                        queue.push_back(n_idx);
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    fn clear_player(&mut self, player: usize) {
+        for (idx, parent) in self.parent[player].iter_mut().enumerate() {
+            *parent = idx;
+        }
+        self.rank[player].fill(0);
+    }
+
+    fn find(&self, player: usize, mut node: usize) -> usize {
+        while self.parent[player][node] != node {
+            node = self.parent[player][node];
+        }
+        node
+    }
+
+    fn union(&mut self, player: usize, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(player, a), self.find(player, b));
+        if root_a == root_b {
+            return;
+        }
+        let (rank_a, rank_b) = (self.rank[player][root_a], self.rank[player][root_b]);
+        if rank_a < rank_b {
+            self.parent[player][root_a] = root_b;
+        } else {
+            self.parent[player][root_b] = root_a;
+            if rank_a == rank_b {
+                self.rank[player][root_a] += 1;
+            }
+        }
+    }
+
+    fn in_bounds(&self, pos: Vec2) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.columns && (pos.y as usize) < self.rows
+    }
+
+    fn index(&self, pos: Vec2) -> usize {
+        pos.x as usize + pos.y as usize * self.columns
+    }
+
+    fn position(&self, idx: usize) -> Vec2 {
+        Vec2::new((idx % self.columns) as i32, (idx / self.columns) as i32)
+    }
+
+    fn top(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    fn bottom(&self) -> usize {
+        self.columns * self.rows + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connects() {
+        // A 2x2 checker board has 3x3 stone corners
+        let mut stones = vec![Stone::empty(); 9];
+        let mut chains = StoneChains::new(2, 2);
+        for (x, y) in [(0, 0), (1, 1), (0, 1), (1, 2)] {
+            stones[x + y * 3] = Stone::new(PlayerId::A);
+            assert!(!chains.connects(PlayerId::A));
+            chains.add(&stones, Vec2::new(x as i32, y as i32));
+        }
+        assert!(chains.connects(PlayerId::A));
+        assert!(!chains.connects(PlayerId::B));
+
+        // Removing a link needs a rebuild
+        stones[1 + 3] = Stone::empty();
+        chains.rebuild(&stones, PlayerId::A);
+        assert!(!chains.connects(PlayerId::A));
+
+        chains.clear();
+        stones[3] = Stone::new(PlayerId::B);
+        chains.rebuild(&stones, PlayerId::A);
+        assert!(!chains.connects(PlayerId::A));
+    }
+
+    #[test]
+    fn gaps() {
+        // A 2x2 checker board has 3x3 stone corners
+        let mut stones = vec![Stone::empty(); 9];
+        let chains = StoneChains::new(2, 2);
+        let gaps = chains.gaps(&stones, PlayerId::A);
+        assert!(gaps.iter().all(|(_, gap)| *gap == Some(3)));
+
+        // A's stone in the middle column shortens the chains through it
+        stones[1 + 3] = Stone::new(PlayerId::A);
+        let gaps = chains.gaps(&stones, PlayerId::A);
+        assert_eq!(gaps[Vec2::new(1, 0)], Some(2));
+        assert_eq!(gaps[Vec2::new(1, 1)], Some(2));
+        assert_eq!(gaps[Vec2::new(0, 0)], Some(3));
+        // and blocks B through that corner
+        let gaps = chains.gaps(&stones, PlayerId::B);
+        assert_eq!(gaps[Vec2::new(1, 1)], None);
+        assert_eq!(gaps[Vec2::new(0, 1)], Some(3));
+
+        // A wall of B's stones cuts A off everywhere
+        for x in 0..3 {
+            stones[x + 3] = Stone::new(PlayerId::B);
+        }
+        assert!(chains.gaps(&stones, PlayerId::A).iter().all(|(_, gap)| gap.is_none()));
+    }
+}
@@ -0,0 +1,239 @@
+/**
+ * Chat between the players and spectators of a network game. Each message is sent by an
+ * authenticated profile and relayed to everyone else in the game with the sender's name,
+ * unless chat has been turned off for the game.
+ *
+ * On the wire a chat message is one line, its fields separated by tabs. Clients send only
+ * the text, and the server fills in the sender as it relays it, so nobody can speak for
+ * someone else:
+ *
+ *   chat <text>              client to server
+ *   chat <sender> <text>     server to the other participants and spectators
+ */
+use crate::auth::{Profile, Seats};
+use crate::game::PlayerId;
+
+// longest message text, in characters
+pub const MAX_CHAT_LENGTH: usize = 500;
+const CHAT_WORD: &str = "chat";
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ChatError {
+    // Chat is turned off for the game
+    #[error("Chat is turned off for this game")]
+    Disabled,
+    // The sender is neither playing nor watching the game
+    #[error("{0} is not in this game")]
+    NotInGame(String),
+    // The message has no text
+    #[error("Chat messages can not be empty")]
+    Empty,
+    // The message is longer than MAX_CHAT_LENGTH, by its length in characters
+    #[error("Chat messages can be at most {MAX_CHAT_LENGTH} characters, not {0}")]
+    TooLong(usize),
+    // A line is not a chat message
+    #[error("Not a chat message: {0}")]
+    Parse(String),
+}
+
+/**
+ * ChatMessage is a message as relayed, with who sent it.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+    pub sender: String,
+    pub text: String,
+}
+
+impl ChatMessage {
+    /**
+     * to_line
+     * ret - The message as the server sends it, without a line ending.
+     */
+    pub fn to_line(&self) -> String {
+        format!("{}\t{}\t{}", CHAT_WORD, self.sender, self.text)
+    }
+
+    /**
+     * parse_line
+     * Read a message relayed by the server.
+     * line - Line received, with or without its line ending.
+     * ret - The message, or ChatError::Parse if the line is not a relayed chat message.
+     */
+    pub fn parse_line(line: &str) -> Result<ChatMessage, ChatError> {
+        let err = || ChatError::Parse(String::from(line.trim_end()));
+        let mut fields = line.trim_end_matches(['\r', '\n']).splitn(3, '\t');
+        if fields.next() != Some(CHAT_WORD) {
+            return Err(err());
+        }
+        let (Some(sender), Some(text)) = (fields.next(), fields.next()) else {
+            return Err(err());
+        };
+        Ok(ChatMessage { sender: String::from(sender), text: String::from(text) })
+    }
+}
+
+/**
+ * request_line
+ * Write the line a client sends to chat. Tabs and line breaks in the text become spaces.
+ * text - What to say.
+ * ret - The line, without a line ending.
+ */
+pub fn request_line(text: &str) -> String {
+    format!("{}\t{}", CHAT_WORD, text.replace(['\t', '\r', '\n'], " "))
+}
+
+/**
+ * parse_request
+ * Read a line a client sent to chat.
+ * line - Line received, with or without its line ending.
+ * ret - The text to say, or None if the line is not a chat request.
+ */
+pub fn parse_request(line: &str) -> Option<&str> {
+    line.trim_end_matches(['\r', '\n']).strip_prefix(CHAT_WORD)?.strip_prefix('\t')
+}
+
+/**
+ * ChatChannel is the chat of one game: who may talk in it and who hears them.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatChannel {
+    enabled: bool,
+    // names of the profiles playing, and of those watching, in the order they joined
+    participants: Vec<String>,
+    spectators: Vec<String>,
+}
+
+impl ChatChannel {
+    /**
+     * new
+     * Open the chat of a game, with its seated players as participants. Chat starts on.
+     * seats - Who plays the game.
+     */
+    pub fn new(seats: &Seats) -> ChatChannel {
+        let mut participants: Vec<String> = Vec::new();
+        for name in [PlayerId::A, PlayerId::B].into_iter().filter_map(|player| seats.player(player)) {
+            if !participants.iter().any(|joined| joined == name) {
+                participants.push(String::from(name));
+            }
+        }
+        ChatChannel { enabled: true, participants, spectators: Vec::new() }
+    }
+
+    /**
+     * set_enabled
+     * Turn chat for the game on or off. Spectators stay either way.
+     * enabled - False to refuse every message.
+     */
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /**
+     * enabled
+     * ret - True if messages are relayed.
+     */
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /**
+     * watch
+     * Add a spectator, who hears the chat and may talk in it.
+     * profile - Authenticated profile watching the game.
+     */
+    pub fn watch(&mut self, profile: &Profile) {
+        if !self.is_member(&profile.name) {
+            self.spectators.push(profile.name.clone());
+        }
+    }
+
+    /**
+     * leave
+     * Stop a spectator hearing the chat. Participants stay for the whole game.
+     * name - Name of the spectator's profile.
+     */
+    pub fn leave(&mut self, name: &str) {
+        self.spectators.retain(|spectator| spectator != name);
+    }
+
+    /**
+     * send
+     * Check a message may be relayed, and find who it goes to.
+     * sender - Authenticated profile sending the message.
+     * text - What they said.
+     * ret - The message with its sender, and the names of everyone else in the game, or a
+     * ChatError saying why it is refused.
+     */
+    pub fn send(&self, sender: &Profile, text: &str) -> Result<(ChatMessage, Vec<&str>), ChatError> {
+        if !self.enabled {
+            return Err(ChatError::Disabled);
+        }
+        if !self.is_member(&sender.name) {
+            return Err(ChatError::NotInGame(sender.name.clone()));
+        }
+        let text = text.trim();
+        let length = text.chars().count();
+        if length == 0 {
+            return Err(ChatError::Empty);
+        }
+        if length > MAX_CHAT_LENGTH {
+            return Err(ChatError::TooLong(length));
+        }
+        let recipients = self.participants.iter().chain(self.spectators.iter())
+            .filter(|name| **name != sender.name)
+            .map(String::as_str)
+            .collect();
+        let message = ChatMessage { sender: sender.name.clone(), text: text.replace(['\t', '\r', '\n'], " ") };
+        Ok((message, recipients))
+    }
+
+    /* Helper function checking @name plays or watches the game. */
+    fn is_member(&self, name: &str) -> bool {
+        self.participants.iter().chain(self.spectators.iter()).any(|member| member == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str) -> Profile {
+        Profile { name: String::from(name) }
+    }
+
+    #[test]
+    fn send() {
+        let mut seats = Seats::default();
+        seats.sit(&profile("Ada"), PlayerId::A).unwrap();
+        seats.sit(&profile("Grace Hopper"), PlayerId::B).unwrap();
+        let mut channel = ChatChannel::new(&seats);
+        channel.watch(&profile("Alan"));
+
+        // Messages reach everyone else, with the sender filled in
+        let (message, recipients) = channel.send(&profile("Ada"), "  good luck ").unwrap();
+        assert_eq!(message, ChatMessage { sender: String::from("Ada"), text: String::from("good luck") });
+        assert_eq!(recipients, ["Grace Hopper", "Alan"]);
+        assert_eq!(channel.send(&profile("Alan"), "hi").unwrap().1, ["Ada", "Grace Hopper"]);
+
+        assert_eq!(channel.send(&profile("Edsger"), "hi"), Err(ChatError::NotInGame(String::from("Edsger"))));
+        assert_eq!(channel.send(&profile("Ada"), " "), Err(ChatError::Empty));
+        assert_eq!(channel.send(&profile("Ada"), &"x".repeat(MAX_CHAT_LENGTH + 1)), Err(ChatError::TooLong(MAX_CHAT_LENGTH + 1)));
+        channel.leave("Alan");
+        assert_eq!(channel.send(&profile("Ada"), "hi").unwrap().1, ["Grace Hopper"]);
+        channel.set_enabled(false);
+        assert_eq!(channel.send(&profile("Ada"), "hi"), Err(ChatError::Disabled));
+    }
+
+    #[test]
+    fn lines() {
+        let request = request_line("gg\twell played\n");
+        assert_eq!(parse_request(&format!("{}\r\n", request)), Some("gg well played "));
+        assert_eq!(parse_request("chatter"), None);
+
+        let message = ChatMessage { sender: String::from("Grace Hopper"), text: String::from("thanks, you too") };
+        assert_eq!(ChatMessage::parse_line(&format!("{}\n", message.to_line())), Ok(message));
+        assert!(matches!(ChatMessage::parse_line("chat\tAda"), Err(ChatError::Parse(_))));
+        assert!(matches!(ChatMessage::parse_line("move 1,0 0,0"), Err(ChatError::Parse(_))));
+    }
+}
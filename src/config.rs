@@ -0,0 +1,560 @@
+/**
+ * Settings chosen before a game starts: which board to play on, and how the
+ * game is allowed to end.
+ */
+use std::fmt;
+use std::time::Duration;
+
+use crate::board::{Board, Direction, Terrain, BOARD_HEIGHT, BOARD_WIDTH};
+use crate::game::{CheckerHeight, PlayerId, REPETITION_LIMIT, STARTING_STONES};
+use crate::vec::Vec2;
+
+/**
+ * Variant is a named board size, with a starting layout and stone pools scaled to fit it.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variant {
+    // The original 8x6 board with 32 stones per player
+    Standard,
+    // A 10x8 board with ten checkers and 50 stones per player
+    Grand10x8,
+}
+
+impl Variant {
+    /**
+     * width
+     * ret - Number of checker columns on the board.
+     */
+    pub fn width(&self) -> usize {
+        match self {
+            Variant::Standard => BOARD_WIDTH,
+            Variant::Grand10x8 => 10,
+        }
+    }
+
+    /**
+     * height
+     * ret - Number of checker rows on the board.
+     */
+    pub fn height(&self) -> usize {
+        match self {
+            Variant::Standard => BOARD_HEIGHT,
+            Variant::Grand10x8 => 8,
+        }
+    }
+
+    /**
+     * stones
+     * ret - Number of stones each player starts with in their pool.
+     */
+    pub fn stones(&self) -> i32 {
+        match self {
+            Variant::Standard => STARTING_STONES,
+            Variant::Grand10x8 => 50,
+        }
+    }
+
+    /**
+     * home_columns
+     * Get the two columns a player's checkers start in.
+     * player - Id of player to look up.
+     * ret - Column numbers, nearest the player's own edge first.
+     */
+    pub fn home_columns(&self, player: PlayerId) -> [i32; 2] {
+        let last = self.width() as i32 - 1;
+        match player {
+            PlayerId::A => [last, last - 1],
+            PlayerId::B => [0, 1],
+        }
+    }
+
+    /**
+     * starting_checkers
+     * Get player A's starting checkers. Player B's are the same, mirrored left to right.
+     * ret - Vector of (position, height) pairs.
+     */
+    pub fn starting_checkers(&self) -> Vec<(Vec2, CheckerHeight)> {
+        let home = self.width() as i32 - 1;
+        let front = home - 1;
+        let (one, two, three) = (CheckerHeight::ONE, CheckerHeight::TWO, CheckerHeight::THREE);
+        match self {
+            Variant::Standard => vec![
+                (Vec2::new(front, 2), one), (Vec2::new(front, 3), one),
+                (Vec2::new(home, 1), two), (Vec2::new(home, 4), two),
+                (Vec2::new(home, 3), three), (Vec2::new(home, 2), three),
+            ],
+            Variant::Grand10x8 => vec![
+                (Vec2::new(front, 2), one), (Vec2::new(front, 3), one),
+                (Vec2::new(front, 4), one), (Vec2::new(front, 5), one),
+                (Vec2::new(home, 1), two), (Vec2::new(home, 6), two),
+                (Vec2::new(home, 2), three), (Vec2::new(home, 3), three),
+                (Vec2::new(home, 4), three), (Vec2::new(home, 5), three),
+            ],
+        }
+    }
+}
+
+/**
+ * Handicap gives one player a head start, to balance games between players of
+ * different strength. Positions are absolute board positions.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Handicap {
+    // Stones added to the player's pool
+    ExtraStones(i32),
+    // A checker of the given height placed at the start of the game
+    ExtraChecker(Vec2, CheckerHeight),
+    // A line of stones on the board at the start of the game, not taken from the pool
+    StoneLine { start: Vec2, direction: Direction, length: usize },
+}
+
+/**
+ * RulesConfig holds the rule switches that vary between variants, so the Board and
+ * Game can apply them without separate copies of the rule code.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RulesConfig {
+    // rule of negation: stones cannot be placed on a corner of a square holding a checker
+    pub negation: bool,
+    // rule of circularity: sliding a stone straight back to where it was last turn loses
+    pub circularity: bool,
+    // how many squares away, in a straight line, a checker can attack from
+    pub fire_range: i32,
+    // when true, a player also wins by destroying every opposing checker
+    pub elimination: bool,
+    // furthest a stone may slide in one move (at least 1), or None to slide until blocked
+    pub slide_limit: Option<usize>,
+    // when true, checkers only move up, down, left or right, never diagonally
+    pub orthogonal_checkers: bool,
+    // number of actions in a turn, each of a different kind (eg a checker move and an attack)
+    pub actions_per_turn: usize,
+    // pie rule: after the first player's first turn, the second may swap sides instead of moving
+    pub pie_rule: bool,
+    // when true, attacks roll no dice and always do their expected damage, rounded to nearest
+    pub deterministic_combat: bool,
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        RulesConfig {
+            negation: true,
+            circularity: true,
+            fire_range: 2,
+            elimination: false,
+            slide_limit: None,
+            orthogonal_checkers: false,
+            actions_per_turn: 1,
+            pie_rule: false,
+            deterministic_combat: false,
+        }
+    }
+}
+
+/**
+ * TimeControl gives each player a clock that runs while they choose their moves. Running
+ * out of time does not end the game.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeControl {
+    // time on each clock at the start of the game
+    pub initial: Duration,
+    // time added to a player's clock after each of their turns
+    pub increment: Duration,
+    // players are warned when their time left drops below this
+    pub warning: Duration,
+}
+
+/**
+ * GlyphTheme is the set of characters a board is drawn with.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphTheme {
+    // checkers of player A and of player B, in that order, indexed by height
+    pub checkers: [[char; 4]; 2],
+    // stones of player A and of player B, in that order
+    pub stones: [char; 2],
+    // a corner without a stone
+    pub empty_stone: char,
+    // a square without a checker
+    pub empty_checker: char,
+}
+
+impl GlyphTheme {
+    /**
+     * classic
+     * Letters for player A's checkers, digits for player B's, and a and b for stones.
+     */
+    pub fn classic() -> GlyphTheme {
+        GlyphTheme {
+            checkers: [['.', 'A', 'B', 'C'], ['.', '1', '2', '3']],
+            stones: ['a', 'b'],
+            empty_stone: '.',
+            empty_checker: '_',
+        }
+    }
+
+    /**
+     * unicode
+     * Circled digits for checkers, white for player A and black for player B, with
+     * matching round stones.
+     */
+    pub fn unicode() -> GlyphTheme {
+        GlyphTheme {
+            checkers: [['·', '①', '②', '③'], ['·', '❶', '❷', '❸']],
+            stones: ['○', '●'],
+            empty_stone: '·',
+            empty_checker: '_',
+        }
+    }
+
+    /**
+     * named
+     * Look up a bundled theme by name.
+     * name - classic or unicode.
+     * ret - The theme, or None for any other name.
+     */
+    pub fn named(name: &str) -> Option<GlyphTheme> {
+        match name {
+            "classic" => Some(GlyphTheme::classic()),
+            "unicode" => Some(GlyphTheme::unicode()),
+            _ => None,
+        }
+    }
+
+    /**
+     * checker
+     * player - Owner of the checker.
+     * height - Height of the checker.
+     * ret - Glyph for the checker.
+     */
+    pub fn checker(&self, player: PlayerId, height: CheckerHeight) -> char {
+        self.checkers[player.index()][height.get()]
+    }
+
+    /**
+     * stone
+     * player - Owner of the stone.
+     * ret - Glyph for the stone.
+     */
+    pub fn stone(&self, player: PlayerId) -> char {
+        self.stones[player.index()]
+    }
+}
+
+impl Default for GlyphTheme {
+    fn default() -> Self {
+        GlyphTheme::classic()
+    }
+}
+
+// narrowest terminal, in columns, the full layout's status lines fit in
+pub const FULL_LAYOUT_COLUMNS: usize = 60;
+
+/**
+ * BoardLayout is how much room the console view of a game takes up.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoardLayout {
+    // the board with marks, and a line each for status, progress and clocks
+    #[default]
+    Full,
+    // the board, already one character per corner and square, and a short status line per
+    // player, to fit in 40 columns
+    Compact,
+}
+
+impl BoardLayout {
+    /**
+     * for_columns
+     * columns - Width of the terminal.
+     * ret - Compact if the full layout would not fit, else Full.
+     */
+    pub fn for_columns(columns: usize) -> BoardLayout {
+        if columns < FULL_LAYOUT_COLUMNS { BoardLayout::Compact } else { BoardLayout::Full }
+    }
+
+    /**
+     * detect
+     * ret - The layout for the terminal's width, or Full if it can not be found.
+     */
+    pub fn detect() -> BoardLayout {
+        crate::console::terminal_columns().map_or(BoardLayout::Full, BoardLayout::for_columns)
+    }
+}
+
+/**
+ * StartPosition replaces a variant's starting layout with an exact set of pieces.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StartPosition {
+    // (owner, position, height)
+    pub checkers: Vec<(PlayerId, Vec2, CheckerHeight)>,
+    // (owner, position)
+    pub stones: Vec<(PlayerId, Vec2)>,
+}
+
+impl StartPosition {
+    /**
+     * from_board
+     * Take down the pieces of a board, such as the final position of a game.
+     * board - Board to copy the pieces of.
+     */
+    pub fn from_board(board: &Board) -> StartPosition {
+        StartPosition {
+            checkers: board.checkers().into_iter()
+                .filter_map(|(pos, checker)| checker.owner.map(|owner| (owner, pos, checker.height)))
+                .collect(),
+            stones: board.stones().into_iter()
+                .filter_map(|(pos, stone)| stone.owner.map(|owner| (owner, pos)))
+                .collect(),
+        }
+    }
+}
+
+/**
+ * Written as the checker and stone lines of a scenario file, so a position can be read back
+ * with Scenario::parse.
+ */
+impl fmt::Display for StartPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let side = |player: &PlayerId| if *player == PlayerId::A { "a" } else { "b" };
+        for (owner, pos, height) in self.checkers.iter() {
+            writeln!(f, "checker {} {} {} {}", side(owner), pos.x, pos.y, height.get())?;
+        }
+        for (owner, pos) in self.stones.iter() {
+            writeln!(f, "stone {} {} {}", side(owner), pos.x, pos.y)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * HillConfig sets up the king-of-the-hill win: a player wins by ending this many of
+ * their turns in a row with a checker on any of the hill squares.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct HillConfig {
+    pub squares: Vec<Vec2>,
+    pub turns: u32,
+}
+
+impl HillConfig {
+    /**
+     * centre
+     * Create a hill on the central four checker squares of a variant's board.
+     * variant - Board the hill is on.
+     * turns - Consecutive turns a player has to hold the hill for.
+     */
+    pub fn centre(variant: Variant, turns: u32) -> HillConfig {
+        let (x, y) = (variant.width() as i32 / 2, variant.height() as i32 / 2);
+        HillConfig {
+            squares: vec![
+                Vec2::new(x - 1, y - 1), Vec2::new(x, y - 1),
+                Vec2::new(x - 1, y), Vec2::new(x, y),
+            ],
+            turns,
+        }
+    }
+}
+
+/**
+ * GameConfig collects the options a Game is created with.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    pub variant: Variant,
+    pub rules: RulesConfig,
+    // number of times a position may occur before the game is drawn, or None to never draw
+    pub repetition_limit: Option<u32>,
+    // handicaps as (id of player receiving it, handicap)
+    pub handicaps: Vec<(PlayerId, Handicap)>,
+    // stone pool sizes as (id of player, stones), overriding the variant's pool size
+    pub stone_pools: Vec<(PlayerId, i32)>,
+    // when true, players take turns placing their starting checkers in their home columns
+    // before the main game, instead of starting from the variant's layout
+    pub free_setup: bool,
+    // king-of-the-hill win condition, or None to play without it
+    pub hill: Option<HillConfig>,
+    // number of moves after which the game is stopped, or None to play until it ends
+    pub move_limit: Option<usize>,
+    // when a game hits the move limit, the material lead needed to be awarded the win,
+    // or None to always call it a draw
+    pub adjudication_margin: Option<i32>,
+    // checker squares that are not Plain ground, empty for the default terrain-free game
+    pub terrain: Vec<(Vec2, Terrain)>,
+    // pieces to start from instead of the variant's layout, or None for the usual layout
+    pub start_position: Option<StartPosition>,
+    // characters the board is drawn with
+    pub glyphs: GlyphTheme,
+    // clocks for both players, or None for an untimed game
+    pub time_control: Option<TimeControl>,
+    // player who makes the first move, so repeated games can alternate it
+    pub first_player: PlayerId,
+}
+
+impl GameConfig {
+    /**
+     * for_variant
+     * Create the default configuration for a variant.
+     */
+    pub fn for_variant(variant: Variant) -> GameConfig {
+        GameConfig {
+            variant,
+            ..GameConfig::default()
+        }
+    }
+
+    /**
+     * with_handicap
+     * Add a handicap for a player.
+     * player - Id of player receiving the handicap.
+     * handicap - Head start to give them.
+     * ret - The configuration, for chaining.
+     */
+    pub fn with_handicap(mut self, player: PlayerId, handicap: Handicap) -> GameConfig {
+        self.handicaps.push((player, handicap));
+        self
+    }
+
+    /**
+     * with_stone_pool
+     * Give a player a stone pool of a different size from the variant's.
+     * player - Id of player whose pool to change.
+     * stones - Number of stones in their pool, before any ExtraStones handicaps.
+     * ret - The configuration, for chaining.
+     */
+    pub fn with_stone_pool(mut self, player: PlayerId, stones: i32) -> GameConfig {
+        self.stone_pools.retain(|(id, _)| *id != player);
+        self.stone_pools.push((player, stones));
+        self
+    }
+
+    /**
+     * stones_for
+     * Get the size of a player's stone pool, including ExtraStones handicaps.
+     * player - Id of player to look up.
+     * ret - Number of stones they start with.
+     */
+    pub fn stones_for(&self, player: PlayerId) -> i32 {
+        let base = self.stone_pools.iter()
+            .find(|(id, _)| *id == player)
+            .map_or(self.variant.stones(), |(_, stones)| *stones);
+        base + self.extra_stones_for(player)
+    }
+
+    /**
+     * extra_stones_for
+     * Get the total of a player's ExtraStones handicaps.
+     * player - Id of player to look up.
+     * ret - Number of stones to add to their pool.
+     */
+    pub fn extra_stones_for(&self, player: PlayerId) -> i32 {
+        self.handicaps.iter()
+            .filter(|(id, _)| *id == player)
+            .map(|(_, handicap)| match handicap {
+                Handicap::ExtraStones(count) => *count,
+                _ => 0
+            })
+            .sum()
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            variant: Variant::Standard,
+            rules: RulesConfig::default(),
+            repetition_limit: Some(REPETITION_LIMIT),
+            handicaps: Vec::new(),
+            stone_pools: Vec::new(),
+            free_setup: false,
+            hill: None,
+            move_limit: None,
+            adjudication_margin: None,
+            terrain: Vec::new(),
+            start_position: None,
+            glyphs: GlyphTheme::default(),
+            time_control: None,
+            first_player: PlayerId::A,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starting_checkers_fit() {
+        for variant in [Variant::Standard, Variant::Grand10x8] {
+            let checkers = variant.starting_checkers();
+            for (pos, height) in checkers.iter() {
+                assert!(pos.x >= 0 && pos.x < variant.width() as i32);
+                assert!(pos.y >= 0 && pos.y < variant.height() as i32);
+                assert!(!height.is_zero());
+            }
+            // No two checkers share a square
+            for (i, (pos, _)) in checkers.iter().enumerate() {
+                assert!(checkers[i + 1..].iter().all(|(other, _)| other != pos));
+            }
+        }
+    }
+
+    #[test]
+    fn home_columns() {
+        for variant in [Variant::Standard, Variant::Grand10x8] {
+            let columns_a = variant.home_columns(PlayerId::A);
+            for (pos, _) in variant.starting_checkers() {
+                assert!(columns_a.contains(&pos.x));
+            }
+            assert_eq!(variant.home_columns(PlayerId::B), [0, 1]);
+        }
+    }
+
+    #[test]
+    fn grand_is_larger() {
+        let standard = Variant::Standard;
+        let grand = Variant::Grand10x8;
+        assert!(grand.width() > standard.width() && grand.height() > standard.height());
+        assert!(grand.stones() > standard.stones());
+        assert!(grand.starting_checkers().len() > standard.starting_checkers().len());
+    }
+
+    #[test]
+    fn extra_stones_for() {
+        let config = GameConfig::default()
+            .with_handicap(PlayerId::B, Handicap::ExtraStones(4))
+            .with_handicap(PlayerId::B, Handicap::ExtraChecker(Vec2::new(1, 0), CheckerHeight::ONE))
+            .with_handicap(PlayerId::B, Handicap::ExtraStones(2))
+            .with_handicap(PlayerId::A, Handicap::ExtraStones(1));
+        assert_eq!(config.extra_stones_for(PlayerId::B), 6);
+        assert_eq!(config.extra_stones_for(PlayerId::A), 1);
+    }
+
+    #[test]
+    fn stones_for() {
+        let config = GameConfig::default()
+            .with_stone_pool(PlayerId::A, 20)
+            .with_stone_pool(PlayerId::A, 24)
+            .with_handicap(PlayerId::A, Handicap::ExtraStones(2));
+        assert_eq!(config.stones_for(PlayerId::A), 26);
+        assert_eq!(config.stones_for(PlayerId::B), STARTING_STONES);
+        assert_eq!(GameConfig::for_variant(Variant::Grand10x8).stones_for(PlayerId::B), 50);
+    }
+
+    #[test]
+    fn hill_centre() {
+        let hill = HillConfig::centre(Variant::Standard, 3);
+        assert_eq!(hill.squares.len(), 4);
+        assert!(hill.squares.contains(&Vec2::new(3, 2)) && hill.squares.contains(&Vec2::new(4, 3)));
+        let grand = HillConfig::centre(Variant::Grand10x8, 3);
+        assert!(grand.squares.contains(&Vec2::new(4, 3)) && grand.squares.contains(&Vec2::new(5, 4)));
+    }
+
+    #[test]
+    fn for_variant() {
+        let config = GameConfig::for_variant(Variant::Grand10x8);
+        assert_eq!(config.variant, Variant::Grand10x8);
+        assert_eq!(config.repetition_limit, GameConfig::default().repetition_limit);
+    }
+}
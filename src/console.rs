@@ -0,0 +1,492 @@
+/**
+ * Console menus for players at the keyboard. Input is read from any reader and menus are
+ * written to any writer, so the prompts can be driven by scripted input in tests.
+ *
+ * Letters may be typed in either case, blank lines are ignored, h prints a summary of the
+ * rules, d describes the position in words, t asks to take back the player's last turn, and q
+ * (or the end of input) quits.
+ * In the list of slides, v and a number previews where that stone would come to rest.
+ * Anything else that is not on offer prints the menu again.
+ *
+ * The text shown comes from a messages::Catalog, so the menus can be shown in the player's
+ * language. The letters typed are the same in every language.
+ */
+use std::io::{BufRead, Write};
+
+use crate::game::{Decision, Intent, MoveRequest};
+use crate::messages::{Catalog, Key};
+
+// environment variable shells set to the terminal's width
+const COLUMNS_VARIABLE: &str = "COLUMNS";
+
+/**
+ * terminal_columns
+ * Find how wide the terminal is, from the COLUMNS variable, or else from the terminal
+ * stdout is connected to.
+ * ret - Width in columns, or None if stdout is not a terminal and COLUMNS is not set.
+ */
+pub fn terminal_columns() -> Option<usize> {
+    if let Some(columns) = std::env::var(COLUMNS_VARIABLE).ok().and_then(|columns| columns.parse().ok()) {
+        return Some(columns);
+    }
+    #[cfg(unix)]
+    {
+        // SAFETY: TIOCGWINSZ only writes a winsize, which is zeroed and owned here
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let found = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        (found && size.ws_col > 0).then_some(size.ws_col as usize)
+    }
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/**
+ * Command is one line of console input.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    // a menu letter, in upper case
+    Letter(char),
+    // a number from a list
+    Number(usize),
+    // v and a number from a list, to see the move before making it
+    Preview(usize),
+    Help,
+    Quit,
+    // anything else
+    Invalid,
+}
+
+/**
+ * parse_command
+ * Read one line of input.
+ * line - Line as typed.
+ * ret - The command, or None for a blank line.
+ */
+pub fn parse_command(line: &str) -> Option<Command> {
+    let word = line.trim();
+    if word.is_empty() {
+        return None;
+    }
+    if let Ok(number) = word.parse() {
+        return Some(Command::Number(number));
+    }
+    if let Some(number) = word.strip_prefix(['v', 'V']).and_then(|rest| rest.trim_start().parse().ok()) {
+        return Some(Command::Preview(number));
+    }
+    let mut chars = word.chars();
+    let command = match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => match letter.to_ascii_uppercase() {
+            'H' => Command::Help,
+            'Q' => Command::Quit,
+            letter => Command::Letter(letter),
+        },
+        _ => Command::Invalid,
+    };
+    Some(command)
+}
+
+/**
+ * Console asks a player for moves through a pair of input and output streams.
+ */
+pub struct Console<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    catalog: Catalog,
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    /**
+     * new
+     * Create a console showing English text.
+     * input - Where the player's typing is read from.
+     * output - Where menus and prompts are written.
+     */
+    pub fn new(input: R, output: W) -> Console<R, W> {
+        Console::with_catalog(input, output, Catalog::default())
+    }
+
+    /**
+     * with_catalog
+     * input - Where the player's typing is read from.
+     * output - Where menus and prompts are written.
+     * catalog - Messages to show the player.
+     */
+    pub fn with_catalog(input: R, output: W, catalog: Catalog) -> Console<R, W> {
+        Console { input, output, catalog }
+    }
+
+    /**
+     * choose_move
+     * Show the kinds of move on offer, then the moves of the chosen kind, until the
+     * player picks one. Checker moves are chosen by checker first, then destination.
+     * Once the player has moved, they may ask to take back their last turn instead.
+     * request - Moves to choose from.
+     * ret - The decision, or None if the player quit.
+     */
+    pub fn choose_move(&mut self, request: &MoveRequest) -> Option<Decision> {
+        let can_take_back = request.view.history.iter().any(|(mover, _)| *mover == request.player);
+        loop {
+            let mut menu = vec![];
+            if request.place_checkers.is_empty() {
+                menu.push(('M', Key::MoveChecker, !request.move_checkers.is_empty()));
+                menu.push(('A', Key::AttackChecker, !request.fire_checkers.is_empty()));
+                menu.push(('P', Key::PlaceStone, !request.place_stones.is_empty()));
+                menu.push(('S', Key::SlideStone, !request.slide_stones.is_empty()));
+                menu.push(('W', Key::SwapSides, !request.swap_sides.is_empty()));
+            } else {
+                menu.push(('C', Key::PlaceStartingChecker, true));
+            }
+            menu.push(('T', Key::TakeBack, can_take_back));
+            menu.push(('H', Key::RulesSummaryItem, true));
+            menu.push(('D', Key::DescribeItem, true));
+            menu.push(('Q', Key::QuitItem, true));
+            self.say_line(Key::WhatToDo);
+            for (letter, label, _) in menu.iter().filter(|(_, _, offered)| *offered) {
+                let item = format!("{} - {}\n", letter, self.catalog.text(*label));
+                self.say(&item);
+            }
+            let letter = match self.read_command(Key::EnterLetter) {
+                Command::Quit => return None,
+                Command::Help => {
+                    self.say_line(Key::RulesSummary);
+                    continue;
+                },
+                Command::Letter(letter) if menu.iter().any(|(offered, _, on)| *offered == letter && *on) => letter,
+                _ => {
+                    self.say_line(Key::NotAChoice);
+                    continue;
+                },
+            };
+            if letter == 'T' {
+                return Some(Decision::TakeBack);
+            }
+            if letter == 'D' {
+                let description = format!("\n{}", request.view.board.describe());
+                self.say(&description);
+                continue;
+            }
+            let chosen = match letter {
+                'M' => self.choose_checker_move(request),
+                'A' => self.choose_from(&request.fire_checkers),
+                'P' => self.choose_from(&request.place_stones),
+                'S' => self.choose_slide(request),
+                'C' => self.choose_from(&request.place_checkers),
+                _ => Ok(Some(Intent::SwapSides)),
+            };
+            match chosen {
+                Ok(Some(intent)) => return Some(Decision::Move(intent)),
+                // the player backed out to the menu
+                Ok(None) => continue,
+                Err(Quit) => return None,
+            }
+        }
+    }
+
+    /**
+     * confirm
+     * Ask the player a yes or no question, answered with the catalog's yes and no letters.
+     * question - Question to ask, without the answers.
+     * ret - True if the player answered yes. Quitting or the end of input counts as no.
+     */
+    pub fn confirm(&mut self, question: &str) -> bool {
+        let letter = |key| self.catalog.text(key).chars().next().map(|c| c.to_ascii_uppercase());
+        let (yes, no) = (letter(Key::YesLetter).unwrap_or('Y'), letter(Key::NoLetter).unwrap_or('N'));
+        loop {
+            let prompt = self.catalog.format(Key::Question, &[&question, &yes, &no]);
+            self.say(&prompt);
+            let Some(line) = self.read_text() else {
+                return false;
+            };
+            // the answers come before the h and q commands, as a catalog may answer with them
+            let mut chars = line.trim().chars().map(|c| c.to_ascii_uppercase());
+            let answer = match (chars.next(), chars.next()) {
+                (Some(letter), None) => Some(letter),
+                _ => None,
+            };
+            match parse_command(&line) {
+                _ if answer == Some(yes) => return true,
+                _ if answer == Some(no) => return false,
+                Some(Command::Quit) => return false,
+                None => (),
+                Some(_) => {
+                    let again = format!("{}\n", self.catalog.format(Key::AnswerYesOrNo, &[&yes, &no]));
+                    self.say(&again);
+                },
+            }
+        }
+    }
+
+    /*
+     * Helper function choosing a checker, then one of its moves.
+     */
+    fn choose_checker_move(&mut self, request: &MoveRequest) -> Result<Option<Intent>, Quit> {
+        let sources = request.checker_moves_by_source();
+        let labels: Vec<String> = sources.iter()
+            .map(|(from, moves)| self.catalog.format(Key::CheckerWithMoves, &[from, &moves.len()]))
+            .collect();
+        let Some(idx) = self.choose_index(&labels)? else {
+            return Ok(None);
+        };
+        self.choose_from(&sources[idx].1)
+    }
+
+    /*
+     * Helper function choosing a slide, letting the player see where a stone would come to
+     * rest before making up their mind.
+     */
+    fn choose_slide(&mut self, request: &MoveRequest) -> Result<Option<Intent>, Quit> {
+        let slides = &request.slide_stones;
+        loop {
+            for (idx, intent) in slides.iter().enumerate() {
+                let item = format!("{} - {}\n", idx, self.catalog.describe(intent));
+                self.say(&item);
+            }
+            match self.read_command(Key::EnterNumberOrPreview) {
+                Command::Number(idx) if idx < slides.len() => return Ok(Some(slides[idx])),
+                Command::Preview(idx) if idx < slides.len() => {
+                    self.preview_slide(request, slides[idx]);
+                    let question = self.catalog.text(Key::MakeSlide).to_string();
+                    if self.confirm(&question) {
+                        return Ok(Some(slides[idx]));
+                    }
+                },
+                Command::Quit => return Err(Quit),
+                Command::Help => self.say_line(Key::RulesSummary),
+                Command::Letter('B') => return Ok(None),
+                _ => self.say_line(Key::NotAChoice),
+            }
+        }
+    }
+
+    /*
+     * Helper function showing the board as it would be after a slide, on a copy of the board.
+     */
+    fn preview_slide(&mut self, request: &MoveRequest, intent: Intent) {
+        let Intent::SlideStone(from, dir) = intent else {
+            return;
+        };
+        let mut board = request.view.board.clone();
+        let shown = match board.slide_stone_result(from, dir) {
+            Ok(to) => {
+                let _ = board.slide_stone(from, dir);
+                format!("\n{}\n{}\n", board, self.catalog.format(Key::WouldComeToRest, &[&from, &to]))
+            },
+            Err(err) => format!("{}\n", self.catalog.format(Key::SlideImpossible, &[&err])),
+        };
+        self.say(&shown);
+    }
+
+    /*
+     * Helper function choosing one of @moves.
+     */
+    fn choose_from(&mut self, moves: &[Intent]) -> Result<Option<Intent>, Quit> {
+        let labels: Vec<String> = moves.iter().map(|intent| self.catalog.describe(intent)).collect();
+        Ok(self.choose_index(&labels)?.map(|idx| moves[idx]))
+    }
+
+    /*
+     * Helper function listing @labels by number and reading the player's choice. Returns
+     * None if the player goes back to the menu with B.
+     */
+    fn choose_index(&mut self, labels: &[String]) -> Result<Option<usize>, Quit> {
+        loop {
+            for (idx, label) in labels.iter().enumerate() {
+                self.say(&format!("{} - {}\n", idx, label));
+            }
+            match self.read_command(Key::EnterNumber) {
+                Command::Number(idx) if idx < labels.len() => return Ok(Some(idx)),
+                Command::Quit => return Err(Quit),
+                Command::Help => self.say_line(Key::RulesSummary),
+                Command::Letter('B') => return Ok(None),
+                _ => self.say_line(Key::NotAChoice),
+            }
+        }
+    }
+
+    /*
+     * Helper function prompting with the message @prompt until a line that is not blank is read.
+     */
+    fn read_command(&mut self, prompt: Key) -> Command {
+        loop {
+            let text = self.catalog.text(prompt).to_string();
+            self.say(&text);
+            if let Some(command) = self.read_line() {
+                return command;
+            }
+        }
+    }
+
+    /*
+     * Helper function reading one line, None if it is blank. The end of the input, or an
+     * error reading it, counts as quitting.
+     */
+    fn read_line(&mut self) -> Option<Command> {
+        match self.read_text() {
+            Some(line) => parse_command(&line),
+            None => Some(Command::Quit),
+        }
+    }
+
+    /* Helper function reading one line as typed, None at the end of the input or on an error. */
+    fn read_text(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match self.input.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line),
+        }
+    }
+
+    /* Helper function writing to the player. A console that cannot be written to is ignored. */
+    fn say(&mut self, text: &str) {
+        let _ = self.output.write_all(text.as_bytes());
+        let _ = self.output.flush();
+    }
+
+    /* Helper function writing a message from the catalog on a line of its own. */
+    fn say_line(&mut self, key: Key) {
+        let text = format!("\n{}\n", self.catalog.text(key));
+        self.say(&text);
+    }
+}
+
+/* The player asked to quit. */
+struct Quit;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, PlayerFactory, PlayerId, STARTING_STONES};
+    use crate::messages::Language;
+    use std::io::Cursor;
+
+    /* Helper function running a console over scripted input, returning the move and what was shown. */
+    fn run(script: &str, request: &MoveRequest) -> (Option<Decision>, String) {
+        let mut output = Vec::new();
+        let chosen = Console::new(Cursor::new(script), &mut output).choose_move(request);
+        (chosen, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parse_command() {
+        assert_eq!(super::parse_command("  \n"), None);
+        assert_eq!(super::parse_command("p\n"), Some(Command::Letter('P')));
+        assert_eq!(super::parse_command(" 12 "), Some(Command::Number(12)));
+        assert_eq!(super::parse_command("q"), Some(Command::Quit));
+        assert_eq!(super::parse_command("H"), Some(Command::Help));
+        assert_eq!(super::parse_command("move"), Some(Command::Invalid));
+        assert_eq!(super::parse_command("-1"), Some(Command::Invalid));
+        assert_eq!(super::parse_command("v 2"), Some(Command::Preview(2)));
+        assert_eq!(super::parse_command("V0"), Some(Command::Preview(0)));
+        assert_eq!(super::parse_command("vx"), Some(Command::Invalid));
+    }
+
+    #[test]
+    fn choose_move() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let game = Game::new(&mut player_a, &mut player_b);
+        let request = game.move_request(PlayerId::A);
+
+        // Lower case letters and blank lines are fine
+        let (chosen, shown) = run("\n\np\n\n1\n", &request);
+        assert_eq!(chosen, Some(Decision::Move(request.place_stones[1])));
+        assert_eq!(shown.matches("Enter a letter: ").count(), 3);
+
+        // A checker first, then its destination
+        let (chosen, _) = run("m\n0\n0\n", &request);
+        assert_eq!(chosen, Some(Decision::Move(request.checker_moves_by_source()[0].1[0])));
+
+        // Invalid input and help show the menu again, and going back returns to it
+        let (chosen, shown) = run("x\nh\nw\na\np\n999\nb\ns\nq\n", &request);
+        assert_eq!(chosen, None);
+        assert_eq!(shown.matches("What would you like to do?").count(), 7);
+        assert_eq!(shown.matches("That is not one of the choices.").count(), 5);
+        assert!(shown.contains(Catalog::default().text(Key::RulesSummary)));
+        assert!(!shown.contains("W - Swap sides"));
+        assert!(!shown.contains("T - Take back"));
+
+        // Describing the position shows the menu again
+        let (chosen, shown) = run("d\nq\n", &request);
+        assert_eq!(chosen, None);
+        assert!(shown.contains(&request.view.board.describe()));
+        assert_eq!(shown.matches("What would you like to do?").count(), 2);
+
+        // The end of the input quits
+        assert_eq!(run("p\n", &request).0, None);
+        assert_eq!(run("", &request).0, None);
+    }
+
+    #[test]
+    fn preview_slide() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let corner = game.move_request(PlayerId::A).place_stones[0];
+        game.try_apply_move(PlayerId::A, corner).unwrap();
+        let request = game.move_request(PlayerId::A);
+        let slide = request.slide_stones[0];
+        let Intent::SlideStone(from, dir) = slide else {
+            panic!("expected a slide");
+        };
+        let to = request.view.board.slide_stone_result(from, dir).unwrap();
+
+        // Cancelling a preview lists the slides again, and the board is left alone
+        let (chosen, shown) = run("s\nv0\nn\nv 0\ny\n", &request);
+        assert_eq!(chosen, Some(Decision::Move(slide)));
+        assert_eq!(shown.matches(&format!("would come to rest at {}", to)).count(), 2);
+        assert!(request.view.board.stone_at(from).unwrap().owner.is_some());
+
+        let (chosen, _) = run("s\nv0\nn\n0\n", &request);
+        assert_eq!(chosen, Some(Decision::Move(slide)));
+        assert_eq!(run("s\nv99\nb\nq\n", &request).0, None);
+    }
+
+    #[test]
+    fn take_back() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let first = game.move_request(PlayerId::A).place_stones[0];
+        game.try_apply_move(PlayerId::A, first).unwrap();
+        let reply = game.move_request(PlayerId::B).place_stones[0];
+        game.try_apply_move(PlayerId::B, reply).unwrap();
+
+        // Only a player who has moved may ask
+        let (chosen, shown) = run("t\n", &game.move_request(PlayerId::A));
+        assert_eq!(chosen, Some(Decision::TakeBack));
+        assert!(shown.contains("T - Take back your last turn"));
+
+        let answer = |script: &str| Console::new(Cursor::new(script), Vec::new()).confirm("Allow it?");
+        assert!(answer("y\n"));
+        assert!(!answer("maybe\nn\n"));
+        assert!(!answer(""));
+
+        // Answers a catalog gives the letters of commands are still answers
+        let catalog = Catalog::parse(Language::English, "yes_letter = Hai\nno_letter = Quit").unwrap();
+        let answer = |script: &str| Console::with_catalog(Cursor::new(script), Vec::new(), catalog.clone()).confirm("Allow it?");
+        assert!(answer("h\n"));
+        assert!(!answer("y\nq\n"));
+        assert!(!answer(""));
+    }
+
+    #[test]
+    fn languages() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let game = Game::new(&mut player_a, &mut player_b);
+        let request = game.move_request(PlayerId::A);
+
+        let mut output = Vec::new();
+        let mut console = Console::with_catalog(Cursor::new("p\n0\no\n"), &mut output, Catalog::new(Language::French));
+        assert_eq!(console.choose_move(&request), Some(Decision::Move(request.place_stones[0])));
+        // French answers yes with O
+        assert!(console.confirm("D'accord ?"));
+        let shown = String::from_utf8(output).unwrap();
+        assert!(shown.contains("P - Poser une pierre"));
+        assert!(shown.contains("D'accord ? (O/N)"));
+        assert!(!shown.contains("Place stone"));
+    }
+}
@@ -0,0 +1,212 @@
+/**
+ * A player decided by vote, for audiences playing against the engine or for putting several
+ * bots together into one. Voters cast votes through Ballots while the player's window for a
+ * move is open, and the move with the most votes is played.
+ */
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::game::{Decide, Intent, MoveRequest};
+
+/**
+ * Vote is one voter's choice of move.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vote {
+    // name of the voter, who has one vote a move
+    pub voter: String,
+    pub intent: Intent,
+}
+
+/**
+ * Ballot casts votes for a CrowdPlayer. It can be cloned, to give one to each voter or to
+ * each thread that collects votes.
+ */
+#[derive(Clone, Debug)]
+pub struct Ballot {
+    sender: Sender<Vote>,
+}
+
+impl Ballot {
+    /**
+     * vote
+     * Cast a vote for the move being decided. A voter voting again changes their vote.
+     * voter - Name of the voter.
+     * intent - Move voted for.
+     * ret - False if the player is gone, so no more votes can be counted.
+     */
+    pub fn vote(&self, voter: impl Into<String>, intent: Intent) -> bool {
+        self.sender.send(Vote { voter: voter.into(), intent }).is_ok()
+    }
+}
+
+/**
+ * CrowdPlayer decides each move by vote. When asked for a move it opens a window, counts
+ * the votes cast during it, and plays the legal move with the most. Votes cast between
+ * windows were for another position and are thrown away, and a window never runs past the
+ * time left on the player's clock.
+ */
+pub struct CrowdPlayer {
+    window: Duration,
+    votes: Mutex<Receiver<Vote>>,
+    // decides when nobody votes for a legal move, or None to play the first move offered
+    fallback: Option<Box<dyn Decide + Send>>,
+}
+
+impl CrowdPlayer {
+    /**
+     * new
+     * window - How long votes are taken for each move.
+     * ret - The player, and the ballot votes are cast with.
+     */
+    pub fn new(window: Duration) -> (CrowdPlayer, Ballot) {
+        let (sender, receiver) = mpsc::channel();
+        let player = CrowdPlayer { window, votes: Mutex::new(receiver), fallback: None };
+        (player, Ballot { sender })
+    }
+
+    /**
+     * with_fallback
+     * Decide moves nobody votes for with another decider, such as an engine.
+     * fallback - Decider to use.
+     * ret - The player.
+     */
+    pub fn with_fallback(mut self, fallback: Box<dyn Decide + Send>) -> CrowdPlayer {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /**
+     * window
+     * ret - How long votes are taken for each move.
+     */
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /* Helper function collecting the votes cast until the window for @request closes. */
+    fn collect(&self, request: &MoveRequest) -> Vec<Vote> {
+        let window = match &request.clock {
+            Some(clock) => self.window.min(clock.remaining),
+            None => self.window,
+        };
+        let receiver = self.votes.lock().expect("vote receiver poisoned");
+        while receiver.try_recv().is_ok() {}
+        let closes = Instant::now() + window;
+        let mut votes = Vec::new();
+        loop {
+            match receiver.recv_timeout(closes.saturating_duration_since(Instant::now())) {
+                Ok(vote) => votes.push(vote),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => return votes,
+            }
+        }
+    }
+}
+
+impl Decide for CrowdPlayer {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        if let Some(intent) = tally(&self.collect(request), request) {
+            return intent;
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.choose_move(request),
+            None => *request.all_moves().next().expect("asked for a move with none offered"),
+        }
+    }
+}
+
+/**
+ * tally
+ * Count votes, each voter's last vote for a legal move counting once.
+ * votes - Votes in the order they were cast.
+ * request - Moves that can be played.
+ * ret - The move with the most votes, ties going to the move that reached its count first,
+ *       or None if no legal move was voted for.
+ */
+pub fn tally(votes: &[Vote], request: &MoveRequest) -> Option<Intent> {
+    let mut ballots: HashMap<&str, (usize, Intent)> = HashMap::new();
+    for (order, vote) in votes.iter().enumerate() {
+        if request.all_moves().any(|intent| *intent == vote.intent) {
+            ballots.insert(&vote.voter, (order, vote.intent));
+        }
+    }
+    let mut ballots: Vec<(usize, Intent)> = ballots.into_values().collect();
+    ballots.sort_by_key(|(order, _)| *order);
+    // votes for each move, and the order its last counted vote came in
+    let mut counts: Vec<(Intent, usize, usize)> = Vec::new();
+    for (order, intent) in ballots {
+        match counts.iter_mut().find(|(counted, _, _)| *counted == intent) {
+            Some((_, count, reached)) => {
+                *count += 1;
+                *reached = order;
+            },
+            None => counts.push((intent, 1, order)),
+        }
+    }
+    counts.into_iter()
+        .max_by(|(_, a, a_reached), (_, b, b_reached)| a.cmp(b).then(b_reached.cmp(a_reached)))
+        .map(|(intent, _, _)| intent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    use crate::analysis::Replayed;
+    use crate::game::{Game, Player, PlayerId, STARTING_STONES};
+
+    /* Helper function making a vote. */
+    fn vote(voter: &str, intent: Intent) -> Vote {
+        Vote { voter: String::from(voter), intent }
+    }
+
+    #[test]
+    fn tally() {
+        let replayed = Replayed;
+        let (mut player_a, mut player_b) = (Player::new(PlayerId::A, STARTING_STONES, &replayed), Player::new(PlayerId::B, STARTING_STONES, &replayed));
+        let game = Game::new(&mut player_a, &mut player_b);
+        let request = game.move_request(PlayerId::A);
+        let moves: Vec<Intent> = request.all_moves().copied().take(3).collect();
+        let illegal = Intent::FireChecker(crate::vec::Vec2::new(-1, -1));
+
+        assert_eq!(super::tally(&[], &request), None);
+        assert_eq!(super::tally(&[vote("ann", illegal)], &request), None);
+        // Voters changing their minds count once, for their last legal choice
+        let votes = [
+            vote("ann", moves[0]), vote("bob", moves[0]), vote("ann", moves[1]),
+            vote("cat", moves[1]), vote("bob", illegal), vote("dan", moves[2]),
+        ];
+        assert_eq!(super::tally(&votes, &request), Some(moves[1]));
+        // A tie goes to the move that got its votes first
+        let votes = [vote("ann", moves[2]), vote("bob", moves[0]), vote("cat", moves[0]), vote("dan", moves[2])];
+        assert_eq!(super::tally(&votes, &request), Some(moves[0]));
+    }
+
+    #[test]
+    fn window() {
+        let replayed = Replayed;
+        let (mut player_a, mut player_b) = (Player::new(PlayerId::A, STARTING_STONES, &replayed), Player::new(PlayerId::B, STARTING_STONES, &replayed));
+        let game = Game::new(&mut player_a, &mut player_b);
+        let request = game.move_request(PlayerId::A);
+        let chosen = request.all_moves().copied().nth(2).unwrap();
+
+        let (crowd, ballot) = CrowdPlayer::new(Duration::from_millis(200));
+        // Votes from before the window opens are stale
+        ballot.vote("ann", request.all_moves().copied().nth(1).unwrap());
+        let voter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            ballot.vote("bob", chosen)
+        });
+        assert_eq!(crowd.choose_move(&request), chosen);
+        assert!(voter.join().unwrap());
+
+        // Nobody voting leaves the move to the fallback, or the first move offered
+        let (crowd, _ballot) = CrowdPlayer::new(Duration::ZERO);
+        assert_eq!(crowd.choose_move(&request), *request.all_moves().next().unwrap());
+        let crowd = crowd.with_fallback(Box::new(crate::engine::Engine::default()));
+        assert!(request.all_moves().any(|intent| *intent == crowd.choose_move(&request)));
+    }
+}
@@ -0,0 +1,159 @@
+/**
+ * Asynchronous counterpart to the Decide trait, for players whose moves arrive
+ * from somewhere slow (a socket, a GUI event loop) and should be awaited rather
+ * than blocked on.
+ */
+use std::future::{self, Future};
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use crate::game::{Decide, Intent, MoveRequest};
+
+/**
+ * Future returned by AsyncDecide::choose_move.
+ */
+pub type DecideFuture<'a> = Pin<Box<dyn Future<Output = Intent> + Send + 'a>>;
+
+pub trait AsyncDecide {
+    /**
+     * choose_move
+     * Pick one of the moves offered in request, possibly waiting on outside input.
+     * request - Legal moves and game state for the player to move.
+     * ret - Future resolving to the chosen move.
+     */
+    fn choose_move<'a>(&'a self, request: &'a MoveRequest<'a>) -> DecideFuture<'a>;
+}
+
+/**
+ * SyncDecider adapts any Decide implementation to AsyncDecide. The wrapped decider
+ * runs to completion when the future is created, so it should not block for long.
+ */
+pub struct SyncDecider<D: Decide + Sync> {
+    pub inner: D,
+}
+
+impl<D: Decide + Sync> SyncDecider<D> {
+    pub fn new(inner: D) -> SyncDecider<D> {
+        SyncDecider { inner }
+    }
+}
+
+impl<D: Decide + Sync> AsyncDecide for SyncDecider<D> {
+    fn choose_move<'a>(&'a self, request: &'a MoveRequest<'a>) -> DecideFuture<'a> {
+        Box::pin(future::ready(self.inner.choose_move(request)))
+    }
+}
+
+/**
+ * BlockingDecider adapts an AsyncDecide implementation to Decide, so it can be used
+ * with the synchronous Game loop. Each call parks the current thread until the
+ * future completes.
+ */
+pub struct BlockingDecider<A: AsyncDecide> {
+    pub inner: A,
+}
+
+impl<A: AsyncDecide> BlockingDecider<A> {
+    pub fn new(inner: A) -> BlockingDecider<A> {
+        BlockingDecider { inner }
+    }
+}
+
+impl<A: AsyncDecide> Decide for BlockingDecider<A> {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        block_on(self.inner.choose_move(request))
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/**
+ * block_on
+ * Drive a future to completion on the current thread, parking between polls.
+ * future - Future to run.
+ * ret - Output of the future.
+ */
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, PlayerFactory, PlayerId, STARTING_STONES};
+
+    struct FirstPlace;
+
+    impl Decide for FirstPlace {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            request.place_stones[0]
+        }
+    }
+
+    /* Future that is pending on its first poll, like a reply that has not arrived yet. */
+    struct Delayed {
+        intent: Intent,
+        polled: bool,
+    }
+
+    impl Future for Delayed {
+        type Output = Intent;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Intent> {
+            if self.polled {
+                Poll::Ready(self.intent)
+            } else {
+                self.polled = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    struct LastSlideOrPlace;
+
+    impl AsyncDecide for LastSlideOrPlace {
+        fn choose_move<'a>(&'a self, request: &'a MoveRequest<'a>) -> DecideFuture<'a> {
+            let intent = *request.slide_stones.last().unwrap_or(&request.place_stones[0]);
+            Box::pin(Delayed { intent, polled: false })
+        }
+    }
+
+    #[test]
+    fn sync_decider() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let game = Game::new(&mut player_a, &mut player_b);
+        let request = game.move_request(PlayerId::A);
+
+        let decider = SyncDecider::new(FirstPlace);
+        assert_eq!(block_on(decider.choose_move(&request)), request.place_stones[0]);
+    }
+
+    #[test]
+    fn blocking_decider() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let game = Game::new(&mut player_a, &mut player_b);
+        let request = game.move_request(PlayerId::A);
+
+        let decider = BlockingDecider::new(LastSlideOrPlace);
+        assert_eq!(Decide::choose_move(&decider, &request), request.place_stones[0]);
+    }
+}
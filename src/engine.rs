@@ -0,0 +1,968 @@
+/**
+ * A searching engine player: alpha-beta minimax over the legal moves, to a fixed number of
+ * plies with iterative deepening, scoring leaves with an Evaluator and remembering scored
+ * positions in a transposition table. After each move it reports what the search did as a
+ * SearchInfo, which games send on as GameEvent::Searched.
+ *
+ * The engine searches its own copy of the position, set up from the move request with its
+ * own dice, so it can not see how the real game's dice will fall. It searches as though the
+ * turn started with the move asked for, and places starting checkers in the order offered
+ * during free setup.
+ *
+ * Engines are tuned with named options, set one at a time by a protocol or read from a
+ * file of one option per line, # starting a comment line:
+ *
+ *   depth <plies>          plies to search
+ *   hash <megabytes>       size of the transposition table, 0 for none, up to 65536
+ *   threads <n>            threads the root moves are shared out between, each with its
+ *                          own share of the table
+ *   contempt <points>      how much worse than the evaluation a draw is scored
+ *   randomness <points>    moves scoring within this much of the best may be played instead
+ *   seed <n>|none          seed for every random choice, or none to choose differently each time
+ *   multipv <n>            best moves to report with exact scores, each with its line
+ *
+ * With a seed, the dice and the choice between close moves are drawn from the seed and the
+ * position, and each search starts with an empty table, so the same position and options
+ * always give the same move.
+ */
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::Terrain;
+use crate::config::{GameConfig, StartPosition};
+use crate::game::{Decide, Game, GameOutcome, GameView, Intent, MoveRequest, Player, PlayerId, STARTING_STONES};
+use crate::scenario::{arg, number};
+use crate::scoring::Evaluator;
+use crate::vec::Vec2;
+
+// plies searched by Engine::default
+pub const DEFAULT_ENGINE_DEPTH: usize = 2;
+// score of a won position, above anything the evaluator gives
+const WIN_SCORE: f32 = 100_000.0;
+// most megabytes the hash option may give the transposition table
+const MAX_HASH: usize = 1 << 16;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EngineError {
+    // The options file could not be read
+    #[error("Could not read engine options: {0}")]
+    Io(#[from] io::Error),
+    // A line of an options file could not be understood, with its line number counting from 1
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    // No option has the name
+    #[error("Unknown engine option '{0}'")]
+    UnknownOption(String),
+    // The value does not suit the option
+    #[error("Option {name}: {message}")]
+    InvalidValue { name: String, message: String },
+}
+
+/**
+ * EngineOptions are the settings an engine searches with.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EngineOptions {
+    // plies to search, at least 1
+    pub depth: usize,
+    // megabytes the transposition table may use, 0 for none, up to MAX_HASH
+    pub hash: usize,
+    // threads to search with, at least 1
+    pub threads: usize,
+    // evaluation points a draw is scored below an even position, so engines avoid draws
+    pub contempt: f32,
+    // evaluation points within which of the best score a move may be played instead
+    pub randomness: f32,
+    // seed every random choice is drawn from, or None to draw from the operating system
+    pub seed: Option<u64>,
+    // best moves reported in SearchInfo::lines, at least 1
+    pub multipv: usize,
+}
+
+impl Default for EngineOptions {
+    fn default() -> EngineOptions {
+        EngineOptions { depth: DEFAULT_ENGINE_DEPTH, hash: 16, threads: 1, contempt: 0.0, randomness: 0.0, seed: None, multipv: 1 }
+    }
+}
+
+impl EngineOptions {
+    // names of every option, in the order they are listed
+    pub const NAMES: [&'static str; 7] = ["depth", "hash", "threads", "contempt", "randomness", "seed", "multipv"];
+
+    /**
+     * set
+     * Change one option.
+     * name - Name of the option.
+     * value - New value, as text.
+     * ret - Ok, EngineError::UnknownOption, or EngineError::InvalidValue saying what is wrong
+     * with the value. Nothing changes on an error.
+     */
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), EngineError> {
+        let invalid = |message: String| EngineError::InvalidValue { name: String::from(name), message };
+        match name {
+            "depth" | "threads" | "multipv" => {
+                let count: usize = number(value).map_err(invalid)?;
+                if count == 0 {
+                    return Err(invalid(String::from("must be at least 1")));
+                }
+                match name {
+                    "depth" => self.depth = count,
+                    "threads" => self.threads = count,
+                    _ => self.multipv = count,
+                }
+            },
+            "hash" => {
+                let megabytes: usize = number(value).map_err(invalid)?;
+                if megabytes > MAX_HASH {
+                    return Err(invalid(format!("must be at most {}", MAX_HASH)));
+                }
+                self.hash = megabytes;
+            },
+            "contempt" | "randomness" => {
+                let points: f32 = number(value).map_err(invalid)?;
+                if !points.is_finite() || (name == "randomness" && points < 0.0) {
+                    return Err(invalid(format!("out of range: {}", value)));
+                }
+                if name == "contempt" { self.contempt = points } else { self.randomness = points }
+            },
+            "seed" if value == "none" => self.seed = None,
+            "seed" => self.seed = Some(number(value).map_err(invalid)?),
+            _ => return Err(EngineError::UnknownOption(String::from(name))),
+        }
+        Ok(())
+    }
+
+    /**
+     * get
+     * name - Name of the option.
+     * ret - Its value as text, or None if no option has the name.
+     */
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "depth" => Some(self.depth.to_string()),
+            "hash" => Some(self.hash.to_string()),
+            "threads" => Some(self.threads.to_string()),
+            "contempt" => Some(self.contempt.to_string()),
+            "randomness" => Some(self.randomness.to_string()),
+            "seed" => Some(self.seed.map_or(String::from("none"), |seed| seed.to_string())),
+            "multipv" => Some(self.multipv.to_string()),
+            _ => None,
+        }
+    }
+
+    /**
+     * load
+     * Read and parse an options file.
+     * path - File to read.
+     * ret - The default options changed by the file, or an EngineError if it could not be
+     * read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<EngineOptions, EngineError> {
+        EngineOptions::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * parse
+     * Parse the text of an options file.
+     * text - Contents of the file.
+     * ret - The default options changed by the text, or EngineError::Parse describing the
+     * first problem found.
+     */
+    pub fn parse(text: &str) -> Result<EngineOptions, EngineError> {
+        let mut options = EngineOptions::default();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| EngineError::Parse { line: idx + 1, message };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            options.set(words[0], arg(&words, 1).map_err(err)?).map_err(|error| err(error.to_string()))?;
+        }
+        Ok(options)
+    }
+}
+
+impl Display for EngineOptions {
+    /* Write the options as an options file would list them. */
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        for name in EngineOptions::NAMES {
+            writeln!(formatter, "{} {}", name, self.get(name).unwrap_or_default())?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * SearchInfo is what an engine's search for one move did.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchInfo {
+    // plies of the deepest search completed
+    pub depth: usize,
+    // positions visited, over every depth
+    pub nodes: u64,
+    pub elapsed: Duration,
+    // positions looked up in the transposition table, and those found there
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    // score of the chosen move for the player who made it
+    pub score: f32,
+    // moves the engine expects to be played, starting with its own
+    pub pv: Vec<(PlayerId, Intent)>,
+    // the best moves with exact scores and their lines, best first, as many as the multipv
+    // option asks for and there are moves; the chosen move may not be among them under randomness
+    pub lines: Vec<ScoredLine>,
+}
+
+impl SearchInfo {
+    /**
+     * nps
+     * ret - Positions visited a second.
+     */
+    pub fn nps(&self) -> u64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 { (self.nodes as f64 / seconds) as u64 } else { self.nodes }
+    }
+
+    /**
+     * tt_hit_rate
+     * ret - Share of transposition table lookups that found the position, or None without any.
+     */
+    pub fn tt_hit_rate(&self) -> Option<f32> {
+        (self.tt_probes > 0).then(|| self.tt_hits as f32 / self.tt_probes as f32)
+    }
+}
+
+/**
+ * ScoredLine is one move the engine considered, with its score and the line it expects after it.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoredLine {
+    // score for the player searching
+    pub score: f32,
+    // the move, then the moves expected after it
+    pub line: Vec<(PlayerId, Intent)>,
+}
+
+/*
+ * Bound is how a remembered score relates to the position's true score, which depends on
+ * whether the search of the position was cut off.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/* Entry is what the transposition table remembers about a position. */
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    depth: usize,
+    score: f32,
+    bound: Bound,
+    best: Option<Intent>,
+}
+
+/* Counters kept while searching. */
+#[derive(Clone, Copy, Default)]
+struct Stats {
+    nodes: u64,
+    tt_probes: u64,
+    tt_hits: u64,
+}
+
+// a root move's score, with the line it expects
+type Ranked = (f32, Vec<(PlayerId, Intent)>);
+
+/**
+ * Engine chooses moves by searching the position.
+ */
+pub struct Engine {
+    evaluator: Evaluator,
+    options: EngineOptions,
+    // scored positions, by key, from every single-threaded search since the table was last cleared
+    table: RefCell<HashMap<u64, Entry>>,
+    info: RefCell<Option<SearchInfo>>,
+}
+
+impl Default for Engine {
+    fn default() -> Engine {
+        Engine::with_options(Evaluator::default(), EngineOptions::default())
+    }
+}
+
+/* Decider for the engine's copy of the game, which is never asked for a move. */
+struct Searcher;
+
+impl Decide for Searcher {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        *request.all_moves().next().expect("no moves offered")
+    }
+}
+
+impl Engine {
+    /**
+     * new
+     * Create an engine with the default options, searching a number of plies.
+     * evaluator - Scores positions at the end of each line.
+     * depth - Plies to search, at least 1.
+     */
+    pub fn new(evaluator: Evaluator, depth: usize) -> Engine {
+        Engine::with_options(evaluator, EngineOptions { depth: depth.max(1), ..EngineOptions::default() })
+    }
+
+    /**
+     * with_options
+     * evaluator - Scores positions at the end of each line.
+     * options - Settings to search with.
+     */
+    pub fn with_options(evaluator: Evaluator, options: EngineOptions) -> Engine {
+        Engine { evaluator, options, table: RefCell::new(HashMap::new()), info: RefCell::new(None) }
+    }
+
+    /**
+     * options
+     * ret - The settings the engine searches with.
+     */
+    pub fn options(&self) -> &EngineOptions {
+        &self.options
+    }
+
+    /**
+     * set_option
+     * Change one setting between moves. Changing the hash size empties the table.
+     * name - Name of the option.
+     * value - New value, as text.
+     * ret - Ok, or an EngineError from EngineOptions::set.
+     */
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), EngineError> {
+        let hash = self.options.hash;
+        self.options.set(name, value)?;
+        if self.options.hash != hash {
+            self.table.borrow_mut().clear();
+        }
+        Ok(())
+    }
+
+    /**
+     * search
+     * Search the position of a move request.
+     * request - Legal moves and game state for the player to move.
+     * ret - The move to play, with what the search did.
+     */
+    pub fn search(&self, request: &MoveRequest) -> (Intent, SearchInfo) {
+        self.search_with(request, &self.options)
+    }
+
+    /**
+     * best_moves
+     * Search the position of a move request for its best moves, whatever the multipv option.
+     * request - Legal moves and game state for the player to move.
+     * count - Number of moves wanted.
+     * ret - Up to count moves with exact scores and their lines, best first.
+     */
+    pub fn best_moves(&self, request: &MoveRequest, count: usize) -> Vec<ScoredLine> {
+        let options = EngineOptions { multipv: count.max(1), ..self.options };
+        self.search_with(request, &options).1.lines
+    }
+
+    /*
+     * Helper function searching the position of @request with @options in place of the engine's.
+     */
+    fn search_with(&self, request: &MoveRequest, options: &EngineOptions) -> (Intent, SearchInfo) {
+        let job = self.job_with(request, options);
+        if options.seed.is_some() {
+            self.table.borrow_mut().clear();
+        }
+        job.run_on(&mut self.table.borrow_mut(), None)
+    }
+
+    /**
+     * job
+     * Set up a search of the position of a move request that can be run on another thread,
+     * with its own table, and stopped part way.
+     * request - Legal moves and game state for the player to move.
+     * ret - The search, ready to run.
+     */
+    pub fn job(&self, request: &MoveRequest) -> SearchJob {
+        self.job_with(request, &self.options)
+    }
+
+    /*
+     * Helper function setting up a search of the position of @request with @options.
+     */
+    fn job_with(&self, request: &MoveRequest, options: &EngineOptions) -> SearchJob {
+        let player = request.player;
+        let rng = match options.seed {
+            Some(seed) => {
+                let mut hasher = DefaultHasher::new();
+                (seed, request.view.board.position_hash(), player, request.view.stones).hash(&mut hasher);
+                StdRng::seed_from_u64(hasher.finish())
+            },
+            None => StdRng::from_rng(rand::thread_rng()).expect("thread rng failed"),
+        };
+        SearchJob {
+            evaluator: self.evaluator,
+            // a multipv of 0, set on the options directly, keeps the best move all the same
+            options: EngineOptions { multipv: options.multipv.max(1), ..*options },
+            config: position_config(&request.view),
+            player,
+            moves: request.all_moves().copied().collect(),
+            setup: !request.place_checkers.is_empty(),
+            rng,
+        }
+    }
+
+    /**
+     * search_info
+     * ret - What the search for the engine's last move did, or None before its first move.
+     */
+    pub fn search_info(&self) -> Option<SearchInfo> {
+        self.info.borrow().clone()
+    }
+}
+
+/**
+ * SearchJob is a search of one position, set up by Engine::job, that owns everything it needs.
+ */
+pub struct SearchJob {
+    evaluator: Evaluator,
+    options: EngineOptions,
+    // the position, as a game would start from it
+    config: GameConfig,
+    player: PlayerId,
+    // moves offered to player, in the order offered
+    moves: Vec<Intent>,
+    // true during free setup, when the first placement offered is played without searching
+    setup: bool,
+    rng: StdRng,
+}
+
+impl SearchJob {
+    /**
+     * infinite
+     * Search one ply deeper at a time until stopped, whatever the depth option.
+     * ret - The job, changed.
+     */
+    pub fn infinite(mut self) -> SearchJob {
+        self.options.depth = usize::MAX;
+        self
+    }
+
+    /**
+     * set_depth
+     * depth - Plies to search, at least 1, in place of the depth option.
+     */
+    pub fn set_depth(&mut self, depth: usize) {
+        self.options.depth = depth.max(1);
+    }
+
+    /**
+     * run
+     * Search with a table of its own, until the depth is reached or the search is stopped.
+     * The first ply is always searched in full, so there is a move to play.
+     * stop - Set from any thread to stop searching and report the deepest search completed.
+     * ret - The move to play, with what the search did.
+     */
+    pub fn run(self, stop: &AtomicBool) -> (Intent, SearchInfo) {
+        self.run_on(&mut HashMap::new(), Some(stop))
+    }
+
+    /*
+     * Helper function running the search with @table, until the depth is reached or @stop is set.
+     */
+    fn run_on(mut self, table: &mut HashMap<u64, Entry>, stop: Option<&AtomicBool>) -> (Intent, SearchInfo) {
+        let started = Instant::now();
+        let first = *self.moves.first().expect("no moves offered");
+        let mut info = SearchInfo { depth: 0, nodes: 0, elapsed: Duration::ZERO, tt_probes: 0, tt_hits: 0, score: 0.0, pv: vec![(self.player, first)], lines: Vec::new() };
+        if self.setup {
+            info.elapsed = started.elapsed();
+            return (first, info);
+        }
+
+        let (ranked, stats, depth) = self.search_threads(table, stop);
+        let Some(best) = ranked.first().map(|(score, _)| *score) else {
+            info.elapsed = started.elapsed();
+            return (first, info);
+        };
+        // Any move close enough to the best may be played, the closer the likelier
+        let randomness = self.options.randomness;
+        let (score, pv) = if randomness > 0.0 {
+            ranked.iter()
+                .filter(|(score, _)| *score > best - randomness)
+                .map(|ranked| (ranked.0 + self.rng.gen_range(0.0..randomness), ranked))
+                .max_by(|x, y| x.0.total_cmp(&y.0))
+                .map(|(_, ranked)| ranked.clone())
+                .expect("best move is within range of itself")
+        } else {
+            ranked[0].clone()
+        };
+        info.depth = depth;
+        info.score = score;
+        info.nodes = stats.nodes;
+        info.tt_probes = stats.tt_probes;
+        info.tt_hits = stats.tt_hits;
+        info.elapsed = started.elapsed();
+        let intent = pv.first().map_or(first, |(_, intent)| *intent);
+        info.pv = pv;
+        info.lines = ranked.into_iter().take(self.options.multipv).map(|(score, line)| ScoredLine { score, line }).collect();
+        (intent, info)
+    }
+
+    /*
+     * Helper function searching the root moves, shared out between threads, each with dice
+     * of its own. A single thread uses @table, and more each have one of their own.
+     * ret - Every move that could be searched with its score and line, best first, the
+     * counters of every thread added up, and the depth every thread completed.
+     */
+    fn search_threads(&mut self, table: &mut HashMap<u64, Entry>, stop: Option<&AtomicBool>) -> (Vec<Ranked>, Stats, usize) {
+        let (evaluator, options, root) = (self.evaluator, self.options, self.player);
+        let config = &self.config;
+        let threads = options.threads.clamp(1, self.moves.len().max(1));
+        let capacity = options.hash.saturating_mul(1024 * 1024) / mem::size_of::<(u64, Entry)>();
+        if threads == 1 {
+            let mut search = Search { evaluator, contempt: options.contempt, table, capacity, stats: Stats::default(), stop };
+            let (ranked, depth) = search.deepen(config, self.rng.gen(), root, &self.moves, &options);
+            return (ranked, search.stats, depth);
+        }
+
+        // Moves are dealt out in turn, so every thread gets some of the likelier ones
+        let mut shares: Vec<Vec<Intent>> = vec![Vec::new(); threads];
+        for (idx, intent) in self.moves.iter().enumerate() {
+            shares[idx % threads].push(*intent);
+        }
+        let rng = &mut self.rng;
+        let results: Vec<(Vec<Ranked>, Stats, usize)> = thread::scope(|scope| {
+            let handles: Vec<_> = shares.into_iter().map(|share| (share, rng.gen::<[u8; 32]>())).map(|(share, seed)| scope.spawn(move || {
+                let mut table = HashMap::new();
+                let mut search = Search { evaluator, contempt: options.contempt, table: &mut table, capacity: capacity / threads, stats: Stats::default(), stop };
+                let (ranked, depth) = search.deepen(config, seed, root, &share, &options);
+                (ranked, search.stats, depth)
+            })).collect();
+            handles.into_iter().map(|handle| handle.join().expect("search thread panicked")).collect()
+        });
+        let mut ranked = Vec::new();
+        let mut stats = Stats::default();
+        let mut depth = options.depth;
+        for (share, counted, completed) in results {
+            ranked.extend(share);
+            stats.nodes += counted.nodes;
+            stats.tt_probes += counted.tt_probes;
+            stats.tt_hits += counted.tt_hits;
+            depth = depth.min(completed);
+        }
+        ranked.sort_by(|x, y| y.0.total_cmp(&x.0));
+        (ranked, stats, depth)
+    }
+}
+
+impl Decide for Engine {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        let (intent, info) = self.search(request);
+        *self.info.borrow_mut() = Some(info);
+        intent
+    }
+
+    fn search_info(&self) -> Option<SearchInfo> {
+        Engine::search_info(self)
+    }
+}
+
+/* Search is one thread's search, with its own table and counters. */
+struct Search<'t> {
+    evaluator: Evaluator,
+    contempt: f32,
+    table: &'t mut HashMap<u64, Entry>,
+    // entries the table may hold before it is emptied, 0 to not use it
+    capacity: usize,
+    stats: Stats,
+    // set to give up the depth being searched, or None for searches that run to their depth
+    stop: Option<&'t AtomicBool>,
+}
+
+impl Search<'_> {
+    /*
+     * Helper function searching @moves of @root in the position @config starts from, with the
+     * dice seeded by @seed, one ply deeper at a time up to the depth of @options, each depth
+     * trying the moves best first. The best multipv moves, and any scoring within randomness
+     * of them, get exact scores. Once stopped, the depth being searched is given up, except
+     * the first.
+     * ret - The moves with their scores and lines, best first, and the depth completed.
+     */
+    fn deepen(&mut self, config: &GameConfig, seed: [u8; 32], root: PlayerId, moves: &[Intent], options: &EngineOptions) -> (Vec<Ranked>, usize) {
+        let (searcher_a, searcher_b) = (Searcher, Searcher);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &searcher_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &searcher_b);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config.clone());
+        game.set_silent(true);
+        game.board.reseed(seed);
+
+        let stop = self.stop.take();
+        let mut ranked: Vec<Ranked> = Vec::new();
+        let mut completed = 0;
+        for depth in 1..=options.depth {
+            let searched: Vec<Intent> = ranked.iter().filter_map(|(_, line)| line.first().map(|(_, intent)| *intent)).collect();
+            let ordered: Vec<Intent> = searched.iter().chain(moves.iter().filter(|intent| !searched.contains(intent))).copied().collect();
+            let deeper = self.root(&mut game, root, &ordered, depth, options.randomness, options.multipv);
+            if self.stopped() {
+                break;
+            }
+            ranked = deeper;
+            completed = depth;
+            // the first depth always finishes, so there is a move to play
+            self.stop = stop;
+            if self.stopped() {
+                break;
+            }
+        }
+        (ranked, completed)
+    }
+
+    /* Helper function checking whether the search has been stopped. */
+    fn stopped(&self) -> bool {
+        self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    /*
+     * Helper function scoring each of @moves of @root to @depth plies, against the lowest
+     * score of the @keep best so far, less @margin.
+     * ret - The moves with their scores and lines, best first.
+     */
+    fn root(&mut self, game: &mut Game, root: PlayerId, moves: &[Intent], depth: usize, margin: f32, keep: usize) -> Vec<Ranked> {
+        let mut ranked: Vec<Ranked> = Vec::new();
+        for intent in moves {
+            if game.try_apply_move(root, *intent).is_err() {
+                continue;
+            }
+            let alpha = ranked.get(keep - 1).map_or(f32::NEG_INFINITY, |(kept, _)| kept - margin);
+            let (score, mut line) = self.alpha_beta(game, root, depth - 1, alpha, f32::INFINITY, 1);
+            game.undo_move();
+            if self.stopped() {
+                break;
+            }
+            line.insert(0, (root, *intent));
+            let idx = ranked.partition_point(|(ranked, _)| *ranked >= score);
+            ranked.insert(idx, (score, line));
+        }
+        ranked
+    }
+
+    /*
+     * Helper function scoring the game's position for @root, @depth plies deep, between the
+     * scores @alpha and @beta the search is still interested in. Every move is taken back
+     * before returning, so the game is left as it was.
+     */
+    fn alpha_beta(&mut self, game: &mut Game, root: PlayerId, depth: usize, mut alpha: f32, mut beta: f32, ply: usize) -> Ranked {
+        self.stats.nodes += 1;
+        if let Some(outcome) = game.check_for_outcome() {
+            // quicker wins and slower losses score better
+            let score = match outcome {
+                GameOutcome::Win { winner, .. } if winner == root => WIN_SCORE - ply as f32,
+                GameOutcome::Win { .. } => ply as f32 - WIN_SCORE,
+                GameOutcome::Draw(_) => -self.contempt,
+            };
+            return (score, Vec::new());
+        }
+        if depth == 0 {
+            return (self.evaluator.evaluate(&game.board, root), Vec::new());
+        }
+
+        let player = game.side_to_move();
+        let key = position_key(game, player, root);
+        let remembered = self.probe(key);
+        if let Some(entry) = remembered {
+            if entry.depth >= depth {
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+                if usable {
+                    return (entry.score, entry.best.map(|intent| vec![(player, intent)]).unwrap_or_default());
+                }
+            }
+        }
+
+        let mut moves: Vec<Intent> = game.move_request(player).all_moves().copied().collect();
+        if let Some(best) = remembered.and_then(|entry| entry.best) {
+            if let Some(idx) = moves.iter().position(|intent| *intent == best) {
+                moves[..=idx].rotate_right(1);
+            }
+        }
+        let maximising = player == root;
+        let (start_alpha, start_beta) = (alpha, beta);
+        let mut best: Option<Ranked> = None;
+        for intent in moves {
+            if game.try_apply_move(player, intent).is_err() {
+                continue;
+            }
+            let (score, mut line) = self.alpha_beta(game, root, depth - 1, alpha, beta, ply + 1);
+            game.undo_move();
+            if self.stopped() {
+                return (0.0, Vec::new());
+            }
+            let better = best.as_ref().is_none_or(|(best, _)| if maximising { score > *best } else { score < *best });
+            if better {
+                line.insert(0, (player, intent));
+                best = Some((score, line));
+            }
+            if maximising {
+                alpha = alpha.max(score);
+            } else {
+                beta = beta.min(score);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        let Some((score, line)) = best else {
+            return (self.evaluator.evaluate(&game.board, root), Vec::new());
+        };
+        // Scores are for root, so a cut-off bounds them from below at its own nodes and from above at the opponent's
+        let bound = if score <= start_alpha {
+            Bound::Upper
+        } else if score >= start_beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.store(key, Entry { depth, score, bound, best: line.first().map(|(_, intent)| *intent) });
+        (score, line)
+    }
+
+    /* Helper function looking a position up in the table, counting the lookup. */
+    fn probe(&mut self, key: u64) -> Option<Entry> {
+        if self.capacity == 0 {
+            return None;
+        }
+        self.stats.tt_probes += 1;
+        let entry = self.table.get(&key).copied();
+        if entry.is_some() {
+            self.stats.tt_hits += 1;
+        }
+        entry
+    }
+
+    /* Helper function remembering a position, emptying the table first if it is full. */
+    fn store(&mut self, key: u64, entry: Entry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.table.len() >= self.capacity && !self.table.contains_key(&key) {
+            self.table.clear();
+        }
+        self.table.insert(key, entry);
+    }
+}
+
+/* Helper function describing the position of @view as a configuration a game can start from. */
+fn position_config(view: &GameView) -> GameConfig {
+    let board = view.board;
+    let mut position = StartPosition::default();
+    for player in [PlayerId::A, PlayerId::B] {
+        for pos in board.checkers_for_player(player) {
+            let height = board.checker_at(pos).expect("checker listed but missing").height;
+            position.checkers.push((player, pos, height));
+        }
+        for pos in board.stones_for_player(player) {
+            position.stones.push((player, pos));
+        }
+    }
+    let mut config = GameConfig::for_variant(board.variant());
+    config.rules = board.rules();
+    config.start_position = Some(position);
+    for x in 0..board.width() as i32 {
+        for y in 0..board.height() as i32 {
+            let pos = Vec2::new(x, y);
+            if board.terrain_at(pos) != Terrain::Plain {
+                config.terrain.push((pos, board.terrain_at(pos)));
+            }
+        }
+    }
+    config.with_stone_pool(PlayerId::A, view.stones[0]).with_stone_pool(PlayerId::B, view.stones[1])
+}
+
+/*
+ * Helper function finding the key the table keeps the game's position under, with @player to
+ * move, in a search for @root, since scores are kept for the side searching.
+ */
+fn position_key(game: &Game, player: PlayerId, root: PlayerId) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (game.board.position_hash(), player, root, game.view().stones, game.turn_in_progress()).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{GameEvent, Observer};
+    use crate::scenario::Scenario;
+    use std::rc::Rc;
+
+    // A's checker is one step from its far column
+    const ONE_STEP: &str = "
+        rule elimination on
+        stones a 0
+        stones b 0
+        checker a 1 0 1
+        checker b 6 3 1
+    ";
+
+    struct Recorder(Rc<RefCell<Vec<GameEvent>>>);
+
+    impl Observer for Recorder {
+        fn on_event(&mut self, event: &GameEvent, _view: &GameView) {
+            self.0.borrow_mut().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn search() {
+        let scenario = Scenario::parse(ONE_STEP).unwrap();
+        let engine = Engine::new(Evaluator::default(), 2);
+        let other = Engine::default();
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &engine);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &other);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, scenario.config);
+        game.set_silent(true);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.add_observer(Box::new(Recorder(events.clone())));
+
+        // The engine finds the winning step, and says so
+        let outcome = game.play_turn().unwrap();
+        assert!(matches!(outcome, Some(GameOutcome::Win { winner: PlayerId::A, .. })));
+        let info = engine.search_info().unwrap();
+        assert_eq!(info.depth, 2);
+        assert!(info.score > WIN_SCORE - 2.0);
+        assert!(matches!(info.pv[0], (PlayerId::A, Intent::MoveChecker(_, to)) if to.x == 0));
+        assert!(info.nodes > 0 && info.tt_probes >= info.tt_hits);
+        assert!(info.tt_hit_rate().is_none_or(|rate| (0.0..=1.0).contains(&rate)));
+        assert!(events.borrow().contains(&GameEvent::Searched { player: PlayerId::A, info }));
+        assert!(other.search_info().is_none());
+    }
+
+    #[test]
+    fn options() {
+        let mut options = EngineOptions::parse("# stronger\ndepth 3\nhash 0\ncontempt 2.5\n").unwrap();
+        assert_eq!(options, EngineOptions { depth: 3, hash: 0, contempt: 2.5, ..EngineOptions::default() });
+        assert_eq!(EngineOptions::parse(&options.to_string()).unwrap(), options);
+        assert!(matches!(options.set("ponder", "on"), Err(EngineError::UnknownOption(_))));
+        assert!(matches!(options.set("threads", "0"), Err(EngineError::InvalidValue { .. })));
+        assert!(matches!(options.set("randomness", "-1"), Err(EngineError::InvalidValue { .. })));
+        assert!(matches!(options.set("hash", &usize::MAX.to_string()), Err(EngineError::InvalidValue { .. })));
+        assert!(matches!(EngineOptions::parse("depth\n"), Err(EngineError::Parse { line: 1, .. })));
+        assert_eq!(options.get("depth").as_deref(), Some("3"));
+
+        // Threads and a table-less search find the same winning step
+        let scenario = Scenario::parse(ONE_STEP).unwrap();
+        let mut engine = Engine::with_options(Evaluator::default(), options);
+        engine.set_option("threads", "3").unwrap();
+        let (searcher_a, searcher_b) = (Searcher, Searcher);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &searcher_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &searcher_b);
+        let game = Game::with_config(&mut player_a, &mut player_b, scenario.config);
+        let (intent, info) = engine.search(&game.move_request(PlayerId::A));
+        assert!(matches!(intent, Intent::MoveChecker(_, to) if to.x == 0));
+        assert_eq!((info.depth, info.tt_probes), (3, 0));
+        assert_eq!(engine.options().threads, 3);
+    }
+
+    #[test]
+    fn seeded() {
+        // Firing and close scores both draw on the seed
+        let scenario = Scenario::parse("
+            stones a 3
+            stones b 3
+            checker a 5 1 2
+            checker a 6 4 1
+            checker b 4 1 2
+            checker b 2 3 1
+        ").unwrap();
+        let options = EngineOptions { randomness: 5.0, seed: Some(11), ..EngineOptions::default() };
+        let (searcher_a, searcher_b) = (Searcher, Searcher);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &searcher_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &searcher_b);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, scenario.config);
+        let engine = Engine::with_options(Evaluator::default(), options);
+        let (intent, info) = engine.search(&game.move_request(PlayerId::A));
+
+        // Earlier searches and a fresh engine make no difference
+        let other = game.move_request(PlayerId::A).all_moves().copied().last().unwrap();
+        game.apply_move(PlayerId::A, other);
+        engine.search(&game.move_request(PlayerId::B));
+        game.undo_move();
+        let (again, info_again) = engine.search(&game.move_request(PlayerId::A));
+        assert_eq!((again, &info_again.pv, info_again.score), (intent, &info.pv, info.score));
+        let fresh = Engine::with_options(Evaluator::default(), EngineOptions { threads: 2, ..options });
+        let fresh_again = Engine::with_options(Evaluator::default(), EngineOptions { threads: 2, ..options });
+        assert_eq!(fresh.search(&game.move_request(PlayerId::A)).1.pv, fresh_again.search(&game.move_request(PlayerId::A)).1.pv);
+
+        let mut options = options;
+        options.set("seed", "none").unwrap();
+        assert_eq!(options.seed, None);
+        assert_eq!(options.get("seed").as_deref(), Some("none"));
+    }
+
+    #[test]
+    fn best_moves() {
+        // Stepping straight or diagonally onto the far column both win at once
+        let scenario = Scenario::parse(ONE_STEP).unwrap();
+        let (searcher_a, searcher_b) = (Searcher, Searcher);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &searcher_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &searcher_b);
+        let game = Game::with_config(&mut player_a, &mut player_b, scenario.config);
+        let request = game.move_request(PlayerId::A);
+        let engine = Engine::default();
+        let lines = engine.best_moves(&request, 3);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].score, lines[1].score);
+        assert!(lines[1].score > lines[2].score);
+        assert!(lines[..2].iter().all(|line| matches!(line.line[0].1, Intent::MoveChecker(_, to) if to.x == 0)));
+
+        // Searches report the multipv option's number of lines, and no more lines than moves
+        assert_eq!(engine.search(&request).1.lines.len(), 1);
+        let many = engine.best_moves(&request, request.all_moves().count() + 5);
+        assert_eq!(many.len(), request.all_moves().count());
+        assert!(many.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+        // Options set out of range directly, rather than through set, still search
+        let unchecked = EngineOptions { multipv: 0, hash: usize::MAX, ..EngineOptions::default() };
+        let engine = Engine::with_options(Evaluator::default(), unchecked);
+        assert_eq!(engine.search(&request).1.lines.len(), 1);
+    }
+
+    #[test]
+    fn defends() {
+        // B's checker is a step from winning, and only A's fire can stop it
+        let scenario = Scenario::parse("
+            rule elimination on
+            rule deterministic_combat on
+            stones a 0
+            stones b 0
+            checker a 5 1 3
+            checker b 6 1 1
+        ").unwrap();
+        let engine = Engine::new(Evaluator::default(), 2);
+        let (searcher_a, searcher_b) = (Searcher, Searcher);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &searcher_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &searcher_b);
+        let game = Game::with_config(&mut player_a, &mut player_b, scenario.config);
+        let (_, info) = engine.search(&game.move_request(PlayerId::A));
+        assert!(info.score > -WIN_SCORE / 2.0);
+    }
+}
@@ -0,0 +1,109 @@
+/**
+ * One error type covering everything that can go wrong in the library, so applications
+ * can bubble errors up with ? instead of matching each module's error separately.
+ * The module errors stay as they are for code that wants to handle them precisely.
+ */
+use thiserror::Error;
+
+use crate::admin::AdminError;
+use crate::animation::AnimationError;
+use crate::auth::AuthError;
+use crate::board::{FireError, MoveError, PositionError, SlideError};
+use crate::builder::BuildError;
+use crate::chat::ChatError;
+use crate::engine::EngineError;
+use crate::game::{ApplyError, GameError};
+use crate::leaderboard::LeaderboardError;
+use crate::limits::LimitError;
+use crate::matches::MatchError;
+use crate::protocol::ProtocolError;
+use crate::puzzle::PuzzleError;
+use crate::record::RecordError;
+use crate::review::ReviewError;
+use crate::scenario::ScenarioError;
+use crate::scoring::WeightsError;
+#[cfg(feature = "storage")]
+use crate::storage::StorageError;
+
+#[derive(Debug, Error)]
+pub enum IroncladError {
+    #[error(transparent)]
+    Move(#[from] MoveError),
+    #[error(transparent)]
+    Fire(#[from] FireError),
+    #[error(transparent)]
+    Slide(#[from] SlideError),
+    #[error(transparent)]
+    Position(#[from] PositionError),
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error(transparent)]
+    Apply(#[from] ApplyError),
+    #[error(transparent)]
+    Scenario(#[from] ScenarioError),
+    #[error(transparent)]
+    Game(#[from] GameError),
+    #[error(transparent)]
+    Record(#[from] RecordError),
+    #[error(transparent)]
+    Review(#[from] ReviewError),
+    #[error(transparent)]
+    Leaderboard(#[from] LeaderboardError),
+    #[error(transparent)]
+    Puzzle(#[from] PuzzleError),
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+    #[error(transparent)]
+    Chat(#[from] ChatError),
+    #[error(transparent)]
+    Admin(#[from] AdminError),
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    #[error(transparent)]
+    Engine(#[from] EngineError),
+    #[error(transparent)]
+    Match(#[from] MatchError),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error(transparent)]
+    Weights(#[from] WeightsError),
+    #[error(transparent)]
+    Animation(#[from] AnimationError),
+    #[cfg(feature = "storage")]
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::game::{Checker, CheckerHeight, Intent, PlayerId};
+    use crate::vec::Vec2;
+
+    // Mixes calls failing with different module errors, as an application would
+    fn place_and_fire(board: &mut Board) -> Result<(), IroncladError> {
+        board.place_checker_at(Vec2::new(3, 2), Checker::new(CheckerHeight::ONE, PlayerId::B))?;
+        board.fire_checker_at(Vec2::new(9, 9))?;
+        Ok(())
+    }
+
+    #[test]
+    fn conversions() {
+        let mut board = Board::new();
+        let err = place_and_fire(&mut board).unwrap_err();
+        assert!(matches!(err, IroncladError::Fire(FireError::IndexError)));
+        assert_eq!(err.to_string(), FireError::IndexError.to_string());
+
+        let err = place_and_fire(&mut board).unwrap_err();
+        assert!(matches!(err, IroncladError::Move(MoveError::OccupiedError)));
+
+        let apply = ApplyError::IllegalMove { player: PlayerId::A, intent: Intent::PlaceStone(Vec2::new(0, 0)) };
+        let err: IroncladError = apply.clone().into();
+        assert_eq!(err.to_string(), apply.to_string());
+
+        // Sources are passed through, so the underlying cause is not lost
+        let err: IroncladError = crate::scenario::Scenario::load("no/such/scenario.txt").unwrap_err().into();
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
@@ -0,0 +1,106 @@
+/**
+ * Events a game reports as it is played, and the observers that receive them.
+ * Everything a game shows goes through here, so front ends, logs, and servers can
+ * follow a game without it printing anything itself.
+ */
+use std::time::Duration;
+
+use crate::board::Combat;
+use crate::config::BoardLayout;
+use crate::engine::SearchInfo;
+use crate::game::{clock_text, GameOutcome, GameView, Intent, PlayerId};
+use crate::messages::{Catalog, Key};
+
+/**
+ * GameEvent is something that happened in a game.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    // The player is about to be asked for a move. In simultaneous play this is sent
+    // once per round, for the first player.
+    TurnStarted { player: PlayerId },
+    // The player's decider chose a move, which is applied next
+    MoveChosen { player: PlayerId, intent: Intent },
+    // A move was applied, by a decider's choice or from outside the game
+    MoveMade { player: PlayerId, intent: Intent },
+    // The player's decider searched to choose the move, sent right after MoveChosen
+    Searched { player: PlayerId, info: SearchInfo },
+    // The player fired, and the dice were rolled; sent once the attack is on the board
+    DiceRolled { player: PlayerId, combat: Combat },
+    // The player placed the last stone of their pool, and can place no more
+    StonesExhausted { player: PlayerId },
+    // The player's last turn, and the moves played since, were taken back
+    TakenBack { player: PlayerId, moves: usize },
+    // The player asked for a takeback, and it was refused or there was nothing to take back
+    TakeBackRefused { player: PlayerId },
+    // The player's clock dropped below the time control's warning threshold
+    LowTime { player: PlayerId, remaining: Duration },
+    // The game is over
+    GameOver(GameOutcome),
+    // The game was stopped without a result
+    Aborted,
+}
+
+pub trait Observer {
+    /**
+     * on_event
+     * Called by the game for every event, in order.
+     * event - What happened.
+     * view - The game as it stands when the event is sent.
+     */
+    fn on_event(&mut self, event: &GameEvent, view: &GameView);
+}
+
+/**
+ * ConsoleObserver prints the board, with each player's stones and losses, at the start
+ * of each turn and each chosen move to stdout, in the language of its message catalog.
+ * Games use one unless they are made silent, with the compact layout in narrow terminals.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct ConsoleObserver {
+    catalog: Catalog,
+    layout: BoardLayout,
+}
+
+impl ConsoleObserver {
+    /**
+     * new
+     * Print in the layout that suits the terminal's width.
+     * catalog - Messages to print events with.
+     */
+    pub fn new(catalog: Catalog) -> ConsoleObserver {
+        ConsoleObserver { catalog, layout: BoardLayout::detect() }
+    }
+
+    /**
+     * set_layout
+     * layout - How much room the board and status lines take up.
+     */
+    pub fn set_layout(&mut self, layout: BoardLayout) {
+        self.layout = layout;
+    }
+}
+
+impl Observer for ConsoleObserver {
+    fn on_event(&mut self, event: &GameEvent, view: &GameView) {
+        let catalog = &self.catalog;
+        match event {
+            GameEvent::TurnStarted { .. } => println!("\n{}", catalog.show_view_in(view, self.layout)),
+            GameEvent::MoveChosen { player, intent } => {
+                println!("{}", catalog.format(Key::MoveChosen, &[player, &catalog.describe_in_full(intent)]));
+            },
+            GameEvent::TakenBack { player, moves } => println!("{}", catalog.format(Key::TakenBack, &[player, moves])),
+            GameEvent::TakeBackRefused { player } => println!("{}", catalog.format(Key::TakeBackRefused, &[player])),
+            GameEvent::LowTime { player, remaining } => {
+                println!("{}", catalog.format(Key::LowTime, &[player, &clock_text(*remaining)]));
+            },
+            GameEvent::DiceRolled { combat, .. } if !combat.rolls.is_empty() => {
+                let rolls: Vec<String> = combat.rolls.iter().map(u32::to_string).collect();
+                println!("{}", catalog.format(Key::DiceRolled, &[&rolls.join(" "), &combat.terrain_bonus.max(1), &combat.damage]));
+            },
+            GameEvent::StonesExhausted { player } => println!("{}", catalog.format(Key::StonesExhausted, &[player])),
+            GameEvent::Searched { .. } | GameEvent::MoveMade { .. } | GameEvent::DiceRolled { .. } | GameEvent::GameOver(_) => (),
+            GameEvent::Aborted => println!("{}", catalog.text(Key::GameAborted)),
+        }
+    }
+}
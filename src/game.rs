@@ -2,22 +2,205 @@
  * Main class that enforces the rules of the game, collects player
  * input, and applies moves to the board.
  */
-
-use std::io::{self, Write};
+use std::collections::HashMap;
+use std::io;
 use std::fmt::{
     Display,
     Formatter,
 };
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 use std::vec::Vec;
 
 use crate::vec::Vec2;
-use crate::board::{Board, Direction, BOARD_WIDTH, BOARD_HEIGHT};
+use crate::board::{zobrist_key, Board, Direction, FireError, MoveError, SlideError, ZOBRIST_SIDE_SLOTS};
+use crate::config::{GameConfig, TimeControl};
+use crate::console::Console;
+use crate::engine::SearchInfo;
+use crate::events::{ConsoleObserver, GameEvent, Observer};
+use crate::grid::Grid;
+use crate::interrupt;
+use crate::messages::{Catalog, Key};
+use crate::scenario::{Scenario, ScenarioError};
+use crate::scoring;
+use crate::simultaneous;
 
-pub const EMPTY_PLAYER_ID: i32 = -1;
-pub const PLAYER_A_ID: i32 = 1;
-pub const PLAYER_B_ID: i32 = 2;
 pub const STARTING_STONES: i32 = 32;
+// number of times a position may occur before the game is drawn
+pub const REPETITION_LIMIT: u32 = 3;
+
+/**
+ * PlayerId names one of the two sides. Squares and corners with no piece on them have
+ * no owner, written as None wherever ownership is an Option<PlayerId>.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlayerId {
+    // moves first, starting from the right-hand columns
+    A,
+    // moves second, starting from the left-hand columns
+    B,
+}
+
+impl PlayerId {
+    /**
+     * other
+     * ret - The opponent of this player.
+     */
+    pub fn other(self) -> PlayerId {
+        match self {
+            PlayerId::A => PlayerId::B,
+            PlayerId::B => PlayerId::A,
+        }
+    }
+
+    /**
+     * index
+     * ret - 0 for player A and 1 for player B, for indexing per-player arrays.
+     */
+    pub fn index(self) -> usize {
+        match self {
+            PlayerId::A => 0,
+            PlayerId::B => 1,
+        }
+    }
+}
+
+impl Display for PlayerId {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            PlayerId::A => formatter.write_str("A"),
+            PlayerId::B => formatter.write_str("B"),
+        }
+    }
+}
+
+/**
+ * other_player
+ * Get the opponent of a player.
+ * player - Id of a player.
+ * ret - Id of the other player.
+ */
+pub fn other_player(player: PlayerId) -> PlayerId {
+    player.other()
+}
+
+/**
+ * WinReason is the rule that decided a won game.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WinReason {
+    // A checker reached the far column
+    Checker,
+    // A chain of stones connected the top and bottom edges
+    StoneChain,
+    // The opponent broke the Law of Circularity
+    Circularity,
+    // Every opposing checker was destroyed
+    Elimination,
+    // A checker held the hill for the configured number of turns
+    Hill,
+    // The player was ahead on material when the move limit was reached
+    Adjudication,
+    // An arbiter, such as a server administrator, gave the player the game
+    Arbiter,
+}
+
+/**
+ * DrawReason is the rule that decided a drawn game.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DrawReason {
+    // The same position, with the same player to move, occurred too many times
+    Repetition,
+    // The move limit was reached with neither player far enough ahead
+    MoveLimit,
+    // An arbiter, such as a server administrator, declared the game drawn
+    Arbiter,
+}
+
+/**
+ * GameOutcome is how a finished game ended.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameOutcome {
+    Win { winner: PlayerId, reason: WinReason },
+    Draw(DrawReason),
+}
+
+impl Display for WinReason {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let string = match self {
+            WinReason::Checker => "reaching the far side with a checker",
+            WinReason::StoneChain => "connecting the board with stones",
+            WinReason::Circularity => "the Law of Circularity",
+            WinReason::Elimination => "destroying every opposing checker",
+            WinReason::Hill => "holding the hill",
+            WinReason::Adjudication => "adjudication on material",
+            WinReason::Arbiter => "decision of the arbiter",
+        };
+        formatter.write_str(string)
+    }
+}
+
+impl Display for DrawReason {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let string = match self {
+            DrawReason::Repetition => "repetition of position",
+            DrawReason::MoveLimit => "reaching the move limit",
+            DrawReason::Arbiter => "decision of the arbiter",
+        };
+        formatter.write_str(string)
+    }
+}
+
+impl Display for GameOutcome {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            GameOutcome::Win { winner, reason } => write!(formatter, "Player {} wins by {}", winner, reason),
+            GameOutcome::Draw(reason) => write!(formatter, "Draw by {}", reason),
+        }
+    }
+}
+
+/**
+ * ApplyError is why a move was refused by Game::try_apply_move or Board::apply.
+ */
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ApplyError {
+    // The move is not among the legal moves offered to the player right now
+    #[error("Player {player} cannot play {intent} now")]
+    IllegalMove { player: PlayerId, intent: Intent },
+    // The player tried to place a stone with an empty pool
+    #[error("Player {0} has no stones left to place")]
+    NoStones(PlayerId),
+    // The board refused the move
+    #[error(transparent)]
+    Move(#[from] MoveError),
+    #[error(transparent)]
+    Fire(#[from] FireError),
+    #[error(transparent)]
+    Slide(#[from] SlideError),
+}
 
+/**
+ * GameError is why Game::play stopped before the game was over.
+ */
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum GameError {
+    // A decider chose a move that could not be applied
+    #[error(transparent)]
+    Refused(#[from] ApplyError),
+    // The player to move had no legal moves to choose from
+    #[error("Player {0} has no legal moves")]
+    NoMoves(PlayerId),
+    // The game was stopped without a result
+    #[error("The game was aborted")]
+    Aborted,
+    // The player to move quit, leaving the game unfinished
+    #[error("Player {0} quit the game")]
+    Quit(PlayerId),
+}
 
 /**
  * Game is responsible for the main loop of the game.
@@ -28,28 +211,168 @@ pub const STARTING_STONES: i32 = 32;
 pub struct Game<'a> {
     pub board: Board,
     players: [&'a mut Player<'a>; 2],
+    // slides made on each player's last two turns, None for turns that were not slides;
     // most recent stored at 1
-    last_two_slides_a: [Option<Intent>; 2],
-    last_two_slides_b: [Option<Intent>; 2],
+    last_two_slides_a: [Option<SlideRecord>; 2],
+    last_two_slides_b: [Option<SlideRecord>; 2],
+    // every move applied this game, in order, with the id of the player who made it
+    history: Vec<(PlayerId, Intent)>,
+    // every die rolled this game, in order
+    dice: Vec<u32>,
+    // number of times each position, keyed with the player to move, has occurred
+    positions_seen: HashMap<u64, u32>,
+    // moves of simultaneous turns that bounced off each other, which count towards the move limit
+    bounced_moves: usize,
+    // key of the current position in positions_seen
+    position_key: u64,
+    // heights of the checkers player A and player B still have to place during free setup
+    setup_remaining: [Vec<CheckerHeight>; 2],
+    // number of opposing checkers destroyed by player A and player B
+    captures: [u32; 2],
+    // player whose turn is in progress, the actions they have taken so far this turn,
+    // and the slide among them, recorded for circularity once the turn ends
+    turn_player: Option<PlayerId>,
+    turn_actions: Vec<Intent>,
+    turn_slide: Option<SlideRecord>,
+    // consecutive turns player A and player B have ended holding the hill
+    hill_turns: [u32; 2],
+    // true once the second player has taken over the first player's side under the pie rule
+    sides_swapped: bool,
+    // result given by an arbiter, which ends the game whatever the position
+    ruling: Option<GameOutcome>,
+    // true once the game has been stopped without a result
+    aborted: bool,
+    // state before each move of history, most recent last, for undo_move
+    undo_stack: Vec<Snapshot>,
+    // time left to player A and player B, in that order, under the time control
+    clocks: [Duration; 2],
+    // set once a player has been warned of low time, until their clock is back above the warning
+    low_time_warned: [bool; 2],
+    // move lists reused by play from one request to the next
+    scratch: MoveBuffers,
+    // true when the game should print nothing itself, and only tell its observers
+    silent: bool,
+    // how the game prints itself when it is not silent
+    console: ConsoleObserver,
+    observers: Vec<Box<dyn Observer + 'a>>,
+    // stones each player had in their pool and on the board at the start of the game
+    #[cfg(feature = "strict-checks")]
+    stone_totals: [i32; 2],
+    config: GameConfig,
 }
 
 impl<'a> Game<'a> {
     /**
-     * new - Create a new instance of the game.
-     * player_a - Player that moves first.
-     * player_b - Player that moves second.
+     * new - Create a new instance of the game on the standard board.
+     * Players keep the stone pools they were created with, and A moves first.
+     * Panics if both players have the same id.
+     * player_a, player_b - The two players, in either order; each plays the side of its id.
      * ret - New game instance.
      */
     pub fn new(player_a: &'a mut Player<'a>, player_b: &'a mut Player<'a>) -> Game<'a> {
-        Game {
-            board: Board::new(),
-            players: [
-                player_a,
-                player_b,
-            ],
+        Game::build(player_a, player_b, GameConfig::default())
+    }
+
+    /**
+     * with_config - Create a new instance of the game from a configuration.
+     * The players' stone pools are resized to match the configured variant and handicaps.
+     * Panics if a handicap piece can not be placed on the board, or if both players have
+     * the same id.
+     * player_a, player_b - The two players, in either order; each plays the side of its id.
+     * config - Variant and rules to play with, including who moves first.
+     * ret - New game instance.
+     */
+    pub fn with_config(player_a: &'a mut Player<'a>, player_b: &'a mut Player<'a>, config: GameConfig) -> Game<'a> {
+        player_a.set_stone_pool(config.stones_for(player_a.id));
+        player_b.set_stone_pool(config.stones_for(player_b.id));
+        Game::build(player_a, player_b, config)
+    }
+
+    /**
+     * from_scenario
+     * Create a game set up as described by a scenario file.
+     * player_a - First player.
+     * player_b - Second player.
+     * path - Scenario file to load.
+     * ret - The game, or a ScenarioError if the file could not be read or understood.
+     */
+    pub fn from_scenario<P: AsRef<Path>>(player_a: &'a mut Player<'a>, player_b: &'a mut Player<'a>, path: P) -> Result<Game<'a>, ScenarioError> {
+        let scenario = Scenario::load(path)?;
+        Ok(Game::with_config(player_a, player_b, scenario.config))
+    }
+
+    fn build(player_a: &'a mut Player<'a>, player_b: &'a mut Player<'a>, config: GameConfig) -> Game<'a> {
+        assert_ne!(player_a.id, player_b.id, "both players are on side {}", player_a.id);
+        // Seated by id, so players[idx] always plays the side with that index
+        let players = if player_a.id == PlayerId::A { [player_a, player_b] } else { [player_b, player_a] };
+        let mut game = Game {
+            board: Board::for_variant(config.variant),
+            players,
             last_two_slides_a: [None; 2],
             last_two_slides_b: [None; 2],
+            history: Vec::new(),
+            dice: Vec::new(),
+            positions_seen: HashMap::new(),
+            bounced_moves: 0,
+            position_key: 0,
+            setup_remaining: [Vec::new(), Vec::new()],
+            captures: [0; 2],
+            turn_player: None,
+            turn_actions: Vec::new(),
+            turn_slide: None,
+            hill_turns: [0; 2],
+            sides_swapped: false,
+            ruling: None,
+            aborted: false,
+            undo_stack: Vec::new(),
+            clocks: [Duration::ZERO; 2],
+            low_time_warned: [false; 2],
+            scratch: MoveBuffers::default(),
+            silent: false,
+            console: ConsoleObserver::new(Catalog::from_env()),
+            observers: Vec::new(),
+            #[cfg(feature = "strict-checks")]
+            stone_totals: [0; 2],
+            config,
+        };
+        game.board.set_rules(game.config.rules);
+        game.board.set_glyphs(game.config.glyphs);
+        game.start_clocks();
+        for (pos, terrain) in game.config.terrain.iter() {
+            game.board.set_terrain(*pos, *terrain).expect("terrain does not fit on the board");
         }
+        game.place_start_position();
+        game.start_setup();
+        game.place_handicaps();
+        game.record_position(game.config.first_player);
+        game.count_stone_totals();
+        game
+    }
+
+    /**
+     * set_repetition_limit
+     * Set how many times a position may occur before the game is drawn.
+     * limit - Number of occurrences, or None to turn the rule off.
+     */
+    pub fn set_repetition_limit(&mut self, limit: Option<u32>) {
+        self.config.repetition_limit = limit;
+    }
+
+    /**
+     * set_first_player
+     * Choose who moves first, from the next reset on. Repeated games alternate it so
+     * neither player always has the first move.
+     * player - Id of player to move first.
+     */
+    pub fn set_first_player(&mut self, player: PlayerId) {
+        self.config.first_player = player;
+    }
+
+    /**
+     * config - Get the configuration the game was created with.
+     */
+    pub fn config(&self) -> &GameConfig {
+        &self.config
     }
 
     /**
@@ -57,40 +380,416 @@ impl<'a> Game<'a> {
      */
     pub fn reset(&mut self) {
         self.board.reset();
+        self.place_start_position();
+        self.start_setup();
+        self.place_handicaps();
         for player in self.players.iter_mut() {
             player.reset();
         }
-        for i in 0..2 {
-            self.last_two_slides_a[i] = None;
-            self.last_two_slides_b[i] = None;
-        } 
+        self.last_two_slides_a = [None; 2];
+        self.last_two_slides_b = [None; 2];
+        self.history.clear();
+        self.dice.clear();
+        self.captures = [0; 2];
+        self.hill_turns = [0; 2];
+        self.turn_player = None;
+        self.turn_actions.clear();
+        self.turn_slide = None;
+        if self.sides_swapped {
+            self.swap_deciders();
+            self.sides_swapped = false;
+        }
+        self.positions_seen.clear();
+        self.bounced_moves = 0;
+        self.ruling = None;
+        self.aborted = false;
+        self.undo_stack.clear();
+        self.start_clocks();
+        self.record_position(self.config.first_player);
+        self.count_stone_totals();
     }
 
     /**
-     * play the game, alternating turns between players until a winner is determined, then
-     * return that winner.
-     * @ret Reference to winning player.
+     * play
+     * Play turns until the game is over.
+     * @ret How the game ended, or GameError if a turn could not be played.
      */
-    pub fn play(&mut self) -> i32 {
+    pub fn play(&mut self) -> Result<GameOutcome, GameError> {
         loop {
-            for p_num in 0..2 {
-                println!("\n{}", self.board);
-                let player_id = self.players[p_num].id;
-                let move_checkers = self.checker_moves_for(player_id);
-                let fire_checkers = self.checker_fires_for(player_id);
-                let place_stones = self.stone_places_for(player_id);
-                let slide_stones = self.stone_slides_for(player_id);
-                let chosen_move = self.players[p_num].choose_move(
-                    move_checkers, fire_checkers, place_stones, slide_stones
-                );
-                println!("Player {} chose to {}", player_id, chosen_move);
-                self.apply_move(player_id, chosen_move);
-                let win_state = self.check_for_win();
-                if win_state.is_some() {
-                    return win_state.unwrap();
-                }
+            if let Some(outcome) = self.play_turn()? {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /*
+     * Helper function asking the other side to agree to @player taking back their last
+     * turn, and taking it back if they do.
+     */
+    fn offer_takeback(&mut self, player: PlayerId) {
+        let accepted = self.history.iter().any(|(mover, _)| *mover == player)
+            && self.players[player.other().index()].accept_takeback(player);
+        self.settle_takeback(player, accepted);
+    }
+
+    /**
+     * settle_takeback
+     * Finish a takeback the other side was asked about outside the game, such as over the
+     * network, taking back the player's last turn if they agreed and telling the observers.
+     * player - Id of player who asked for the takeback.
+     * accepted - True if the other side agreed.
+     * ret - The number of moves taken back; none if it was refused or there was nothing
+     * to take back.
+     */
+    pub fn settle_takeback(&mut self, player: PlayerId, accepted: bool) -> usize {
+        let moves = if accepted { self.take_back(player).len() } else { 0 };
+        if moves > 0 {
+            self.emit(GameEvent::TakenBack { player, moves });
+        } else {
+            self.emit(GameEvent::TakeBackRefused { player });
+        }
+        moves
+    }
+
+    /**
+     * play_turn
+     * Ask the side to move for their moves and apply them, until their turn is over.
+     * A move the decider was not offered is refused rather than applied.
+     * @ret How the game ended, None if it goes on, or GameError if the turn could not be played.
+     */
+    pub fn play_turn(&mut self) -> Result<Option<GameOutcome>, GameError> {
+        if self.aborted {
+            return Err(GameError::Aborted);
+        }
+        let player_id = self.side_to_move();
+        // A turn is one action, or several under the actions_per_turn rule
+        loop {
+            self.emit(GameEvent::TurnStarted { player: player_id });
+            let buffers = std::mem::take(&mut self.scratch);
+            let request = self.move_request_with(player_id, buffers);
+            if request.all_moves().next().is_none() {
+                self.scratch = request.into_buffers();
+                return Err(GameError::NoMoves(player_id));
+            }
+            let thinking = Instant::now();
+            let decision = self.players[player_id.index()].decide(&request);
+            let elapsed = thinking.elapsed();
+            let chosen_move = match decision {
+                Decision::Move(intent) => intent,
+                Decision::TakeBack => {
+                    self.scratch = request.into_buffers();
+                    self.charge_clock(player_id, elapsed);
+                    self.offer_takeback(player_id);
+                    continue;
+                },
+                Decision::Quit => {
+                    self.scratch = request.into_buffers();
+                    self.charge_clock(player_id, elapsed);
+                    return Err(GameError::Quit(player_id));
+                },
+            };
+            let legal = request.all_moves().any(|intent| *intent == chosen_move);
+            self.scratch = request.into_buffers();
+            self.charge_clock(player_id, elapsed);
+            if !legal {
+                return Err(ApplyError::IllegalMove { player: player_id, intent: chosen_move }.into());
+            }
+            self.emit(GameEvent::MoveChosen { player: player_id, intent: chosen_move });
+            if let Some(info) = self.players[player_id.index()].search_info() {
+                self.emit(GameEvent::Searched { player: player_id, info });
+            }
+            self.make_move(player_id, chosen_move)?;
+            if let Some(outcome) = self.check_for_outcome() {
+                self.emit(GameEvent::GameOver(outcome));
+                return Ok(Some(outcome));
+            }
+            // After a swap the same side moves again, under its new decider
+            if !self.turn_in_progress() && chosen_move != Intent::SwapSides {
+                self.end_clock_turn(player_id);
+                return Ok(None);
+            }
+        }
+    }
+
+    /**
+     * clock
+     * Get the time a player has left under the time control.
+     * player - Id of player to look up.
+     * ret - Time left, or None if the game is untimed.
+     */
+    pub fn clock(&self, player: PlayerId) -> Option<Duration> {
+        self.config.time_control.map(|_| self.clocks[player.index()])
+    }
+
+    /**
+     * set_clocks
+     * Set the time left on both clocks, eg to carry on a saved game. Untimed games keep
+     * no clocks, so this does nothing to them.
+     * clocks - Time left to player A and player B, in that order.
+     */
+    pub fn set_clocks(&mut self, clocks: [Duration; 2]) {
+        let Some(control) = self.config.time_control else {
+            return;
+        };
+        self.clocks = clocks;
+        self.low_time_warned = clocks.map(|clock| clock < control.warning);
+    }
+
+    /*
+     * Helper function setting both clocks to the time control's starting time.
+     */
+    fn start_clocks(&mut self) {
+        let initial = self.config.time_control.map_or(Duration::ZERO, |control| control.initial);
+        self.clocks = [initial; 2];
+        self.low_time_warned = [false; 2];
+    }
+
+    /*
+     * Helper function taking the time @player spent thinking off their clock, and warning
+     * them the first time it drops below the time control's warning.
+     */
+    fn charge_clock(&mut self, player: PlayerId, elapsed: Duration) {
+        let Some(control) = self.config.time_control else {
+            return;
+        };
+        let idx = player.index();
+        self.clocks[idx] = self.clocks[idx].saturating_sub(elapsed);
+        if self.clocks[idx] < control.warning && !self.low_time_warned[idx] {
+            self.low_time_warned[idx] = true;
+            self.emit(GameEvent::LowTime { player, remaining: self.clocks[idx] });
+        }
+    }
+
+    /*
+     * Helper function adding the time control's increment to @player's clock at the end
+     * of their turn.
+     */
+    fn end_clock_turn(&mut self, player: PlayerId) {
+        let Some(TimeControl { increment, warning, .. }) = self.config.time_control else {
+            return;
+        };
+        let idx = player.index();
+        self.clocks[idx] += increment;
+        if self.clocks[idx] >= warning {
+            self.low_time_warned[idx] = false;
+        }
+    }
+
+    /**
+     * set_silent
+     * Turn the game's own console output on or off. Observers are told about every
+     * event either way, so a silent game with no observers shows nothing at all.
+     * silent - True to stop printing the board and chosen moves to stdout.
+     */
+    pub fn set_silent(&mut self, silent: bool) {
+        self.silent = silent;
+    }
+
+    /**
+     * add_observer
+     * Have an observer told about every event from now on, after the console output.
+     * observer - Observer to add. It is kept across resets.
+     */
+    pub fn add_observer(&mut self, observer: Box<dyn Observer + 'a>) {
+        self.observers.push(observer);
+    }
+
+    /*
+     * Helper function sending an event to the console, unless the game is silent,
+     * then to every observer.
+     */
+    fn emit(&mut self, event: GameEvent) {
+        let view = GameView {
+            board: &self.board,
+            stones: [self.players[0].stones, self.players[1].stones],
+            captures: self.captures,
+            history: &self.history,
+            dice: &self.dice,
+            clocks: self.config.time_control.map(|_| self.clocks),
+        };
+        if !self.silent {
+            self.console.on_event(&event, &view);
+        }
+        for observer in self.observers.iter_mut() {
+            observer.on_event(&event, &view);
+        }
+    }
+
+    /**
+     * side_to_move
+     * Get the player whose move it is. The configured first player, A by default, moves first.
+     * @ret The player part way through a turn, the player who just swapped sides,
+     *      or else the opponent of whoever moved last.
+     */
+    pub fn side_to_move(&self) -> PlayerId {
+        match self.history.last() {
+            None => self.config.first_player,
+            Some((player, Intent::SwapSides)) => *player,
+            Some((player, _)) if self.turn_in_progress() => *player,
+            Some((player, _)) => player.other(),
+        }
+    }
+
+    /**
+     * play_simultaneous
+     * Experimental: play the game with both players choosing a move from the same position
+     * each turn, resolved together under the rules in the simultaneous module. A turn where
+     * neither move happens, such as when the two bounce, still counts towards the repetition
+     * and move limits.
+     * @ret How the game ended, or GameError if a turn could not be played.
+     */
+    pub fn play_simultaneous(&mut self) -> Result<GameOutcome, GameError> {
+        loop {
+            if self.aborted {
+                return Err(GameError::Aborted);
+            }
+            let intent_a = self.decide_simultaneous(PlayerId::A)?;
+            let intent_b = self.decide_simultaneous(PlayerId::B)?;
+            if self.apply_simultaneous(intent_a, intent_b).is_empty() {
+                self.bounced_moves += 2;
+                self.record_position(self.side_to_move());
+            }
+            if let Some(outcome) = self.check_for_outcome() {
+                self.emit(GameEvent::GameOver(outcome));
+                return Ok(outcome);
+            }
+            self.end_clock_turn(PlayerId::A);
+            self.end_clock_turn(PlayerId::B);
+        }
+    }
+
+    /*
+     * Helper function asking @player for their move of a simultaneous turn. Takebacks are
+     * refused, since one side's half of a simultaneous turn can not be taken back alone.
+     */
+    fn decide_simultaneous(&mut self, player: PlayerId) -> Result<Intent, GameError> {
+        loop {
+            self.emit(GameEvent::TurnStarted { player });
+            let buffers = std::mem::take(&mut self.scratch);
+            let request = self.move_request_with(player, buffers);
+            if request.all_moves().next().is_none() {
+                self.scratch = request.into_buffers();
+                return Err(GameError::NoMoves(player));
+            }
+            let thinking = Instant::now();
+            let decision = self.players[player.index()].decide(&request);
+            let elapsed = thinking.elapsed();
+            let chosen_move = match decision {
+                Decision::Move(intent) => intent,
+                Decision::TakeBack => {
+                    self.scratch = request.into_buffers();
+                    self.charge_clock(player, elapsed);
+                    self.settle_takeback(player, false);
+                    continue;
+                },
+                Decision::Quit => {
+                    self.scratch = request.into_buffers();
+                    self.charge_clock(player, elapsed);
+                    return Err(GameError::Quit(player));
+                },
+            };
+            let legal = request.all_moves().any(|intent| *intent == chosen_move);
+            self.scratch = request.into_buffers();
+            self.charge_clock(player, elapsed);
+            if !legal {
+                return Err(ApplyError::IllegalMove { player, intent: chosen_move }.into());
+            }
+            self.emit(GameEvent::MoveChosen { player, intent: chosen_move });
+            if let Some(info) = self.players[player.index()].search_info() {
+                self.emit(GameEvent::Searched { player, info });
+            }
+            return Ok(chosen_move);
+        }
+    }
+
+    /**
+     * apply_simultaneous
+     * Resolve and apply one move from each player, chosen from the same position.
+     * intent_a - Move chosen by the first player.
+     * intent_b - Move chosen by the second player.
+     * @ret Moves that were applied, as (player id, move), in the order they were made.
+     */
+    pub fn apply_simultaneous(&mut self, intent_a: Intent, intent_b: Intent) -> Vec<(PlayerId, Intent)> {
+        let mut applied: Vec<(PlayerId, Intent)> = Vec::new();
+        for (idx, intent) in simultaneous::resolve(&self.board, intent_a, intent_b) {
+            let player = self.players[idx].id;
+            if self.try_apply_move(player, intent).is_ok() {
+                applied.push((player, intent));
+            }
+        }
+        applied
+    }
+
+    /**
+     * adjudicate
+     * End the game with a result given by an arbiter, such as a server administrator,
+     * whatever the position. Observers are told the game is over.
+     * winner - Player given the game, or None to declare it drawn.
+     * ret - How the game ended.
+     */
+    pub fn adjudicate(&mut self, winner: Option<PlayerId>) -> GameOutcome {
+        let outcome = match winner {
+            Some(winner) => GameOutcome::Win { winner, reason: WinReason::Arbiter },
+            None => GameOutcome::Draw(DrawReason::Arbiter),
+        };
+        self.ruling = Some(outcome);
+        self.emit(GameEvent::GameOver(outcome));
+        outcome
+    }
+
+    /**
+     * abort
+     * Stop the game without a result. Observers are told, and playing on fails with
+     * GameError::Aborted until the game is reset.
+     */
+    pub fn abort(&mut self) {
+        self.aborted = true;
+        self.emit(GameEvent::Aborted);
+    }
+
+    /**
+     * aborted
+     * ret - True if the game was stopped without a result.
+     */
+    pub fn aborted(&self) -> bool {
+        self.aborted
+    }
+
+    /**
+     * check_for_outcome
+     * Determine if the game is over, either because a player has won or because it is drawn.
+     * @ret - How the game ended, or None if it is still going.
+     */
+    pub fn check_for_outcome(&self) -> Option<GameOutcome> {
+        if self.ruling.is_some() {
+            return self.ruling;
+        }
+        let checks = [
+            (self.check_for_checker_win(), WinReason::Checker),
+            (self.check_for_circularity_win(), WinReason::Circularity),
+            (self.check_for_stone_win(), WinReason::StoneChain),
+            (self.check_for_elimination_win(), WinReason::Elimination),
+            (self.check_for_hill_win(), WinReason::Hill),
+        ];
+        for (winner, reason) in checks {
+            if let Some(winner) = winner {
+                return Some(GameOutcome::Win { winner, reason });
+            }
+        }
+        if let Some(limit) = self.config.repetition_limit {
+            if self.positions_seen.get(&self.position_key).copied().unwrap_or(0) >= limit {
+                return Some(GameOutcome::Draw(DrawReason::Repetition));
+            }
+        }
+        if let Some(limit) = self.config.move_limit {
+            if self.history.len() + self.bounced_moves >= limit {
+                return Some(match self.config.adjudication_margin {
+                    Some(margin) => scoring::adjudicate(&self.view(), PlayerId::A, margin),
+                    None => GameOutcome::Draw(DrawReason::MoveLimit),
+                });
             }
         }
+        None
     }
 
     /**
@@ -98,22 +797,257 @@ impl<'a> Game<'a> {
      * Determine if the board is in a winning state, returning the winning player or None.
      * @ret - Winning player or none.
      */
-    pub fn check_for_win(&mut self) -> Option<i32> {
-        let checks = [
-            self.check_for_checker_win(),
-            self.check_for_circularity_win(),
-            self.check_for_stone_win(),
-        ];
-        for check in checks {
-            if let Some(winner) = check {
-                if winner == PLAYER_A_ID {
-                    return Some(PLAYER_A_ID);
-                } else if winner == PLAYER_B_ID {
-                    return Some(PLAYER_B_ID);
+    pub fn check_for_win(&self) -> Option<PlayerId> {
+        match self.check_for_outcome() {
+            Some(GameOutcome::Win { winner, .. }) => Some(winner),
+            _ => None
+        }
+    }
+
+    /**
+     * view
+     * Get a read-only view of the game.
+     * ret - GameView borrowing the board and move history.
+     */
+    pub fn view(&self) -> GameView<'_> {
+        GameView {
+            board: &self.board,
+            stones: [self.players[0].stones, self.players[1].stones],
+            captures: self.captures,
+            history: &self.history,
+            dice: &self.dice,
+            clocks: self.config.time_control.map(|_| self.clocks),
+        }
+    }
+
+    /**
+     * threat_map
+     * Get how exposed each square is to the opponent's next attack: the number of the
+     * opponent's checkers in fire range of the square, times the chance each one's die
+     * does damage through the square's hill and stone bonus.
+     * player - Id of player whose checkers are threatened.
+     * ret - Expected damage a checker of player standing on each square would take from
+     * an attack on it.
+     */
+    pub fn threat_map(&self, player: PlayerId) -> Grid<f32> {
+        let mut map = Grid::new(self.board.width(), self.board.height(), 0.0);
+        let positions: Vec<Vec2> = map.positions().collect();
+        for pos in positions {
+            map[pos] = self.board.attackers_by(pos, player.other()) as f32 * self.board.hit_chance(pos);
+        }
+        map
+    }
+
+    /**
+     * move_request
+     * Collect everything a player needs to choose their next move.
+     * player - Id of player about to move.
+     * ret - MoveRequest with the legal moves for player and a view of the game.
+     */
+    pub fn move_request(&self, player: PlayerId) -> MoveRequest<'_> {
+        self.move_request_with(player, MoveBuffers::default())
+    }
+
+    /**
+     * move_request_with
+     * Collect everything a player needs to choose their next move, filling move lists
+     * that are passed in rather than allocating new ones. Get the lists back with
+     * MoveRequest::into_buffers to reuse them for the next request.
+     * player - Id of player about to move.
+     * buffers - Move lists to fill. Anything already in them is cleared.
+     * ret - MoveRequest with the legal moves for player and a view of the game.
+     */
+    pub fn move_request_with(&self, player: PlayerId, mut buffers: MoveBuffers) -> MoveRequest<'_> {
+        buffers.clear();
+        let clock = self.config.time_control.map(|control| ClockInfo {
+            remaining: self.clocks[player.index()],
+            increment: control.increment,
+        });
+        if self.in_setup() {
+            self.checker_places_into(player, &mut buffers.place_checkers);
+            let mut request = MoveRequest::from_buffers(player, self.history.len(), 0, buffers, self.view());
+            request.clock = clock;
+            return request;
+        }
+        let action = if Some(player) == self.turn_player { self.turn_actions.len() } else { 0 };
+        self.checker_moves_into(player, &mut buffers.move_checkers);
+        self.checker_fires_into(player, &mut buffers.fire_checkers);
+        self.stone_places_into(player, &mut buffers.place_stones);
+        self.stone_slides_into(player, &mut buffers.slide_stones);
+        if self.can_swap_sides(player) {
+            buffers.swap_sides.push(Intent::SwapSides);
+        }
+        let mut request = MoveRequest::from_buffers(player, self.history.len(), action, buffers, self.view());
+        request.clock = clock;
+        // Each kind of action can only be taken once per turn
+        if action > 0 {
+            for taken in self.turn_actions.iter() {
+                for moves in [
+                    &mut request.move_checkers, &mut request.fire_checkers,
+                    &mut request.place_stones, &mut request.slide_stones,
+                ] {
+                    moves.retain(|intent| !intent.same_kind(taken));
                 }
             }
         }
-        None
+        request
+    }
+
+    /**
+     * can_swap_sides
+     * Determine if the player may take over the other side under the pie rule, which is
+     * only the case for the second player, right after the first player's first turn.
+     * player - Id of player to check.
+     * ret - True if SwapSides is a legal move for the player.
+     */
+    pub fn can_swap_sides(&self, player: PlayerId) -> bool {
+        if !self.config.rules.pie_rule || self.sides_swapped || self.turn_in_progress() {
+            return false;
+        }
+        let first = self.config.first_player;
+        if player != first.other() {
+            return false;
+        }
+        let mut main_moves = self.history.iter()
+            .filter(|(_, intent)| !matches!(intent, Intent::PlaceChecker(..)))
+            .peekable();
+        main_moves.peek().is_some() && main_moves.all(|(id, _)| *id == first)
+    }
+
+    /**
+     * sides_swapped
+     * ret - True if the second player took over the first player's side under the pie rule.
+     */
+    pub fn sides_swapped(&self) -> bool {
+        self.sides_swapped
+    }
+
+    /**
+     * undo_move
+     * Take back the last move, restoring the game to how it was before it.
+     * ret - The move taken back with the player who made it, or None at the start.
+     */
+    pub fn undo_move(&mut self) -> Option<(PlayerId, Intent)> {
+        let snapshot = self.undo_stack.pop()?;
+        if let Some(seen) = self.positions_seen.get_mut(&self.position_key) {
+            *seen -= 1;
+            if *seen == 0 {
+                self.positions_seen.remove(&self.position_key);
+            }
+        }
+        self.restore(snapshot);
+        self.history.pop()
+    }
+
+    /**
+     * take_back
+     * Take back a player's last turn and everything played since, so it is their move again.
+     * player - Id of player asking for the takeback.
+     * ret - The moves taken back, most recent first; empty if the player has not moved.
+     */
+    pub fn take_back(&mut self, player: PlayerId) -> Vec<(PlayerId, Intent)> {
+        let mut undone = Vec::new();
+        if !self.history.iter().any(|(mover, _)| *mover == player) {
+            return undone;
+        }
+        // The other side's replies, then every action of the player's own turn
+        while self.history.last().is_some_and(|(mover, _)| *mover != player) {
+            undone.extend(self.undo_move());
+        }
+        while self.history.last().is_some_and(|(mover, _)| *mover == player) {
+            undone.extend(self.undo_move());
+        }
+        undone
+    }
+
+    /*
+     * Helper function saving everything a move can change, apart from the history and the
+     * count of positions seen, which undo_move takes care of.
+     */
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            board: self.board.clone(),
+            stones: [self.players[0].stones, self.players[1].stones],
+            dice: self.dice.len(),
+            last_two_slides: [self.last_two_slides_a, self.last_two_slides_b],
+            position_key: self.position_key,
+            setup_remaining: self.setup_remaining.clone(),
+            captures: self.captures,
+            turn_player: self.turn_player,
+            turn_actions: self.turn_actions.clone(),
+            turn_slide: self.turn_slide,
+            hill_turns: self.hill_turns,
+            sides_swapped: self.sides_swapped,
+        }
+    }
+
+    /*
+     * Helper function putting back the state saved by snapshot.
+     */
+    fn restore(&mut self, snapshot: Snapshot) {
+        if snapshot.sides_swapped != self.sides_swapped {
+            self.swap_deciders();
+        }
+        self.board = snapshot.board;
+        self.players[0].stones = snapshot.stones[0];
+        self.players[1].stones = snapshot.stones[1];
+        self.dice.truncate(snapshot.dice);
+        [self.last_two_slides_a, self.last_two_slides_b] = snapshot.last_two_slides;
+        self.position_key = snapshot.position_key;
+        self.setup_remaining = snapshot.setup_remaining;
+        self.captures = snapshot.captures;
+        self.turn_player = snapshot.turn_player;
+        self.turn_actions = snapshot.turn_actions;
+        self.turn_slide = snapshot.turn_slide;
+        self.hill_turns = snapshot.hill_turns;
+        self.sides_swapped = snapshot.sides_swapped;
+    }
+
+    /**
+     * turn_in_progress
+     * Determine if the player who last moved has more actions to take this turn.
+     * ret - True if the turn has been started but not finished.
+     */
+    pub fn turn_in_progress(&self) -> bool {
+        !self.turn_actions.is_empty()
+    }
+
+    /**
+     * in_setup
+     * Determine if the game is in its free setup phase, where the only legal moves are
+     * placing starting checkers.
+     * ret - True until every starting checker has been placed.
+     */
+    pub fn in_setup(&self) -> bool {
+        self.setup_remaining.iter().any(|heights| !heights.is_empty())
+    }
+
+    /**
+     * captures_for
+     * Get the number of opposing checkers a player has destroyed this game.
+     * player - Id of player to look up.
+     * ret - Number of checkers captured.
+     */
+    pub fn captures_for(&self, player: PlayerId) -> u32 {
+        self.captures[player.index()]
+    }
+
+    /**
+     * history
+     * Get every move applied so far this game, oldest first.
+     * ret - Slice of (player id, Intent) pairs.
+     */
+    pub fn history(&self) -> &[(PlayerId, Intent)] {
+        &self.history
+    }
+
+    /**
+     * dice
+     * Get every die rolled so far this game, oldest first, for records to replay fights with.
+     * ret - Rolls from 1 to 6; none under deterministic combat.
+     */
+    pub fn dice(&self) -> &[u32] {
+        &self.dice
     }
 
     /**
@@ -122,15 +1056,24 @@ impl<'a> Game<'a> {
      * player - Id of player to get moves for.
      * ret - Vector of Intent.MoveChecker.
      */
-    pub fn checker_moves_for(&self, player: i32) -> Vec<Intent> {
-        let checkers = self.board.checkers_for_player(player);
+    pub fn checker_moves_for(&self, player: PlayerId) -> Vec<Intent> {
         let mut moves: Vec<Intent> = Vec::new();
-        for checker_position in checkers.iter() {
+        self.checker_moves_into(player, &mut moves);
+        moves
+    }
+
+    /**
+     * checker_moves_into
+     * Same as checker_moves_for, appending to an existing vector.
+     * player - Id of player to get moves for.
+     * moves - Vector to push Intent.MoveChecker onto.
+     */
+    pub fn checker_moves_into(&self, player: PlayerId, moves: &mut Vec<Intent>) {
+        for checker_position in self.board.checkers_for_player(player).iter() {
             for neighbour_position in self.empty_checker_n_at(*checker_position).iter() {
                 moves.push(Intent::MoveChecker(*checker_position, *neighbour_position));
             }
         }
-        moves
     }
 
     /**
@@ -139,20 +1082,25 @@ impl<'a> Game<'a> {
      * player - Id of player to get attack moves for.
      * ret - Vector of Intent.FireChecker
      */
-    pub fn checker_fires_for(&self, player: i32) -> Vec<Intent> {
-        let other_player = match player {
-            PLAYER_A_ID => PLAYER_B_ID,
-            _ => PLAYER_A_ID
-        };
-
+    pub fn checker_fires_for(&self, player: PlayerId) -> Vec<Intent> {
         let mut moves: Vec<Intent> = Vec::new();
-        let other_checkers = self.board.checkers_for_player(other_player);
+        self.checker_fires_into(player, &mut moves);
+        moves
+    }
+
+    /**
+     * checker_fires_into
+     * Same as checker_fires_for, appending to an existing vector.
+     * player - Id of player to get attack moves for.
+     * moves - Vector to push Intent.FireChecker onto.
+     */
+    pub fn checker_fires_into(&self, player: PlayerId, moves: &mut Vec<Intent>) {
+        let other_checkers = self.board.checkers_for_player(other_player(player));
         for checker_pos in other_checkers.iter() {
             if let Ok(_num) = self.board.can_fire_checker_at(*checker_pos) {
                 moves.push(Intent::FireChecker(*checker_pos));
             }
         }
-        moves
     }
 
     /**
@@ -161,12 +1109,25 @@ impl<'a> Game<'a> {
      * player - Id of player to get stone place moves for.
      * ret - Vector of Intent.PlaceStone
      */
-    pub fn stone_places_for(&self, _player: i32) -> Vec<Intent> {
+    pub fn stone_places_for(&self, player: PlayerId) -> Vec<Intent> {
         let mut moves: Vec<Intent> = Vec::new();
+        self.stone_places_into(player, &mut moves);
+        moves
+    }
+
+    /**
+     * stone_places_into
+     * Same as stone_places_for, appending to an existing vector.
+     * player - Id of player to get stone place moves for.
+     * moves - Vector to push Intent.PlaceStone onto.
+     */
+    pub fn stone_places_into(&self, player: PlayerId, moves: &mut Vec<Intent>) {
+        if self.players.iter().any(|p| p.id == player && p.stones <= 0) {
+            return;
+        }
         for stone_pos in self.valid_stone_places().iter() {
             moves.push(Intent::PlaceStone(*stone_pos));
         }
-        moves
     }
     
     /**
@@ -175,182 +1136,474 @@ impl<'a> Game<'a> {
      * player - Id of player to get stone slide moves for.
      * ret - Vector of Intent.SlideStone.
      */
-    pub fn stone_slides_for(&self, player: i32) -> Vec<Intent> {
+    pub fn stone_slides_for(&self, player: PlayerId) -> Vec<Intent> {
         let mut moves: Vec<Intent> = Vec::new();
+        self.stone_slides_into(player, &mut moves);
+        moves
+    }
+
+    /**
+     * stone_slides_into
+     * Same as stone_slides_for, appending to an existing vector.
+     * player - Id of player to get stone slide moves for.
+     * moves - Vector to push Intent.SlideStone onto.
+     */
+    pub fn stone_slides_into(&self, player: PlayerId, moves: &mut Vec<Intent>) {
         let stone_positions = self.board.stones_for_player(player);
         for stone_position in stone_positions.iter() {
             for direction in self.empty_stone_n_at(*stone_position).iter() {
                 moves.push(Intent::SlideStone(*stone_position, *direction));
             }
         }
-        moves
     }
 
-    /**
-     * apply_move
-     * Apply the move to the game state, using current player as the player executing the move.
-     * intent - Intent specifying action to be taken.
+    /**
+     * checker_places_for
+     * Get all legal starting checker placements for the player during free setup:
+     * one of their remaining heights, on an empty square in one of their home columns.
+     * player - Id of player to get checker place moves for.
+     * ret - Vector of Intent.PlaceChecker, empty outside the setup phase.
+     */
+    pub fn checker_places_for(&self, player: PlayerId) -> Vec<Intent> {
+        let mut moves: Vec<Intent> = Vec::new();
+        self.checker_places_into(player, &mut moves);
+        moves
+    }
+
+    /**
+     * checker_places_into
+     * Same as checker_places_for, appending to an existing vector.
+     * player - Id of player to get checker place moves for.
+     * moves - Vector to push Intent.PlaceChecker onto.
+     */
+    pub fn checker_places_into(&self, player: PlayerId, moves: &mut Vec<Intent>) {
+        let mut heights = self.setup_remaining[player.index()].clone();
+        heights.sort();
+        heights.dedup();
+        for column in self.config.variant.home_columns(player) {
+            for yi in 0..self.board.height() as i32 {
+                let position = Vec2::new(column, yi);
+                if self.board.checker_at(position).unwrap().owner.is_some() {
+                    continue;
+                }
+                for height in heights.iter() {
+                    moves.push(Intent::PlaceChecker(position, *height));
+                }
+            }
+        }
+    }
+
+    /**
+     * selectable_pieces
+     * Get the pieces a player can act with this turn: checkers that can move or take
+     * part in an attack, and stones that can slide. Meant for front ends that let the
+     * player pick a piece first and a destination second.
+     * player - Id of player to get pieces for.
+     * ret - Vector of PieceAt, checkers first.
+     */
+    pub fn selectable_pieces(&self, player: PlayerId) -> Vec<PieceAt> {
+        let mut pieces: Vec<PieceAt> = Vec::new();
+        for checker_position in self.board.checkers_for_player(player) {
+            let piece = PieceAt::Checker(checker_position);
+            if !self.moves_for_piece(piece).is_empty() {
+                pieces.push(piece);
+            }
+        }
+        for stone_position in self.board.stones_for_player(player) {
+            if !self.empty_stone_n_at(stone_position).is_empty() {
+                pieces.push(PieceAt::Stone(stone_position));
+            }
+        }
+        pieces
+    }
+
+    /**
+     * moves_for_piece
+     * Get the legal moves of the piece at a position, for its owner.
+     * For a checker, these are its moves and every attack it is in range to take part in.
+     * For a stone, these are its slides.
+     * piece - Position of the piece, and which grid it is on.
+     * ret - Vector of Intent, empty if there is no piece there.
+     */
+    pub fn moves_for_piece(&self, piece: PieceAt) -> Vec<Intent> {
+        let mut moves: Vec<Intent> = Vec::new();
+        match piece {
+            PieceAt::Checker(pos) => {
+                let owner = match self.board.checker_at(pos) {
+                    Ok(Checker { owner: Some(owner), .. }) => *owner,
+                    _ => return moves
+                };
+                for neighbour_position in self.empty_checker_n_at(pos) {
+                    moves.push(Intent::MoveChecker(pos, neighbour_position));
+                }
+                for fire in self.checker_fires_for(owner) {
+                    if let Intent::FireChecker(target) = fire {
+                        if self.board.in_fire_range(pos, target) {
+                            moves.push(fire);
+                        }
+                    }
+                }
+            },
+            PieceAt::Stone(pos) => {
+                match self.board.stone_at(pos) {
+                    Ok(stone) if stone.owner.is_some() => (),
+                    _ => return moves
+                };
+                for direction in self.empty_stone_n_at(pos) {
+                    moves.push(Intent::SlideStone(pos, direction));
+                }
+            }
+        }
+        moves
+    }
+
+    /**
+     * try_apply_move
+     * Apply the move if it is one of the legal moves offered to the player, or refuse it.
+     * Unlike apply_move, which expects its caller to have checked, this never panics on
+     * a bad move, so it suits moves from untrusted sources such as the network.
+     * current_player - Id of player making the move.
+     * intent - Intent specifying action to be taken.
+     * ret - Ok if the move was applied, or ApplyError saying why it was not.
+     */
+    pub fn try_apply_move(&mut self, current_player: PlayerId, intent: Intent) -> Result<(), ApplyError> {
+        let buffers = std::mem::take(&mut self.scratch);
+        let request = self.move_request_with(current_player, buffers);
+        let legal = request.all_moves().any(|legal| *legal == intent);
+        self.scratch = request.into_buffers();
+        if !legal {
+            return Err(ApplyError::IllegalMove { player: current_player, intent });
+        }
+        self.make_move(current_player, intent)
+    }
+
+    /**
+     * apply_move
+     * Apply the move to the game state, using current player as the player executing the move.
+     * Meant for moves the caller knows to be legal: panics if the board refuses the move.
+     * intent - Intent specifying action to be taken.
+     */
+    pub fn apply_move(&mut self, current_player: PlayerId, intent: Intent) {
+        if let Err(err) = self.make_move(current_player, intent) {
+            panic!("{}", err);
+        }
+    }
+
+    /*
+     * Helper function applying a move, returning the reason if the board refuses it. Nothing
+     * is changed when a move is refused. Moves are not checked against the legal move lists.
+     */
+    fn make_move(&mut self, current_player: PlayerId, intent: Intent) -> Result<(), ApplyError> {
+        let snapshot = self.snapshot();
+        match self.apply_intent(current_player, intent) {
+            Ok(()) => {
+                self.undo_stack.push(snapshot);
+                Ok(())
+            },
+            Err(err) => {
+                // a refused move may already have ended the turn in progress
+                self.restore(snapshot);
+                Err(err)
+            },
+        }
+    }
+
+    /*
+     * Helper function applying a move for make_move, which keeps the undo stack.
+     */
+    fn apply_intent(&mut self, current_player: PlayerId, intent: Intent) -> Result<(), ApplyError> {
+        if intent == Intent::SwapSides {
+            // Only the deciders change hands: the board, pools and side to move stay as they are
+            if !self.can_swap_sides(current_player) {
+                return Err(ApplyError::IllegalMove { player: current_player, intent });
+            }
+            self.swap_deciders();
+            self.sides_swapped = true;
+            self.history.push((current_player, intent));
+            self.strict_check();
+            return Ok(());
+        }
+        if self.turn_in_progress() && self.turn_player != Some(current_player) {
+            self.end_turn();
+        }
+        let actions_per_turn = if self.in_setup() { 1 } else { self.config.rules.actions_per_turn };
+        let mut slide: Option<SlideRecord> = None;
+        let mut setup_idx: Option<usize> = None;
+        let mut exhausted = false;
+        match intent {
+            Intent::PlaceStone(_) if self.players[current_player.index()].stones <= 0 => {
+                return Err(ApplyError::NoStones(current_player));
+            },
+            Intent::SlideStone(from, direction) => {
+                slide = self.board.slide_stone_result(from, direction).ok().map(|to| SlideRecord { from, to });
+            },
+            Intent::PlaceChecker(_, height) => {
+                let idx = self.setup_remaining[current_player.index()].iter()
+                    .position(|h| *h == height)
+                    .ok_or(ApplyError::IllegalMove { player: current_player, intent })?;
+                setup_idx = Some(idx);
+            },
+            _ => (),
+        }
+        let combat = match intent {
+            Intent::FireChecker(at) => Some(self.board.apply_fire(current_player, at)?),
+            _ => {
+                self.board.apply(current_player, intent)?;
+                None
+            },
+        };
+        match intent {
+            Intent::FireChecker(position) if self.board.checker_at(position).is_ok_and(|checker| checker.owner.is_none()) => {
+                self.captures[current_player.index()] += 1;
+            },
+            Intent::PlaceStone(_) => {
+                let player = &mut self.players[current_player.index()];
+                player.get_stone();
+                exhausted = player.stones == 0;
+            },
+            _ => (),
+        }
+        if let Some(idx) = setup_idx {
+            self.setup_remaining[current_player.index()].swap_remove(idx);
+        }
+        self.turn_player = Some(current_player);
+        self.turn_actions.push(intent);
+        if slide.is_some() {
+            self.turn_slide = slide;
+        }
+        self.history.push((current_player, intent));
+
+        let turn_over = self.turn_actions.len() >= actions_per_turn
+            || !self.has_any_move(current_player);
+        if turn_over {
+            self.end_turn();
+            self.record_position(other_player(current_player));
+        } else {
+            self.record_position(current_player);
+        }
+        self.strict_check();
+        if let Some(combat) = &combat {
+            self.dice.extend_from_slice(&combat.rolls);
+        }
+        self.emit(GameEvent::MoveMade { player: current_player, intent });
+        if let Some(combat) = combat {
+            self.emit(GameEvent::DiceRolled { player: current_player, combat });
+        }
+        if exhausted {
+            self.emit(GameEvent::StonesExhausted { player: current_player });
+        }
+        Ok(())
+    }
+
+    /*
+     * Helper function finding if @player has any move left, listing them in the scratch
+     * buffers rather than allocating a new request.
+     */
+    fn has_any_move(&mut self, player: PlayerId) -> bool {
+        let buffers = std::mem::take(&mut self.scratch);
+        let request = self.move_request_with(player, buffers);
+        let any = request.all_moves().next().is_some();
+        self.scratch = request.into_buffers();
+        any
+    }
+
+    /*
+     * Helper function remembering how many stones each player has in all, for strict_check.
+     */
+    fn count_stone_totals(&mut self) {
+        #[cfg(feature = "strict-checks")]
+        for player in self.players.iter() {
+            self.stone_totals[player.id.index()] = player.stones + self.board.stones_for_player(player.id).len() as i32;
+        }
+    }
+
+    /*
+     * Helper function checking the game's invariants after a move, in debug builds with the
+     * strict-checks feature: the board is consistent, no pool has run below empty, every stone
+     * is either in its owner's pool or on the board, and no checker is left to place at height 0.
+     */
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        for player in self.players.iter() {
+            let idx = player.id.index();
+            debug_assert_eq!(self.board.check_invariants(), Ok(()));
+            debug_assert!(player.stones >= 0, "player {} has {} stones", player.id, player.stones);
+            debug_assert_eq!(player.stones + self.board.stones_for_player(player.id).len() as i32, self.stone_totals[idx],
+                "player {} has stones missing", player.id);
+            debug_assert!(self.setup_remaining[idx].iter().all(|height| !height.is_zero()));
+        }
+    }
+
+    /*
+     * Helper function handing each side's decider to the other side.
+     */
+    fn swap_deciders(&mut self) {
+        let [first, second] = &mut self.players;
+        std::mem::swap(&mut first.decider, &mut second.decider);
+    }
+
+    /*
+     * Helper function finishing the turn in progress, recording its slide, if any, for the
+     * circularity rule.
+     */
+    fn end_turn(&mut self) {
+        let last_two_slides = match self.turn_player {
+            Some(PlayerId::A) => Some(&mut self.last_two_slides_a),
+            Some(PlayerId::B) => Some(&mut self.last_two_slides_b),
+            None => None
+        };
+        if let Some(last_two_slides) = last_two_slides {
+            last_two_slides[0] = last_two_slides[1];
+            last_two_slides[1] = self.turn_slide;
+        }
+        if let (Some(hill), Some(idx)) = (&self.config.hill, self.turn_player.map(PlayerId::index)) {
+            let holds_hill = hill.squares.iter()
+                .any(|pos| self.board.checker_at(*pos).is_ok_and(|checker| checker.owner == self.turn_player));
+            self.hill_turns[idx] = if holds_hill { self.hill_turns[idx] + 1 } else { 0 };
+        }
+        self.turn_actions.clear();
+        self.turn_slide = None;
+    }
+
+    /*
+     * Helper function starting the free setup phase, if it is configured: the board is
+     * cleared of checkers, and each player is given the variant's starting heights to place.
+     */
+    fn start_setup(&mut self) {
+        if !self.config.free_setup {
+            return;
+        }
+        self.board.clear_checkers();
+        let heights: Vec<CheckerHeight> = self.config.variant.starting_checkers()
+            .into_iter()
+            .map(|(_, height)| height)
+            .collect();
+        self.setup_remaining = [heights.clone(), heights];
+    }
+
+    /*
+     * Helper function replacing the variant's layout with the configured start position, if
+     * any. Start positions may have been reached in play, as the engine's searches are.
+     */
+    fn place_start_position(&mut self) {
+        if let Some(position) = &self.config.start_position {
+            self.board.restore_position(position).expect("start position does not fit on the board");
+        }
+    }
+
+    /*
+     * Helper function putting the configured handicap pieces on the board.
      */
-    pub fn apply_move(&mut self, current_player: i32, intent: Intent) {
-        match intent {
-            Intent::FireChecker(position) => {
-                self.board
-                    .fire_checker_at(position)
-                    .unwrap();
-            },
-            Intent::MoveChecker(from, to) => {
-                self.board
-                    .move_checker(from, to)
-                    .unwrap();
-            },
-            Intent::PlaceStone(at) => {
-                match current_player {
-                    PLAYER_A_ID => self.players[0].get_stone(),
-                    PLAYER_B_ID => self.players[1].get_stone(),
-                    _ => None
-                };
-                self.board
-                    .place_stone_at(at, Stone::new(current_player))
-                    .unwrap();
-            },
-            Intent::SlideStone(from, direction) => {
-                self.board
-                    .slide_stone(from, direction)
-                    .unwrap();
-            }
+    fn place_handicaps(&mut self) {
+        for (player, handicap) in self.config.handicaps.iter() {
+            self.board.place_handicap(*player, handicap).expect("handicap does not fit on the board");
         }
-        match current_player {
-            PLAYER_A_ID => {
-                if self.last_two_slides_a.len() == 2 {
-                    self.last_two_slides_a.swap(0, 1);
-                    self.last_two_slides_a[1] = None;
-                }
-                for i in 0..2 {
-                    if let None = self.last_two_slides_a[i] {
-                        self.last_two_slides_a[i] =  Some(intent);
-                    }
-                }
+    }
 
-            },
-            PLAYER_B_ID => {
-                if self.last_two_slides_b.len() == 2 {
-                    self.last_two_slides_b.swap(0, 1);
-                    self.last_two_slides_b[1] = None;
-                }
-                for i in 0..2 {
-                    if let None = self.last_two_slides_b[i] {
-                        self.last_two_slides_b[i] =  Some(intent);
-                    }
-                }
-            },
-            _ => ()
-        }
+    /* 
+     * Helper function counting an occurrence of the current position, with to_move
+     * as the player to move next.
+     */
+    fn record_position(&mut self, to_move: PlayerId) {
+        self.position_key = self.board.position_hash() ^ zobrist_key(ZOBRIST_SIDE_SLOTS + to_move.index() as u64);
+        *self.positions_seen.entry(self.position_key).or_insert(0) += 1;
     }
 
     /* 
      * Helper function returning if a non-straight line of stones 
      * of the same color proceeds from one side of the board to another.
+     * The board keeps each player's stones in a union-find, so this does not search.
      * Returns reference to winner or none.
      */
-    fn check_for_stone_win(&self) -> Option<i32> {
-        // check player A stones first
-        // start at top row of the board, work down
-        for player_id in [PLAYER_A_ID, PLAYER_B_ID] {
-            let mut visited: Vec<Vec2> = Vec::new();
-            let mut frontier: Vec<Vec2> = Vec::new();
-            for xi in 0..=BOARD_WIDTH as i32 {
-                let position = Vec2::new(xi, 0);
-                let stone = self.board.stone_at(position).unwrap();
-                if stone.owner == player_id {
-                    frontier.push(position);
-                }
-            }
-            // While the frontier is not empty
-            while let Some(position) = frontier.pop() {
-                // If we reached the other side of the board, then this player has won.
-                if position.y == BOARD_HEIGHT as i32 {
-                    return Some(player_id);
-                }
-                visited.push(position);
-                // Get all neighbouring squares
-                for neighbour in Board::stone_neighbours(position) {
-                    let stone = self.board.stone_at(neighbour).unwrap();
-                    // Add to frontier if not visited and stones are owned by current player
-                    if !visited.contains(&neighbour) && stone.owner == player_id {
-                        frontier.push(neighbour);
-                    }
-                }
-            }
-        }
-        None
+    fn check_for_stone_win(&self) -> Option<PlayerId> {
+        self.board.stone_chain_winner()
     }
+
     /*
      * Helper function returing if a checker has reached the opposite side of
      * the board it started on.
      * Returns reference to winner or none.
      */
-    fn check_for_checker_win(&self) -> Option<i32> {
-        for yi in 0..BOARD_HEIGHT as i32 {
-             // check if any player B checkers in column 7
+    fn check_for_checker_win(&self) -> Option<PlayerId> {
+        for yi in 0..self.board.height() as i32 {
+             // check if any player B checkers in the last column
             let position_a = Vec2::new(0, yi);
             let checker_at_a = self.board.checker_at(position_a).unwrap().owner;
-            if checker_at_a == PLAYER_A_ID {
-                return Some(PLAYER_A_ID);
+            if checker_at_a == Some(PlayerId::A) {
+                return Some(PlayerId::A);
             }
             // check if any player A checkers are in column 0
-            let position_b = Vec2::new(BOARD_WIDTH as i32 - 1, yi);
+            let position_b = Vec2::new(self.board.width() as i32 - 1, yi);
             let checker_at_b = self.board.checker_at(position_b).unwrap().owner;
-            if checker_at_b == PLAYER_B_ID {
-                return Some(PLAYER_B_ID);
+            if checker_at_b == Some(PlayerId::B) {
+                return Some(PlayerId::B);
             }
         }
         None
     }
 
     /*
-     * Helper function returning winner if law of circularity (circular slide move)
-     * has been violated.
+     * Helper function returning winner if law of circularity has been violated, ie a
+     * player slid a stone on their last turn straight back to where it was the turn before.
+     * Only the recorded slide positions are compared, so the board is left untouched.
      * Returns reference to winner or none.
      */
-    fn check_for_circularity_win(&mut self) -> Option<i32> {
-        if let Some(Intent::SlideStone(from1, dir1)) = self.last_two_slides_a[0] {
-            if let Some(Intent::SlideStone(_, dir2)) = self.last_two_slides_a[1] {
-                let mid_position = self.board.slide_stone(from1, dir1).unwrap();
-                let end_position = self.board.slide_stone(mid_position, dir2).unwrap();
-                if from1 == end_position {
-                    return Some(PLAYER_B_ID);
-                }
-                
-                if let Ok(mid_position) = self.board.slide_stone(end_position, dir2.inverse()) {
-                    self.board.slide_stone(mid_position, dir1.inverse()).unwrap();
-                }
-            }
+    fn check_for_circularity_win(&self) -> Option<PlayerId> {
+        if !self.config.rules.circularity {
+            return None;
         }
-        if let Some(Intent::SlideStone(from1, dir1)) = self.last_two_slides_b[0] {
-            if let Some(Intent::SlideStone(_, dir2)) = self.last_two_slides_b[1] {
-                let mid_position = self.board.slide_stone(from1, dir1).unwrap();
-                let end_position = self.board.slide_stone(mid_position, dir2).unwrap();
-                if from1 == end_position {
-                    return Some(PLAYER_A_ID);
-                }
+        if SlideRecord::is_circular(&self.last_two_slides_a) {
+            return Some(PlayerId::B);
+        }
+        if SlideRecord::is_circular(&self.last_two_slides_b) {
+            return Some(PlayerId::A);
+        }
+        None
+    }
 
-                if let Ok(mid_position) = self.board.slide_stone(end_position, dir2.inverse()) {
-                    self.board.slide_stone(mid_position, dir1.inverse()).unwrap();
-                }
+    /*
+     * Helper function returning the winner if the elimination rule is on and one player
+     * has no checkers left. Never applies during setup, when the board starts empty.
+     * Returns reference to winner or none.
+     */
+    fn check_for_elimination_win(&self) -> Option<PlayerId> {
+        if !self.config.rules.elimination || self.in_setup() {
+            return None;
+        }
+        if self.board.checkers_for_player(PlayerId::B).is_empty() {
+            return Some(PlayerId::A);
+        }
+        if self.board.checkers_for_player(PlayerId::A).is_empty() {
+            return Some(PlayerId::B);
+        }
+        None
+    }
+
+    /*
+     * Helper function returning the winner if king-of-the-hill is on and a player has
+     * ended enough turns in a row with a checker on the hill.
+     * Returns reference to winner or none.
+     */
+    fn check_for_hill_win(&self) -> Option<PlayerId> {
+        let hill = self.config.hill.as_ref()?;
+        for (idx, player) in [PlayerId::A, PlayerId::B].into_iter().enumerate() {
+            if self.hill_turns[idx] >= hill.turns {
+                return Some(player);
             }
         }
         None
     }
 
     /*
-     * Helper function returning empty neighbour positions around a checker position.
+     * Helper function returning empty neighbour positions around a checker position that
+     * the checker can step to.
      * Returns an array of Vec2.
      */
     fn empty_checker_n_at(&self, pos: Vec2) -> Vec<Vec2> {
         let mut empty_neighbours: Vec<Vec2> = Vec::new();
-        for npos in Board::checker_neighbours(pos).iter() {
-            if self.board.checker_at(*npos).unwrap().owner == EMPTY_PLAYER_ID {
-                empty_neighbours.push(*npos);
+        for npos in self.board.checker_neighbours(pos) {
+            if self.board.checker_at(npos).unwrap().owner.is_none() && self.board.checker_can_step(pos, npos) {
+                empty_neighbours.push(npos);
             }
         }
         empty_neighbours
@@ -367,7 +1620,7 @@ impl<'a> Game<'a> {
         for dir in directions.iter() {
             let npos = pos + dir.as_vec();
             if let Ok(stone) = self.board.stone_at(npos) {
-                if stone.owner == EMPTY_PLAYER_ID {
+                if stone.owner.is_none() {
                     empty_directions.push(*dir);
                 }
             }
@@ -375,16 +1628,20 @@ impl<'a> Game<'a> {
         empty_directions
     }
     /*
-     * Helper function returning valid stone placement positions (empty and not bordering
-     * a square with a checker).
+     * Helper function returning valid stone placement positions (empty and, under the
+     * rule of negation, not bordering a square with a checker).
      * Returns an array of Vec2 
      */
     fn valid_stone_places(&self) -> Vec<Vec2> {
         let mut valid_pos : Vec<Vec2> = Vec::new();
         for pos in self.board.empty_stones().iter() {
+            if !self.config.rules.negation {
+                valid_pos.push(*pos);
+                continue;
+            }
             let mut is_valid = true;
-            for cpos in Board::checker_neigbours_of_stone(*pos).iter() {
-                if self.board.checker_at(*cpos).unwrap().owner != EMPTY_PLAYER_ID {
+            for cpos in self.board.checker_neigbours_of_stone(*pos) {
+                if self.board.checker_at(cpos).unwrap().owner.is_some() {
                     is_valid = false;
                     break;
                 }
@@ -398,18 +1655,87 @@ impl<'a> Game<'a> {
 }
 
 pub struct Player<'a> {
-    pub id: i32,
+    pub id: PlayerId,
     pub stones: i32,
     pub decider: &'a dyn Decide,
     pub max_stones: i32,
 }
 
+/*
+ * Snapshot is the state of a game before a move, kept so the move can be undone.
+ */
+struct Snapshot {
+    board: Board,
+    stones: [i32; 2],
+    // dice rolled before the move
+    dice: usize,
+    last_two_slides: [[Option<SlideRecord>; 2]; 2],
+    position_key: u64,
+    setup_remaining: [Vec<CheckerHeight>; 2],
+    captures: [u32; 2],
+    turn_player: Option<PlayerId>,
+    turn_actions: Vec<Intent>,
+    turn_slide: Option<SlideRecord>,
+    hill_turns: [u32; 2],
+    sides_swapped: bool,
+}
+
+/**
+ * SlideRecord stores where a slid stone started and where it came to rest.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlideRecord {
+    pub from: Vec2,
+    pub to: Vec2,
+}
+
+impl SlideRecord {
+    /**
+     * is_circular
+     * Determine if two consecutive slides, oldest first, moved a stone away and back again.
+     * last_two - Slides from a player's last two turns, None for turns that were not slides.
+     * ret - True if the second slide undid the first.
+     */
+    pub fn is_circular(last_two: &[Option<SlideRecord>; 2]) -> bool {
+        match last_two {
+            [Some(first), Some(second)] => second.from == first.to && second.to == first.from,
+            _ => false
+        }
+    }
+}
+
+/**
+ * PieceAt identifies a piece by its position. Checkers and stones live on different
+ * grids, so a position alone is ambiguous.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PieceAt {
+    Checker(Vec2),
+    Stone(Vec2),
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Intent {
     MoveChecker(Vec2, Vec2),
     FireChecker(Vec2),
     PlaceStone(Vec2),
-    SlideStone(Vec2, Direction)
+    SlideStone(Vec2, Direction),
+    // Position and height of a starting checker, only legal during free setup
+    PlaceChecker(Vec2, CheckerHeight),
+    // Take over the other player's side, only legal under the pie rule after the first turn
+    SwapSides,
+}
+
+impl Intent {
+    /**
+     * same_kind
+     * Determine if two moves are the same kind of action, regardless of where they happen.
+     * other - Move to compare with.
+     * ret - True if both are, for example, stone slides.
+     */
+    pub fn same_kind(&self, other: &Intent) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
 }
 
 impl Display for Intent {
@@ -418,16 +1744,274 @@ impl Display for Intent {
             Intent::MoveChecker(from, to) => write!(formatter, "MoveChecker from {} to {}", from, to),
             Intent::FireChecker(at) => write!(formatter, "FireChecker at {}", at),
             Intent::PlaceStone(at) => write!(formatter, "PlaceStone at {}", at),
-            Intent::SlideStone(from, direction) => write!(formatter, "SlideStone from {} toward {}", from, direction)
+            Intent::SlideStone(from, direction) => write!(formatter, "SlideStone from {} toward {}", from, direction),
+            Intent::PlaceChecker(at, height) => write!(formatter, "PlaceChecker of height {} at {}", height, at),
+            Intent::SwapSides => write!(formatter, "SwapSides"),
+        }
+    }
+}
+
+/**
+ * GameView is a read-only snapshot of the game handed to a Decide implementation,
+ * so that it can look at the position, the stone pools, and the moves played so far.
+ */
+pub struct GameView<'a> {
+    pub board: &'a Board,
+    // stones left in the pools of player A and player B, in that order
+    pub stones: [i32; 2],
+    // checkers destroyed by player A and player B, in that order
+    pub captures: [u32; 2],
+    pub history: &'a [(PlayerId, Intent)],
+    // every die rolled so far, in order
+    pub dice: &'a [u32],
+    // time left to player A and player B, in that order, or None when the game is untimed
+    pub clocks: Option<[Duration; 2]>,
+}
+
+impl<'a> GameView<'a> {
+    /**
+     * stones_for
+     * Get the number of stones left in a player's pool.
+     * player - Id of player to look up.
+     * ret - Stones remaining.
+     */
+    pub fn stones_for(&self, player: PlayerId) -> i32 {
+        self.stones[player.index()]
+    }
+
+    /**
+     * status_line
+     * Describe a player's stones and losses, for showing under the board.
+     * player - Id of player to describe.
+     * ret - eg "Player A: 30 stones in pool, 2 on board, 1 checker lost".
+     */
+    pub fn status_line(&self, player: PlayerId) -> String {
+        let lost = self.captures[player.other().index()];
+        format!(
+            "Player {}: {} stones in pool, {} on board, {} checker{} lost",
+            player,
+            self.stones_for(player),
+            self.board.stones_for_player(player).len(),
+            lost,
+            if lost == 1 { "" } else { "s" },
+        )
+    }
+}
+
+impl Display for GameView<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.board)?;
+        writeln!(f, "{}", self.status_line(PlayerId::A))?;
+        writeln!(f, "{}", self.status_line(PlayerId::B))?;
+        let [a, b] = [PlayerId::A, PlayerId::B].map(|player| scoring::progress_bar(&scoring::progress(self.board, player), scoring::PROGRESS_BAR_WIDTH));
+        write!(f, "Progress: Player A {}, Player B {}", a, b)?;
+        if let Some([a, b]) = self.clocks {
+            write!(f, "\nTime left: Player A {}, Player B {}", clock_text(a), clock_text(b))?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * clock_text
+ * Show time on a clock, eg "4:05", or "1:02:05" from an hour up. Part seconds are dropped.
+ * time - Time to show.
+ * ret - Minutes and seconds, with hours in front when there are any.
+ */
+pub fn clock_text(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let (hours, minutes) = (seconds / 3600, seconds / 60 % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds % 60)
+    } else {
+        format!("{}:{:02}", minutes, seconds % 60)
+    }
+}
+
+/**
+ * ClockInfo describes the time control for the player who is moving.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClockInfo {
+    // time left on the player's clock
+    pub remaining: Duration,
+    // time added to the clock after each move
+    pub increment: Duration,
+}
+
+/**
+ * MoveRequest is everything a Decide implementation is given when it is asked for a move:
+ * who is moving, the legal moves grouped by kind, and a read-only view of the game.
+ * New information for deciders should be added here rather than as new parameters.
+ */
+pub struct MoveRequest<'a> {
+    // id of the player who is choosing a move
+    pub player: PlayerId,
+    // number of moves played before this one
+    pub turn: usize,
+    // number of actions the player has already taken this turn, under the actions_per_turn rule
+    pub action: usize,
+    // None when the game is untimed
+    pub clock: Option<ClockInfo>,
+    pub move_checkers: Vec<Intent>,
+    pub fire_checkers: Vec<Intent>,
+    pub place_stones: Vec<Intent>,
+    pub slide_stones: Vec<Intent>,
+    // only offered during the free setup phase, when all other move lists are empty
+    pub place_checkers: Vec<Intent>,
+    // holds Intent::SwapSides when the pie rule lets the player take over the other side
+    pub swap_sides: Vec<Intent>,
+    pub view: GameView<'a>,
+}
+
+impl<'a> MoveRequest<'a> {
+    /*
+     * Helper function wrapping filled move lists up as a request.
+     */
+    fn from_buffers(player: PlayerId, turn: usize, action: usize, buffers: MoveBuffers, view: GameView<'a>) -> MoveRequest<'a> {
+        MoveRequest {
+            player,
+            turn,
+            action,
+            clock: None,
+            move_checkers: buffers.move_checkers,
+            fire_checkers: buffers.fire_checkers,
+            place_stones: buffers.place_stones,
+            slide_stones: buffers.slide_stones,
+            place_checkers: buffers.place_checkers,
+            swap_sides: buffers.swap_sides,
+            view,
+        }
+    }
+
+    /**
+     * into_buffers
+     * Take the move lists back out of the request, so their memory can be reused
+     * for the next one with Game::move_request_with.
+     * ret - MoveBuffers holding this request's move lists.
+     */
+    pub fn into_buffers(self) -> MoveBuffers {
+        MoveBuffers {
+            move_checkers: self.move_checkers,
+            fire_checkers: self.fire_checkers,
+            place_stones: self.place_stones,
+            slide_stones: self.slide_stones,
+            place_checkers: self.place_checkers,
+            swap_sides: self.swap_sides,
+        }
+    }
+
+    /**
+     * checker_moves_by_source
+     * Group the checker moves by the square they start from, so a player can pick a
+     * checker first and its destination second.
+     * ret - Each checker that can move, in the order its moves were offered, with its moves.
+     */
+    pub fn checker_moves_by_source(&self) -> Vec<(Vec2, Vec<Intent>)> {
+        let mut sources: Vec<(Vec2, Vec<Intent>)> = Vec::new();
+        for intent in self.move_checkers.iter() {
+            if let Intent::MoveChecker(from, _) = intent {
+                match sources.iter_mut().find(|(source, _)| source == from) {
+                    Some((_, moves)) => moves.push(*intent),
+                    None => sources.push((*from, vec![*intent])),
+                }
+            }
+        }
+        sources
+    }
+
+    /**
+     * all_moves
+     * Iterate over every offered move, regardless of kind.
+     */
+    pub fn all_moves(&self) -> impl Iterator<Item = &Intent> {
+        self.move_checkers.iter()
+            .chain(self.fire_checkers.iter())
+            .chain(self.place_stones.iter())
+            .chain(self.slide_stones.iter())
+            .chain(self.place_checkers.iter())
+            .chain(self.swap_sides.iter())
+    }
+}
+
+/**
+ * MoveBuffers holds the move lists of a MoveRequest between requests, so batch
+ * simulations can generate moves every turn without allocating new vectors.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct MoveBuffers {
+    pub move_checkers: Vec<Intent>,
+    pub fire_checkers: Vec<Intent>,
+    pub place_stones: Vec<Intent>,
+    pub slide_stones: Vec<Intent>,
+    pub place_checkers: Vec<Intent>,
+    pub swap_sides: Vec<Intent>,
+}
+
+impl MoveBuffers {
+    /**
+     * clear
+     * Empty every list, keeping its capacity.
+     */
+    pub fn clear(&mut self) {
+        for moves in [
+            &mut self.move_checkers, &mut self.fire_checkers, &mut self.place_stones,
+            &mut self.slide_stones, &mut self.place_checkers, &mut self.swap_sides,
+        ] {
+            moves.clear();
         }
     }
 }
 
+/**
+ * Decision is what a player does when it is their move: make one, ask to take back
+ * their last turn, or quit, leaving the game unfinished.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decision {
+    Move(Intent),
+    TakeBack,
+    Quit,
+}
+
 pub trait Decide {
-    fn choose_move(
-        &self, move_checkers: Vec<Intent>, fire_checkers: Vec<Intent>, 
-        place_stones: Vec<Intent>, slide_stones: Vec<Intent>
-    ) -> Intent;
+    /**
+     * choose_move
+     * Pick one of the moves offered in request.
+     * request - Legal moves and game state for the player to move.
+     * ret - The chosen move, which should be one of the offered moves.
+     */
+    fn choose_move(&self, request: &MoveRequest) -> Intent;
+
+    /**
+     * decide
+     * Choose a move, or ask for a takeback. Only deciders that ask for takebacks, such as
+     * people at the console, need to implement this.
+     * request - Legal moves and game state for the player to move.
+     * ret - The decision, by default a move from choose_move.
+     */
+    fn decide(&self, request: &MoveRequest) -> Decision {
+        Decision::Move(self.choose_move(request))
+    }
+
+    /**
+     * accept_takeback
+     * Agree or not to the opponent taking back their last turn.
+     * player - Id of player asking for the takeback.
+     * ret - True to allow it, which is the default.
+     */
+    fn accept_takeback(&self, _player: PlayerId) -> bool {
+        true
+    }
+
+    /**
+     * search_info
+     * Report what went into the last move chosen, for deciders that search.
+     * ret - What the search did, or None, which is the default.
+     */
+    fn search_info(&self) -> Option<SearchInfo> {
+        None
+    }
 }
 
 /**
@@ -445,136 +2029,70 @@ impl ConsolePlayer {
     }
 }
 
-impl Decide for ConsolePlayer {
-
-    fn choose_move(
-        &self, move_checkers: Vec<Intent>, fire_checkers: Vec<Intent>, 
-        place_stones: Vec<Intent>, slide_stones: Vec<Intent>
-    ) -> Intent {
-
-        let chosen_move: Option<Intent> = None;
-        while let None = chosen_move {
-            print!("\nWhat would you like to do? (Type your choice, then press ENTER)\n");
-            println!("M - Move checker");
-            println!("A - Attack checker");
-            println!("P - Place stone");
-            println!("S - Slide stone");
-
-            print!("Enter a letter: ");
-            io::stdout().flush().unwrap();
-            let mut line = String::new();
-            while let Err(_) = io::stdin().read_line(&mut line){
-                print!("Enter a letter: ");
-            }
-            
-            let choice = line.chars().collect::<Vec<char>>()[0];
-            match choice {
-                'M' => {
-                    let mut idx = 0;
-                    for move_checker in move_checkers.iter() {
-                        if let Intent::MoveChecker(from, to) = move_checker {
-                            println!("{idx} - move checker {from} to {to}");
-                        }
-                        idx += 1;
-                    }
-
-                    let mut line = String::new();
-                    loop {
-                        print!("Enter the number of your choice: ");
-                        io::stdout().flush().unwrap();
-                        while let Err(_) = io::stdin().read_line(&mut line) {}
-                        if let Ok(idx) = line.trim().parse::<usize>() { 
-                            if idx < move_checkers.len() {
-                                return move_checkers[idx];
-                            }
-                        }
-                    }
-
-                },
-                'A' => {
-                    let mut idx = 0;
-                    for fire_checker in fire_checkers.iter() {
-                        if let Intent::FireChecker(at) = fire_checker {
-                            println!("{idx} - attack checker at {at}");
-                        }
-                        idx += 1;
-                    }
-
-                    let mut line = String::new();
-                    loop {
-                        print!("Enter the number of your choice: ");
-                        io::stdout().flush().unwrap();
-                        while let Err(_) = io::stdin().read_line(&mut line) {}
-                        if let Ok(idx) = line.trim().parse::<usize>() { 
-                            if idx < fire_checkers.len() {
-                                return fire_checkers[idx];
-                            }
-                        }
-                    }
-
-                },
-                'P' => {
-                    let mut idx = 0;
-                    for place_stone in place_stones.iter() {
-                        if let Intent::PlaceStone(at) = place_stone {
-                            println!("{idx} - place stone at {at}");
-                        }
-                        idx += 1;
-                    }
-
-                    let mut line = String::new();
-                    loop {
-                        print!("Enter the number of your choice: ");
-                        io::stdout().flush().unwrap();
-                        while let Err(_) = io::stdin().read_line(&mut line) {}
-                        if let Ok(idx) = line.trim().parse::<usize>() { 
-                            if idx < place_stones.len() {
-                                return place_stones[idx];
-                            }
-                        }
-                    }
-
-                },
-                'S' => {
-                    let mut idx = 0;
-                    for slide_stone in slide_stones.iter() {
-                        if let Intent::SlideStone(from, dir) = slide_stone {
-                            println!("{idx} - slide stone from {from} {dir}");
-                        }
-                        idx += 1;
-                    }
+impl Default for ConsolePlayer {
+    fn default() -> Self {
+        ConsolePlayer::new()
+    }
+}
 
-                    let mut line = String::new();
-                    loop {
-                        print!("Enter the number of your choice: ");
-                        io::stdout().flush().unwrap();
-                        while let Err(_) = io::stdin().read_line(&mut line) {}
-                        if let Ok(idx) = line.trim().parse::<usize>() { 
-                            if idx < slide_stones.len() {
-                                return slide_stones[idx];
-                            }
-                        }
-                    }
-                },
-                _ => {
-                    continue;
-                },
+impl Decide for ConsolePlayer {
+    /*
+     * Asked for a move only, takebacks are not offered, and quitting can not be handed back
+     * to the game, so it ends the program.
+     */
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        loop {
+            match self.decide(request) {
+                Decision::Move(intent) => return intent,
+                Decision::TakeBack => continue,
+                Decision::Quit => std::process::exit(0),
             }
-        } 
-
+        }
+    }
 
+    /*
+     * Quitting from the menu, or the end of input, raises the interrupt flag as Ctrl-C would,
+     * so the console game saves and stops.
+     */
+    fn decide(&self, request: &MoveRequest) -> Decision {
+        let stdin = io::stdin();
+        let catalog = console_catalog();
+        let goodbye = catalog.text(Key::Goodbye).to_string();
+        let mut console = Console::with_catalog(stdin.lock(), io::stdout(), catalog.clone());
+        match console.choose_move(request) {
+            Some(decision) => decision,
+            None => {
+                println!("\n{}", goodbye);
+                interrupt::raise();
+                Decision::Quit
+            },
+        }
+    }
 
-        Intent::PlaceStone(Vec2::new(0, 0))
+    fn accept_takeback(&self, player: PlayerId) -> bool {
+        let stdin = io::stdin();
+        let catalog = console_catalog();
+        let question = format!("\n{}", catalog.format(Key::AskTakeBack, &[&player]));
+        Console::with_catalog(stdin.lock(), io::stdout(), catalog.clone()).confirm(&question)
     }
 }
 
+/*
+ * Helper function giving the messages ConsolePlayers show, read from the environment the
+ * first time they are needed rather than on every move.
+ */
+fn console_catalog() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(Catalog::from_env)
+}
+
 impl<'a> Player<'a> {
     /**
      * new - Create a new instance.
      * nstones - Number of stones the player has.
      * decide - Object that decides what moves to take.
      */
-    pub fn new(_id: i32, nstones: i32, decide: &'a dyn Decide) -> Player<'a> {
+    pub fn new(_id: PlayerId, nstones: i32, decide: &'a dyn Decide) -> Player<'a> {
         Player {
             id: _id,
             stones: nstones,
@@ -583,6 +2101,15 @@ impl<'a> Player<'a> {
         }
     }
 
+    /**
+     * set_stone_pool - Change the size of the pile, and fill it.
+     * nstones - Number of stones the player has.
+     */
+    pub fn set_stone_pool(&mut self, nstones: i32) {
+        self.stones = nstones;
+        self.max_stones = nstones;
+    }
+
     /** 
      * get_stone - Remove a stone from the pile.
      * ret - Some if there are stones left, otherwise None.
@@ -605,11 +2132,20 @@ impl<'a> Player<'a> {
 }
 
 impl<'a> Decide for Player<'a> {
-    fn choose_move(
-        &self, move_checkers: Vec<Intent>, fire_checkers: Vec<Intent>, 
-        place_stones: Vec<Intent>, slide_stones: Vec<Intent>
-    ) -> Intent {
-        self.decider.choose_move(move_checkers, fire_checkers, place_stones, slide_stones)
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        self.decider.choose_move(request)
+    }
+
+    fn decide(&self, request: &MoveRequest) -> Decision {
+        self.decider.decide(request)
+    }
+
+    fn accept_takeback(&self, player: PlayerId) -> bool {
+        self.decider.accept_takeback(player)
+    }
+
+    fn search_info(&self) -> Option<SearchInfo> {
+        self.decider.search_info()
     }
 }
 
@@ -619,44 +2155,137 @@ impl<'a> Decide for Player<'a> {
 pub struct PlayerFactory;
 
 impl<'a> PlayerFactory {
-    pub fn console_player(id: i32, nstones: i32) -> Player<'a> {
+    pub fn console_player(id: PlayerId, nstones: i32) -> Player<'a> {
         Player::new(id, nstones, &ConsolePlayer{})
     }
 }
 
+/**
+ * CheckerHeight is the height of a checker, from ZERO for an empty square up to MAX.
+ * Heights are only made through checked constructors and arithmetic, so a checker can
+ * never be taller than the board knows how to draw or combat knows how to damage.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CheckerHeight(u8);
+
+impl CheckerHeight {
+    pub const ZERO: CheckerHeight = CheckerHeight(0);
+    pub const ONE: CheckerHeight = CheckerHeight(1);
+    pub const TWO: CheckerHeight = CheckerHeight(2);
+    pub const THREE: CheckerHeight = CheckerHeight(3);
+    pub const MAX: CheckerHeight = CheckerHeight::THREE;
+
+    /**
+     * new
+     * height - Height from 0 to MAX.
+     * ret - The height, or None if it is out of range.
+     */
+    pub fn new(height: usize) -> Option<CheckerHeight> {
+        if height <= CheckerHeight::MAX.get() {
+            Some(CheckerHeight(height as u8))
+        } else {
+            None
+        }
+    }
+
+    /**
+     * get
+     * ret - The height as a number, from 0 to MAX.
+     */
+    pub fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    /**
+     * is_zero
+     * ret - True for the height of an empty square or a destroyed checker.
+     */
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /**
+     * checked_add
+     * amount - Levels to add.
+     * ret - The taller height, or None if it would be above MAX.
+     */
+    pub fn checked_add(self, amount: usize) -> Option<CheckerHeight> {
+        CheckerHeight::new(self.get().checked_add(amount)?)
+    }
+
+    /**
+     * saturating_sub
+     * amount - Levels to take away, such as damage from an attack.
+     * ret - The lower height, stopping at ZERO.
+     */
+    pub fn saturating_sub(self, amount: usize) -> CheckerHeight {
+        CheckerHeight(self.get().saturating_sub(amount) as u8)
+    }
+}
+
+impl Display for CheckerHeight {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(formatter, "{}", self.0)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Checker {
-    pub height: usize,
-    pub owner: i32
+    pub height: CheckerHeight,
+    pub owner: Option<PlayerId>
 }
 
 impl Checker {
-    pub fn new(h: usize, o: i32) -> Checker {
-        Checker { height: h, owner: o }
+    pub fn new(h: CheckerHeight, o: PlayerId) -> Checker {
+        Checker { height: h, owner: Some(o) }
+    }
+
+    // No checker: the square is empty
+    pub fn empty() -> Checker {
+        Checker { height: CheckerHeight::ZERO, owner: None }
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Stone {
-    pub owner: i32
+    pub owner: Option<PlayerId>
 }
 
 impl Stone {
-    pub fn new(o: i32) -> Stone {
-        Stone { owner: o }
+    pub fn new(o: PlayerId) -> Stone {
+        Stone { owner: Some(o) }
+    }
+
+    // No stone: the corner is empty
+    pub fn empty() -> Stone {
+        Stone { owner: None }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::board::{Terrain, BOARD_HEIGHT};
+    use crate::config::{Handicap, HillConfig, RulesConfig, TimeControl, Variant};
+
+    #[test]
+    fn checker_height() {
+        assert_eq!(CheckerHeight::new(3), Some(CheckerHeight::MAX));
+        assert_eq!(CheckerHeight::new(4), None);
+        assert_eq!(CheckerHeight::TWO.checked_add(1), Some(CheckerHeight::THREE));
+        assert_eq!(CheckerHeight::MAX.checked_add(1), None);
+        assert_eq!(CheckerHeight::ONE.saturating_sub(3), CheckerHeight::ZERO);
+        assert!(Checker::empty().height.is_zero());
+    }
 
     #[test]
     fn player_get_stone() {
-        let mut player = Player::new(1, 1, &ConsolePlayer);
+        let mut player = Player::new(PlayerId::A, 1, &ConsolePlayer);
         match player.get_stone() {
             None => panic!("Expecting to get a stone!"),
-            Some(stone) => assert_eq!(stone.owner, 1)
+            Some(stone) => assert_eq!(stone.owner, Some(PlayerId::A))
         } 
         assert_eq!(player.stones, 0);
         match player.get_stone() {
@@ -667,12 +2296,12 @@ mod test {
 
     #[test]
     pub fn check_for_win() {
-        let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-        let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
         let mut game = Game::new(&mut player_a, &mut player_b);
         // Stones wins
         // -- normal case, straight line down the middle of the board
-        for player_id in [PLAYER_A_ID, PLAYER_B_ID] {
+        for player_id in [PlayerId::A, PlayerId::B] {
             for yi in 0..=(BOARD_HEIGHT as i32) {
                 let place_position = Vec2::new(4, yi);
                 game.apply_move(player_id, Intent::PlaceStone(place_position));
@@ -686,44 +2315,225 @@ mod test {
         assert_eq!(game.check_for_win(), None);
 
         game.reset();
-        game.apply_move(PLAYER_A_ID, Intent::MoveChecker(Vec2::new(7, 1), Vec2::new(7, 0)));
+        game.apply_move(PlayerId::A, Intent::MoveChecker(Vec2::new(7, 1), Vec2::new(7, 0)));
         for xi in (1..=7).rev() {
             let from_position = Vec2::new(xi, 0);
             let to_position = Vec2::new(xi - 1, 0);
-            game.apply_move(PLAYER_A_ID, Intent::MoveChecker(from_position, to_position));
+            game.apply_move(PlayerId::A, Intent::MoveChecker(from_position, to_position));
         }
 
-        assert_eq!(game.check_for_win(), Some(PLAYER_A_ID));
+        assert_eq!(game.check_for_win(), Some(PlayerId::A));
 
         game.reset();
-        game.apply_move(PLAYER_B_ID, Intent::MoveChecker(Vec2::new(0, 1), Vec2::new(0, 0)));
+        game.apply_move(PlayerId::B, Intent::MoveChecker(Vec2::new(0, 1), Vec2::new(0, 0)));
         for xi in 0..=6 {
             let from_position = Vec2::new(xi, 0);
             let to_position = Vec2::new(xi + 1, 0);
-            game.apply_move(PLAYER_B_ID, Intent::MoveChecker(from_position, to_position));
+            game.apply_move(PlayerId::B, Intent::MoveChecker(from_position, to_position));
         }
-        assert_eq!(game.check_for_win(), Some(PLAYER_B_ID));
+        assert_eq!(game.check_for_win(), Some(PlayerId::B));
 
         // Circularity wins
         game.reset();
         let from_position = Vec2::new(4, 0);
         let to_position = Vec2::new(4, BOARD_HEIGHT as i32);
-        game.apply_move(PLAYER_A_ID, Intent::PlaceStone(from_position));
-        game.apply_move(PLAYER_A_ID, Intent::SlideStone(from_position, Direction::Down));
-        game.apply_move(PLAYER_A_ID, Intent::SlideStone(to_position, Direction::Up));
-        assert_eq!(game.check_for_win(), Some(PLAYER_B_ID));
+        game.apply_move(PlayerId::A, Intent::PlaceStone(from_position));
+        game.apply_move(PlayerId::A, Intent::SlideStone(from_position, Direction::Down));
+        game.apply_move(PlayerId::A, Intent::SlideStone(to_position, Direction::Up));
+        assert_eq!(game.check_for_win(), Some(PlayerId::B));
+
+        game.reset();
+        game.apply_move(PlayerId::B, Intent::PlaceStone(from_position));
+        game.apply_move(PlayerId::B, Intent::SlideStone(from_position, Direction::Down));
+        game.apply_move(PlayerId::B, Intent::SlideStone(to_position, Direction::Up));
+        assert_eq!(game.check_for_win(), Some(PlayerId::A));
+    }
+
+    #[test]
+    pub fn check_for_outcome() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        assert_eq!(game.check_for_outcome(), None);
+
+        for yi in 0..=(BOARD_HEIGHT as i32) {
+            game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(4, yi)));
+        }
+        assert_eq!(
+            game.check_for_outcome(),
+            Some(GameOutcome::Win { winner: PlayerId::B, reason: WinReason::StoneChain })
+        );
+    }
+
+    #[test]
+    pub fn repetition_draw() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let (a_home, a_away) = (Vec2::new(7, 1), Vec2::new(7, 0));
+        let (b_home, b_away) = (Vec2::new(0, 4), Vec2::new(0, 5));
+        let shuffle = |game: &mut Game| {
+            game.apply_move(PlayerId::A, Intent::MoveChecker(a_home, a_away));
+            game.apply_move(PlayerId::B, Intent::MoveChecker(b_home, b_away));
+            game.apply_move(PlayerId::A, Intent::MoveChecker(a_away, a_home));
+            game.apply_move(PlayerId::B, Intent::MoveChecker(b_away, b_home));
+        };
+
+        // Start position seen for the second time
+        shuffle(&mut game);
+        assert_eq!(game.check_for_outcome(), None);
+        // and for the third time
+        shuffle(&mut game);
+        assert_eq!(game.check_for_outcome(), Some(GameOutcome::Draw(DrawReason::Repetition)));
+
+        // The same pieces with the other player to move is a different position
+        game.reset();
+        shuffle(&mut game);
+        game.apply_move(PlayerId::A, Intent::MoveChecker(a_home, a_away));
+        game.apply_move(PlayerId::A, Intent::MoveChecker(a_away, a_home));
+        assert_eq!(game.check_for_outcome(), None);
+        game.apply_move(PlayerId::B, Intent::MoveChecker(b_home, b_away));
+        game.apply_move(PlayerId::B, Intent::MoveChecker(b_away, b_home));
+        assert_eq!(game.check_for_outcome(), Some(GameOutcome::Draw(DrawReason::Repetition)));
+
+        // Turning the rule off
+        game.reset();
+        game.set_repetition_limit(None);
+        for _ in 0..3 {
+            shuffle(&mut game);
+        }
+        assert_eq!(game.check_for_outcome(), None);
+    }
+
+    #[test]
+    pub fn with_config() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let variant = Variant::Grand10x8;
+        let mut game = Game::with_config(&mut player_a, &mut player_b, GameConfig::for_variant(variant));
+        assert_eq!(game.board.width(), variant.width());
+        assert_eq!(game.board.height(), variant.height());
+        assert_eq!(game.view().stones, [variant.stones(); 2]);
+        assert_eq!(game.board.checkers_for_player(PlayerId::A).len(), variant.starting_checkers().len());
+
+        // Wins are checked against the edges of the larger board
+        let last_column = variant.width() as i32 - 1;
+        game.board.place_checker_at(Vec2::new(last_column, 0), Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
+        assert_eq!(game.check_for_win(), None);
+        game.board.place_checker_at(Vec2::new(last_column, 7), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        assert_eq!(game.check_for_win(), Some(PlayerId::B));
+
+        game.reset();
+        for yi in 0..=(variant.height() as i32) {
+            game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(5, yi)));
+        }
+        assert_eq!(game.check_for_win(), Some(PlayerId::A));
+        assert_eq!(game.view().stones_for(PlayerId::A), variant.stones() - 9);
+    }
+
+    #[test]
+    pub fn handicaps() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let config = GameConfig::default()
+            .with_handicap(PlayerId::B, Handicap::ExtraStones(4))
+            .with_handicap(PlayerId::B, Handicap::ExtraChecker(Vec2::new(1, 1), CheckerHeight::ONE))
+            .with_handicap(PlayerId::B, Handicap::StoneLine { start: Vec2::new(4, 0), direction: Direction::Down, length: 2 });
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+
+        assert_eq!(game.view().stones, [STARTING_STONES, STARTING_STONES + 4]);
+        assert_eq!(game.board.checkers_for_player(PlayerId::B).len(), 7);
+        assert_eq!(game.board.stones_for_player(PlayerId::B).len(), 2);
+
+        // Handicaps survive a reset
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 4)));
+        game.apply_move(PlayerId::B, Intent::MoveChecker(Vec2::new(1, 1), Vec2::new(2, 1)));
+        game.reset();
+        assert_eq!(game.view().stones, [STARTING_STONES, STARTING_STONES + 4]);
+        assert_eq!(game.board.checker_at(Vec2::new(1, 1)).unwrap().owner, Some(PlayerId::B));
+        assert_eq!(game.board.stones_for_player(PlayerId::B).len(), 2);
+    }
+
+    #[test]
+    pub fn free_setup() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let config = GameConfig { free_setup: true, ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+
+        assert!(game.in_setup());
+        assert!(game.board.checkers_for_player(PlayerId::A).is_empty());
+        let request = game.move_request(PlayerId::A);
+        assert!(request.move_checkers.is_empty() && request.place_stones.is_empty());
+        // 12 home squares, and heights 1, 2 and 3 to choose from
+        assert_eq!(request.place_checkers.len(), 12 * 3);
+        for intent in request.place_checkers.iter() {
+            match intent {
+                Intent::PlaceChecker(at, _) => assert!(at.x == 6 || at.x == 7),
+                _ => panic!("Expected only PlaceChecker during setup")
+            }
+        }
+
+        // Take turns placing the standard layout in a different order
+        let layout = Variant::Standard.starting_checkers();
+        for (pos, height) in layout.iter().rev() {
+            game.apply_move(PlayerId::A, Intent::PlaceChecker(*pos, *height));
+            game.apply_move(PlayerId::B, Intent::PlaceChecker(Vec2::new(7 - pos.x, pos.y), *height));
+        }
+        assert!(!game.in_setup());
+        assert!(game.checker_places_for(PlayerId::A).is_empty());
+        assert!(game.move_request(PlayerId::A).place_checkers.is_empty());
+        assert_eq!(game.board.as_string(), Board::new().as_string());
+
+        // Heights run out as they are placed
+        game.reset();
+        assert!(game.in_setup());
+        game.apply_move(PlayerId::B, Intent::PlaceChecker(Vec2::new(0, 0), CheckerHeight::THREE));
+        game.apply_move(PlayerId::B, Intent::PlaceChecker(Vec2::new(0, 1), CheckerHeight::THREE));
+        assert!(!game.checker_places_for(PlayerId::B).iter().any(|intent| matches!(intent, Intent::PlaceChecker(_, CheckerHeight::THREE))));
+        assert_eq!(game.checker_places_for(PlayerId::B).len(), 10 * 2);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn place_checker_outside_setup() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.apply_move(PlayerId::A, Intent::PlaceChecker(Vec2::new(7, 0), CheckerHeight::ONE));
+    }
+
+    #[test]
+    pub fn apply_simultaneous() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+
+        // Both placing on the same corner bounces
+        let corner = Vec2::new(4, 0);
+        assert!(game.apply_simultaneous(Intent::PlaceStone(corner), Intent::PlaceStone(corner)).is_empty());
+        assert_eq!(game.board.stone_at(corner).unwrap().owner, None);
+        assert_eq!(game.view().stones, [STARTING_STONES; 2]);
+
+        // Independent moves both happen
+        let a = Intent::PlaceStone(corner);
+        let b = Intent::MoveChecker(Vec2::new(1, 2), Vec2::new(2, 2));
+        assert_eq!(game.apply_simultaneous(a, b), vec![(PlayerId::A, a), (PlayerId::B, b)]);
 
+        // An attack on a checker that moved away misses
         game.reset();
-        game.apply_move(PLAYER_B_ID, Intent::PlaceStone(from_position));
-        game.apply_move(PLAYER_B_ID, Intent::SlideStone(from_position, Direction::Down));
-        game.apply_move(PLAYER_B_ID, Intent::SlideStone(to_position, Direction::Up));
-        assert_eq!(game.check_for_win(), Some(PLAYER_A_ID));
+        game.board.place_checker_at(Vec2::new(4, 2), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        let fire = Intent::FireChecker(Vec2::new(4, 2));
+        let retreat = Intent::MoveChecker(Vec2::new(4, 2), Vec2::new(3, 2));
+        assert!(game.checker_fires_for(PlayerId::A).contains(&fire));
+        assert_eq!(game.apply_simultaneous(fire, retreat), vec![(PlayerId::B, retreat)]);
+        assert_eq!(game.board.checker_at(Vec2::new(3, 2)).unwrap().owner, Some(PlayerId::B));
     }
 
     #[test]
     pub fn checker_moves_for() {
-        let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-        let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
         let game = Game::new(&mut player_a, &mut player_b);
 
         {
@@ -737,7 +2547,7 @@ mod test {
                 Intent::MoveChecker(Vec2::new(6, 3), Vec2::new(6, 4)), Intent::MoveChecker(Vec2::new(6, 3), Vec2::new(5, 2)),
                 Intent::MoveChecker(Vec2::new(6, 3), Vec2::new(5, 4)), Intent::MoveChecker(Vec2::new(6, 3), Vec2::new(5, 3)),
             ];
-            let actual_a_moves = game.checker_moves_for(PLAYER_A_ID);
+            let actual_a_moves = game.checker_moves_for(PlayerId::A);
 
             for move_a in actual_a_moves.iter() {
                 println!("{move_a}");
@@ -745,7 +2555,7 @@ mod test {
             
             for move_a in expected_a_moves.iter() {
                 // println!("{move_a}");
-                assert!(actual_a_moves.contains(&move_a));
+                assert!(actual_a_moves.contains(move_a));
             }
         }
         {
@@ -759,28 +2569,28 @@ mod test {
                 Intent::MoveChecker(Vec2::new(1, 3), Vec2::new(1, 4)), Intent::MoveChecker(Vec2::new(1, 3), Vec2::new(2, 3)),
                 Intent::MoveChecker(Vec2::new(1, 3), Vec2::new(2, 4)), Intent::MoveChecker(Vec2::new(1, 3), Vec2::new(2, 2)),
             ];
-            let actual_b_moves = game.checker_moves_for(PLAYER_B_ID);
+            let actual_b_moves = game.checker_moves_for(PlayerId::B);
             for move_b in expected_b_moves.iter() {
                 println!("{move_b}");
-                assert!(actual_b_moves.contains(&move_b));
+                assert!(actual_b_moves.contains(move_b));
             }
         }
     }
 
     #[test]
     pub fn checker_fires_for()  {
-        let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-        let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
         let mut game = Game::new(&mut player_a, &mut player_b);
 
         {
-            let fireable_positions = vec![Vec2::new(5, 2), Vec2::new(5, 3), Vec2::new(5, 5)];
+            let fireable_positions = [Vec2::new(5, 2), Vec2::new(5, 3), Vec2::new(5, 5)];
             for pos in fireable_positions.iter() {
-                game.board.place_checker_at(*pos, Checker::new(1, PLAYER_B_ID)).unwrap();
+                game.board.place_checker_at(*pos, Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
             }
 
             let expected = fireable_positions.iter().map(|pos| Intent::FireChecker(*pos)).collect::<Vec<Intent>>();
-            let actual = game.checker_fires_for(PLAYER_A_ID);
+            let actual = game.checker_fires_for(PlayerId::A);
 
             println!("Actual moves for Player A:");
             for move_a in actual.iter() {
@@ -793,13 +2603,13 @@ mod test {
         }
         game.reset();
         {
-            let fireable_positions = vec![Vec2::new(3, 2), Vec2::new(3, 3), Vec2::new(3, 5)];
+            let fireable_positions = [Vec2::new(3, 2), Vec2::new(3, 3), Vec2::new(3, 5)];
             for pos in fireable_positions.iter() {
-                game.board.place_checker_at(*pos, Checker::new(1, PLAYER_A_ID)).unwrap();
+                game.board.place_checker_at(*pos, Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
             }
 
             let expected = fireable_positions.iter().map(|pos| Intent::FireChecker(*pos)).collect::<Vec<Intent>>();
-            let actual = game.checker_fires_for(PLAYER_B_ID);
+            let actual = game.checker_fires_for(PlayerId::B);
             
             println!("Actual moves for Player B:");
             for move_b in actual.iter() {
@@ -814,29 +2624,46 @@ mod test {
 
     #[test]
     pub fn stone_places_for() {
-        let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-        let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
         let game = Game::new(&mut player_a, &mut player_b);
         
-        for player in vec![PLAYER_A_ID, PLAYER_B_ID] {
+        for player in [PlayerId::A, PlayerId::B] {
             // The expected number is 37, because the 6 checkers on each side border 2*13 unique squares, and 63 - 26 = 37
             assert_eq!(game.stone_places_for(player).len(), 37);
         }
     }
     
+    #[test]
+    pub fn stone_places_with_empty_pool() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let config = GameConfig::default()
+            .with_stone_pool(PlayerId::A, 1)
+            .with_stone_pool(PlayerId::B, 24);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        assert_eq!(game.view().stones, [1, 24]);
+
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(0, 0)));
+        assert!(game.stone_places_for(PlayerId::A).is_empty());
+        assert!(game.move_request(PlayerId::A).place_stones.is_empty());
+        assert_eq!(game.stone_places_for(PlayerId::B).len(), 36);
+
+        game.reset();
+        assert_eq!(game.stone_places_for(PlayerId::A).len(), 37);
+    }
+
     #[test]
     pub fn stone_slides_for() {
-        let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-        let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
         let mut game = Game::new(&mut player_a, &mut player_b);
 
         let stone_location = Vec2::new(0, 0);
-        game.apply_move(PLAYER_A_ID, Intent::PlaceStone(stone_location));
+        game.apply_move(PlayerId::A, Intent::PlaceStone(stone_location));
 
-        let expected = vec![
-            Intent::SlideStone(stone_location, Direction::Down), Intent::SlideStone(stone_location, Direction::Right),    
-        ];
-        let actual = game.stone_slides_for(PLAYER_A_ID);
+        let expected = [Intent::SlideStone(stone_location, Direction::Down), Intent::SlideStone(stone_location, Direction::Right)];
+        let actual = game.stone_slides_for(PlayerId::A);
         for move_actual in actual.iter() {
             println!("{move_actual}");
         }
@@ -847,8 +2674,8 @@ mod test {
 
     #[test]
     pub fn apply_move() {
-        let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-        let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
 
         // Moving a checker
         let mut game = Game::new(&mut player_a, &mut player_b);
@@ -857,69 +2684,877 @@ mod test {
         let to_position = Vec2::new(7, 0);
         
         println!("{}", &game.board);
-        game.apply_move(PLAYER_A_ID, Intent::MoveChecker(from_position, to_position)); 
+        game.apply_move(PlayerId::A, Intent::MoveChecker(from_position, to_position)); 
         println!("{}", &game.board);
 
-        assert_eq!(game.board.checker_at(to_position).unwrap().owner, PLAYER_A_ID);
-        assert_eq!(game.board.checker_at(from_position).unwrap().owner, EMPTY_PLAYER_ID);
+        assert_eq!(game.board.checker_at(to_position).unwrap().owner, Some(PlayerId::A));
+        assert_eq!(game.board.checker_at(from_position).unwrap().owner, None);
         
         // Placing a stone
         game.reset();
         let stone_position = Vec2::new(4, 4);
-        game.apply_move(PLAYER_A_ID, Intent::PlaceStone(stone_position));
-        assert_eq!(game.board.stone_at(stone_position).unwrap().owner, PLAYER_A_ID);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(stone_position));
+        assert_eq!(game.board.stone_at(stone_position).unwrap().owner, Some(PlayerId::A));
         assert_eq!(game.players[0].stones, STARTING_STONES - 1);
 
         // Sliding a stone
-        game.apply_move(PLAYER_A_ID, Intent::SlideStone(stone_position, Direction::Up));
-        assert_eq!(game.board.stone_at(stone_position).unwrap().owner, EMPTY_PLAYER_ID);
-        assert_eq!(game.board.stone_at(Vec2::new(4, 0)).unwrap().owner, PLAYER_A_ID);
+        game.apply_move(PlayerId::A, Intent::SlideStone(stone_position, Direction::Up));
+        assert_eq!(game.board.stone_at(stone_position).unwrap().owner, None);
+        assert_eq!(game.board.stone_at(Vec2::new(4, 0)).unwrap().owner, Some(PlayerId::A));
 
         // Firing at a checker
         let fire_position = Vec2::new(2, 2);
-        game.board.place_checker_at(fire_position, Checker::new(1, PLAYER_A_ID)).unwrap();
-        game.apply_move(PLAYER_B_ID, Intent::FireChecker(fire_position));
-        assert_eq!(game.board.checker_at(fire_position).unwrap().owner, EMPTY_PLAYER_ID);
+        game.board.place_checker_at(fire_position, Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
+        game.apply_move(PlayerId::B, Intent::FireChecker(fire_position));
+        assert_eq!(game.board.checker_at(fire_position).unwrap().owner, None);
+    }
+
+    #[test]
+    pub fn try_apply_move() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+
+        // Moving onto an occupied square is refused without touching the game
+        let blocked = Intent::MoveChecker(Vec2::new(7, 1), Vec2::new(7, 2));
+        assert_eq!(game.try_apply_move(PlayerId::A, blocked), Err(ApplyError::IllegalMove { player: PlayerId::A, intent: blocked }));
+        assert!(game.history.is_empty());
+
+        assert_eq!(game.try_apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 4))), Ok(()));
+        assert_eq!(game.board.stone_at(Vec2::new(4, 4)).unwrap().owner, Some(PlayerId::A));
     }
 
     #[test]
     pub fn reset() {
-        let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-        let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
         let mut game = Game::new(&mut player_a, &mut player_b);
 
         // Place some stones for a, b
-        game.apply_move(PLAYER_A_ID, Intent::PlaceStone(Vec2::new(4, 4)));
-        game.apply_move(PLAYER_B_ID, Intent::PlaceStone(Vec2::new(4, 3)));
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 4)));
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 3)));
         // Place checkers for a, b
-        game.apply_move(PLAYER_A_ID, Intent::MoveChecker(Vec2::new(7, 1), Vec2::new(7, 0)));
-        game.apply_move(PLAYER_B_ID, Intent::MoveChecker(Vec2::new(0, 1), Vec2::new(0, 0)));
+        game.apply_move(PlayerId::A, Intent::MoveChecker(Vec2::new(7, 1), Vec2::new(7, 0)));
+        game.apply_move(PlayerId::B, Intent::MoveChecker(Vec2::new(0, 1), Vec2::new(0, 0)));
         // reset
         game.reset();
         // Assert that stone_counters reset, stones not on board
-        assert_eq!(game.board.stone_at(Vec2::new(4, 4)).unwrap().owner, EMPTY_PLAYER_ID);
-        assert_eq!(game.board.stone_at(Vec2::new(4, 3)).unwrap().owner, EMPTY_PLAYER_ID);
+        assert_eq!(game.board.stone_at(Vec2::new(4, 4)).unwrap().owner, None);
+        assert_eq!(game.board.stone_at(Vec2::new(4, 3)).unwrap().owner, None);
         assert_eq!(game.players[0].stones, STARTING_STONES);
         assert_eq!(game.players[1].stones, STARTING_STONES);
 
         // Assert that checkers not on board.
-        assert_eq!(game.board.checker_at(Vec2::new(7, 0)).unwrap().owner, EMPTY_PLAYER_ID);
-        assert_eq!(game.board.checker_at(Vec2::new(0, 0)).unwrap().owner, EMPTY_PLAYER_ID);
+        assert_eq!(game.board.checker_at(Vec2::new(7, 0)).unwrap().owner, None);
+        assert_eq!(game.board.checker_at(Vec2::new(0, 0)).unwrap().owner, None);
 
         // Assert that last moves are empty
-        for i in 0..2 {
-            assert_eq!(game.last_two_slides_a[i], None);
-            assert_eq!(game.last_two_slides_b[i], None);
+        assert_eq!(game.last_two_slides_a, [None; 2]);
+        assert_eq!(game.last_two_slides_b, [None; 2]);
+
+        // The first move passes to B from the next game, and is recorded
+        game.set_first_player(PlayerId::B);
+        game.reset();
+        assert_eq!(game.side_to_move(), PlayerId::B);
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 4)));
+        assert_eq!(game.side_to_move(), PlayerId::A);
+        assert_eq!(crate::record::GameRecord::from_game(&game).first, PlayerId::B);
+    }
+
+    #[test]
+    pub fn players_in_either_order() {
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, 5);
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let config = GameConfig { first_player: PlayerId::B, ..GameConfig::default() };
+        let game = Game::build(&mut player_b, &mut player_a, config);
+        assert_eq!((game.players[0].id, game.players[1].id), (PlayerId::A, PlayerId::B));
+        assert_eq!(game.view().stones, [STARTING_STONES, 5]);
+        assert_eq!(game.side_to_move(), PlayerId::B);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn players_on_one_side() {
+        let mut first = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut second = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        Game::new(&mut first, &mut second);
+    }
+
+    #[test]
+    pub fn circularity_does_not_touch_board() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+
+        let start = Vec2::new(4, 0);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(start));
+        game.apply_move(PlayerId::A, Intent::SlideStone(start, Direction::Down));
+        game.apply_move(PlayerId::A, Intent::SlideStone(Vec2::new(4, 6), Direction::Left));
+        let before = game.board.as_string();
+        assert_eq!(game.check_for_win(), None);
+        assert_eq!(game.board.as_string(), before);
+    }
+
+    #[test]
+    pub fn rules_config() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let rules = RulesConfig { negation: false, circularity: false, fire_range: 1, ..RulesConfig::default() };
+        let config = GameConfig { rules, ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        assert_eq!(game.board.rules(), rules);
+
+        // Without negation every empty corner can take a stone, even beside a checker
+        assert_eq!(game.stone_places_for(PlayerId::A).len(), 63);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(7, 2)));
+
+        // Without circularity sliding straight back is allowed
+        let start = Vec2::new(4, 0);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(start));
+        game.apply_move(PlayerId::A, Intent::SlideStone(start, Direction::Down));
+        game.apply_move(PlayerId::A, Intent::SlideStone(Vec2::new(4, BOARD_HEIGHT as i32), Direction::Up));
+        assert_eq!(game.check_for_win(), None);
+
+        // A checker two squares away is out of range
+        game.reset();
+        game.board.place_checker_at(Vec2::new(4, 2), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        assert!(game.checker_fires_for(PlayerId::A).is_empty());
+        game.board.place_checker_at(Vec2::new(5, 2), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        assert_eq!(game.checker_fires_for(PlayerId::A), vec![Intent::FireChecker(Vec2::new(5, 2))]);
+    }
+
+    #[test]
+    pub fn elimination() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let rules = RulesConfig { elimination: true, ..RulesConfig::default() };
+        let config = GameConfig { rules, ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        assert_eq!(game.check_for_outcome(), None);
+
+        // Leave player B with one checker of height one, beside a player A checker
+        for pos in game.board.checkers_for_player(PlayerId::B) {
+            game.board.place_checker_at(pos, Checker::empty()).unwrap();
+        }
+        let target = Vec2::new(5, 2);
+        game.board.place_checker_at(target, Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        // With no stones around it every roll hits, so one attack destroys it
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.set_silent(true);
+        game.add_observer(Box::new(Recorder(events.clone())));
+        game.apply_move(PlayerId::A, Intent::FireChecker(target));
+        let Some(GameEvent::DiceRolled { player, combat }) = events.borrow().last().cloned() else {
+            panic!("no dice were rolled");
+        };
+        assert_eq!((player, combat.target), (PlayerId::A, target));
+        assert_eq!((combat.rolls.len(), combat.damage), (combat.attackers.len(), combat.attackers.len()));
+        assert_eq!(game.captures_for(PlayerId::A), 1);
+        assert_eq!(game.captures_for(PlayerId::B), 0);
+        assert!(game.view().status_line(PlayerId::B).ends_with(", 1 checker lost"));
+        assert_eq!(
+            game.check_for_outcome(),
+            Some(GameOutcome::Win { winner: PlayerId::A, reason: WinReason::Elimination })
+        );
+
+        game.reset();
+        assert_eq!(game.captures_for(PlayerId::A), 0);
+
+        // Off by default
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        for pos in game.board.checkers_for_player(PlayerId::B) {
+            game.board.place_checker_at(pos, Checker::empty()).unwrap();
+        }
+        assert_eq!(game.check_for_outcome(), None);
+    }
+
+    #[test]
+    pub fn actions_per_turn() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let rules = RulesConfig { actions_per_turn: 2, ..RulesConfig::default() };
+        let config = GameConfig { rules, ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+
+        // After placing a stone, player A may do anything but place another
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 0)));
+        assert!(game.turn_in_progress());
+        let request = game.move_request(PlayerId::A);
+        assert_eq!(request.action, 1);
+        assert!(request.place_stones.is_empty());
+        assert!(!request.move_checkers.is_empty() && !request.slide_stones.is_empty());
+        // Player B's options are unaffected
+        assert!(!game.move_request(PlayerId::B).place_stones.is_empty());
+
+        game.apply_move(PlayerId::A, Intent::SlideStone(Vec2::new(4, 0), Direction::Down));
+        assert!(!game.turn_in_progress());
+        assert_eq!(game.move_request(PlayerId::A).action, 0);
+
+        // Sliding straight back on the next turn is still circular, counted per turn
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(0, 0)));
+        game.apply_move(PlayerId::B, Intent::MoveChecker(Vec2::new(1, 2), Vec2::new(2, 2)));
+        game.apply_move(PlayerId::A, Intent::SlideStone(Vec2::new(4, BOARD_HEIGHT as i32), Direction::Up));
+        assert_eq!(game.check_for_win(), None);
+        game.apply_move(PlayerId::A, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2)));
+        assert_eq!(game.check_for_win(), Some(PlayerId::B));
+    }
+
+    struct Fixed(Intent);
+
+    impl Decide for Fixed {
+        fn choose_move(&self, _request: &MoveRequest) -> Intent {
+            self.0
+        }
+    }
+
+    #[test]
+    pub fn pie_rule() {
+        let first = Fixed(Intent::PlaceStone(Vec2::new(4, 0)));
+        let second = Fixed(Intent::PlaceStone(Vec2::new(0, 0)));
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &first);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &second);
+        let rules = RulesConfig { pie_rule: true, ..RulesConfig::default() };
+        let config = GameConfig { rules, ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+
+        assert!(!game.can_swap_sides(PlayerId::B));
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 0)));
+        assert!(!game.can_swap_sides(PlayerId::A));
+        assert_eq!(game.move_request(PlayerId::B).swap_sides, vec![Intent::SwapSides]);
+        assert!(game.move_request(PlayerId::B).all_moves().any(|intent| *intent == Intent::SwapSides));
+
+        // After swapping, the second decider plays side A and the first plays side B, still to move
+        game.apply_move(PlayerId::B, Intent::SwapSides);
+        assert!(game.sides_swapped());
+        assert!(!game.can_swap_sides(PlayerId::B));
+        let request = game.move_request(PlayerId::B);
+        assert_eq!(game.players[1].choose_move(&request), first.0);
+        assert_eq!(game.players[0].choose_move(&game.move_request(PlayerId::A)), second.0);
+        assert_eq!(game.board.stone_at(Vec2::new(4, 0)).unwrap().owner, Some(PlayerId::A));
+
+        // The chance to swap passes once player B has moved
+        game.reset();
+        assert!(!game.sides_swapped());
+        assert_eq!(game.players[0].choose_move(&game.move_request(PlayerId::A)), first.0);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 0)));
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(0, 0)));
+        assert!(!game.can_swap_sides(PlayerId::B));
+    }
+
+    #[test]
+    pub fn play_turn() {
+        let corner = Intent::PlaceStone(Vec2::new(4, 0));
+        let first = Fixed(corner);
+        let second = Fixed(corner);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &first);
+        let mut player_b = Player::new(PlayerId::B, 0, &second);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+
+        assert_eq!(game.side_to_move(), PlayerId::A);
+        assert_eq!(game.play_turn(), Ok(None));
+        assert_eq!(game.side_to_move(), PlayerId::B);
+        // B repeats a move that is no longer legal, and it is refused rather than applied
+        let refused = ApplyError::IllegalMove { player: PlayerId::B, intent: corner };
+        assert_eq!(game.play(), Err(GameError::Refused(refused)));
+        assert_eq!(game.history().len(), 1);
+
+        // Moves the board refuses come back as errors, leaving the game untouched
+        assert_eq!(game.make_move(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 4))), Err(ApplyError::NoStones(PlayerId::B)));
+        let blocked = Intent::MoveChecker(Vec2::new(0, 1), Vec2::new(0, 2));
+        assert_eq!(game.make_move(PlayerId::B, blocked), Err(ApplyError::Move(MoveError::OccupiedError)));
+        assert_eq!(game.history().len(), 1);
+    }
+
+    #[test]
+    pub fn play_simultaneous() {
+        // Both sides placing on the same corner every turn bounce until the position has repeated
+        let corner = Fixed(Intent::PlaceStone(Vec2::new(4, 0)));
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &corner);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &corner);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.set_silent(true);
+        assert_eq!(game.play_simultaneous(), Ok(GameOutcome::Draw(DrawReason::Repetition)));
+        assert!(game.history().is_empty());
+
+        // Without a repetition limit, the bounced moves run into the move limit
+        let config = GameConfig { repetition_limit: None, move_limit: Some(10), ..GameConfig::default() };
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &corner);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &corner);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        game.set_silent(true);
+        assert_eq!(game.play_simultaneous(), Ok(GameOutcome::Draw(DrawReason::MoveLimit)));
+        assert_eq!(game.board.stone_at(Vec2::new(4, 0)).unwrap().owner, None);
+    }
+
+    /* Decider that always picks the first move offered. */
+    struct FirstOffered;
+
+    impl Decide for FirstOffered {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            *request.all_moves().next().unwrap()
+        }
+    }
+
+    /* Observer that keeps every event it is sent. */
+    struct Recorder(Rc<RefCell<Vec<GameEvent>>>);
+
+    impl Observer for Recorder {
+        fn on_event(&mut self, event: &GameEvent, _view: &GameView) {
+            self.0.borrow_mut().push(event.clone());
+        }
+    }
+
+    #[test]
+    pub fn observers() {
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.set_silent(true);
+        game.add_observer(Box::new(Recorder(events.clone())));
+
+        game.play_turn().unwrap();
+        game.play_turn().unwrap();
+        let moves: Vec<(PlayerId, Intent)> = game.history().to_vec();
+        assert_eq!(*events.borrow(), vec![
+            GameEvent::TurnStarted { player: PlayerId::A },
+            GameEvent::MoveChosen { player: moves[0].0, intent: moves[0].1 },
+            GameEvent::MoveMade { player: moves[0].0, intent: moves[0].1 },
+            GameEvent::TurnStarted { player: PlayerId::B },
+            GameEvent::MoveChosen { player: moves[1].0, intent: moves[1].1 },
+            GameEvent::MoveMade { player: moves[1].0, intent: moves[1].1 },
+        ]);
+    }
+
+    #[test]
+    pub fn stones_exhausted() {
+        let corner = Intent::PlaceStone(Vec2::new(4, 0));
+        let mut player_a = Player::new(PlayerId::A, 1, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.set_silent(true);
+        game.add_observer(Box::new(Recorder(events.clone())));
+
+        assert!(game.stone_places_for(PlayerId::A).contains(&corner));
+        game.make_move(PlayerId::A, corner).unwrap();
+        assert_eq!(*events.borrow(), vec![
+            GameEvent::MoveMade { player: PlayerId::A, intent: corner },
+            GameEvent::StonesExhausted { player: PlayerId::A },
+        ]);
+        // With the pool empty, no places are offered and placing is refused
+        assert!(game.stone_places_for(PlayerId::A).is_empty());
+        assert!(game.move_request(PlayerId::A).place_stones.is_empty());
+        let other = Intent::PlaceStone(Vec2::new(0, 0));
+        assert_eq!(game.make_move(PlayerId::A, other), Err(ApplyError::NoStones(PlayerId::A)));
+
+        // Taking the stone back refills the pool
+        assert_eq!(game.take_back(PlayerId::A).len(), 1);
+        assert_eq!(game.stone_places_for(PlayerId::A).len(), game.stone_places_for(PlayerId::B).len());
+    }
+
+    #[test]
+    pub fn clocks() {
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let control = TimeControl {
+            initial: Duration::from_secs(60),
+            increment: Duration::from_secs(5),
+            warning: Duration::from_secs(30),
+        };
+        let config = GameConfig { time_control: Some(control), ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.set_silent(true);
+        game.add_observer(Box::new(Recorder(events.clone())));
+        assert_eq!(game.move_request(PlayerId::A).clock, Some(ClockInfo { remaining: control.initial, increment: control.increment }));
+
+        // A turn takes its thinking time off the clock and adds the increment
+        game.play_turn().unwrap();
+        let left = game.clock(PlayerId::A).unwrap();
+        assert!(left > control.initial && left <= control.initial + control.increment);
+        assert_eq!(game.view().clocks, Some([left, control.initial]));
+
+        // The warning is sent once, until the clock is back above it
+        game.charge_clock(PlayerId::B, Duration::from_secs(40));
+        game.charge_clock(PlayerId::B, Duration::from_secs(1));
+        let warning = GameEvent::LowTime { player: PlayerId::B, remaining: Duration::from_secs(20) };
+        assert_eq!(events.borrow().iter().filter(|event| matches!(event, GameEvent::LowTime { .. })).count(), 1);
+        assert!(events.borrow().contains(&warning));
+        game.charge_clock(PlayerId::B, Duration::from_secs(100));
+        assert_eq!(game.clock(PlayerId::B), Some(Duration::ZERO));
+
+        // Clocks set below the warning, as when resuming a game, count as already warned
+        game.set_clocks([Duration::from_secs(10), Duration::from_secs(50)]);
+        assert_eq!(game.clock(PlayerId::A), Some(Duration::from_secs(10)));
+        game.charge_clock(PlayerId::A, Duration::from_secs(1));
+        assert_eq!(events.borrow().iter().filter(|event| matches!(event, GameEvent::LowTime { .. })).count(), 1);
+
+        game.reset();
+        assert_eq!(game.clock(PlayerId::B), Some(control.initial));
+        assert_eq!(clock_text(Duration::from_millis(65_900)), "1:05");
+    }
+
+    #[test]
+    pub fn arbiter() {
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.set_silent(true);
+        game.add_observer(Box::new(Recorder(events.clone())));
+
+        let outcome = game.adjudicate(Some(PlayerId::B));
+        assert_eq!(outcome, GameOutcome::Win { winner: PlayerId::B, reason: WinReason::Arbiter });
+        assert_eq!(game.check_for_outcome(), Some(outcome));
+        assert_eq!(events.borrow().last(), Some(&GameEvent::GameOver(outcome)));
+
+        game.reset();
+        assert_eq!(game.check_for_outcome(), None);
+        game.abort();
+        assert!(game.aborted());
+        assert_eq!(game.play_turn(), Err(GameError::Aborted));
+        assert_eq!(events.borrow().last(), Some(&GameEvent::Aborted));
+        game.reset();
+        assert!(game.play_turn().is_ok());
+    }
+
+    #[test]
+    pub fn undo_move() {
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.set_silent(true);
+        assert_eq!(game.undo_move(), None);
+        assert!(game.take_back(PlayerId::A).is_empty());
+
+        let start = game.board.position_hash();
+        let stones = game.players[0].stones;
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 0)));
+        let after_a = game.board.position_hash();
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(2, 0)));
+        assert_eq!(game.undo_move(), Some((PlayerId::B, Intent::PlaceStone(Vec2::new(2, 0)))));
+        assert_eq!(game.board.position_hash(), after_a);
+        assert_eq!(game.side_to_move(), PlayerId::B);
+
+        // Taking back A's turn also takes back B's reply
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(2, 0)));
+        assert_eq!(game.take_back(PlayerId::A).len(), 2);
+        assert_eq!(game.board.position_hash(), start);
+        assert_eq!(game.players[0].stones, stones);
+        assert!(game.history().is_empty());
+        assert_eq!(game.side_to_move(), PlayerId::A);
+
+        // Refused moves leave nothing to undo
+        assert!(game.make_move(PlayerId::A, Intent::PlaceStone(Vec2::new(99, 99))).is_err());
+        assert_eq!(game.undo_move(), None);
+    }
+
+    /* Decider that asks for one takeback once it has moved, then picks the first move offered. */
+    struct TakesBackOnce(std::cell::Cell<bool>);
+
+    impl Decide for TakesBackOnce {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            *request.all_moves().next().unwrap()
+        }
+
+        fn decide(&self, request: &MoveRequest) -> Decision {
+            if !request.view.history.is_empty() && !self.0.replace(true) {
+                return Decision::TakeBack;
+            }
+            Decision::Move(self.choose_move(request))
+        }
+    }
+
+    /* Decider that never agrees to a takeback. */
+    struct Refuses;
+
+    impl Decide for Refuses {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            *request.all_moves().next().unwrap()
+        }
+
+        fn accept_takeback(&self, _player: PlayerId) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    pub fn takeback_in_play() {
+        let asks = TakesBackOnce(std::cell::Cell::new(false));
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &asks);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.set_silent(true);
+        game.add_observer(Box::new(Recorder(events.clone())));
+
+        game.play_turn().unwrap();
+        game.play_turn().unwrap();
+        // A takes back the round, then plays its move again
+        game.play_turn().unwrap();
+        assert!(events.borrow().contains(&GameEvent::TakenBack { player: PlayerId::A, moves: 2 }));
+        assert_eq!(game.history().len(), 1);
+        assert_eq!(game.side_to_move(), PlayerId::B);
+
+        let asks = TakesBackOnce(std::cell::Cell::new(false));
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &asks);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &Refuses);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.set_silent(true);
+        game.add_observer(Box::new(Recorder(events.clone())));
+        game.play_turn().unwrap();
+        game.play_turn().unwrap();
+        game.play_turn().unwrap();
+        assert!(events.borrow().contains(&GameEvent::TakeBackRefused { player: PlayerId::A }));
+        assert_eq!(game.history().len(), 3);
+    }
+
+    /* Decider that quits as soon as it is asked to move. */
+    struct Quits;
+
+    impl Decide for Quits {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            *request.all_moves().next().unwrap()
+        }
+
+        fn decide(&self, _request: &MoveRequest) -> Decision {
+            Decision::Quit
+        }
+    }
+
+    #[test]
+    pub fn quit_in_play() {
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &Quits);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.set_silent(true);
+        // Quitting hands the game back unfinished, as it was left
+        assert_eq!(game.play(), Err(GameError::Quit(PlayerId::B)));
+        assert_eq!(game.history().len(), 1);
+        assert_eq!(game.side_to_move(), PlayerId::B);
+    }
+
+    #[test]
+    pub fn hill() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let config = GameConfig {
+            hill: Some(HillConfig::centre(Variant::Standard, 2)),
+            ..GameConfig::default()
+        };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+
+        // Player A steps onto the hill, then has to hold it for a second turn
+        game.apply_move(PlayerId::A, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2)));
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(2, 0)));
+        game.apply_move(PlayerId::A, Intent::MoveChecker(Vec2::new(5, 2), Vec2::new(4, 2)));
+        assert_eq!(game.check_for_outcome(), None);
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(3, 0)));
+        assert_eq!(game.check_for_outcome(), None);
+
+        // Stepping off resets the count
+        game.apply_move(PlayerId::A, Intent::MoveChecker(Vec2::new(4, 2), Vec2::new(5, 2)));
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 0)));
+        game.apply_move(PlayerId::A, Intent::MoveChecker(Vec2::new(5, 2), Vec2::new(4, 2)));
+        assert_eq!(game.check_for_outcome(), None);
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(5, 0)));
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(8, 0)));
+        assert_eq!(
+            game.check_for_outcome(),
+            Some(GameOutcome::Win { winner: PlayerId::A, reason: WinReason::Hill })
+        );
+
+        game.reset();
+        assert_eq!(game.check_for_outcome(), None);
+    }
+
+    #[test]
+    pub fn move_limit() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let config = GameConfig { move_limit: Some(2), ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 0)));
+        assert_eq!(game.check_for_outcome(), None);
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(0, 0)));
+        assert_eq!(game.check_for_outcome(), Some(GameOutcome::Draw(DrawReason::MoveLimit)));
+
+        // With adjudication the side ahead on material wins instead
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let config = GameConfig { move_limit: Some(2), adjudication_margin: Some(5), ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        game.board.place_checker_at(Vec2::new(4, 2), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 0)));
+        game.apply_move(PlayerId::B, Intent::FireChecker(Vec2::new(6, 2)));
+        assert_eq!(
+            game.check_for_outcome(),
+            Some(GameOutcome::Win { winner: PlayerId::B, reason: WinReason::Adjudication })
+        );
+    }
+
+    #[test]
+    pub fn terrain() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let config = GameConfig { terrain: vec![(Vec2::new(5, 2), Terrain::Mud)], ..GameConfig::default() };
+        let game = Game::with_config(&mut player_a, &mut player_b, config);
+        let moves = game.checker_moves_for(PlayerId::A);
+        assert!(!moves.contains(&Intent::MoveChecker(Vec2::new(6, 3), Vec2::new(5, 2))));
+        assert!(moves.contains(&Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2))));
+        assert!(moves.contains(&Intent::MoveChecker(Vec2::new(6, 3), Vec2::new(5, 4))));
+    }
+
+    #[test]
+    pub fn from_scenario() {
+        let path = std::env::temp_dir().join(format!("ironclad-scenario-{}.txt", std::process::id()));
+        std::fs::write(&path, "rule elimination on\nstones a 3\nchecker a 5 2 1\nchecker b 3 2 1\n").unwrap();
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::from_scenario(&mut player_a, &mut player_b, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(game.view().stones, [3, STARTING_STONES]);
+        assert_eq!(game.board.checkers_for_player(PlayerId::B), vec![Vec2::new(3, 2)]);
+        game.apply_move(PlayerId::A, Intent::FireChecker(Vec2::new(3, 2)));
+        assert_eq!(
+            game.check_for_outcome(),
+            Some(GameOutcome::Win { winner: PlayerId::A, reason: WinReason::Elimination })
+        );
+
+        // The scenario's position comes back on reset
+        game.reset();
+        assert_eq!(game.board.checkers_for_player(PlayerId::A), vec![Vec2::new(5, 2)]);
+    }
+
+    #[test]
+    pub fn circularity_false_positives() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let start = Vec2::new(4, 0);
+        let bottom = Vec2::new(4, BOARD_HEIGHT as i32);
+
+        // Sliding back after the path was blocked does not return the stone to its start
+        game.apply_move(PlayerId::A, Intent::PlaceStone(start));
+        game.apply_move(PlayerId::A, Intent::SlideStone(start, Direction::Down));
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(4, 2)));
+        game.apply_move(PlayerId::A, Intent::SlideStone(bottom, Direction::Up));
+        assert_eq!(game.board.stone_at(Vec2::new(4, 3)).unwrap().owner, Some(PlayerId::A));
+        assert_eq!(game.check_for_win(), None);
+
+        // A different move in between breaks up the pair of slides
+        game.reset();
+        game.apply_move(PlayerId::A, Intent::PlaceStone(start));
+        game.apply_move(PlayerId::A, Intent::SlideStone(start, Direction::Down));
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(3, 3)));
+        game.apply_move(PlayerId::A, Intent::SlideStone(bottom, Direction::Up));
+        assert_eq!(game.check_for_win(), None);
+
+        // The opponent's slides are tracked separately
+        game.reset();
+        game.apply_move(PlayerId::A, Intent::PlaceStone(start));
+        game.apply_move(PlayerId::B, Intent::PlaceStone(Vec2::new(2, 0)));
+        game.apply_move(PlayerId::A, Intent::SlideStone(start, Direction::Down));
+        game.apply_move(PlayerId::B, Intent::SlideStone(Vec2::new(2, 0), Direction::Down));
+        assert_eq!(game.check_for_win(), None);
+    }
+
+    #[test]
+    pub fn slide_record_is_circular() {
+        let a = Vec2::new(1, 1);
+        let b = Vec2::new(1, 5);
+        let c = Vec2::new(3, 5);
+        assert!(SlideRecord::is_circular(&[Some(SlideRecord { from: a, to: b }), Some(SlideRecord { from: b, to: a })]));
+        assert!(!SlideRecord::is_circular(&[Some(SlideRecord { from: a, to: b }), Some(SlideRecord { from: b, to: c })]));
+        assert!(!SlideRecord::is_circular(&[None, Some(SlideRecord { from: b, to: a })]));
+        assert!(!SlideRecord::is_circular(&[Some(SlideRecord { from: a, to: b }), None]));
+    }
+
+    #[test]
+    pub fn view() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+
+        let stone_position = Vec2::new(4, 4);
+        game.apply_move(PlayerId::B, Intent::PlaceStone(stone_position));
+
+        let view = game.view();
+        assert_eq!(view.stones_for(PlayerId::A), STARTING_STONES);
+        assert_eq!(view.stones_for(PlayerId::B), STARTING_STONES - 1);
+        assert_eq!(view.status_line(PlayerId::B), format!("Player B: {} stones in pool, 1 on board, 0 checkers lost", STARTING_STONES - 1));
+        assert!(view.to_string().starts_with(&game.board.to_string()));
+        assert!(view.to_string().contains(&format!("{}\n", view.status_line(PlayerId::B))));
+        assert!(view.to_string().lines().last().unwrap().starts_with("Progress: Player A ["));
+        assert_eq!(view.history, &[(PlayerId::B, Intent::PlaceStone(stone_position))]);
+        assert_eq!(view.board.stone_at(stone_position).unwrap().owner, Some(PlayerId::B));
+
+        game.reset();
+        assert!(game.history().is_empty());
+    }
+
+    #[test]
+    fn threat_map() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        let map = game.threat_map(PlayerId::A);
+        assert_eq!((map.width(), map.height()), (game.board.width(), game.board.height()));
+        for (pos, threat) in map.iter() {
+            let attackers = game.board.attackers_by(pos, PlayerId::B);
+            assert_eq!(*threat, attackers as f32 * game.board.hit_chance(pos));
+        }
+        // With a hill and a stone on a corner the die must roll two or more, so one roll in six misses
+        let request = game.move_request(PlayerId::A);
+        let (pos, threat, corner) = map.iter()
+            .filter(|(_, threat)| **threat > 0.0)
+            .find_map(|(pos, threat)| game.board.stone_neighbours_of_checker(pos)
+                .find(|corner| request.place_stones.contains(&Intent::PlaceStone(*corner)))
+                .map(|corner| (pos, *threat, corner)))
+            .unwrap();
+        drop(request);
+        game.board.set_terrain(pos, Terrain::Hill).unwrap();
+        game.apply_move(PlayerId::A, Intent::PlaceStone(corner));
+        let attackers = game.board.attackers_by(pos, PlayerId::B) as f32;
+        assert_eq!(game.threat_map(PlayerId::A)[pos], threat - attackers / 6.0);
+    }
+
+    /* Decider that picks a place move on a square next to the opponent's last stone. */
+    struct FollowLastStone;
+
+    impl Decide for FollowLastStone {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            if let Some((_, Intent::PlaceStone(at))) = request.view.history.last() {
+                let target = Intent::PlaceStone(at.down());
+                if request.place_stones.contains(&target) && request.view.board.stone_at(at.down()).is_ok() {
+                    return target;
+                }
+            }
+            request.place_stones[0]
+        }
+    }
+
+    #[test]
+    pub fn move_request() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(Vec2::new(4, 4)));
+
+        let request = game.move_request(PlayerId::B);
+        assert_eq!(request.player, PlayerId::B);
+        assert_eq!(request.turn, 1);
+        assert_eq!(request.clock, None);
+        assert_eq!(request.move_checkers, game.checker_moves_for(PlayerId::B));
+        assert_eq!(request.place_stones, game.stone_places_for(PlayerId::B));
+        let total = request.move_checkers.len() + request.fire_checkers.len()
+            + request.place_stones.len() + request.slide_stones.len();
+        assert_eq!(request.all_moves().count(), total);
+
+        let follower = Player::new(PlayerId::B, STARTING_STONES, &FollowLastStone);
+        assert_eq!(follower.choose_move(&request), Intent::PlaceStone(Vec2::new(4, 5)));
+    }
+
+    #[test]
+    pub fn move_request_with() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let game = Game::new(&mut player_a, &mut player_b);
+
+        let request = game.move_request_with(PlayerId::A, MoveBuffers::default());
+        let expected: Vec<Intent> = game.move_request(PlayerId::A).all_moves().copied().collect();
+        assert_eq!(request.all_moves().copied().collect::<Vec<Intent>>(), expected);
+
+        // Reused lists are cleared first and keep their memory
+        let buffers = request.into_buffers();
+        let capacity = buffers.place_stones.capacity();
+        let request = game.move_request_with(PlayerId::B, buffers);
+        assert_eq!(request.place_stones, game.stone_places_for(PlayerId::B));
+        assert_eq!(request.move_checkers, game.checker_moves_for(PlayerId::B));
+        assert!(request.place_stones.capacity() >= capacity);
+    }
+
+    #[test]
+    pub fn selectable_pieces() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+
+        // At the start, every checker has an empty neighbour, and there are no stones.
+        let pieces = game.selectable_pieces(PlayerId::B);
+        assert_eq!(pieces.len(), 6);
+        for checker_position in game.board.checkers_for_player(PlayerId::B) {
+            assert!(pieces.contains(&PieceAt::Checker(checker_position)));
+        }
+
+        // A stone that can slide is selectable, one that is boxed in is not.
+        let boxed = Vec2::new(4, 3);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(boxed));
+        for position in [boxed.up(), boxed.down(), boxed.left(), boxed.right()] {
+            game.apply_move(PlayerId::B, Intent::PlaceStone(position));
+        }
+        assert!(!game.selectable_pieces(PlayerId::A).contains(&PieceAt::Stone(boxed)));
+        assert!(game.selectable_pieces(PlayerId::B).contains(&PieceAt::Stone(boxed.left())));
+    }
+
+    #[test]
+    pub fn checker_moves_by_source() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let game = Game::new(&mut player_a, &mut player_b);
+        let request = game.move_request(PlayerId::B);
+        let sources = request.checker_moves_by_source();
+        // Every checker that can move is listed once, with exactly its own moves
+        assert_eq!(sources.len(), game.selectable_pieces(PlayerId::B).iter().filter(|piece| matches!(piece, PieceAt::Checker(_))).count());
+        for (from, moves) in sources.iter() {
+            assert!(moves.iter().all(|intent| matches!(intent, Intent::MoveChecker(start, _) if start == from)));
         }
+        assert_eq!(sources.iter().map(|(_, moves)| moves.len()).sum::<usize>(), request.move_checkers.len());
+    }
+
+    #[test]
+    pub fn moves_for_piece() {
+        let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+        let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+
+        // Moves for a checker are the matching subset of checker_moves_for
+        let position = Vec2::new(1, 2);
+        let moves = game.moves_for_piece(PieceAt::Checker(position));
+        let expected: Vec<Intent> = game.checker_moves_for(PlayerId::B).into_iter()
+            .filter(|intent| matches!(intent, Intent::MoveChecker(from, _) if *from == position))
+            .collect();
+        assert_eq!(moves, expected);
+
+        // Attacks show up for checkers in range of the target, and only those
+        let target = Vec2::new(3, 2);
+        game.board.place_checker_at(target, Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
+        assert!(game.moves_for_piece(PieceAt::Checker(position)).contains(&Intent::FireChecker(target)));
+        assert!(!game.moves_for_piece(PieceAt::Checker(Vec2::new(0, 4))).contains(&Intent::FireChecker(target)));
+
+        // Stones slide
+        let stone_position = Vec2::new(4, 0);
+        game.apply_move(PlayerId::A, Intent::PlaceStone(stone_position));
+        let slides = game.moves_for_piece(PieceAt::Stone(stone_position));
+        assert_eq!(slides.len(), 3);
+        assert!(slides.contains(&Intent::SlideStone(stone_position, Direction::Down)));
+
+        // Empty squares have no moves
+        assert!(game.moves_for_piece(PieceAt::Checker(Vec2::new(4, 4))).is_empty());
+        assert!(game.moves_for_piece(PieceAt::Stone(Vec2::new(4, 4))).is_empty());
     }
 
     mod player {
-        use crate::game::PlayerFactory;
+        use crate::game::{PlayerFactory, PlayerId};
 
         #[test]
         pub fn reset() {
             // Number of stones should reset
-            let mut player = PlayerFactory::console_player(2, 5);
+            let mut player = PlayerFactory::console_player(PlayerId::B, 5);
             player.get_stone();
             player.get_stone();
             player.reset();
@@ -929,12 +3564,12 @@ mod test {
         #[test]
         pub fn get_stone() {
             let nstones = 5;
-            let id = 2;
+            let id = PlayerId::B;
             let mut player = PlayerFactory::console_player(id, nstones);
             let mut stone_counter = 0;
             while let Some(stone) = player.get_stone() {
                 stone_counter += 1;
-                assert_eq!(stone.owner, id);
+                assert_eq!(stone.owner, Some(id));
             }
             assert_eq!(player.stones, 0);
             assert_eq!(stone_counter, nstones);
@@ -0,0 +1,122 @@
+/**
+ * Grid is a value for every square of a rectangular board, indexed by position, used for
+ * maps laid over the board such as threat maps.
+ */
+use std::ops::{Index, IndexMut};
+
+use crate::vec::Vec2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    // row by row, indexed x + y * width
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /**
+     * new
+     * Create a grid with every cell set to the same value.
+     * width - Number of columns.
+     * height - Number of rows.
+     * fill - Value of every cell.
+     */
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+}
+
+impl<T> Grid<T> {
+    /**
+     * width
+     * ret - Number of columns.
+     */
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /**
+     * height
+     * ret - Number of rows.
+     */
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /**
+     * get
+     * pos - Cell to look at.
+     * ret - The cell's value, or None if pos is off the grid.
+     */
+    pub fn get(&self, pos: Vec2) -> Option<&T> {
+        self.index_of(pos).map(|idx| &self.cells[idx])
+    }
+
+    /**
+     * get_mut
+     * pos - Cell to change.
+     * ret - The cell's value, or None if pos is off the grid.
+     */
+    pub fn get_mut(&mut self, pos: Vec2) -> Option<&mut T> {
+        self.index_of(pos).map(|idx| &mut self.cells[idx])
+    }
+
+    /**
+     * iter
+     * ret - Every cell with its position, row by row.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2, &T)> + '_ {
+        self.cells.iter().enumerate().map(|(idx, cell)| (self.position_of(idx), cell))
+    }
+
+    /**
+     * positions
+     * ret - Every position on the grid, row by row.
+     */
+    pub fn positions(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.cells.len()).map(|idx| self.position_of(idx))
+    }
+
+    fn index_of(&self, pos: Vec2) -> Option<usize> {
+        let in_bounds = pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height;
+        in_bounds.then(|| pos.x as usize + pos.y as usize * self.width)
+    }
+
+    fn position_of(&self, idx: usize) -> Vec2 {
+        Vec2::new((idx % self.width) as i32, (idx / self.width) as i32)
+    }
+}
+
+impl<T> Index<Vec2> for Grid<T> {
+    type Output = T;
+
+    /* Panics if pos is off the grid; use get to check. */
+    fn index(&self, pos: Vec2) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("{} is off the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Vec2> for Grid<T> {
+    fn index_mut(&mut self, pos: Vec2) -> &mut T {
+        self.get_mut(pos).unwrap_or_else(|| panic!("{} is off the grid", pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index() {
+        let mut grid = Grid::new(3, 2, 0);
+        grid[Vec2::new(2, 1)] = 5;
+        *grid.get_mut(Vec2::new(0, 1)).unwrap() += 1;
+        assert_eq!(grid.get(Vec2::new(2, 1)), Some(&5));
+        assert_eq!(grid.get(Vec2::new(3, 0)), None);
+        assert_eq!(grid.get(Vec2::new(0, -1)), None);
+        let cells: Vec<(Vec2, i32)> = grid.iter().filter(|(_, cell)| **cell != 0).map(|(pos, cell)| (pos, *cell)).collect();
+        assert_eq!(cells, vec![(Vec2::new(0, 1), 1), (Vec2::new(2, 1), 5)]);
+        assert_eq!(grid.positions().count(), 6);
+    }
+}
@@ -0,0 +1,295 @@
+/**
+ * Board math for graphical front ends: where squares and stone corners are drawn in
+ * pixels, which one a pixel falls on, and the pieces to draw as plain data. A toolkit only
+ * has to implement Painter, drawing each sprite its own way, and send clicks back through
+ * Geometry::hit_test.
+ */
+use crate::board::{Board, Terrain};
+use crate::game::PlayerId;
+use crate::overlay::{Mark, Overlay};
+use crate::vec::Vec2;
+
+// share of a square, around each corner, where a click picks the corner rather than a square
+const CORNER_SHARE: f32 = 0.25;
+
+/**
+ * Point is a position in pixels, x to the right and y down.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+}
+
+/**
+ * Rect is an area in pixels, from its top left corner to its bottom right.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Rect {
+    /**
+     * center
+     * ret - The middle of the area.
+     */
+    pub fn center(&self) -> Point {
+        Point::new((self.min.x + self.max.x) / 2.0, (self.min.y + self.max.y) / 2.0)
+    }
+
+    /**
+     * contains
+     * point - Pixel to check.
+     * ret - True if the point is inside the area or on its edge.
+     */
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+}
+
+/**
+ * Hit is what a pixel falls on.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hit {
+    // a checker square, by its position
+    Square(Vec2),
+    // a stone corner, by its position
+    Corner(Vec2),
+}
+
+/**
+ * Geometry is where a board is drawn: the pixel of its top left corner, and the size of
+ * each square.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometry {
+    pub origin: Point,
+    pub square: f32,
+    width: usize,
+    height: usize,
+}
+
+impl Geometry {
+    /**
+     * fit
+     * Find the largest squares that fit a board into an area, centering it.
+     * board - Board to draw, which sets the number of squares.
+     * area - Pixels the board may take up.
+     */
+    pub fn fit(board: &Board, area: Rect) -> Geometry {
+        let (width, height) = (board.width(), board.height());
+        let square = ((area.max.x - area.min.x) / width as f32).min((area.max.y - area.min.y) / height as f32).max(0.0);
+        let origin = Point::new(
+            area.min.x + (area.max.x - area.min.x - square * width as f32) / 2.0,
+            area.min.y + (area.max.y - area.min.y - square * height as f32) / 2.0,
+        );
+        Geometry { origin, square, width, height }
+    }
+
+    /**
+     * bounds
+     * ret - Area the whole board is drawn in.
+     */
+    pub fn bounds(&self) -> Rect {
+        Rect {
+            min: self.origin,
+            max: Point::new(self.origin.x + self.square * self.width as f32, self.origin.y + self.square * self.height as f32),
+        }
+    }
+
+    /**
+     * square_rect
+     * pos - Position of a checker square.
+     * ret - Area the square is drawn in.
+     */
+    pub fn square_rect(&self, pos: Vec2) -> Rect {
+        let min = self.corner_point(pos);
+        Rect { min, max: Point::new(min.x + self.square, min.y + self.square) }
+    }
+
+    /**
+     * corner_point
+     * pos - Position of a stone corner. Corner (x, y) is the top left of square (x, y).
+     * ret - Pixel at the corner.
+     */
+    pub fn corner_point(&self, pos: Vec2) -> Point {
+        Point::new(self.origin.x + pos.x as f32 * self.square, self.origin.y + pos.y as f32 * self.square)
+    }
+
+    /**
+     * hit_test
+     * Find what a click lands on. Clicks near a corner pick the corner, for placing and
+     * sliding stones, and clicks elsewhere pick the square under them.
+     * point - Pixel clicked.
+     * ret - The corner or square, or None if the click is off the board.
+     */
+    pub fn hit_test(&self, point: Point) -> Option<Hit> {
+        if self.square <= 0.0 {
+            return None;
+        }
+        let x = (point.x - self.origin.x) / self.square;
+        let y = (point.y - self.origin.y) / self.square;
+        let corner = Vec2::new(x.round() as i32, y.round() as i32);
+        let near_corner = (x - x.round()).abs() <= CORNER_SHARE && (y - y.round()).abs() <= CORNER_SHARE;
+        if near_corner && (0..=self.width as i32).contains(&corner.x) && (0..=self.height as i32).contains(&corner.y) {
+            return Some(Hit::Corner(corner));
+        }
+        let square = Vec2::new(x.floor() as i32, y.floor() as i32);
+        if (0..self.width as i32).contains(&square.x) && (0..self.height as i32).contains(&square.y) {
+            Some(Hit::Square(square))
+        } else {
+            None
+        }
+    }
+}
+
+/**
+ * Piece is what a sprite shows.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Piece {
+    // a checker square and its terrain
+    Square(Terrain),
+    Checker { owner: PlayerId, height: usize },
+    Stone { owner: PlayerId },
+}
+
+/**
+ * Sprite is one thing to draw: a piece, where it goes, and any mark laid over it.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprite {
+    pub piece: Piece,
+    // board position of the square or corner
+    pub at: Vec2,
+    // area to draw in; stones get a square a third of a board square across
+    pub rect: Rect,
+    pub mark: Option<Mark>,
+}
+
+/**
+ * sprites
+ * List everything to draw for a board, in drawing order: squares, then checkers, then
+ * stones, so stones sit on top of the squares they touch.
+ * board - Board to draw.
+ * overlay - Marks to lay over the pieces, if any.
+ * geometry - Where the board is drawn.
+ * ret - The sprites.
+ */
+pub fn sprites(board: &Board, overlay: Option<&Overlay>, geometry: &Geometry) -> Vec<Sprite> {
+    let mut sprites = Vec::new();
+    for y in 0..board.height() as i32 {
+        for x in 0..board.width() as i32 {
+            let at = Vec2::new(x, y);
+            let piece = Piece::Square(board.terrain_at(at));
+            sprites.push(Sprite { piece, at, rect: geometry.square_rect(at), mark: None });
+        }
+    }
+    for (at, checker) in board.checkers() {
+        let Some(owner) = checker.owner else { continue };
+        let piece = Piece::Checker { owner, height: checker.height.get() };
+        let mark = overlay.and_then(|overlay| overlay.checker_mark(at));
+        sprites.push(Sprite { piece, at, rect: geometry.square_rect(at), mark });
+    }
+    let half = geometry.square / 6.0;
+    for (at, stone) in board.stones() {
+        let Some(owner) = stone.owner else { continue };
+        let center = geometry.corner_point(at);
+        let rect = Rect { min: Point::new(center.x - half, center.y - half), max: Point::new(center.x + half, center.y + half) };
+        let mark = overlay.and_then(|overlay| overlay.stone_mark(at));
+        sprites.push(Sprite { piece: Piece::Stone { owner }, at, rect, mark });
+    }
+    sprites
+}
+
+pub trait Painter {
+    /**
+     * paint_sprite
+     * Draw one sprite, in the toolkit's own way.
+     * sprite - What to draw and where.
+     */
+    fn paint_sprite(&mut self, sprite: &Sprite);
+}
+
+/**
+ * paint
+ * Draw a board with a painter, sprite by sprite in drawing order.
+ * board - Board to draw.
+ * overlay - Marks to lay over the pieces, if any.
+ * geometry - Where the board is drawn.
+ * painter - Toolkit adapter doing the drawing.
+ */
+pub fn paint<P: Painter + ?Sized>(board: &Board, overlay: Option<&Overlay>, geometry: &Geometry, painter: &mut P) {
+    for sprite in sprites(board, overlay, geometry) {
+        painter.paint_sprite(&sprite);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Checker, CheckerHeight, Stone};
+
+    fn area(width: f32, height: f32) -> Rect {
+        Rect { min: Point::new(0.0, 0.0), max: Point::new(width, height) }
+    }
+
+    #[test]
+    fn geometry() {
+        let board = Board::new();
+        let (width, height) = (board.width() as f32, board.height() as f32);
+        // A wide area leaves space either side of the board
+        let geometry = Geometry::fit(&board, area(width * 40.0 + 100.0, height * 40.0));
+        assert_eq!(geometry.square, 40.0);
+        assert_eq!(geometry.origin, Point::new(50.0, 0.0));
+        assert_eq!(geometry.square_rect(Vec2::new(1, 2)), Rect { min: Point::new(90.0, 80.0), max: Point::new(130.0, 120.0) });
+        assert_eq!(geometry.bounds().max, Point::new(50.0 + width * 40.0, height * 40.0));
+
+        assert_eq!(geometry.hit_test(Point::new(110.0, 100.0)), Some(Hit::Square(Vec2::new(1, 2))));
+        assert_eq!(geometry.hit_test(Point::new(92.0, 83.0)), Some(Hit::Corner(Vec2::new(1, 2))));
+        assert_eq!(geometry.hit_test(Point::new(48.0, 1.0)), Some(Hit::Corner(Vec2::new(0, 0))));
+        assert_eq!(geometry.hit_test(Point::new(20.0, 20.0)), None);
+        assert_eq!(geometry.hit_test(Point::new(60.0, height * 40.0 + 20.0)), None);
+    }
+
+    #[test]
+    fn sprites() {
+        let mut board = Board::new();
+        board.clear_checkers();
+        board.place_checker_at(Vec2::new(2, 1), Checker::new(CheckerHeight::TWO, PlayerId::B)).unwrap();
+        board.place_stone_at(Vec2::new(3, 3), Stone::new(PlayerId::A)).unwrap();
+        let mut overlay = Overlay::new(&board);
+        overlay.mark_checker(Vec2::new(2, 1), Some(Mark::Selected));
+        let geometry = Geometry::fit(&board, area(board.width() as f32 * 30.0, board.height() as f32 * 30.0));
+
+        let sprites = super::sprites(&board, Some(&overlay), &geometry);
+        let squares = board.width() * board.height();
+        assert_eq!(sprites.len(), squares + 2);
+        assert!(sprites[..squares].iter().all(|sprite| matches!(sprite.piece, Piece::Square(_))));
+        let checker = sprites[squares];
+        assert_eq!((checker.piece, checker.mark), (Piece::Checker { owner: PlayerId::B, height: 2 }, Some(Mark::Selected)));
+        let stone = sprites[squares + 1];
+        assert_eq!(stone.piece, Piece::Stone { owner: PlayerId::A });
+        assert_eq!(stone.rect.center(), geometry.corner_point(Vec2::new(3, 3)));
+
+        // Painters are handed the same sprites, in order
+        struct Collect(Vec<Sprite>);
+        impl Painter for Collect {
+            fn paint_sprite(&mut self, sprite: &Sprite) {
+                self.0.push(*sprite);
+            }
+        }
+        let mut painter = Collect(Vec::new());
+        paint(&board, Some(&overlay), &geometry, &mut painter);
+        assert_eq!(painter.0, sprites);
+    }
+}
@@ -0,0 +1,77 @@
+/**
+ * Interrupt handling for the console game: once installed, Ctrl-C no longer kills the process
+ * but raises a flag the game loop checks between moves, so it can save the game and exit
+ * cleanly instead of dying mid-write. A move being entered is finished first.
+ *
+ * Only Unix signals are handled; elsewhere install reports false and Ctrl-C ends the
+ * process as before.
+ */
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// raised by the signal handler, and lowered by clear
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/**
+ * install
+ * Catch Ctrl-C from now on, raising the interrupted flag instead of ending the process.
+ * ret - True if Ctrl-C is caught, false if it could not be on this platform.
+ */
+pub fn install() -> bool {
+    #[cfg(unix)]
+    {
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        let previous = unsafe { libc::signal(libc::SIGINT, on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t) };
+        previous != libc::SIG_ERR
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+/**
+ * interrupted
+ * ret - True if Ctrl-C was pressed since the handler was installed or the flag cleared.
+ */
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/**
+ * raise
+ * Raise the interrupted flag as Ctrl-C would, eg for a quit command or in tests.
+ */
+pub fn raise() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/**
+ * clear
+ * Lower the interrupted flag.
+ */
+pub fn clear() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/* Helper function run by the operating system when Ctrl-C is pressed. */
+#[cfg(unix)]
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag() {
+        #[cfg(unix)]
+        assert!(install());
+        clear();
+        assert!(!interrupted());
+        raise();
+        assert!(interrupted());
+        clear();
+        assert!(!interrupted());
+    }
+}
@@ -0,0 +1,229 @@
+/**
+ * Journals are append-only logs of live games. Every move is added to the file as soon as
+ * the game reports it made, apart from any full saves, so a crash loses nothing and a disputed
+ * game can be audited move by move. Lines are never rewritten: a takeback is logged as a
+ * line of its own. One journal can hold many games in turn.
+ *
+ * Besides the header, move and dice lines of a record, a journal has:
+ *
+ *   game                 starts a new game; its header lines follow
+ *   takeback <moves>     the last moves, and the dice they rolled, were taken back
+ *
+ * Each dice line holds the dice rolled by the move before it.
+ */
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::config::{GameConfig, Variant};
+use crate::events::{GameEvent, Observer};
+use crate::game::{GameView, Intent, PlayerId};
+use crate::record::{move_line, parse_move, GameRecord, RecordError, RecordedMove};
+use crate::scenario::{arg, number};
+
+/**
+ * Journal is an observer appending the games it is sent to a journal file.
+ */
+#[derive(Debug)]
+pub struct Journal {
+    file: File,
+    // header of every game, with the first player filled in as each game starts
+    header: GameRecord,
+    // moves of the current game written so far, with the number of dice each rolled,
+    // or None before the first game starts
+    written: Option<Vec<((PlayerId, Intent), usize)>>,
+}
+
+impl Journal {
+    /**
+     * open
+     * Open a journal to add games to, creating it if it does not exist.
+     * path - Journal file.
+     * config - Setup the games are played from.
+     * ret - The journal, or RecordError::Io if it could not be opened.
+     */
+    pub fn open<P: AsRef<Path>>(path: P, config: &GameConfig) -> Result<Journal, RecordError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut header = GameRecord::new(config.variant);
+        header.free_setup = config.free_setup;
+        Ok(Journal { file, header, written: None })
+    }
+
+    /**
+     * sync
+     * Log the moves made since the last event, and any taken back.
+     * view - The game as it stands.
+     * ret - Ok, or RecordError::Io if the journal could not be written.
+     */
+    pub fn sync(&mut self, view: &GameView) -> Result<(), RecordError> {
+        let Some(written) = self.written.as_mut() else {
+            return Ok(());
+        };
+        let mut lines = String::new();
+        let kept = written.iter().zip(view.history).take_while(|((move_written, _), made)| move_written == *made).count();
+        if kept < written.len() {
+            lines.push_str(&format!("takeback {}\n", written.len() - kept));
+            written.truncate(kept);
+        }
+        let dice: usize = written.iter().map(|(_, rolled)| rolled).sum();
+        for (idx, (player, intent)) in view.history.iter().enumerate().skip(kept) {
+            lines.push_str(&move_line(&RecordedMove::new(*player, *intent)));
+            lines.push('\n');
+            // Every fire is reported as soon as it is made, so new dice are the last move's
+            let rolled = if idx + 1 == view.history.len() { &view.dice[dice..] } else { &[] };
+            if !rolled.is_empty() {
+                let rolled: Vec<String> = rolled.iter().map(u32::to_string).collect();
+                lines.push_str(&format!("dice {}\n", rolled.join(" ")));
+            }
+            written.push(((*player, *intent), rolled.len()));
+        }
+        self.file.write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    /* Helper function logging the start of a game, with @first to move. */
+    fn start(&mut self, first: PlayerId) -> Result<(), RecordError> {
+        self.header.first = first;
+        write!(self.file, "game\n{}", self.header)?;
+        self.written = Some(Vec::new());
+        Ok(())
+    }
+}
+
+impl Observer for Journal {
+    fn on_event(&mut self, event: &GameEvent, view: &GameView) {
+        // A game starts with the first turn the journal sees, which may be part way through a
+        // resumed game, or with a turn from an empty history after a game was written; a
+        // takeback to the start is still the same game
+        let starting = match &self.written {
+            None => true,
+            Some(written) => view.history.is_empty() && !written.is_empty(),
+        };
+        if let GameEvent::TurnStarted { player } = event {
+            if starting {
+                let first = view.history.first().map_or(*player, |(first, _)| *first);
+                if let Err(err) = self.start(first) {
+                    eprintln!("Journal not written: {}", err);
+                }
+            }
+        }
+        if let Err(err) = self.sync(view) {
+            eprintln!("Journal not written: {}", err);
+        }
+    }
+}
+
+/**
+ * recover
+ * Rebuild the last game of a journal, as far as it got.
+ * path - Journal file.
+ * ret - Record of the game, or RecordError if the journal could not be read or understood.
+ */
+pub fn recover<P: AsRef<Path>>(path: P) -> Result<GameRecord, RecordError> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/**
+ * parse
+ * Rebuild the last game of a journal's text.
+ * text - Contents of the journal.
+ * ret - Record of the game, or RecordError::Parse describing the first problem found.
+ */
+pub fn parse(text: &str) -> Result<GameRecord, RecordError> {
+    let mut record = GameRecord::new(Variant::Standard);
+    // number of dice rolled by each move of the record
+    let mut rolled: Vec<usize> = Vec::new();
+    let mut started = false;
+    for (idx, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        let err = |message: String| RecordError::Parse { line: idx + 1, message };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.first().copied() {
+            None => (),
+            Some("game") => {
+                (record, rolled, started) = (GameRecord::new(Variant::Standard), Vec::new(), true);
+            },
+            Some(_) if !started => return Err(err(String::from("journal does not start with a game"))),
+            Some("takeback") => {
+                let moves: usize = number(arg(&words, 1).map_err(err)?).map_err(err)?;
+                if moves > record.moves.len() {
+                    return Err(err(format!("only {} moves to take back", record.moves.len())));
+                }
+                let kept = record.moves.len() - moves;
+                let dice: usize = rolled.drain(kept..).sum();
+                record.moves.truncate(kept);
+                record.dice.truncate(record.dice.len() - dice);
+            },
+            Some(directive @ ("variant" | "free_setup" | "first")) => {
+                let header = GameRecord::parse(line).map_err(|_| err(format!("could not read '{}'", line)))?;
+                match directive {
+                    "variant" => record.variant = header.variant,
+                    "free_setup" => record.free_setup = header.free_setup,
+                    _ => record.first = header.first,
+                }
+            },
+            Some("dice") => {
+                let Some(last) = rolled.last_mut() else {
+                    return Err(err(String::from("dice before any move")));
+                };
+                let dice = GameRecord::parse(line).map_err(|_| err(format!("could not read '{}'", line)))?.dice;
+                *last += dice.len();
+                record.dice.extend(dice);
+            },
+            Some(_) => {
+                record.moves.push(parse_move(line).map_err(err)?);
+                rolled.push(0);
+            },
+        }
+    }
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Replayed;
+    use crate::game::{Game, Player, STARTING_STONES};
+
+    #[test]
+    fn journal() {
+        let path = std::env::temp_dir().join(format!("ironclad-journal-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let (replayed_a, replayed_b) = (Replayed, Replayed);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &replayed_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &replayed_b);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.set_silent(true);
+        game.add_observer(Box::new(Journal::open(&path, game.config()).unwrap()));
+
+        for _ in 0..3 {
+            game.play_turn().unwrap();
+        }
+        game.take_back(PlayerId::B);
+        game.play_turn().unwrap();
+        let first_game = GameRecord::from_game(&game);
+        assert_eq!(recover(&path).unwrap().moves, first_game.moves);
+
+        // The next game is logged after the first, which stays in the journal untouched
+        game.set_first_player(PlayerId::B);
+        game.reset();
+        game.play_turn().unwrap();
+        let recovered = recover(&path).unwrap();
+        assert_eq!((recovered.first, recovered.moves.len()), (PlayerId::B, 1));
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text.matches("game\n").count(), 2);
+        assert_eq!(text.matches("takeback ").count(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parse() {
+        let text = "game\nvariant grand\nfirst b\nb fire 3,3\ndice 2 6\na place 4,4\nb fire 3,3\ndice 5\ntakeback 2\n";
+        let record = super::parse(text).unwrap();
+        assert_eq!((record.variant, record.first, record.moves.len()), (Variant::Grand10x8, PlayerId::B, 1));
+        assert_eq!(record.dice, vec![2, 6]);
+        assert!(matches!(super::parse("a place 4,4"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(super::parse("game\ntakeback 1"), Err(RecordError::Parse { line: 2, .. })));
+        assert!(matches!(super::parse("game\ndice 3"), Err(RecordError::Parse { line: 2, .. })));
+    }
+}
@@ -0,0 +1,241 @@
+/**
+ * Leaderboard: wins, losses, draws and an Elo rating for every player, updated one
+ * finished game at a time.
+ *
+ * Saved one player per line, the name last so it may hold spaces, # starts a comment line:
+ *
+ *   <wins> <losses> <draws> <rating> <name>
+ */
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::PlayerId;
+use crate::record::GameResult;
+use crate::scenario::{arg, number};
+
+// rating of a player before their first game
+pub const INITIAL_RATING: f64 = 1500.0;
+// most rating points changing hands in one game
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LeaderboardError {
+    // The file could not be read or written
+    #[error("Could not access leaderboard: {0}")]
+    Io(#[from] io::Error),
+    // A line could not be understood, with its line number counting from 1
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+/**
+ * Standing is one player's results.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub rating: f64,
+}
+
+impl Standing {
+    /**
+     * new
+     * Create the standing of a player who has not played yet.
+     * name - Player's name.
+     */
+    pub fn new(name: &str) -> Standing {
+        Standing { name: String::from(name), wins: 0, losses: 0, draws: 0, rating: INITIAL_RATING }
+    }
+
+    /**
+     * games
+     * ret - Number of games the player has finished.
+     */
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+}
+
+/**
+ * Leaderboard holds the standing of every player who has finished a game.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Leaderboard {
+    standings: Vec<Standing>,
+}
+
+impl Leaderboard {
+    /**
+     * load
+     * Read and parse a leaderboard file.
+     * path - File to read.
+     * ret - The leaderboard, or a LeaderboardError if it could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Leaderboard, LeaderboardError> {
+        Leaderboard::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * save
+     * Write the leaderboard to a file, replacing anything already there.
+     * path - File to write.
+     * ret - Ok, or LeaderboardError::Io if the file could not be written.
+     */
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), LeaderboardError> {
+        let text: String = self.standings.iter()
+            .map(|standing| format!("{} {} {} {:.3} {}\n", standing.wins, standing.losses, standing.draws, standing.rating, standing.name))
+            .collect();
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /**
+     * parse
+     * Parse the text of a leaderboard file.
+     * text - Contents of the file.
+     * ret - The leaderboard, or a LeaderboardError describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<Leaderboard, LeaderboardError> {
+        let mut leaderboard = Leaderboard::default();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| LeaderboardError::Parse { line: idx + 1, message };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let name = words.get(4..).map(|name| name.join(" ")).filter(|name| !name.is_empty())
+                .ok_or_else(|| err(String::from("missing player name")))?;
+            if leaderboard.standing(&name).is_some() {
+                return Err(err(format!("'{}' is listed twice", name)));
+            }
+            leaderboard.standings.push(Standing {
+                wins: number(arg(&words, 0).map_err(err)?).map_err(err)?,
+                losses: number(arg(&words, 1).map_err(err)?).map_err(err)?,
+                draws: number(arg(&words, 2).map_err(err)?).map_err(err)?,
+                rating: number(arg(&words, 3).map_err(err)?).map_err(err)?,
+                name,
+            });
+        }
+        Ok(leaderboard)
+    }
+
+    /**
+     * record
+     * Count a finished game, and move rating points from the loser to the winner, or
+     * towards the lower rated player on a draw.
+     * players - Names of the players of side A and side B.
+     * result - How the game ended.
+     */
+    pub fn record(&mut self, players: [&str; 2], result: GameResult) {
+        let ratings = players.map(|name| self.standing(name).map_or(INITIAL_RATING, |standing| standing.rating));
+        for side in [PlayerId::A, PlayerId::B] {
+            let score = match result {
+                GameResult::Win(winner) if winner == side => 1.0,
+                GameResult::Win(_) => 0.0,
+                GameResult::Draw => 0.5,
+            };
+            let expected = 1.0 / (1.0 + 10f64.powf((ratings[side.other().index()] - ratings[side.index()]) / 400.0));
+            let standing = self.standing_mut(players[side.index()]);
+            standing.rating += K_FACTOR * (score - expected);
+            match result {
+                GameResult::Win(winner) if winner == side => standing.wins += 1,
+                GameResult::Win(_) => standing.losses += 1,
+                GameResult::Draw => standing.draws += 1,
+            }
+        }
+    }
+
+    /**
+     * standing
+     * name - Player's name.
+     * ret - Their standing, or None if they have not finished a game.
+     */
+    pub fn standing(&self, name: &str) -> Option<&Standing> {
+        self.standings.iter().find(|standing| standing.name == name)
+    }
+
+    /**
+     * standings
+     * ret - Every player's standing, highest rated first, ties by name.
+     */
+    pub fn standings(&self) -> Vec<&Standing> {
+        let mut standings: Vec<&Standing> = self.standings.iter().collect();
+        standings.sort_by(|a, b| b.rating.total_cmp(&a.rating).then_with(|| a.name.cmp(&b.name)));
+        standings
+    }
+
+    /* Helper function finding @name's standing, adding them if they are new. */
+    fn standing_mut(&mut self, name: &str) -> &mut Standing {
+        let idx = match self.standings.iter().position(|standing| standing.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.standings.push(Standing::new(name));
+                self.standings.len() - 1
+            },
+        };
+        &mut self.standings[idx]
+    }
+}
+
+impl Display for Leaderboard {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "{:>4}  {:<20} {:>6} {:>5} {:>5} {:>5}", "Rank", "Player", "Rating", "Won", "Lost", "Drawn")?;
+        for (rank, standing) in self.standings().into_iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<20} {:>6.0} {:>5} {:>5} {:>5}",
+                rank + 1, standing.name, standing.rating, standing.wins, standing.losses, standing.draws,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record(["Ada", "Grace Hopper"], GameResult::Win(PlayerId::A));
+        let ada = leaderboard.standing("Ada").unwrap();
+        assert_eq!((ada.wins, ada.losses, ada.rating), (1, 0, INITIAL_RATING + 16.0));
+        assert_eq!(leaderboard.standing("Grace Hopper").unwrap().rating, INITIAL_RATING - 16.0);
+
+        // A draw moves points towards the lower rated player, and the total is kept
+        leaderboard.record(["Grace Hopper", "Ada"], GameResult::Draw);
+        let grace = leaderboard.standing("Grace Hopper").unwrap();
+        assert_eq!((grace.losses, grace.draws, grace.games()), (1, 1, 2));
+        assert!(grace.rating > INITIAL_RATING - 16.0);
+        let total: f64 = leaderboard.standings().iter().map(|standing| standing.rating).sum();
+        assert!((total - 2.0 * INITIAL_RATING).abs() < 1e-9);
+
+        leaderboard.record(["Alan", "Ada"], GameResult::Win(PlayerId::B));
+        let names: Vec<&str> = leaderboard.standings().iter().map(|standing| standing.name.as_str()).collect();
+        assert_eq!(names, ["Ada", "Grace Hopper", "Alan"]);
+        assert!(leaderboard.to_string().lines().nth(1).unwrap().starts_with("   1  Ada"));
+    }
+
+    #[test]
+    fn parse() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record(["Ada", "Grace Hopper"], GameResult::Win(PlayerId::B));
+        let path = std::env::temp_dir().join(format!("ironclad-leaderboard-{}", std::process::id()));
+        leaderboard.save(&path).unwrap();
+        let loaded = Leaderboard::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.standing("Grace Hopper").unwrap().wins, 1);
+        assert!((loaded.standing("Ada").unwrap().rating - leaderboard.standing("Ada").unwrap().rating).abs() < 1e-3);
+
+        assert!(matches!(Leaderboard::parse("1 0 0 1500"), Err(LeaderboardError::Parse { line: 1, .. })));
+        assert!(matches!(Leaderboard::parse("# Ada\n1 0 0 x Ada"), Err(LeaderboardError::Parse { line: 2, .. })));
+        assert!(matches!(Leaderboard::parse("1 0 0 1500 Ada\n0 1 0 1500 Ada"), Err(LeaderboardError::Parse { line: 2, .. })));
+    }
+}
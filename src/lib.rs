@@ -1,21 +1,511 @@
+pub mod admin;
+pub mod analysis;
+pub mod animation;
+pub mod auth;
 pub mod board;
+pub mod bot;
+pub mod builder;
+pub mod chains;
+pub mod chat;
+pub mod config;
+pub mod console;
+pub mod crowd;
+pub mod decide_async;
+pub mod engine;
+pub mod error;
+pub mod events;
 pub mod game;
+pub mod grid;
+#[cfg(feature = "gui")]
+pub mod gui;
+pub mod interrupt;
+pub mod journal;
+pub mod leaderboard;
+pub mod limits;
+pub mod matches;
+pub mod messages;
+pub mod record;
+pub mod render;
+pub mod review;
+pub mod openings;
+pub mod overlay;
+pub mod playback;
+pub mod protocol;
+pub mod puzzle;
+pub mod scenario;
+pub mod scoring;
+pub mod simultaneous;
+pub mod sprt;
+#[cfg(feature = "storage")]
+pub mod storage;
+pub mod tablebase;
+pub mod takeback;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod tuning;
 pub mod vec;
+pub mod verify;
+pub mod win_model;
 
+use std::io;
+use std::path::Path;
+
+#[cfg(feature = "storage")]
 use game::PlayerFactory;
-use game::{PLAYER_A_ID, PLAYER_B_ID, STARTING_STONES, Game};
+use game::{ConsolePlayer, Decide, Player};
+use game::{CheckerHeight, Game, GameError, GameOutcome, PlayerId, STARTING_STONES};
+use journal::Journal;
+use matches::Series;
+use config::{GameConfig, GlyphTheme};
+use scoring::Evaluator;
+use engine::Engine;
+use error::IroncladError;
+use messages::{Catalog, Key};
+use openings::OpeningStats;
+use playback::{Playback, Spectator};
+use puzzle::Target;
+use record::{notation, GameRecord};
+use scenario::Scenario;
+use tablebase::Tablebase;
+use tuning::{Tuner, TuningSet};
+use review::{Review, ReviewError};
+#[cfg(feature = "storage")]
+use storage::Storage;
+
+// number of alternatives to each move kept by the analyse command
+const ANALYSIS_ALTERNATIVES: usize = 3;
+// number of moves at the start of each game collected by the openings command
+const OPENING_PLIES: usize = 12;
+// number of positions the openings command describes
+const OPENING_REPORT_POSITIONS: usize = 10;
+// environment variable naming the journal console games append their moves to, if any
+const JOURNAL_VARIABLE: &str = "IRONCLAD_JOURNAL";
+// environment variable choosing the console game's glyph theme, eg "unicode"
+const GLYPHS_VARIABLE: &str = "IRONCLAD_GLYPHS";
+// environment variable setting the pause between animation frames, in milliseconds
+const FRAME_DELAY_VARIABLE: &str = "IRONCLAD_FRAME_DELAY";
+// environment variable naming the directory games are stored in, and the directory used without it
+#[cfg(feature = "storage")]
+const STORAGE_VARIABLE: &str = "IRONCLAD_STORAGE";
+#[cfg(feature = "storage")]
+const DEFAULT_STORAGE: &str = "games";
+// names console games are stored under
+#[cfg(feature = "storage")]
+const CONSOLE_PLAYERS: [&str; 2] = ["Player A", "Player B"];
+// file the record of an interrupted console game is saved to, without storage
+#[cfg(not(feature = "storage"))]
+const AUTOSAVE_FILE: &str = "autosave.txt";
 
 
 /**
- * Creates two ConsolePlayers and plays games until the program is forcefully terminated (for now).
+ * Creates two ConsolePlayers and plays games until interrupted with Ctrl-C, alternating
+ * which player moves first so neither keeps the first-move advantage. The interrupt
+ * takes effect once the move being entered is made: the game is saved and the program
+ * returns. Built with storage, each game is stored under a new id as it is played, and an
+ * interrupted game can be resumed; otherwise its record is saved to autosave.txt.
  */
 pub fn run() {
-    let mut player_a = PlayerFactory::console_player(PLAYER_A_ID, STARTING_STONES);
-    let mut player_b = PlayerFactory::console_player(PLAYER_B_ID, STARTING_STONES);
-    let mut game = Game::new(&mut player_a, &mut player_b);
+    play_console_games(None, None);
+}
+
+/**
+ * Plays games as run() does, with the console taking side @human and the engine the other
+ * side. Who moves first still alternates between games.
+ * @human Id of the side played from the console.
+ */
+pub fn play(human: PlayerId) {
+    play_console_games(None, Some(human.other()));
+}
+
+/**
+ * Plays one game of the engine against itself, animating each move in the terminal with
+ * the frame delay set by IRONCLAD_FRAME_DELAY, as a spectator would see it.
+ * @ret Ok once the game is over, or an IroncladError if it could not be played.
+ */
+pub fn watch() -> Result<(), IroncladError> {
+    let (engine_a, engine_b) = (Engine::default(), Engine::default());
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &engine_a);
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &engine_b);
+    let config = GameConfig { glyphs: glyphs_from_env(), ..GameConfig::default() };
+    let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+    game.set_silent(true);
+    game.add_observer(Box::new(Spectator::new(playback_from_env().unwrap_or_default())));
+    let outcome = game.play()?;
+    println!("\n{}", Catalog::from_env().outcome(&outcome));
+    Ok(())
+}
+
+/**
+ * Plays a best-of-@games match between two ConsolePlayers, printing the score after each
+ * game, until one player has won more than half the games or every game is played. Games
+ * are stored or saved as for run(), and an interrupt ends the match.
+ * @games Most games the match can take, an odd number.
+ * @ret Ok once the match is over, or an IroncladError if @games is even.
+ */
+pub fn best_of(games: usize) -> Result<(), IroncladError> {
+    play_console_games(Some(Series::new(games)?), None);
+    Ok(())
+}
+
+/*
+ * Helper function playing console games until interrupted, or until @series is decided, with
+ * the engine playing @engine_side, if any, and the console every other side.
+ */
+fn play_console_games(mut series: Option<Series>, engine_side: Option<PlayerId>) {
+    interrupt::install();
+    let (console, engine) = (ConsolePlayer::new(), Engine::default());
+    let decider = |side| -> &dyn Decide { if engine_side == Some(side) { &engine } else { &console } };
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, decider(PlayerId::A));
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, decider(PlayerId::B));
+    let config = GameConfig { glyphs: glyphs_from_env(), ..GameConfig::default() };
+    let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+    add_journal(&mut game);
+    let catalog = Catalog::from_env();
 
     loop {
-        game.play();
+        #[cfg(feature = "storage")]
+        let result = play_stored(&mut game, None, &catalog);
+        #[cfg(not(feature = "storage"))]
+        let result = play_console(&mut game, &catalog);
+        let interrupted = matches!(result, Ok(None));
+        if let (Some(series), Ok(Some(outcome))) = (series.as_mut(), &result) {
+            series.record(outcome);
+        }
+        report(&catalog, result);
+        if interrupted {
+            return;
+        }
+        if let Some(series) = &series {
+            println!("{}", catalog.series(series));
+            if series.is_over() {
+                return;
+            }
+        }
+        game.set_first_player(game.config().first_player.other());
         game.reset();
     }
-}
\ No newline at end of file
+}
+
+/**
+ * Carries on the stored game @id from the console, where it was left, and keeps storing it.
+ * Games are read from the directory named by IRONCLAD_STORAGE, or games if it is not set.
+ * @id Id the game was stored under.
+ * @ret Ok once the game is over, or an IroncladError if it could not be read or resumed.
+ */
+#[cfg(feature = "storage")]
+pub fn resume(id: u64) -> Result<(), IroncladError> {
+    interrupt::install();
+    let stored = open_storage()?.get(id)?;
+    let mut player_a = PlayerFactory::console_player(PlayerId::A, STARTING_STONES);
+    let mut player_b = PlayerFactory::console_player(PlayerId::B, STARTING_STONES);
+    let config = GameConfig { glyphs: glyphs_from_env(), ..stored.config() };
+    let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+    stored.resume(&mut game)?;
+    add_journal(&mut game);
+    let catalog = Catalog::from_env();
+    report(&catalog, play_stored(&mut game, Some(id), &catalog));
+    Ok(())
+}
+
+/**
+ * Prints the leaderboard of the stored games, highest rated first.
+ * @ret Ok, or an IroncladError if the leaderboard could not be read.
+ */
+#[cfg(feature = "storage")]
+pub fn standings() -> Result<(), IroncladError> {
+    print!("{}", open_storage()?.leaderboard()?);
+    Ok(())
+}
+
+/**
+ * Writes stored games to the archive @path, to be imported elsewhere.
+ * @path Archive file to write.
+ * @player Name of the player whose games to write, or None for every stored game.
+ * @ret Ok, or an IroncladError if the games could not be read or the archive written.
+ */
+#[cfg(feature = "storage")]
+pub fn export<P: AsRef<Path>>(path: P, player: Option<&str>) -> Result<(), IroncladError> {
+    let storage = open_storage()?;
+    let games = match player {
+        Some(name) => storage.by_player(name)?,
+        None => storage.games()?,
+    };
+    let ids: Vec<u64> = games.iter().map(|game| game.id).collect();
+    storage.export(&ids, path)?;
+    println!("Exported {} games", ids.len());
+    Ok(())
+}
+
+/**
+ * Stores every game of the archive @path under new ids.
+ * @path Archive file to read.
+ * @ret Ok, or an IroncladError if the archive could not be read or understood.
+ */
+#[cfg(feature = "storage")]
+pub fn import<P: AsRef<Path>>(path: P) -> Result<(), IroncladError> {
+    let ids = open_storage()?.import(path)?;
+    match (ids.first(), ids.last()) {
+        (Some(first), Some(last)) => println!("Imported {} games as ids {} to {}", ids.len(), first, last),
+        _ => println!("Imported 0 games"),
+    }
+    Ok(())
+}
+
+/*
+ * Helper function playing @game to its end, storing it after every turn under @id, or
+ * under a new id if it has none. The game's own errors are passed back to be reported,
+ * and storage errors end the game like them.
+ */
+#[cfg(feature = "storage")]
+fn play_stored(game: &mut Game, id: Option<u64>, catalog: &Catalog) -> Result<Option<GameOutcome>, IroncladError> {
+    let storage = open_storage()?;
+    let id = match id {
+        Some(id) => id,
+        None => storage.insert(CONSOLE_PLAYERS, game)?,
+    };
+    println!("{}", catalog.format(Key::GameSaved, &[&id, &id]));
+    loop {
+        let turn = game.play_turn();
+        storage.update(id, game)?;
+        let quit = match turn {
+            Ok(Some(outcome)) => return Ok(Some(outcome)),
+            Ok(None) => false,
+            Err(GameError::Quit(_)) => true,
+            Err(err) => return Err(err.into()),
+        };
+        if quit || interrupt::interrupted() {
+            println!("{}", catalog.format(Key::GameSaved, &[&id, &id]));
+            return Ok(None);
+        }
+    }
+}
+
+/*
+ * Helper function playing a console game until it ends, or until it is interrupted or a
+ * player quits, when its record is saved to AUTOSAVE_FILE and None returned.
+ */
+#[cfg(not(feature = "storage"))]
+fn play_console(game: &mut Game, catalog: &Catalog) -> Result<Option<GameOutcome>, IroncladError> {
+    loop {
+        let quit = match game.play_turn() {
+            Ok(Some(outcome)) => return Ok(Some(outcome)),
+            Ok(None) => false,
+            Err(GameError::Quit(_)) => true,
+            Err(err) => return Err(err.into()),
+        };
+        if quit || interrupt::interrupted() {
+            GameRecord::from_game(game).save(AUTOSAVE_FILE)?;
+            println!("{}", catalog.format(Key::GameAutosaved, &[&AUTOSAVE_FILE]));
+            return Ok(None);
+        }
+    }
+}
+
+/* Helper function opening the storage named by the environment. */
+#[cfg(feature = "storage")]
+fn open_storage() -> Result<Storage, IroncladError> {
+    let dir = std::env::var(STORAGE_VARIABLE).unwrap_or_else(|_| String::from(DEFAULT_STORAGE));
+    Ok(Storage::open(dir)?)
+}
+
+/* Helper function printing how a console game ended, with None for an interrupted game. */
+fn report<E: Into<IroncladError>>(catalog: &Catalog, result: Result<Option<GameOutcome>, E>) {
+    match result {
+        Ok(Some(outcome)) => println!("{}", catalog.outcome(&outcome)),
+        Ok(None) => println!("{}", catalog.text(Key::Interrupted)),
+        Err(err) => println!("{}", catalog.format(Key::GameStopped, &[&err.into()])),
+    }
+}
+
+/* Helper function adding the journal named in the environment, if any, to @game. */
+fn add_journal(game: &mut Game) {
+    let Ok(path) = std::env::var(JOURNAL_VARIABLE) else {
+        return;
+    };
+    match Journal::open(&path, game.config()) {
+        Ok(journal) => game.add_observer(Box::new(journal)),
+        Err(err) => eprintln!("Journal {} not opened: {}", path, err),
+    }
+}
+
+/* Helper function reading the frame delay set in the environment, if any. */
+fn playback_from_env() -> Option<Playback> {
+    std::env::var(FRAME_DELAY_VARIABLE).ok()
+        .and_then(|millis| millis.parse().ok())
+        .map(|millis| Playback::new(std::time::Duration::from_millis(millis)))
+}
+
+/* Helper function reading the glyph theme chosen in the environment. */
+fn glyphs_from_env() -> GlyphTheme {
+    std::env::var(GLYPHS_VARIABLE).ok()
+        .and_then(|name| GlyphTheme::named(&name))
+        .unwrap_or_default()
+}
+
+/**
+ * Rebuilds the last game of the journal @journal, as far as it got, and writes its record.
+ * @journal Journal console games were logged to.
+ * @output File to write the record to.
+ * @ret Ok, or an IroncladError if the journal could not be read or the record written.
+ */
+pub fn recover<P: AsRef<Path>, Q: AsRef<Path>>(journal: P, output: Q) -> Result<(), IroncladError> {
+    let record = journal::recover(journal)?;
+    record.save(output)?;
+    println!("Recovered {} moves", record.moves.len());
+    Ok(())
+}
+
+/**
+ * Replays the recorded game in @path with its recorded dice, and prints whether it ends in
+ * the recorded position and result, or each way it diverged.
+ * @path Game record to verify.
+ * @ret Ok if it replays as recorded, or an IroncladError if it could not be read or diverged.
+ */
+pub fn verify<P: AsRef<Path>>(path: P) -> Result<(), IroncladError> {
+    let record = GameRecord::load(path)?;
+    let divergences = verify::verify(&record);
+    if divergences.is_empty() {
+        println!("Replayed {} moves as recorded", record.moves.len());
+        return Ok(());
+    }
+    for divergence in divergences.iter() {
+        println!("{}", divergence);
+    }
+    Err(ReviewError::Diverged(divergences.len()).into())
+}
+
+/**
+ * Steps through the recorded game in @path from the console.
+ * @path Game record to review.
+ * @ret Ok once the user quits, or a ReviewError if the record could not be read or replayed.
+ */
+pub fn review<P: AsRef<Path>>(path: P) -> Result<(), ReviewError> {
+    let mut review = Review::load(path)?;
+    review::run_console(&mut review, playback_from_env());
+    Ok(())
+}
+
+/**
+ * Analyses the recorded game in @path and writes it, annotated, to @output.
+ * @path Game record to analyse.
+ * @output File to write the annotated record to.
+ * @ret Ok, or an IroncladError if the record could not be read, replayed, or written.
+ */
+pub fn analyse<P: AsRef<Path>, Q: AsRef<Path>>(path: P, output: Q) -> Result<(), IroncladError> {
+    let record = GameRecord::load(path)?;
+    let analysis = analysis::analyse(&record, ANALYSIS_ALTERNATIVES)?;
+    analysis::export(&record, &analysis, output)?;
+    Ok(())
+}
+
+/**
+ * Draws the main line of the recorded game in @path as SVG frames in the directory @dir, one
+ * a ply with the move highlighted, and prints how many were written.
+ * @path Game record to draw.
+ * @dir Directory to write the frames to, created if it is missing.
+ * @ret Ok, or an IroncladError if the record could not be read or replayed, or a frame
+ * could not be written.
+ */
+pub fn frames<P: AsRef<Path>, Q: AsRef<Path>>(path: P, dir: Q) -> Result<(), IroncladError> {
+    let written = animation::export(&GameRecord::load(path)?, &dir)?;
+    println!("Wrote {} frames to {}", written, dir.as_ref().display());
+    Ok(())
+}
+
+/**
+ * Collects opening statistics from every game record in the directory @dir, writes them to
+ * @output, and prints the most played positions.
+ * @dir Directory of game records.
+ * @output File to write the statistics to.
+ * @ret Ok, or an IroncladError if a record could not be read or replayed, or the statistics
+ * could not be written.
+ */
+pub fn openings<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, output: Q) -> Result<(), IroncladError> {
+    let mut stats = OpeningStats::default();
+    for path in record_paths(dir)? {
+        stats.add(&GameRecord::load(path)?, OPENING_PLIES)?;
+    }
+    stats.save(output)?;
+    print!("{}", stats.report(OPENING_REPORT_POSITIONS));
+    Ok(())
+}
+
+/**
+ * Tunes the evaluation weights against the positions of every game record in the directory
+ * @dir, starting from the default weights, writes the tuned weights to @output, and prints
+ * them with how well they predict the results.
+ * @dir Directory of game records. Records without a result are skipped.
+ * @output File to write the weights to, which Evaluator::load reads.
+ * @ret Ok, or an IroncladError if a record could not be read or replayed, or the weights
+ * could not be written.
+ */
+pub fn tune<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, output: Q) -> Result<(), IroncladError> {
+    let mut set = TuningSet::default();
+    for path in record_paths(dir)? {
+        set.add(&GameRecord::load(path)?)?;
+    }
+    let report = Tuner::default().tune(&Evaluator::default(), &set);
+    report.evaluator.save(output)?;
+    println!("Tuned on {} positions in {} rounds, scale {:.4}", set.positions().len(), report.rounds, report.scale);
+    println!("Error {:.6} -> {:.6}", report.initial_error, report.error);
+    print!("{}", report.evaluator);
+    Ok(())
+}
+
+/* Helper function listing the files in the directory @dir, in name order. */
+fn record_paths<P: AsRef<Path>>(dir: P) -> Result<Vec<std::path::PathBuf>, IroncladError> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir).map_err(record::RecordError::from)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/**
+ * Searches the puzzle in the scenario @path for the first moves that force a win for the
+ * side to move within @plies plies, under deterministic combat, and prints them with the
+ * quickest winning line.
+ * @path Scenario of the puzzle.
+ * @plies Most plies the win may take, counting the first move.
+ * @ret Ok, or an IroncladError if the scenario could not be read or the game is already over.
+ */
+pub fn solve<P: AsRef<Path>>(path: P, plies: usize) -> Result<(), IroncladError> {
+    let scenario = Scenario::load(path)?;
+    let solution = puzzle::solve(&scenario, Target { winner: PlayerId::A, plies })?;
+    if !solution.proven() {
+        println!("Refuted: Player A can not force a win within {} plies ({} positions searched)", plies, solution.nodes);
+        return Ok(());
+    }
+    println!("Proven: Player A wins within {} plies ({} positions searched)", plies, solution.nodes);
+    for (player, intent) in solution.keys.iter() {
+        println!("Key: {} {}", player, notation(*intent));
+    }
+    let line: Vec<String> = solution.line.iter().map(|(player, intent)| format!("{} {}", player, notation(*intent))).collect();
+    println!("Line: {}", line.join(", "));
+    Ok(())
+}
+
+/**
+ * Solves every standard board endgame with up to @checkers checkers a side and writes the
+ * tablebase to @output.
+ * @checkers Most checkers of each side.
+ * @output File to write the tablebase to.
+ * @ret Ok, or an IroncladError if the tablebase could not be written.
+ */
+pub fn tablebase<P: AsRef<Path>>(checkers: usize, output: P) -> Result<(), IroncladError> {
+    let tablebase = Tablebase::generate(config::Variant::Standard, checkers, CheckerHeight::MAX);
+    tablebase.save(output)?;
+    println!("Solved {} positions", tablebase.len());
+    Ok(())
+}
+
+/**
+ * Runs the engine protocol on standard input and output until quit or the end of input.
+ * See the protocol module for the commands.
+ * @ret Ok, or an IroncladError if an answer could not be written.
+ */
+pub fn engine() -> Result<(), IroncladError> {
+    protocol::serve(Engine::default(), io::BufReader::new(io::stdin()), io::stdout().lock())?;
+    Ok(())
+}
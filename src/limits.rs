@@ -0,0 +1,176 @@
+/**
+ * Protection for a server against misbehaving clients: each connection may send only so
+ * many messages a second, each no bigger than a limit, and is disconnected after too many
+ * illegal moves in a row.
+ *
+ * Messages are limited with a token bucket: a connection may send a burst of messages at
+ * once, and then keeps sending at the steady rate.
+ */
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum LimitError {
+    // The message is bigger than the limit, with its size in bytes
+    #[error("Message of {size} bytes is over the limit of {max}")]
+    TooLarge { size: usize, max: usize },
+    // The connection is sending faster than the rate limit
+    #[error("Too many messages, slow down")]
+    TooFast,
+    // The connection submitted this many illegal moves in a row
+    #[error("{0} illegal moves in a row")]
+    IllegalMoves(u32),
+}
+
+/**
+ * Verdict is what a server should do with a connection's message.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    // Handle the message
+    Accept,
+    // Drop the message and tell the client why, keeping the connection
+    Reject(LimitError),
+    // Close the connection
+    Disconnect(LimitError),
+}
+
+/**
+ * Limits are the bounds every connection is held to.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    // messages a connection may send at once
+    pub burst: u32,
+    // messages a second a connection may keep sending after a burst
+    pub rate: u32,
+    // biggest message, in bytes, including its line ending
+    pub max_message_bytes: usize,
+    // illegal moves in a row before a connection is closed
+    pub max_illegal_moves: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits { burst: 20, rate: 5, max_message_bytes: 1024, max_illegal_moves: 5 }
+    }
+}
+
+/**
+ * ConnectionLimiter holds one connection to the limits.
+ */
+#[derive(Clone, Debug)]
+pub struct ConnectionLimiter {
+    limits: Limits,
+    // messages the connection may send right now, and when that was last worked out
+    allowance: f64,
+    checked: Instant,
+    // illegal moves submitted since the last legal one
+    illegal_moves: u32,
+}
+
+impl ConnectionLimiter {
+    /**
+     * new
+     * Start limiting a connection with a full burst allowance.
+     * limits - Bounds to hold it to.
+     * now - When the connection was opened.
+     */
+    pub fn new(limits: Limits, now: Instant) -> ConnectionLimiter {
+        ConnectionLimiter { limits, allowance: limits.burst as f64, checked: now, illegal_moves: 0 }
+    }
+
+    /**
+     * message
+     * Check a message as it arrives, before it is read any further. Messages over the size
+     * limit close the connection, since the rest of the stream can not be trusted.
+     * size - Size of the message in bytes, including its line ending.
+     * now - When it arrived.
+     * ret - What to do with it.
+     */
+    pub fn message(&mut self, size: usize, now: Instant) -> Verdict {
+        if size > self.limits.max_message_bytes {
+            return Verdict::Disconnect(LimitError::TooLarge { size, max: self.limits.max_message_bytes });
+        }
+        let elapsed = now.saturating_duration_since(self.checked);
+        self.checked = now;
+        self.allowance = (self.allowance + elapsed.as_secs_f64() * self.limits.rate as f64).min(self.limits.burst as f64);
+        if self.allowance < 1.0 {
+            return Verdict::Reject(LimitError::TooFast);
+        }
+        self.allowance -= 1.0;
+        Verdict::Accept
+    }
+
+    /**
+     * illegal_move
+     * Count a move the game refused.
+     * ret - Verdict::Disconnect once there have been too many in a row, else Verdict::Accept.
+     */
+    pub fn illegal_move(&mut self) -> Verdict {
+        self.illegal_moves += 1;
+        if self.illegal_moves >= self.limits.max_illegal_moves {
+            Verdict::Disconnect(LimitError::IllegalMoves(self.illegal_moves))
+        } else {
+            Verdict::Accept
+        }
+    }
+
+    /**
+     * legal_move
+     * Count a move the game applied, starting the count of illegal moves again.
+     */
+    pub fn legal_move(&mut self) {
+        self.illegal_moves = 0;
+    }
+
+    /**
+     * wait
+     * ret - How long until the connection may send another message.
+     */
+    pub fn wait(&self) -> Duration {
+        if self.allowance >= 1.0 || self.limits.rate == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((1.0 - self.allowance) / self.limits.rate as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages() {
+        let limits = Limits { burst: 3, rate: 2, max_message_bytes: 64, max_illegal_moves: 3 };
+        let start = Instant::now();
+        let mut limiter = ConnectionLimiter::new(limits, start);
+
+        // A burst is allowed, then messages at the steady rate
+        for _ in 0..3 {
+            assert_eq!(limiter.message(10, start), Verdict::Accept);
+        }
+        assert_eq!(limiter.message(10, start), Verdict::Reject(LimitError::TooFast));
+        assert_eq!(limiter.wait(), Duration::from_millis(500));
+        assert_eq!(limiter.message(10, start + Duration::from_millis(500)), Verdict::Accept);
+        assert_eq!(limiter.message(10, start + Duration::from_millis(600)), Verdict::Reject(LimitError::TooFast));
+
+        // A long quiet spell refills only up to the burst
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(limiter.message(10, later), Verdict::Accept);
+        }
+        assert_eq!(limiter.message(10, later), Verdict::Reject(LimitError::TooFast));
+        assert_eq!(limiter.message(65, later), Verdict::Disconnect(LimitError::TooLarge { size: 65, max: 64 }));
+    }
+
+    #[test]
+    fn illegal_moves() {
+        let mut limiter = ConnectionLimiter::new(Limits { max_illegal_moves: 3, ..Limits::default() }, Instant::now());
+        assert_eq!(limiter.illegal_move(), Verdict::Accept);
+        assert_eq!(limiter.illegal_move(), Verdict::Accept);
+        limiter.legal_move();
+        assert_eq!(limiter.illegal_move(), Verdict::Accept);
+        assert_eq!(limiter.illegal_move(), Verdict::Accept);
+        assert_eq!(limiter.illegal_move(), Verdict::Disconnect(LimitError::IllegalMoves(3)));
+    }
+}
@@ -1,5 +1,45 @@
 
 fn main() {    
-    ironclad::run()
+    let args: Vec<String> = std::env::args().collect();
+    let usage = "Usage: ironclad [review <record> | verify <record> | recover <journal> <output> | analyse <record> <output> | frames <record> <output dir> | openings <records dir> <output> | tune <records dir> <output> | best-of <games> | play <a or b> | watch | solve <scenario> <plies> | tablebase <checkers> <output> | engine | resume <id> | standings | export <archive> [player] | import <archive>]";
+    let result = match (args.get(1).map(String::as_str), args.get(2), args.get(3)) {
+        (None, _, _) => {
+            ironclad::run();
+            Ok(())
+        },
+        (Some("review"), Some(path), None) => ironclad::review(path).map_err(ironclad::error::IroncladError::from),
+        (Some("verify"), Some(path), None) => ironclad::verify(path),
+        (Some("recover"), Some(journal), Some(output)) => ironclad::recover(journal, output),
+        (Some("analyse"), Some(path), Some(output)) => ironclad::analyse(path, output),
+        (Some("frames"), Some(path), Some(dir)) => ironclad::frames(path, dir),
+        (Some("openings"), Some(dir), Some(output)) => ironclad::openings(dir, output),
+        (Some("tune"), Some(dir), Some(output)) => ironclad::tune(dir, output),
+        (Some("solve"), Some(path), Some(plies)) if plies.parse::<usize>().is_ok() => ironclad::solve(path, plies.parse().unwrap()),
+        (Some("tablebase"), Some(checkers), Some(output)) if checkers.parse::<usize>().is_ok() => {
+            ironclad::tablebase(checkers.parse().unwrap(), output)
+        },
+        (Some("engine"), None, None) => ironclad::engine(),
+        (Some("watch"), None, None) => ironclad::watch(),
+        (Some("play"), Some(side), None) if side == "a" || side == "b" => {
+            ironclad::play(if side == "a" { ironclad::game::PlayerId::A } else { ironclad::game::PlayerId::B });
+            Ok(())
+        },
+        (Some("best-of"), Some(games), None) if games.parse::<usize>().is_ok() => ironclad::best_of(games.parse().unwrap()),
+        #[cfg(feature = "storage")]
+        (Some("resume"), Some(id), None) if id.parse::<u64>().is_ok() => ironclad::resume(id.parse().unwrap()),
+        #[cfg(feature = "storage")]
+        (Some("standings"), None, None) => ironclad::standings(),
+        #[cfg(feature = "storage")]
+        (Some("export"), Some(path), player) => ironclad::export(path, player.map(String::as_str)),
+        #[cfg(feature = "storage")]
+        (Some("import"), Some(path), None) => ironclad::import(path),
+        _ => {
+            eprintln!("{}", usage);
+            std::process::exit(2);
+        },
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
-
@@ -0,0 +1,519 @@
+/**
+ * Engine matches: two deciders play each other from a suite of opening positions, each
+ * position twice with the sides swapped, so neither engine gains from a lopsided opening
+ * and comparisons between engine versions need fewer games. Series keep the score of
+ * best-of-N matches between any two players, console or engine.
+ *
+ * Opening suites hold one position per line, as scenario directives separated by ;, and
+ * # starts a comment line:
+ *
+ *   name Flank; stones a 5; stones b 5; checker a 6 0 1; checker b 1 5 1
+ *
+ * See the scenario module for the directives. Openings without a move limit get
+ * DEFAULT_MATCH_MOVE_LIMIT, so every game ends.
+ */
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::config::StartPosition;
+use crate::game::{Decide, Game, GameError, GameOutcome, Player, PlayerId, STARTING_STONES};
+use crate::scenario::{Scenario, ScenarioError};
+
+// moves after which a match game from an opening without a move limit is drawn
+pub const DEFAULT_MATCH_MOVE_LIMIT: usize = 200;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MatchError {
+    // The suite file could not be read
+    #[error("Could not read opening suite: {0}")]
+    Io(#[from] io::Error),
+    // An opening could not be understood, with its line number counting from 1
+    #[error("Line {line}: {error}")]
+    Opening { line: usize, error: ScenarioError },
+    // The suite has no openings to play
+    #[error("The opening suite is empty")]
+    Empty,
+    // A best-of series needs an odd number of games, so it can not end level on wins alone
+    #[error("A match must be the best of an odd number of games, not {0}")]
+    EvenSeries(usize),
+    // A game could not be played to the end
+    #[error(transparent)]
+    Game(#[from] GameError),
+}
+
+/**
+ * OpeningSuite is the positions a match is played from.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpeningSuite {
+    openings: Vec<Scenario>,
+}
+
+impl OpeningSuite {
+    /**
+     * new
+     * Create a suite from positions already read.
+     * openings - Positions, in the order they are played.
+     * ret - The suite, or MatchError::Empty if there are no positions.
+     */
+    pub fn new(openings: Vec<Scenario>) -> Result<OpeningSuite, MatchError> {
+        if openings.is_empty() {
+            return Err(MatchError::Empty);
+        }
+        Ok(OpeningSuite { openings })
+    }
+
+    /**
+     * load
+     * Read and parse an opening suite file.
+     * path - File to read.
+     * ret - The suite, or a MatchError if it could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<OpeningSuite, MatchError> {
+        OpeningSuite::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * parse
+     * Parse the text of an opening suite file.
+     * text - Contents of the file.
+     * ret - The suite, or a MatchError describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<OpeningSuite, MatchError> {
+        let mut openings = Vec::new();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let scenario = Scenario::parse(&line.replace(';', "\n"))
+                .map_err(|error| MatchError::Opening { line: idx + 1, error })?;
+            openings.push(scenario);
+        }
+        OpeningSuite::new(openings)
+    }
+
+    /**
+     * openings
+     * ret - Every position in the suite, in the order they are played.
+     */
+    pub fn openings(&self) -> &[Scenario] {
+        &self.openings
+    }
+}
+
+/**
+ * MatchScore counts the results of games for one engine.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MatchScore {
+    /**
+     * record
+     * Count the result of a game.
+     * outcome - How the game ended.
+     * side - Side the engine played.
+     */
+    pub fn record(&mut self, outcome: &GameOutcome, side: PlayerId) {
+        match outcome {
+            GameOutcome::Win { winner, .. } if *winner == side => self.wins += 1,
+            GameOutcome::Win { .. } => self.losses += 1,
+            GameOutcome::Draw(_) => self.draws += 1,
+        }
+    }
+
+    /**
+     * add
+     * Count every game of another score as well.
+     * other - Score to add.
+     */
+    pub fn add(&mut self, other: &MatchScore) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+    }
+
+    /**
+     * games
+     * ret - Number of games counted.
+     */
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /**
+     * points
+     * ret - Wins plus half the draws.
+     */
+    pub fn points(&self) -> f32 {
+        self.wins as f32 + self.draws as f32 / 2.0
+    }
+}
+
+/**
+ * GameSummary is everything known about a finished simulated game, so results can be
+ * studied without playing the game again.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSummary {
+    // winner, or the draw, and the rule that decided it
+    pub outcome: GameOutcome,
+    // moves made, counting each action of a turn and each setup placement
+    pub moves: usize,
+    pub duration: Duration,
+    pub final_position: StartPosition,
+    // seeds the game was played from: the board's dice first, then any the deciders drew from
+    pub seeds: Vec<[u8; 32]>,
+}
+
+impl GameSummary {
+    /**
+     * of
+     * Sum up a game that has just ended.
+     * game - The game.
+     * outcome - How it ended.
+     * started - When play began.
+     * seeds - Seeds it was played from, the board's dice first.
+     */
+    pub fn of(game: &Game, outcome: GameOutcome, started: Instant, seeds: Vec<[u8; 32]>) -> GameSummary {
+        GameSummary {
+            outcome,
+            moves: game.history().len(),
+            duration: started.elapsed(),
+            final_position: StartPosition::from_board(&game.board),
+            seeds,
+        }
+    }
+
+    /**
+     * winner
+     * ret - Id of the player who won, or None for a draw.
+     */
+    pub fn winner(&self) -> Option<PlayerId> {
+        match self.outcome {
+            GameOutcome::Win { winner, .. } => Some(winner),
+            GameOutcome::Draw(_) => None,
+        }
+    }
+}
+
+/**
+ * MatchReport is how the first engine of a match fared, overall and from each opening.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchReport {
+    pub total: MatchScore,
+    // scores from each opening of the suite, in the suite's order
+    pub by_opening: Vec<MatchScore>,
+    // every game played, in order, the first engine taking side A in the first of each pair
+    pub games: Vec<GameSummary>,
+}
+
+/**
+ * Series is the score of a best-of-N match: the first player to win more than half the games
+ * takes the match, and it stops as soon as the result can no longer change. Draws count
+ * toward the games played, so a series with draws can end level.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Series {
+    best_of: usize,
+    // games won by player A and by player B
+    wins: [u32; 2],
+    draws: u32,
+}
+
+impl Series {
+    /**
+     * new
+     * best_of - Most games the match can take, an odd number.
+     * ret - A series with no games played, or MatchError::EvenSeries if best_of is even or 0.
+     */
+    pub fn new(best_of: usize) -> Result<Series, MatchError> {
+        if best_of.is_multiple_of(2) {
+            return Err(MatchError::EvenSeries(best_of));
+        }
+        Ok(Series { best_of, wins: [0; 2], draws: 0 })
+    }
+
+    /**
+     * record
+     * Count the result of a game.
+     * outcome - How the game ended.
+     */
+    pub fn record(&mut self, outcome: &GameOutcome) {
+        match outcome {
+            GameOutcome::Win { winner, .. } => self.wins[winner.index()] += 1,
+            GameOutcome::Draw(_) => self.draws += 1,
+        }
+    }
+
+    /**
+     * best_of
+     * ret - Most games the match can take.
+     */
+    pub fn best_of(&self) -> usize {
+        self.best_of
+    }
+
+    /**
+     * games
+     * ret - Games played so far.
+     */
+    pub fn games(&self) -> usize {
+        (self.wins[0] + self.wins[1] + self.draws) as usize
+    }
+
+    /**
+     * wins
+     * player - Id of player to look up.
+     * ret - Games the player has won.
+     */
+    pub fn wins(&self, player: PlayerId) -> u32 {
+        self.wins[player.index()]
+    }
+
+    /**
+     * draws
+     * ret - Games drawn.
+     */
+    pub fn draws(&self) -> u32 {
+        self.draws
+    }
+
+    /**
+     * is_over
+     * ret - True once every game is played, or one player is too far ahead to be caught.
+     */
+    pub fn is_over(&self) -> bool {
+        let left = (self.best_of - self.games().min(self.best_of)) as u32;
+        self.wins[0] > self.wins[1] + left || self.wins[1] > self.wins[0] + left || left == 0
+    }
+
+    /**
+     * winner
+     * ret - The player who has taken the match, or None while it goes on or if it ended level.
+     */
+    pub fn winner(&self) -> Option<PlayerId> {
+        if !self.is_over() || self.wins[0] == self.wins[1] {
+            return None;
+        }
+        Some(if self.wins[0] > self.wins[1] { PlayerId::A } else { PlayerId::B })
+    }
+}
+
+impl fmt::Display for Series {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Player A {}, Player B {}, {} drawn, best of {}", self.wins[0], self.wins[1], self.draws, self.best_of)
+    }
+}
+
+/**
+ * play_series
+ * Play games of a best-of-N match on a game until the match is decided, resetting the game
+ * between them and alternating who moves first, starting with the game's first player.
+ * game - Game to play on, with the two players already seated.
+ * best_of - Most games the match can take, an odd number.
+ * ret - The final score, or MatchError::EvenSeries if best_of is even, or MatchError::Game
+ * if a game could not be played to the end.
+ */
+pub fn play_series(game: &mut Game, best_of: usize) -> Result<Series, MatchError> {
+    let mut series = Series::new(best_of)?;
+    while !series.is_over() {
+        series.record(&game.play()?);
+        game.set_first_player(game.config().first_player.other());
+        game.reset();
+    }
+    Ok(series)
+}
+
+/**
+ * play_pair
+ * Play one opening twice, the first engine taking side A and then side B. Both games roll
+ * the same dice, so only the engines' choices differ between them.
+ * opening - Position and rules to play from.
+ * engines - The two engines; results are for the first.
+ * seed - Seed for the board's dice.
+ * ret - Summaries of the game with the first engine as A and as B, in that order, or
+ * MatchError::Game if either game could not be played to the end.
+ */
+pub fn play_pair(opening: &Scenario, engines: [&dyn Decide; 2], seed: [u8; 32]) -> Result<[GameSummary; 2], MatchError> {
+    let first = play_game(opening, [engines[0], engines[1]], seed)?;
+    let second = play_game(opening, [engines[1], engines[0]], seed)?;
+    Ok([first, second])
+}
+
+/**
+ * play_match
+ * Play every opening of a suite twice, the engines swapping sides in between.
+ * suite - Openings to play.
+ * engines - The two engines; results are for the first.
+ * rng - Source of the seeds for the board's dice.
+ * ret - How the first engine fared, or MatchError::Game if a game could not be played to
+ * the end.
+ */
+pub fn play_match<R: Rng>(suite: &OpeningSuite, engines: [&dyn Decide; 2], rng: &mut R) -> Result<MatchReport, MatchError> {
+    let mut report = MatchReport::default();
+    for opening in suite.openings() {
+        let games = play_pair(opening, engines, rng.gen())?;
+        let mut score = MatchScore::default();
+        score.record(&games[0].outcome, PlayerId::A);
+        score.record(&games[1].outcome, PlayerId::B);
+        report.total.add(&score);
+        report.by_opening.push(score);
+        report.games.extend(games);
+    }
+    Ok(report)
+}
+
+/**
+ * play_engine_series
+ * Play a best-of-N match between two engines from one opening, each game with new dice and
+ * the other side moving first, until the match is decided.
+ * opening - Position and rules to play from.
+ * engines - The engines taking side A and side B, in that order.
+ * best_of - Most games the match can take, an odd number.
+ * rng - Source of the seeds for the board's dice.
+ * ret - The final score, or MatchError::EvenSeries if best_of is even, or MatchError::Game
+ * if a game could not be played to the end.
+ */
+pub fn play_engine_series<R: Rng>(opening: &Scenario, engines: [&dyn Decide; 2], best_of: usize, rng: &mut R) -> Result<Series, MatchError> {
+    let mut series = Series::new(best_of)?;
+    let mut opening = opening.clone();
+    while !series.is_over() {
+        series.record(&play_game(&opening, engines, rng.gen())?.outcome);
+        opening.config.first_player = opening.config.first_player.other();
+    }
+    Ok(series)
+}
+
+/* Helper function playing @opening to the end, with deciders for side A and side B in that order. */
+fn play_game(opening: &Scenario, deciders: [&dyn Decide; 2], seed: [u8; 32]) -> Result<GameSummary, MatchError> {
+    let mut config = opening.config.clone();
+    config.move_limit = config.move_limit.or(Some(DEFAULT_MATCH_MOVE_LIMIT));
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, deciders[0]);
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, deciders[1]);
+    let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+    game.set_silent(true);
+    game.board.reseed(seed);
+    let started = Instant::now();
+    let outcome = match game.check_for_outcome() {
+        Some(outcome) => outcome,
+        None => game.play()?,
+    };
+    Ok(GameSummary::of(&game, outcome, started, vec![seed]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Intent, MoveRequest};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /* Engine that only moves checkers, as close to the far column as it can or as far from it. */
+    struct Walker {
+        forward: bool,
+    }
+
+    impl Decide for Walker {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            let goal = if request.player == PlayerId::A { 0 } else { 7 };
+            let progress = |intent: &Intent| match intent {
+                Intent::MoveChecker(_, to) if self.forward => -(to.x - goal).abs(),
+                Intent::MoveChecker(_, to) => (to.x - goal).abs(),
+                _ => i32::MIN,
+            };
+            *request.all_moves().max_by_key(|intent| progress(intent)).unwrap()
+        }
+    }
+
+    const RUNNER: Walker = Walker { forward: true };
+    const LAGGARD: Walker = Walker { forward: false };
+
+    const SUITE: &str = "
+        # two races, the first even and the second with A a step ahead
+        name Even; rule elimination on; stones a 0; stones b 0; checker a 6 0 1; checker b 1 5 1
+        name Ahead; rule elimination on; stones a 0; stones b 0; checker a 5 0 1; checker b 1 5 1
+    ";
+
+    #[test]
+    fn parse() {
+        let suite = OpeningSuite::parse(SUITE).unwrap();
+        assert_eq!(suite.openings().len(), 2);
+        assert_eq!(suite.openings()[1].name.as_deref(), Some("Ahead"));
+        assert!(matches!(OpeningSuite::parse("# nothing\n"), Err(MatchError::Empty)));
+        assert!(matches!(OpeningSuite::parse("stones a 0\nchecker a 6 0"), Err(MatchError::Opening { line: 2, .. })));
+    }
+
+    #[test]
+    fn series() {
+        let win = |winner| GameOutcome::Win { winner, reason: crate::game::WinReason::Checker };
+        let mut series = Series::new(5).unwrap();
+        series.record(&win(PlayerId::A));
+        series.record(&GameOutcome::Draw(crate::game::DrawReason::MoveLimit));
+        series.record(&win(PlayerId::A));
+        assert!(!series.is_over());
+        assert_eq!(series.winner(), None);
+        // With two games left, B can only draw level
+        series.record(&win(PlayerId::A));
+        assert!(series.is_over());
+        assert_eq!(series.winner(), Some(PlayerId::A));
+        assert_eq!(series.to_string(), "Player A 3, Player B 0, 1 drawn, best of 5");
+
+        let mut level = Series::new(1).unwrap();
+        level.record(&GameOutcome::Draw(crate::game::DrawReason::MoveLimit));
+        assert!(level.is_over() && level.winner().is_none());
+        assert!(matches!(Series::new(4), Err(MatchError::EvenSeries(4))));
+        assert!(matches!(Series::new(0), Err(MatchError::EvenSeries(0))));
+    }
+
+    #[test]
+    fn play_series() {
+        // A's runner is a step ahead, so A takes the first two games and the match
+        let config = OpeningSuite::parse(SUITE).unwrap().openings()[1].config.clone();
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &RUNNER);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &RUNNER);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        game.set_silent(true);
+        let series = super::play_series(&mut game, 3).unwrap();
+        assert_eq!((series.games(), series.winner()), (2, Some(PlayerId::A)));
+        assert!(matches!(super::play_series(&mut game, 2), Err(MatchError::EvenSeries(2))));
+    }
+
+    #[test]
+    fn play_match() {
+        let suite = OpeningSuite::parse(SUITE).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let report = super::play_match(&suite, [&RUNNER, &LAGGARD], &mut rng).unwrap();
+        assert_eq!(report.by_opening.len(), 2);
+        assert_eq!(report.total.games(), 4);
+        assert_eq!(report.total, MatchScore { wins: 4, draws: 0, losses: 0 });
+        assert_eq!(report.games.len(), 4);
+        let first = &report.games[0];
+        assert_eq!((first.winner(), first.seeds.len()), (Some(PlayerId::A), 1));
+        // The final position reads back as a scenario
+        let end = Scenario::parse(&first.final_position.to_string()).unwrap();
+        assert_eq!(end.config.start_position.as_ref(), Some(&first.final_position));
+        assert!(first.moves > 0);
+
+        // Between equal engines each pair splits, whoever the opening favours
+        let pair = play_pair(&suite.openings()[1], [&RUNNER, &RUNNER], [0; 32]).unwrap();
+        let mut score = MatchScore::default();
+        score.record(&pair[0].outcome, PlayerId::A);
+        score.record(&pair[1].outcome, PlayerId::B);
+        assert_eq!(score.points(), 1.0);
+
+        let series = play_engine_series(&suite.openings()[0], [&LAGGARD, &RUNNER], 5, &mut rng).unwrap();
+        assert_eq!((series.games(), series.winner()), (3, Some(PlayerId::B)));
+    }
+}
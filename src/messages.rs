@@ -0,0 +1,613 @@
+/**
+ * Message catalog for the console game. Every line the console shows a player is looked up
+ * here by Key, in the player's Language, so the game can be played in another language
+ * without touching the menus.
+ *
+ * Besides the bundled languages, a catalog file can replace any of the messages:
+ *
+ *   # comment
+ *   <key name> = <text>
+ *
+ * where {} marks where a value goes, in order, and \n is a line break. Keys left out keep
+ * the bundled text. The command letters typed at the menus are the same in every language.
+ */
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::board::Direction;
+use crate::config::BoardLayout;
+use crate::game::{clock_text, DrawReason, GameOutcome, GameView, Intent, PlayerId, WinReason};
+use crate::matches::Series;
+use crate::scoring::{progress, progress_bar, PROGRESS_BAR_WIDTH};
+
+// width of the progress bars in the compact layout's status lines
+const COMPACT_BAR_WIDTH: usize = 5;
+// environment variable choosing the bundled language, eg "fr"
+pub const LANGUAGE_VARIABLE: &str = "IRONCLAD_LANG";
+// environment variable naming a catalog file to load over the bundled language
+pub const MESSAGES_VARIABLE: &str = "IRONCLAD_MESSAGES";
+
+/**
+ * CatalogError is why a catalog file could not be loaded.
+ */
+#[derive(Debug, thiserror::Error)]
+pub enum CatalogError {
+    // The file could not be read
+    #[error("Could not read message catalog: {0}")]
+    Io(#[from] io::Error),
+    // A line could not be understood, with its line number counting from 1
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+/**
+ * Language is one of the bundled translations.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+impl Language {
+    /**
+     * from_code
+     * Find a language by its code, eg "fr" or "fr_FR.UTF-8".
+     * code - Language code, in any case, optionally followed by a region.
+     * ret - The language, or None if it is not bundled.
+     */
+    pub fn from_code(code: &str) -> Option<Language> {
+        let lang = code.split(['_', '-', '.']).next().unwrap_or("").to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Language::English),
+            "fr" => Some(Language::French),
+            _ => None,
+        }
+    }
+
+    /**
+     * code
+     * ret - Two letter code of the language.
+     */
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+        }
+    }
+}
+
+/**
+ * Key names one message of the catalog.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    // menu
+    WhatToDo,
+    MoveChecker,
+    AttackChecker,
+    PlaceStone,
+    SlideStone,
+    SwapSides,
+    PlaceStartingChecker,
+    TakeBack,
+    RulesSummaryItem,
+    DescribeItem,
+    QuitItem,
+    EnterLetter,
+    NotAChoice,
+    EnterNumber,
+    EnterNumberOrPreview,
+    // yes or no questions
+    YesLetter,
+    NoLetter,
+    Question,
+    AnswerYesOrNo,
+    MakeSlide,
+    WouldComeToRest,
+    SlideImpossible,
+    // moves in lists
+    CheckerWithMoves,
+    MoveTo,
+    MoveFromTo,
+    AttackAt,
+    PlaceStoneAt,
+    SlideFrom,
+    PlaceCheckerAt,
+    SwapSidesMove,
+    Up,
+    Down,
+    Left,
+    Right,
+    // game
+    RulesSummary,
+    Goodbye,
+    AskTakeBack,
+    MoveChosen,
+    DiceRolled,
+    StonesExhausted,
+    TakenBack,
+    TakeBackRefused,
+    Status,
+    StatusOneLost,
+    Progress,
+    Clocks,
+    StatusCompact,
+    ClocksCompact,
+    LowTime,
+    Wins,
+    Draw,
+    GameStopped,
+    GameSaved,
+    GameAutosaved,
+    Interrupted,
+    SeriesScore,
+    SeriesWon,
+    SeriesDrawn,
+    GameAborted,
+    ByChecker,
+    ByStoneChain,
+    ByCircularity,
+    ByElimination,
+    ByHill,
+    ByAdjudication,
+    ByRepetition,
+    ByMoveLimit,
+    ByArbiter,
+}
+
+// name, English and French text of each key, in the order Key lists them
+const MESSAGES: [(Key, &str, &str, &str); 68] = [
+    (Key::WhatToDo, "what_to_do",
+        "What would you like to do? (Type your choice, then press ENTER)",
+        "Que voulez-vous faire ? (Tapez votre choix, puis appuyez sur ENTRÉE)"),
+    (Key::MoveChecker, "move_checker", "Move checker", "Déplacer un pion"),
+    (Key::AttackChecker, "attack_checker", "Attack checker", "Attaquer un pion"),
+    (Key::PlaceStone, "place_stone", "Place stone", "Poser une pierre"),
+    (Key::SlideStone, "slide_stone", "Slide stone", "Glisser une pierre"),
+    (Key::SwapSides, "swap_sides", "Swap sides", "Changer de camp"),
+    (Key::PlaceStartingChecker, "place_starting_checker", "Place starting checker", "Placer un pion de départ"),
+    (Key::TakeBack, "take_back", "Take back your last turn", "Reprendre votre dernier tour"),
+    (Key::RulesSummaryItem, "rules_summary_item", "Rules summary", "Résumé des règles"),
+    (Key::DescribeItem, "describe_item", "Describe the position in words", "Décrire la position en mots"),
+    (Key::QuitItem, "quit_item", "Quit", "Quitter"),
+    (Key::EnterLetter, "enter_letter", "Enter a letter: ", "Entrez une lettre : "),
+    (Key::NotAChoice, "not_a_choice", "That is not one of the choices.", "Ce n'est pas l'un des choix."),
+    (Key::EnterNumber, "enter_number",
+        "Enter the number of your choice, or B to go back: ",
+        "Entrez le numéro de votre choix, ou B pour revenir : "),
+    (Key::EnterNumberOrPreview, "enter_number_or_preview",
+        "Enter the number of your choice, V and a number to preview it, or B to go back: ",
+        "Entrez le numéro de votre choix, V et un numéro pour le prévisualiser, ou B pour revenir : "),
+    (Key::YesLetter, "yes_letter", "Y", "O"),
+    (Key::NoLetter, "no_letter", "N", "N"),
+    (Key::Question, "question", "{} ({}/{}) ", "{} ({}/{}) "),
+    (Key::AnswerYesOrNo, "answer_yes_or_no", "Please answer {} or {}.", "Répondez {} ou {}, s'il vous plaît."),
+    (Key::MakeSlide, "make_slide", "Make this slide?", "Faire ce glissement ?"),
+    (Key::WouldComeToRest, "would_come_to_rest",
+        "The stone at {} would come to rest at {}.",
+        "La pierre en {} s'arrêterait en {}."),
+    (Key::SlideImpossible, "slide_impossible", "That slide is not possible: {}", "Ce glissement est impossible : {}"),
+    (Key::CheckerWithMoves, "checker_with_moves", "checker at {} ({} moves)", "pion en {} ({} coups)"),
+    (Key::MoveTo, "move_to", "move to {}", "aller en {}"),
+    (Key::MoveFromTo, "move_from_to", "move checker from {} to {}", "déplacer le pion de {} à {}"),
+    (Key::AttackAt, "attack_at", "attack checker at {}", "attaquer le pion en {}"),
+    (Key::PlaceStoneAt, "place_stone_at", "place stone at {}", "poser une pierre en {}"),
+    (Key::SlideFrom, "slide_from", "slide stone from {} {}", "glisser la pierre de {} vers {}"),
+    (Key::PlaceCheckerAt, "place_checker_at",
+        "place checker of height {} at {}",
+        "placer un pion de hauteur {} en {}"),
+    (Key::SwapSidesMove, "swap_sides_move", "swap sides", "changer de camp"),
+    (Key::Up, "up", "up", "le haut"),
+    (Key::Down, "down", "down", "le bas"),
+    (Key::Left, "left", "left", "la gauche"),
+    (Key::Right, "right", "right", "la droite"),
+    (Key::RulesSummary, "rules_summary",
+        "Rules of Ironclad, in short:
+  Each turn, take one action: move a checker one square, attack with your checkers,
+  place a stone from your pool on a free corner, or slide one of your stones.
+  Every one of your checkers in range of an enemy checker joins an attack on it and rolls
+  a die. Each roll that reaches the target's defence does one damage; the defence is one
+  for a hill and one for each stone on the target's corners.
+  Win by moving a checker to the far column, or by joining the top and bottom edges
+  with a chain of your stones.",
+        "Les règles d'Ironclad, en bref :
+  À chaque tour, faites une action : déplacer un pion d'une case, attaquer avec vos pions,
+  poser une pierre de votre réserve sur un coin libre, ou glisser l'une de vos pierres.
+  Chacun de vos pions à portée d'un pion ennemi se joint à l'attaque et lance un dé.
+  Chaque lancer qui atteint la défense de la cible inflige un dégât ; la défense est de un
+  pour une colline et de un pour chaque pierre sur les coins de la cible.
+  Gagnez en amenant un pion à la colonne opposée, ou en reliant les bords haut et bas
+  par une chaîne de vos pierres."),
+    (Key::Goodbye, "goodbye", "Goodbye.", "Au revoir."),
+    (Key::AskTakeBack, "ask_take_back",
+        "Player {} asks to take back their last turn. Allow it?",
+        "Le joueur {} demande à reprendre son dernier tour. L'accepter ?"),
+    (Key::MoveChosen, "move_chosen", "Player {} chose to {}", "Le joueur {} a choisi de {}"),
+    (Key::DiceRolled, "dice_rolled", "Dice rolled {}, hitting on {} or more: {} damage",
+        "Dés lancés {}, touchant sur {} ou plus : {} dégâts"),
+    (Key::StonesExhausted, "stones_exhausted",
+        "Player {} has placed their last stone",
+        "Le joueur {} a posé sa dernière pierre"),
+    (Key::TakenBack, "taken_back", "Player {} took back {} moves", "Le joueur {} a repris {} coups"),
+    (Key::TakeBackRefused, "take_back_refused",
+        "Player {} may not take back their move",
+        "Le joueur {} ne peut pas reprendre son coup"),
+    (Key::Status, "status",
+        "Player {}: {} stones in pool, {} on board, {} checkers lost",
+        "Joueur {} : {} pierres en réserve, {} sur le plateau, {} pions perdus"),
+    (Key::StatusOneLost, "status_one_lost",
+        "Player {}: {} stones in pool, {} on board, {} checker lost",
+        "Joueur {} : {} pierres en réserve, {} sur le plateau, {} pion perdu"),
+    (Key::Progress, "progress", "Progress: Player A {}, Player B {}", "Progression : joueur A {}, joueur B {}"),
+    (Key::Clocks, "clocks", "Time left: Player A {}, Player B {}", "Temps restant : joueur A {}, joueur B {}"),
+    (Key::StatusCompact, "status_compact", "{}: {}+{} stones, {} lost {}", "{} : {}+{} pierres, {} perdus {}"),
+    (Key::ClocksCompact, "clocks_compact", "Time: A {}, B {}", "Temps : A {}, B {}"),
+    (Key::LowTime, "low_time", "Player {} is running low on time: {} left", "Le joueur {} manque de temps : il reste {}"),
+    (Key::Wins, "wins", "Player {} wins by {}", "Le joueur {} gagne par {}"),
+    (Key::Draw, "draw", "Draw by {}", "Partie nulle par {}"),
+    (Key::GameStopped, "game_stopped", "Game stopped: {}", "Partie arrêtée : {}"),
+    (Key::GameSaved, "game_saved",
+        "Saving as game {}. Carry it on later with: ironclad resume {}",
+        "Partie enregistrée sous le numéro {}. Reprenez-la plus tard avec : ironclad resume {}"),
+    (Key::GameAutosaved, "game_autosaved", "Game saved to {}", "Partie enregistrée dans {}"),
+    (Key::Interrupted, "interrupted", "Interrupted; goodbye", "Interrompu ; au revoir"),
+    (Key::SeriesScore, "series_score",
+        "Match after {} of {} games: Player A {}, Player B {}, {} drawn",
+        "Match après {} parties sur {} : joueur A {}, joueur B {}, {} nulles"),
+    (Key::SeriesWon, "series_won", "Player {} wins the match {} to {}", "Le joueur {} gagne le match {} à {}"),
+    (Key::SeriesDrawn, "series_drawn", "The match is drawn {} to {}", "Le match est nul, {} à {}"),
+    (Key::GameAborted, "game_aborted", "The game was aborted", "La partie a été annulée"),
+    (Key::ByChecker, "by_checker", "reaching the far side with a checker", "l'arrivée d'un pion de l'autre côté"),
+    (Key::ByStoneChain, "by_stone_chain", "connecting the board with stones", "une chaîne de pierres d'un bord à l'autre"),
+    (Key::ByCircularity, "by_circularity", "the Law of Circularity", "la loi de circularité"),
+    (Key::ByElimination, "by_elimination", "destroying every opposing checker", "la destruction de tous les pions adverses"),
+    (Key::ByHill, "by_hill", "holding the hill", "la tenue de la colline"),
+    (Key::ByAdjudication, "by_adjudication", "adjudication on material", "arbitrage sur le matériel"),
+    (Key::ByRepetition, "by_repetition", "repetition of position", "répétition de la position"),
+    (Key::ByMoveLimit, "by_move_limit", "reaching the move limit", "la limite de coups"),
+    (Key::ByArbiter, "by_arbiter", "decision of the arbiter", "décision de l'arbitre"),
+];
+
+/**
+ * Catalog holds the console's messages in one language.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Catalog {
+    language: Language,
+    // messages replaced by a catalog file
+    overrides: HashMap<Key, String>,
+}
+
+impl Catalog {
+    /**
+     * new
+     * language - Bundled language to show messages in.
+     */
+    pub fn new(language: Language) -> Catalog {
+        Catalog { language, overrides: HashMap::new() }
+    }
+
+    /**
+     * from_env
+     * Choose the catalog from the IRONCLAD_LANG and IRONCLAD_MESSAGES environment
+     * variables. An unknown language is English, and a catalog file that cannot be loaded
+     * is reported on stderr and left out.
+     * ret - The catalog.
+     */
+    pub fn from_env() -> Catalog {
+        let language = std::env::var(LANGUAGE_VARIABLE).ok()
+            .and_then(|code| Language::from_code(&code))
+            .unwrap_or_default();
+        let Ok(path) = std::env::var(MESSAGES_VARIABLE) else {
+            return Catalog::new(language);
+        };
+        Catalog::load(language, &path).unwrap_or_else(|err| {
+            eprintln!("{}: {}", path, err);
+            Catalog::new(language)
+        })
+    }
+
+    /**
+     * load
+     * Read a catalog file over a bundled language.
+     * language - Language of the messages the file leaves out.
+     * path - File to read.
+     * ret - The catalog, or a CatalogError if the file could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(language: Language, path: P) -> Result<Catalog, CatalogError> {
+        Catalog::parse(language, &fs::read_to_string(path)?)
+    }
+
+    /**
+     * parse
+     * Parse the contents of a catalog file over a bundled language.
+     * language - Language of the messages the text leaves out.
+     * text - Contents of a catalog file.
+     * ret - The catalog, or CatalogError::Parse describing the first problem found.
+     */
+    pub fn parse(language: Language, text: &str) -> Result<Catalog, CatalogError> {
+        let mut catalog = Catalog::new(language);
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| CatalogError::Parse { line: idx + 1, message };
+            let (name, message) = line.split_once('=').ok_or_else(|| err(String::from("expected <key> = <text>")))?;
+            let name = name.trim();
+            let key = MESSAGES.iter().find(|entry| entry.1 == name)
+                .ok_or_else(|| err(format!("unknown key {}", name)))?.0;
+            catalog.overrides.insert(key, message.trim().replace("\\n", "\n"));
+        }
+        Ok(catalog)
+    }
+
+    /**
+     * language
+     * ret - The bundled language of the catalog.
+     */
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /**
+     * text
+     * Look up a message.
+     * key - Message to look up.
+     * ret - The message, with {} where its values go.
+     */
+    pub fn text(&self, key: Key) -> &str {
+        if let Some(message) = self.overrides.get(&key) {
+            return message;
+        }
+        let (_, _, english, french) = MESSAGES[key as usize];
+        match self.language {
+            Language::English => english,
+            Language::French => french,
+        }
+    }
+
+    /**
+     * format
+     * Look up a message and fill in its values.
+     * key - Message to look up.
+     * values - Values for each {} of the message, in order. Extra values are left out, and
+     * a {} with no value is left empty.
+     * ret - The message with its values.
+     */
+    pub fn format(&self, key: Key, values: &[&dyn Display]) -> String {
+        let mut parts = self.text(key).split("{}");
+        let mut message = String::from(parts.next().unwrap_or(""));
+        for (idx, part) in parts.enumerate() {
+            if let Some(value) = values.get(idx) {
+                message.push_str(&value.to_string());
+            }
+            message.push_str(part);
+        }
+        message
+    }
+
+    /**
+     * describe
+     * Describe a move for a list of moves.
+     * intent - Move to describe.
+     * ret - eg "place stone at (4, 0)". Checker moves give only the destination.
+     */
+    pub fn describe(&self, intent: &Intent) -> String {
+        match intent {
+            Intent::MoveChecker(_, to) => self.format(Key::MoveTo, &[to]),
+            Intent::SwapSides => self.text(Key::SwapSidesMove).to_string(),
+            _ => self.describe_in_full(intent),
+        }
+    }
+
+    /**
+     * describe_in_full
+     * Describe a move on its own, such as one just played.
+     * intent - Move to describe.
+     * ret - eg "move checker from (6, 2) to (5, 2)".
+     */
+    pub fn describe_in_full(&self, intent: &Intent) -> String {
+        match intent {
+            Intent::MoveChecker(from, to) => self.format(Key::MoveFromTo, &[from, to]),
+            Intent::FireChecker(at) => self.format(Key::AttackAt, &[at]),
+            Intent::PlaceStone(at) => self.format(Key::PlaceStoneAt, &[at]),
+            Intent::SlideStone(from, dir) => self.format(Key::SlideFrom, &[from, &self.direction(*dir)]),
+            Intent::PlaceChecker(at, height) => self.format(Key::PlaceCheckerAt, &[height, at]),
+            Intent::SwapSides => self.text(Key::SwapSidesMove).to_string(),
+        }
+    }
+
+    /**
+     * status_line
+     * Describe a player's stones and losses, for showing under the board.
+     * view - The game as it stands.
+     * player - Id of player to describe.
+     * ret - eg "Player A: 30 stones in pool, 2 on board, 1 checker lost".
+     */
+    pub fn status_line(&self, view: &GameView, player: PlayerId) -> String {
+        let lost = view.captures[player.other().index()];
+        let key = if lost == 1 { Key::StatusOneLost } else { Key::Status };
+        self.format(key, &[&player, &view.stones_for(player), &view.board.stones_for_player(player).len(), &lost])
+    }
+
+    /**
+     * show_view
+     * Render the board with both players' status lines and progress under it.
+     * view - The game as it stands.
+     * ret - The board and status lines, with the clocks of a timed game, like GameView's
+     * Display in this catalog's language.
+     */
+    pub fn show_view(&self, view: &GameView) -> String {
+        self.show_view_in(view, BoardLayout::Full)
+    }
+
+    /**
+     * show_view_in
+     * Render the board and status lines as show_view does, in a layout.
+     * view - The game as it stands.
+     * layout - Full for show_view's lines, or Compact for the board and one short line per
+     * player, with their stones in the pool and on the board, losses and progress, then the
+     * clocks of a timed game.
+     * ret - The board and status lines.
+     */
+    pub fn show_view_in(&self, view: &GameView, layout: BoardLayout) -> String {
+        if layout == BoardLayout::Compact {
+            let mut shown = view.board.to_string();
+            for player in [PlayerId::A, PlayerId::B] {
+                let bar = progress_bar(&progress(view.board, player), COMPACT_BAR_WIDTH);
+                let lost = view.captures[player.other().index()];
+                shown.push_str(&self.format(Key::StatusCompact, &[&player, &view.stones_for(player), &view.board.stones_for_player(player).len(), &lost, &bar]));
+                shown.push('\n');
+            }
+            if let Some([a, b]) = view.clocks {
+                shown.push_str(&self.format(Key::ClocksCompact, &[&clock_text(a), &clock_text(b)]));
+            }
+            return shown.trim_end().to_string();
+        }
+        let mut shown = format!("{}{}\n{}", view.board, self.status_line(view, PlayerId::A), self.status_line(view, PlayerId::B));
+        let [a, b] = [PlayerId::A, PlayerId::B].map(|player| progress_bar(&progress(view.board, player), PROGRESS_BAR_WIDTH));
+        shown.push('\n');
+        shown.push_str(&self.format(Key::Progress, &[&a, &b]));
+        if let Some([a, b]) = view.clocks {
+            shown.push('\n');
+            shown.push_str(&self.format(Key::Clocks, &[&clock_text(a), &clock_text(b)]));
+        }
+        shown
+    }
+
+    /**
+     * outcome
+     * Describe how a game ended.
+     * outcome - The result of the game.
+     * ret - eg "Player A wins by holding the hill".
+     */
+    pub fn outcome(&self, outcome: &GameOutcome) -> String {
+        match outcome {
+            GameOutcome::Win { winner, reason } => {
+                let reason = self.text(match reason {
+                    WinReason::Checker => Key::ByChecker,
+                    WinReason::StoneChain => Key::ByStoneChain,
+                    WinReason::Circularity => Key::ByCircularity,
+                    WinReason::Elimination => Key::ByElimination,
+                    WinReason::Hill => Key::ByHill,
+                    WinReason::Adjudication => Key::ByAdjudication,
+                    WinReason::Arbiter => Key::ByArbiter,
+                });
+                self.format(Key::Wins, &[winner, &reason])
+            },
+            GameOutcome::Draw(reason) => {
+                let reason = self.text(match reason {
+                    DrawReason::Repetition => Key::ByRepetition,
+                    DrawReason::MoveLimit => Key::ByMoveLimit,
+                    DrawReason::Arbiter => Key::ByArbiter,
+                });
+                self.format(Key::Draw, &[&reason])
+            },
+        }
+    }
+
+    /**
+     * series
+     * Describe the score of a best-of-N match, with its winner once it is over.
+     * series - The match.
+     * ret - eg "Match after 3 of 5 games: Player A 3, Player B 0, 0 drawn" followed by
+     * "Player A wins the match 3 to 0" on a line of its own.
+     */
+    pub fn series(&self, series: &Series) -> String {
+        let (a, b) = (series.wins(PlayerId::A), series.wins(PlayerId::B));
+        let mut shown = self.format(Key::SeriesScore, &[&series.games(), &series.best_of(), &a, &b, &series.draws()]);
+        if series.is_over() {
+            shown.push('\n');
+            shown.push_str(&match series.winner() {
+                Some(winner) => self.format(Key::SeriesWon, &[&winner, &a.max(b), &a.min(b)]),
+                None => self.format(Key::SeriesDrawn, &[&a, &b]),
+            });
+        }
+        shown
+    }
+
+    /* Helper function naming a direction. */
+    fn direction(&self, dir: Direction) -> &str {
+        self.text(match dir {
+            Direction::Up => Key::Up,
+            Direction::Down => Key::Down,
+            Direction::Left => Key::Left,
+            Direction::Right => Key::Right,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::vec::Vec2;
+    use std::time::Duration;
+
+    #[test]
+    fn messages() {
+        // Every key is listed once, in order, with a name and both languages
+        for (idx, (key, name, english, french)) in MESSAGES.iter().enumerate() {
+            assert_eq!(*key as usize, idx);
+            assert_eq!(MESSAGES.iter().filter(|entry| entry.1 == *name).count(), 1);
+            assert_eq!(english.matches("{}").count(), french.matches("{}").count(), "{}", name);
+        }
+        assert_eq!(Language::from_code("fr_FR.UTF-8"), Some(Language::French));
+        assert_eq!(Language::from_code("EN"), Some(Language::English));
+        assert_eq!(Language::from_code("xx"), None);
+    }
+
+    #[test]
+    fn format() {
+        let english = Catalog::default();
+        let french = Catalog::new(Language::French);
+        let place = Intent::PlaceStone(Vec2::new(4, 0));
+        assert_eq!(english.describe(&place), format!("place stone at {}", Vec2::new(4, 0)));
+        assert_eq!(french.format(Key::MoveChosen, &[&PlayerId::A, &french.describe(&place)]),
+            format!("Le joueur A a choisi de poser une pierre en {}", Vec2::new(4, 0)));
+        assert_eq!(english.format(Key::TakenBack, &[&PlayerId::B]), "Player B took back  moves");
+        let outcome = GameOutcome::Win { winner: PlayerId::B, reason: WinReason::Hill };
+        assert_eq!(english.outcome(&outcome), outcome.to_string());
+        let mut series = Series::new(3).unwrap();
+        series.record(&outcome);
+        assert_eq!(english.series(&series), "Match after 1 of 3 games: Player A 0, Player B 1, 0 drawn");
+        series.record(&outcome);
+        assert!(french.series(&series).ends_with("Le joueur B gagne le match 2 à 0"));
+
+        // The console shows the same status lines as the game view
+        let board = Board::new();
+        let mut view = GameView { board: &board, stones: [30, 29], captures: [1, 0], history: &[], dice: &[], clocks: None };
+        assert_eq!(english.status_line(&view, PlayerId::B), view.status_line(PlayerId::B));
+        assert_eq!(english.show_view(&view), view.to_string());
+        view.clocks = Some([Duration::from_secs(65), Duration::from_secs(3725)]);
+        assert!(english.show_view(&view).ends_with("Time left: Player A 1:05, Player B 1:02:05"));
+        assert_eq!(english.show_view(&view), view.to_string());
+
+        // The compact layout fits narrow terminals, in every language
+        for catalog in [&english, &french] {
+            let compact = catalog.show_view_in(&view, BoardLayout::Compact);
+            assert!(compact.starts_with(&board.to_string()));
+            assert!(compact.lines().all(|line| line.chars().count() <= 40), "{}", compact);
+            assert!(catalog.show_view(&view).lines().any(|line| line.chars().count() > 40));
+        }
+        assert!(english.show_view_in(&view, BoardLayout::Compact).contains("\nB: 29+0 stones, 1 lost [#"));
+        assert_eq!(BoardLayout::for_columns(40), BoardLayout::Compact);
+        assert_eq!(BoardLayout::for_columns(80), BoardLayout::Full);
+    }
+
+    #[test]
+    fn parse() {
+        let catalog = Catalog::parse(Language::French, "# Ahoy\ngoodbye = Fair winds.\nmove_chosen = {} sailed to {}\\n").unwrap();
+        assert_eq!(catalog.text(Key::Goodbye), "Fair winds.");
+        assert_eq!(catalog.format(Key::MoveChosen, &[&"A", &"port"]), "A sailed to port\n");
+        assert_eq!(catalog.text(Key::QuitItem), "Quitter");
+        assert!(matches!(Catalog::parse(Language::English, "\nfarewell = Bye"), Err(CatalogError::Parse { line: 2, .. })));
+        assert!(matches!(Catalog::parse(Language::English, "goodbye"), Err(CatalogError::Parse { line: 1, .. })));
+    }
+}
@@ -0,0 +1,286 @@
+/**
+ * Opening statistics: the moves played from each position early in a corpus of recorded
+ * games, with how often each was played and how the games went for the player who chose
+ * it. Positions are matched by hash, so games that reach the same position by different
+ * move orders share their statistics.
+ *
+ * Statistics are saved as plain text, one line per move of a position:
+ *
+ *   <position hash in hex> <games> <wins> <draws> <losses> a|b <move in record notation>
+ *
+ * Games without a recorded result count toward games only.
+ */
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use crate::board::Board;
+use crate::game::{Intent, PlayerId};
+use crate::record::{notation, parse_move, GameRecord, GameResult, RecordError};
+use crate::review::{start_board, ReviewError};
+
+/**
+ * MoveStats is how one move from one position fared in the corpus.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MoveStats {
+    pub player: PlayerId,
+    pub intent: Intent,
+    // games in which the move was played, with or without a result
+    pub games: u32,
+    // results of those games for player
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MoveStats {
+    /**
+     * win_rate
+     * ret - Share of the decided games won by the player, counting draws as half, or None
+     * if no game with the move has a result.
+     */
+    pub fn win_rate(&self) -> Option<f32> {
+        let decided = self.wins + self.draws + self.losses;
+        (decided > 0).then(|| (self.wins as f32 + self.draws as f32 / 2.0) / decided as f32)
+    }
+}
+
+/**
+ * OpeningStats collects MoveStats for the positions in the first plies of many games.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OpeningStats {
+    // moves from each position and side to move, most played first
+    positions: HashMap<(u64, PlayerId), Vec<MoveStats>>,
+}
+
+impl OpeningStats {
+    /**
+     * build
+     * Collect statistics from a corpus of records.
+     * records - Games to learn from.
+     * plies - Number of moves at the start of each game to collect.
+     * ret - The statistics, or ReviewError::Replay if a record holds an illegal move.
+     */
+    pub fn build(records: &[GameRecord], plies: usize) -> Result<OpeningStats, ReviewError> {
+        let mut stats = OpeningStats::default();
+        for record in records {
+            stats.add(record, plies)?;
+        }
+        Ok(stats)
+    }
+
+    /**
+     * add
+     * Collect statistics from one more record.
+     * record - Game to learn from. Its variations are ignored.
+     * plies - Number of moves at the start of the game to collect.
+     * ret - Ok, or ReviewError::Replay if the record holds an illegal move. Moves before
+     * the illegal one are still counted.
+     */
+    pub fn add(&mut self, record: &GameRecord, plies: usize) -> Result<(), ReviewError> {
+        let mut board = start_board(record);
+        for (idx, recorded) in record.moves.iter().take(plies).enumerate() {
+            let moves = self.positions.entry((board.position_hash(), recorded.player)).or_default();
+            let stats = match moves.iter().position(|stats| stats.intent == recorded.intent) {
+                Some(found) => &mut moves[found],
+                None => {
+                    moves.push(MoveStats { player: recorded.player, intent: recorded.intent, games: 0, wins: 0, draws: 0, losses: 0 });
+                    moves.last_mut().unwrap()
+                },
+            };
+            stats.games += 1;
+            match record.result {
+                Some(GameResult::Win(winner)) if winner == recorded.player => stats.wins += 1,
+                Some(GameResult::Win(_)) => stats.losses += 1,
+                Some(GameResult::Draw) => stats.draws += 1,
+                None => (),
+            }
+            moves.sort_by_key(|stats| std::cmp::Reverse(stats.games));
+            board.apply(recorded.player, recorded.intent).map_err(|source| ReviewError::Replay {
+                ply: idx + 1,
+                notation: notation(recorded.intent),
+                source,
+            })?;
+        }
+        Ok(())
+    }
+
+    /**
+     * moves
+     * Look up the moves played from a position.
+     * board - Position to look up.
+     * player - Id of player to move.
+     * ret - Moves played from the position, most played first; empty if it was never reached.
+     */
+    pub fn moves(&self, board: &Board, player: PlayerId) -> &[MoveStats] {
+        self.positions.get(&(board.position_hash(), player)).map_or(&[], Vec::as_slice)
+    }
+
+    /**
+     * positions
+     * ret - Number of distinct positions, counting each side to move separately.
+     */
+    pub fn positions(&self) -> usize {
+        self.positions.len()
+    }
+
+    /**
+     * report
+     * Describe the most played positions and their moves, for reading.
+     * limit - Most positions to describe.
+     * ret - One paragraph per position, most played first.
+     */
+    pub fn report(&self, limit: usize) -> String {
+        let mut positions: Vec<(&(u64, PlayerId), &Vec<MoveStats>)> = self.positions.iter().collect();
+        let games = |moves: &Vec<MoveStats>| moves.iter().map(|stats| stats.games).sum::<u32>();
+        positions.sort_by_key(|(key, moves)| (std::cmp::Reverse(games(moves)), key.0, key.1.index()));
+        let mut report = String::new();
+        for ((hash, player), moves) in positions.into_iter().take(limit) {
+            report.push_str(&format!("Position {:016x}, {} to move, {} games\n", hash, player, games(moves)));
+            for stats in moves {
+                let rate = stats.win_rate().map_or(String::from("-"), |rate| format!("{:.0}%", rate * 100.0));
+                report.push_str(&format!("  {:<20} {:>5} games  {:>4} score\n", notation(stats.intent), stats.games, rate));
+            }
+        }
+        report
+    }
+
+    /**
+     * load
+     * Read statistics saved by save.
+     * path - File to read.
+     * ret - The statistics, or a RecordError if they could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<OpeningStats, RecordError> {
+        OpeningStats::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * save
+     * Write the statistics to a file, replacing anything already there.
+     * path - File to write.
+     * ret - Ok, or RecordError::Io if the file could not be written.
+     */
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecordError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /**
+     * parse
+     * Parse saved statistics.
+     * text - Contents of a statistics file.
+     * ret - The statistics, or RecordError::Parse describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<OpeningStats, RecordError> {
+        let mut stats = OpeningStats::default();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| RecordError::Parse { line: idx + 1, message };
+            let mut words = line.splitn(6, ' ');
+            let mut field = || words.next().ok_or_else(|| err(String::from("missing field")));
+            let hash = u64::from_str_radix(field()?, 16).map_err(|e| err(e.to_string()))?;
+            let mut counts = [0; 4];
+            for count in counts.iter_mut() {
+                *count = field()?.parse().map_err(|e: std::num::ParseIntError| err(e.to_string()))?;
+            }
+            let recorded = parse_move(field()?).map_err(err)?;
+            let [games, wins, draws, losses] = counts;
+            stats.positions.entry((hash, recorded.player)).or_default()
+                .push(MoveStats { player: recorded.player, intent: recorded.intent, games, wins, draws, losses });
+        }
+        for moves in stats.positions.values_mut() {
+            moves.sort_by_key(|stats| std::cmp::Reverse(stats.games));
+        }
+        Ok(stats)
+    }
+}
+
+impl Display for OpeningStats {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mut keys: Vec<&(u64, PlayerId)> = self.positions.keys().collect();
+        keys.sort_by_key(|(hash, player)| (*hash, player.index()));
+        for key in keys {
+            let (hash, player) = key;
+            let side = match player {
+                PlayerId::A => "a",
+                PlayerId::B => "b",
+            };
+            for stats in &self.positions[key] {
+                writeln!(formatter, "{:016x} {} {} {} {} {} {}", hash, stats.games, stats.wins, stats.draws, stats.losses, side, notation(stats.intent))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Variant;
+    use crate::test_support::fought_record;
+    use crate::vec::Vec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn build() {
+        let records: Vec<GameRecord> = [
+            "result a\na move 6,2 5,2\nb place 4,0",
+            "result b\na move 6,2 5,2\nb place 3,0",
+            "a place 4,4\nb place 4,0",
+        ].iter().map(|text| GameRecord::parse(text).unwrap()).collect();
+        let stats = OpeningStats::build(&records, 1).unwrap();
+        let start = Board::new();
+        let moves = stats.moves(&start, PlayerId::A);
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0].intent, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2)));
+        assert_eq!((moves[0].games, moves[0].wins, moves[0].losses), (2, 1, 1));
+        assert_eq!(moves[0].win_rate(), Some(0.5));
+        assert_eq!(moves[1].win_rate(), None);
+        assert!(stats.moves(&start, PlayerId::B).is_empty());
+        assert_eq!(stats.positions(), 1);
+
+        // Later plies are collected from the position reached
+        let stats = OpeningStats::build(&records, 2).unwrap();
+        let mut after = Board::new();
+        after.apply(PlayerId::A, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2))).unwrap();
+        let replies = stats.moves(&after, PlayerId::B);
+        assert_eq!(replies.len(), 2);
+        assert!(replies.iter().all(|stats| stats.games == 1));
+        assert!(stats.report(10).contains("place 4,0"));
+
+        let illegal = GameRecord::parse("a move 6,2 5,2\na place 9,9").unwrap();
+        assert!(matches!(OpeningStats::build(&[illegal], 2), Err(ReviewError::Replay { ply: 2, .. })));
+        assert_eq!(OpeningStats::build(&[GameRecord::new(Variant::Standard)], 5).unwrap().positions(), 0);
+    }
+
+    #[test]
+    fn fought() {
+        let record = fought_record(&mut StdRng::seed_from_u64(7), 12);
+        let once = OpeningStats::build(std::slice::from_ref(&record), record.moves.len()).unwrap();
+        // Each copy of the game replays its own dice, so the second files every move under
+        // the same positions as the first
+        let twice = OpeningStats::build(&[record.clone(), record.clone()], record.moves.len()).unwrap();
+        assert_eq!(twice.positions(), once.positions());
+        let games = |stats: &OpeningStats| stats.positions.values().flatten().map(|stats| stats.games).sum::<u32>();
+        assert_eq!(games(&once), record.moves.len() as u32);
+        assert_eq!(games(&twice), 2 * games(&once));
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = [GameRecord::parse("result draw\na move 6,2 5,2\nb place 4,0").unwrap()];
+        let stats = OpeningStats::build(&records, 4).unwrap();
+        let written = stats.to_string();
+        assert_eq!(OpeningStats::parse(&written).unwrap(), stats);
+        assert!(matches!(OpeningStats::parse("zz 1 0 0 0 a swap"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(OpeningStats::parse("\n00 1 0 0 a swap"), Err(RecordError::Parse { line: 2, .. })));
+    }
+}
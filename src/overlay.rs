@@ -0,0 +1,153 @@
+/**
+ * Overlay holds marks front ends lay over a board, such as the checker the user selected or
+ * squares under threat, apart from the game so they never affect the rules. Board renderers
+ * draw the marks next to the pieces they belong to.
+ */
+use crate::board::Board;
+use crate::grid::Grid;
+use crate::vec::Vec2;
+
+/**
+ * Mark is why a checker square or stone corner is marked.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    // chosen by the user, eg the checker about to move
+    Selected,
+    // could be attacked next turn
+    Threatened,
+    // part of a move suggested by a hint or an engine
+    Suggested,
+}
+
+impl Mark {
+    /**
+     * symbol
+     * ret - Character drawn beside a marked square or corner.
+     */
+    pub fn symbol(&self) -> char {
+        match self {
+            Mark::Selected => '*',
+            Mark::Threatened => '!',
+            Mark::Suggested => '?',
+        }
+    }
+}
+
+/**
+ * Overlay is the marks laid over one board.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overlay {
+    // one per checker square
+    checkers: Grid<Option<Mark>>,
+    // one per stone corner, so one more column and row than checkers
+    stones: Grid<Option<Mark>>,
+}
+
+impl Overlay {
+    /**
+     * new
+     * Create an overlay with no marks.
+     * board - Board the overlay is laid over, which sets its size.
+     */
+    pub fn new(board: &Board) -> Overlay {
+        let (width, height) = (board.width(), board.height());
+        Overlay { checkers: Grid::new(width, height, None), stones: Grid::new(width + 1, height + 1, None) }
+    }
+
+    /**
+     * mark_checker
+     * Mark a checker square, replacing any mark it had.
+     * pos - Square to mark.
+     * mark - Mark to give it, or None to remove its mark.
+     * ret - False if the square is off the board.
+     */
+    pub fn mark_checker(&mut self, pos: Vec2, mark: Option<Mark>) -> bool {
+        self.checkers.get_mut(pos).map(|cell| *cell = mark).is_some()
+    }
+
+    /**
+     * mark_stone
+     * Mark a stone corner, replacing any mark it had.
+     * pos - Corner to mark.
+     * mark - Mark to give it, or None to remove its mark.
+     * ret - False if the corner is off the board.
+     */
+    pub fn mark_stone(&mut self, pos: Vec2, mark: Option<Mark>) -> bool {
+        self.stones.get_mut(pos).map(|cell| *cell = mark).is_some()
+    }
+
+    /**
+     * checker_mark
+     * pos - Checker square to look at.
+     * ret - Its mark, or None if it has none or is off the board.
+     */
+    pub fn checker_mark(&self, pos: Vec2) -> Option<Mark> {
+        self.checkers.get(pos).copied().flatten()
+    }
+
+    /**
+     * stone_mark
+     * pos - Stone corner to look at.
+     * ret - Its mark, or None if it has none or is off the board.
+     */
+    pub fn stone_mark(&self, pos: Vec2) -> Option<Mark> {
+        self.stones.get(pos).copied().flatten()
+    }
+
+    /**
+     * marked_checkers
+     * ret - Every marked checker square with its mark, row by row.
+     */
+    pub fn marked_checkers(&self) -> Vec<(Vec2, Mark)> {
+        self.checkers.iter().filter_map(|(pos, mark)| mark.map(|mark| (pos, mark))).collect()
+    }
+
+    /**
+     * marked_stones
+     * ret - Every marked stone corner with its mark, row by row.
+     */
+    pub fn marked_stones(&self) -> Vec<(Vec2, Mark)> {
+        self.stones.iter().filter_map(|(pos, mark)| mark.map(|mark| (pos, mark))).collect()
+    }
+
+    /**
+     * clear
+     * Remove every mark.
+     */
+    pub fn clear(&mut self) {
+        self.checkers = Grid::new(self.checkers.width(), self.checkers.height(), None);
+        self.stones = Grid::new(self.stones.width(), self.stones.height(), None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks() {
+        let board = Board::new();
+        let mut overlay = Overlay::new(&board);
+        assert!(overlay.mark_checker(Vec2::new(6, 2), Some(Mark::Selected)));
+        assert!(overlay.mark_stone(Vec2::new(8, 6), Some(Mark::Suggested)));
+        assert!(!overlay.mark_checker(Vec2::new(8, 6), Some(Mark::Threatened)));
+        assert_eq!(overlay.checker_mark(Vec2::new(6, 2)), Some(Mark::Selected));
+        assert_eq!(overlay.stone_mark(Vec2::new(8, 6)), Some(Mark::Suggested));
+        assert_eq!(overlay.checker_mark(Vec2::new(-1, 0)), None);
+        assert_eq!(overlay.marked_checkers(), vec![(Vec2::new(6, 2), Mark::Selected)]);
+
+        // Marks show beside their pieces, and the board itself is drawn as before
+        let drawn = board.as_string_with(&overlay);
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(lines[5].chars().nth(12), Some('*'));
+        assert!(lines[12].ends_with('?'));
+        assert_eq!(drawn.replace(['*', '?'], " "), board.as_string());
+
+        overlay.mark_checker(Vec2::new(6, 2), None);
+        overlay.clear();
+        assert!(overlay.marked_checkers().is_empty() && overlay.marked_stones().is_empty());
+        assert_eq!(board.as_string_with(&overlay), board.as_string());
+    }
+}
@@ -0,0 +1,213 @@
+/**
+ * Animated playback of moves in the terminal. A slide is shown as its stone moving one
+ * corner at a time, and an attack as its target flashing before and after the dice change
+ * its height, with a pause between frames. Reviews animate the moves stepped through, and
+ * a Spectator animates each move of a live game as it is made.
+ */
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::Board;
+use crate::events::{GameEvent, Observer};
+use crate::game::{GameView, Intent, Stone};
+use crate::overlay::{Mark, Overlay};
+
+// pause between frames when none is chosen
+pub const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(150);
+// clears the terminal and puts the cursor at the top left
+const CLEAR: &str = "\x1b[2J\x1b[H";
+// times an attack's target flashes before the dice are shown
+const FLASHES: usize = 2;
+
+/**
+ * Playback draws the frames of a move to the terminal, pausing between them.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Playback {
+    delay: Duration,
+}
+
+impl Default for Playback {
+    fn default() -> Playback {
+        Playback::new(DEFAULT_FRAME_DELAY)
+    }
+}
+
+impl Playback {
+    /**
+     * new
+     * delay - Pause between frames.
+     */
+    pub fn new(delay: Duration) -> Playback {
+        Playback { delay }
+    }
+
+    /**
+     * delay
+     * ret - Pause between frames.
+     */
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /**
+     * animate
+     * Draw each frame over the last, pausing after each one.
+     * frames - Boards drawn as text, from frames().
+     * output - Terminal to draw to.
+     * ret - Ok, or the error writing to output.
+     */
+    pub fn animate<W: Write>(&self, frames: &[String], output: &mut W) -> io::Result<()> {
+        for frame in frames {
+            write!(output, "{}{}", CLEAR, frame)?;
+            output.flush()?;
+            thread::sleep(self.delay);
+        }
+        Ok(())
+    }
+}
+
+/**
+ * frames
+ * Draw the steps of a move between the board before it and the board after. Slides step
+ * their stone one corner at a time, and attacks flash their target, then show it marked
+ * after the dice. Other moves, and moves that can not be made on before, have no frames.
+ * before - Board before the move.
+ * intent - Move made.
+ * after - Board after the move, which is not itself a frame but may be marked in one.
+ * ret - The frames, in order.
+ */
+pub fn frames(before: &Board, intent: Intent, after: &Board) -> Vec<String> {
+    let mut frames = Vec::new();
+    match intent {
+        Intent::SlideStone(from, direction) => {
+            let Ok(to) = before.slide_stone_result(from, direction) else {
+                return frames;
+            };
+            let mut board = before.clone();
+            let mut at = from;
+            while at + direction.as_vec() != to {
+                let next = at + direction.as_vec();
+                let (Ok(stone), Ok(_)) = (board.stone_at(at).copied(), board.stone_at(next)) else {
+                    break;
+                };
+                *board.mut_stone_at(next).expect("checked corner") = stone;
+                *board.mut_stone_at(at).expect("checked corner") = Stone::empty();
+                frames.push(board.as_string());
+                at = next;
+            }
+        },
+        Intent::FireChecker(at) => {
+            let mut marked = Overlay::new(before);
+            marked.mark_checker(at, Some(Mark::Threatened));
+            for _ in 0..FLASHES {
+                frames.push(before.as_string_with(&marked));
+                frames.push(before.as_string());
+            }
+            let mut marked = Overlay::new(after);
+            marked.mark_checker(at, Some(Mark::Threatened));
+            frames.push(after.as_string_with(&marked));
+        },
+        _ => (),
+    }
+    frames
+}
+
+/**
+ * Spectator is an observer that animates a live game in the terminal: the board when the
+ * game starts or a turn is taken back, and each move as it is made.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Spectator {
+    playback: Playback,
+    // board as last drawn, which the next move is animated from
+    board: Option<Board>,
+}
+
+impl Spectator {
+    /**
+     * new
+     * playback - How to animate the moves.
+     */
+    pub fn new(playback: Playback) -> Spectator {
+        Spectator { playback, board: None }
+    }
+
+    /* Helper function drawing @frames and then the board in @view, remembering it. */
+    fn show(&mut self, frames: &[String], view: &GameView) {
+        let mut stdout = io::stdout();
+        let drawn = self.playback.animate(frames, &mut stdout)
+            .and_then(|()| write!(stdout, "{}{}", CLEAR, view.board))
+            .and_then(|()| stdout.flush());
+        if let Err(err) = drawn {
+            eprintln!("Could not draw the game: {}", err);
+        }
+        self.board = Some(view.board.clone());
+    }
+}
+
+impl Observer for Spectator {
+    fn on_event(&mut self, event: &GameEvent, view: &GameView) {
+        match event {
+            GameEvent::TurnStarted { .. } if self.board.is_none() => self.show(&[], view),
+            GameEvent::MoveMade { intent, .. } => {
+                let frames = self.board.as_ref().map(|before| frames(before, *intent, view.board)).unwrap_or_default();
+                self.show(&frames, view);
+            },
+            GameEvent::TakenBack { .. } => self.show(&[], view),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Direction;
+    use crate::game::{Checker, CheckerHeight, PlayerId};
+    use crate::vec::Vec2;
+
+    #[test]
+    fn slide() {
+        let mut before = Board::new();
+        before.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::B)).unwrap();
+        let slide = Intent::SlideStone(Vec2::new(4, 0), Direction::Down);
+        let mut after = before.clone();
+        after.apply(PlayerId::B, slide).unwrap();
+        let to = after.stones_for_player(PlayerId::B)[0];
+
+        // One frame per corner passed, with the stone on it
+        let frames = super::frames(&before, slide, &after);
+        assert_eq!(frames.len(), to.y as usize - 1);
+        let mut stepped = before.clone();
+        *stepped.mut_stone_at(Vec2::new(4, 0)).unwrap() = Stone::empty();
+        *stepped.mut_stone_at(Vec2::new(4, 1)).unwrap() = Stone::new(PlayerId::B);
+        assert_eq!(frames[0], stepped.as_string());
+        assert!(frames.iter().all(|frame| *frame != after.as_string()));
+
+        assert!(super::frames(&before, Intent::PlaceStone(Vec2::new(0, 0)), &after).is_empty());
+    }
+
+    #[test]
+    fn fire() {
+        let mut before = Board::from_seed([3; 32]);
+        before.clear_checkers();
+        let target = Vec2::new(3, 2);
+        before.place_checker_at(target, Checker::new(CheckerHeight::THREE, PlayerId::B)).unwrap();
+        before.place_checker_at(Vec2::new(2, 2), Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
+        let mut after = before.clone();
+        after.apply(PlayerId::A, Intent::FireChecker(target)).unwrap();
+
+        let frames = super::frames(&before, Intent::FireChecker(target), &after);
+        assert_eq!(frames.len(), 2 * FLASHES + 1);
+        assert!(frames[0].contains(Mark::Threatened.symbol()));
+        assert_eq!(frames[1], before.as_string());
+        assert_eq!(frames[2 * FLASHES].replace(Mark::Threatened.symbol(), " "), after.as_string());
+
+        let mut output = Vec::new();
+        Playback::new(Duration::ZERO).animate(&frames, &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert_eq!(text.matches(CLEAR).count(), frames.len());
+    }
+}
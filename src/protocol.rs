@@ -0,0 +1,378 @@
+/**
+ * A text protocol for driving the engine from another program, in the style of chess
+ * engine protocols: commands arrive one per line, and the engine answers with lines of its
+ * own. Searches run on a background thread, so the engine keeps reading commands, and
+ * stop ends a search with the best move found so far.
+ *
+ *   uci                                  lists the engine's options, then uciok
+ *   isready                              readyok once earlier commands are done
+ *   setoption name <name> value <value>  see the engine module for the options
+ *   position startpos                    the standard game's starting position
+ *   position scenario <directives>       scenario directives separated by ;
+ *   move a|b <move in record notation>   plays a move in the position
+ *   go [depth <plies>]                   searches, then sends bestmove
+ *   go infinite                          searches until stop
+ *   stop                                 sends bestmove for the search running
+ *   quit
+ *
+ * Each search ends with:
+ *
+ *   info depth <plies> nodes <n> nps <n> score <points> pv <moves separated by ", ">
+ *   bestmove a|b <move>
+ *
+ * Problems are reported as "info string <message>". Fires played with move are resolved
+ * with the engine's own dice, so front ends should send the position a fire leaves instead.
+ */
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::config::GameConfig;
+use crate::engine::{Engine, EngineOptions, SearchInfo};
+use crate::game::{Decide, Game, Intent, MoveRequest, Player, PlayerId, STARTING_STONES};
+use crate::record::{notation, parse_move};
+use crate::scenario::{number, Scenario};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProtocolError {
+    // An answer could not be written
+    #[error("Could not write to the front end: {0}")]
+    Io(#[from] io::Error),
+}
+
+// how often serve checks for a finished search while waiting for commands
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/* Decider for the protocol's copy of the game, which is never asked for a move. */
+struct Replayed;
+
+impl Decide for Replayed {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        *request.all_moves().next().expect("no moves offered")
+    }
+}
+
+/* Running is a search on the background thread. */
+struct Running {
+    player: PlayerId,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<(Intent, SearchInfo)>,
+}
+
+/**
+ * EngineProtocol is one session of the protocol: the engine, the position, and any search
+ * running.
+ */
+pub struct EngineProtocol {
+    engine: Engine,
+    config: GameConfig,
+    // moves played from the position config starts from
+    moves: Vec<(PlayerId, Intent)>,
+    running: Option<Running>,
+    quit: bool,
+}
+
+impl Default for EngineProtocol {
+    fn default() -> EngineProtocol {
+        EngineProtocol::new(Engine::default())
+    }
+}
+
+impl EngineProtocol {
+    /**
+     * new
+     * Start a session at the standard starting position.
+     * engine - Engine to search with.
+     */
+    pub fn new(engine: Engine) -> EngineProtocol {
+        EngineProtocol { engine, config: GameConfig::default(), moves: Vec::new(), running: None, quit: false }
+    }
+
+    /**
+     * handle
+     * Carry out one command.
+     * line - Command received, with or without its line ending.
+     * ret - Lines to send back, without line endings.
+     */
+    pub fn handle(&mut self, line: &str) -> Vec<String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let result = match words.first().copied() {
+            None => Ok(Vec::new()),
+            Some("uci") => Ok(self.describe()),
+            Some("isready") => Ok(vec![String::from("readyok")]),
+            Some("setoption") => self.set_option(&words[1..]),
+            Some("position") => self.set_position(line.trim()["position".len()..].trim()),
+            Some("move") => self.play(line.trim()["move".len()..].trim()),
+            Some("go") => self.go(&words[1..]),
+            Some("stop") => Ok(self.stop()),
+            Some("quit") => {
+                self.stop();
+                self.quit = true;
+                Ok(Vec::new())
+            },
+            Some(other) => Err(format!("unknown command '{}'", other)),
+        };
+        result.unwrap_or_else(|message| vec![format!("info string {}", message)])
+    }
+
+    /**
+     * poll
+     * ret - The lines ending a search that has finished by itself, or none.
+     */
+    pub fn poll(&mut self) -> Vec<String> {
+        if self.running.as_ref().is_some_and(|running| running.handle.is_finished()) {
+            self.stop()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /**
+     * searching
+     * ret - True while a search is running.
+     */
+    pub fn searching(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /**
+     * quit
+     * ret - True once quit has been received.
+     */
+    pub fn quit(&self) -> bool {
+        self.quit
+    }
+
+    /* Helper function answering uci. */
+    fn describe(&self) -> Vec<String> {
+        let options = self.engine.options();
+        let mut lines = vec![String::from("id name ironclad")];
+        for name in EngineOptions::NAMES {
+            lines.push(format!("option name {} default {}", name, options.get(name).unwrap_or_default()));
+        }
+        lines.push(String::from("uciok"));
+        lines
+    }
+
+    /* Helper function answering setoption name <name> value <value>. */
+    fn set_option(&mut self, args: &[&str]) -> Result<Vec<String>, String> {
+        if self.running.is_some() {
+            return Err(String::from("options can not change during a search"));
+        }
+        match args {
+            ["name", name, "value", value] => {
+                self.engine.set_option(name, value).map_err(|error| error.to_string())?;
+                Ok(Vec::new())
+            },
+            _ => Err(String::from("expected setoption name <name> value <value>")),
+        }
+    }
+
+    /* Helper function answering position, with everything after the command word in @rest. */
+    fn set_position(&mut self, rest: &str) -> Result<Vec<String>, String> {
+        let config = match rest.split_once(char::is_whitespace).unwrap_or((rest, "")) {
+            ("startpos", "") => GameConfig::default(),
+            ("scenario", directives) => Scenario::parse(&directives.replace(';', "\n")).map_err(|error| error.to_string())?.config,
+            _ => return Err(String::from("expected position startpos or position scenario <directives>")),
+        };
+        self.config = config;
+        self.moves.clear();
+        Ok(Vec::new())
+    }
+
+    /* Helper function answering move, with the player and move in @rest. */
+    fn play(&mut self, rest: &str) -> Result<Vec<String>, String> {
+        let recorded = parse_move(rest)?;
+        let mut moves = self.moves.clone();
+        moves.push((recorded.player, recorded.intent));
+        self.with_game(&moves, |_| ())?;
+        self.moves = moves;
+        Ok(Vec::new())
+    }
+
+    /* Helper function answering go [depth <plies>] or go infinite. */
+    fn go(&mut self, args: &[&str]) -> Result<Vec<String>, String> {
+        if self.running.is_some() {
+            return Err(String::from("already searching"));
+        }
+        let job = self.with_game(&self.moves, |game| {
+            if game.check_for_outcome().is_some() {
+                return Err(String::from("the game is over"));
+            }
+            let player = game.side_to_move();
+            let request = game.move_request(player);
+            if request.all_moves().next().is_none() {
+                return Err(format!("Player {} has no legal moves", player));
+            }
+            Ok((player, self.engine.job(&request)))
+        })??;
+        let (player, mut job) = job;
+        match args {
+            [] => (),
+            ["infinite"] => job = job.infinite(),
+            ["depth", plies] => job.set_depth(number(plies)?),
+            _ => return Err(String::from("expected go, go depth <plies> or go infinite")),
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let handle = thread::spawn(move || job.run(&flag));
+        self.running = Some(Running { player, stop, handle });
+        Ok(Vec::new())
+    }
+
+    /* Helper function stopping any search running, and reporting what it found. */
+    fn stop(&mut self) -> Vec<String> {
+        let Some(running) = self.running.take() else {
+            return Vec::new();
+        };
+        running.stop.store(true, Ordering::Relaxed);
+        let (intent, info) = running.handle.join().expect("search thread panicked");
+        let pv: Vec<String> = info.pv.iter().map(|(player, intent)| move_text(*player, *intent)).collect();
+        vec![
+            format!("info depth {} nodes {} nps {} score {} pv {}", info.depth, info.nodes, info.nps(), info.score, pv.join(", ")),
+            format!("bestmove {}", move_text(running.player, intent)),
+        ]
+    }
+
+    /*
+     * Helper function setting up a game at the session's position, playing @moves in it and
+     * running @action on it.
+     */
+    fn with_game<T>(&self, moves: &[(PlayerId, Intent)], action: impl FnOnce(&mut Game) -> T) -> Result<T, String> {
+        let (replayed_a, replayed_b) = (Replayed, Replayed);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &replayed_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &replayed_b);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, self.config.clone());
+        game.set_silent(true);
+        for (player, intent) in moves {
+            if game.check_for_outcome().is_some() {
+                return Err(String::from("the game is over"));
+            }
+            if game.side_to_move() != *player {
+                return Err(format!("Player {} is not to move", player));
+            }
+            game.try_apply_move(*player, *intent).map_err(|error| error.to_string())?;
+        }
+        Ok(action(&mut game))
+    }
+}
+
+/* Helper function writing a move as the protocol and records do. */
+fn move_text(player: PlayerId, intent: Intent) -> String {
+    let side = match player {
+        PlayerId::A => "a",
+        PlayerId::B => "b",
+    };
+    format!("{} {}", side, notation(intent))
+}
+
+/**
+ * serve
+ * Run a session, reading commands from input and writing answers to output, until quit
+ * or the end of input. A search running at the end of input is stopped and reported.
+ * engine - Engine to search with.
+ * input - Where commands come from. It is read on a thread of its own.
+ * output - Where answers go.
+ * ret - Ok, or ProtocolError::Io if an answer could not be written.
+ */
+pub fn serve<R: BufRead + Send + 'static, W: Write>(engine: Engine, input: R, mut output: W) -> Result<(), ProtocolError> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in input.lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let mut protocol = EngineProtocol::new(engine);
+    while !protocol.quit() {
+        let mut lines = match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(line) => protocol.handle(&line),
+            Err(RecvTimeoutError::Timeout) => Vec::new(),
+            Err(RecvTimeoutError::Disconnected) => {
+                let lines = protocol.handle("stop");
+                protocol.handle("quit");
+                lines
+            },
+        };
+        lines.extend(protocol.poll());
+        for line in lines {
+            writeln!(output, "{}", line)?;
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_STEP: &str = "position scenario rule elimination on; stones a 0; stones b 0; checker a 2 0 1; checker b 6 3 1";
+
+    /* Helper function waiting for a search that runs to its depth. */
+    fn finish(protocol: &mut EngineProtocol) -> Vec<String> {
+        loop {
+            let lines = protocol.poll();
+            if !lines.is_empty() {
+                return lines;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn commands() {
+        let mut protocol = EngineProtocol::default();
+        let described = protocol.handle("uci");
+        assert_eq!(described.first().map(String::as_str), Some("id name ironclad"));
+        assert_eq!(described.last().map(String::as_str), Some("uciok"));
+        assert_eq!(protocol.handle("isready"), ["readyok"]);
+        assert!(protocol.handle("setoption name depth value 2").is_empty());
+        assert!(protocol.handle("setoption name ponder value on")[0].starts_with("info string"));
+        assert!(protocol.handle("castle")[0].starts_with("info string unknown command"));
+
+        // A's checker walks in, and B's reply is searched from the position after it
+        assert!(protocol.handle(ONE_STEP).is_empty());
+        assert!(protocol.handle("move a move 2,0 1,0").is_empty());
+        assert!(protocol.handle("move a move 1,0 0,0")[0].starts_with("info string"));
+        protocol.handle("go depth 1");
+        assert!(protocol.searching());
+        assert!(protocol.handle("go")[0].starts_with("info string already searching"));
+        let lines = finish(&mut protocol);
+        assert!(lines[0].starts_with("info depth 1 "));
+        assert!(lines[1].starts_with("bestmove b "));
+        assert!(!protocol.searching());
+
+        protocol.handle("move b move 6,3 7,3");
+        assert!(protocol.handle("go")[0].contains("the game is over"));
+        protocol.handle("quit");
+        assert!(protocol.quit());
+    }
+
+    #[test]
+    fn infinite() {
+        let mut protocol = EngineProtocol::default();
+        protocol.handle(ONE_STEP);
+        protocol.handle("go infinite");
+        thread::sleep(Duration::from_millis(50));
+        assert!(protocol.poll().is_empty());
+        let lines = protocol.handle("stop");
+        assert!(lines[0].starts_with("info depth "));
+        assert!(lines[1].starts_with("bestmove a move 2,0 1,"));
+        assert!(protocol.handle("stop").is_empty());
+    }
+
+    #[test]
+    fn serve() {
+        let input = io::Cursor::new("isready\nposition scenario stones a 0; stones b 0; checker a 1 0 1; checker b 6 3 1\ngo depth 1\n");
+        let mut output = Vec::new();
+        super::serve(Engine::default(), input, &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.starts_with("readyok\n"));
+        assert!(text.contains("\nbestmove a move 1,0 0,"));
+    }
+}
@@ -0,0 +1,219 @@
+/**
+ * Puzzle solving: proves or refutes that a player can force a win within a number of plies
+ * from a scenario's position, by searching every line under deterministic combat, so every
+ * move has one outcome. Generated puzzles are validated with it before they are published.
+ *
+ * A ply is one action, so under the actions_per_turn rule a turn spans several plies. The
+ * search grows quickly with the number of plies and pieces: small positions with a few
+ * plies are practical.
+ */
+use crate::game::{Decide, Game, GameOutcome, Intent, MoveRequest, Player, PlayerId, STARTING_STONES};
+use crate::scenario::Scenario;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PuzzleError {
+    // The scenario's game is over before anyone moves
+    #[error("The game is already over")]
+    AlreadyOver,
+    // The target is a win for the player who does not move first
+    #[error("Player {0} does not move first")]
+    NotToMove(PlayerId),
+    // No first move forces the win in time
+    #[error("Player {winner} can not force a win within {plies} plies")]
+    Refuted { winner: PlayerId, plies: usize },
+    // More than one first move forces the win, so the puzzle has no single answer
+    #[error("{0} different first moves force the win")]
+    SeveralKeys(usize),
+}
+
+/**
+ * Target is what a puzzle asks for: a win for a player within a number of plies.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Target {
+    pub winner: PlayerId,
+    pub plies: usize,
+}
+
+/**
+ * Solution is the result of searching a puzzle.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Solution {
+    // every first move that forces the win within the target, in the order they are offered
+    pub keys: Vec<(PlayerId, Intent)>,
+    // the quickest forcing line from any key, against the longest defence; empty if refuted
+    pub line: Vec<(PlayerId, Intent)>,
+    // positions searched
+    pub nodes: u64,
+}
+
+impl Solution {
+    /**
+     * proven
+     * ret - True if the target can be forced.
+     */
+    pub fn proven(&self) -> bool {
+        !self.keys.is_empty()
+    }
+}
+
+/* Decider for the game being searched, which is never asked for a move. */
+struct Searcher;
+
+impl Decide for Searcher {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        *request.all_moves().next().expect("no moves offered")
+    }
+}
+
+/**
+ * solve
+ * Search every line from a scenario's position, with deterministic combat whatever the
+ * scenario says, for the moves that force the target.
+ * scenario - Position and rules of the puzzle.
+ * target - Who must win, and within how many plies.
+ * ret - The solution, PuzzleError::AlreadyOver if the game is over before anyone moves, or
+ * PuzzleError::NotToMove if the winner does not move first.
+ */
+pub fn solve(scenario: &Scenario, target: Target) -> Result<Solution, PuzzleError> {
+    let mut config = scenario.config.clone();
+    config.rules.deterministic_combat = true;
+    let (searcher_a, searcher_b) = (Searcher, Searcher);
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &searcher_a);
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &searcher_b);
+    let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+    game.set_silent(true);
+    if game.check_for_outcome().is_some() {
+        return Err(PuzzleError::AlreadyOver);
+    }
+    let player = game.side_to_move();
+    if player != target.winner {
+        return Err(PuzzleError::NotToMove(target.winner));
+    }
+
+    let mut solution = Solution::default();
+    let moves: Vec<Intent> = game.move_request(player).all_moves().copied().collect();
+    for intent in moves {
+        if game.try_apply_move(player, intent).is_err() {
+            continue;
+        }
+        // Searching one ply deeper at a time finds the quickest line
+        let line = (0..target.plies).find_map(|depth| forced_win(&mut game, target.winner, depth, &mut solution.nodes));
+        game.undo_move();
+        if let Some(mut line) = line {
+            line.insert(0, (player, intent));
+            if solution.line.is_empty() || line.len() < solution.line.len() {
+                solution.line = line;
+            }
+            solution.keys.push((player, intent));
+        }
+    }
+    Ok(solution)
+}
+
+/**
+ * validate
+ * Check a puzzle is fit to publish: its target can be forced, and by only one first move.
+ * scenario - Position and rules of the puzzle.
+ * target - Who must win, and within how many plies.
+ * ret - The solution, or a PuzzleError saying what is wrong with the puzzle.
+ */
+pub fn validate(scenario: &Scenario, target: Target) -> Result<Solution, PuzzleError> {
+    let solution = solve(scenario, target)?;
+    match solution.keys.len() {
+        0 => Err(PuzzleError::Refuted { winner: target.winner, plies: target.plies }),
+        1 => Ok(solution),
+        keys => Err(PuzzleError::SeveralKeys(keys)),
+    }
+}
+
+/*
+ * Helper function finding a line in which @winner wins within @plies plies from the game's
+ * position, whatever the other side plays. The winner takes the first move that forces
+ * the win, and the other side the one that holds out longest. Every move is taken back
+ * before returning, so the game is left as it was.
+ */
+fn forced_win(game: &mut Game, winner: PlayerId, plies: usize, nodes: &mut u64) -> Option<Vec<(PlayerId, Intent)>> {
+    *nodes += 1;
+    if let Some(outcome) = game.check_for_outcome() {
+        return matches!(outcome, GameOutcome::Win { winner: won, .. } if won == winner).then(Vec::new);
+    }
+    if plies == 0 {
+        return None;
+    }
+    let player = game.side_to_move();
+    let moves: Vec<Intent> = game.move_request(player).all_moves().copied().collect();
+    let mut longest: Option<Vec<(PlayerId, Intent)>> = None;
+    for intent in moves {
+        if game.try_apply_move(player, intent).is_err() {
+            continue;
+        }
+        let line = forced_win(game, winner, plies - 1, nodes);
+        game.undo_move();
+        match line {
+            Some(mut line) if player == winner => {
+                line.insert(0, (player, intent));
+                return Some(line);
+            },
+            Some(mut line) => {
+                if longest.as_ref().is_none_or(|longest| line.len() + 1 > longest.len()) {
+                    line.insert(0, (player, intent));
+                    longest = Some(line);
+                }
+            },
+            None if player == winner => (),
+            None => return None,
+        }
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec::Vec2;
+
+    // A's checker is one step from its far column, and B's is out of reach
+    const ONE_STEP: &str = "
+        rule elimination on
+        stones a 0
+        stones b 0
+        checker a 1 0 1
+        checker b 6 3 1
+    ";
+
+    #[test]
+    fn solve() {
+        let scenario = Scenario::parse(ONE_STEP).unwrap();
+        let solution = super::solve(&scenario, Target { winner: PlayerId::A, plies: 1 }).unwrap();
+        assert!(solution.proven());
+        assert_eq!(solution.line.len(), 1);
+        assert!(solution.keys.iter().all(|(player, intent)| *player == PlayerId::A && matches!(intent, Intent::MoveChecker(_, to) if to.x == 0)));
+
+        // Targets are set for the side to move, and A's checker too far back is refuted
+        assert_eq!(super::solve(&scenario, Target { winner: PlayerId::B, plies: 3 }), Err(PuzzleError::NotToMove(PlayerId::B)));
+        let far = Scenario::parse(&ONE_STEP.replace("checker a 1 0 1", "checker a 6 0 1")).unwrap();
+        let refuted = super::solve(&far, Target { winner: PlayerId::A, plies: 2 }).unwrap();
+        assert!(!refuted.proven() && refuted.line.is_empty());
+        assert!(refuted.nodes > 0);
+
+        let over = Scenario::parse(&ONE_STEP.replace("checker a 1 0 1", "checker a 0 0 1")).unwrap();
+        assert_eq!(super::solve(&over, Target { winner: PlayerId::A, plies: 1 }), Err(PuzzleError::AlreadyOver));
+    }
+
+    #[test]
+    fn validate() {
+        // Stepping straight or diagonally onto the far column both win
+        let scenario = Scenario::parse(ONE_STEP).unwrap();
+        assert_eq!(super::validate(&scenario, Target { winner: PlayerId::A, plies: 1 }), Err(PuzzleError::SeveralKeys(2)));
+        assert_eq!(super::validate(&scenario, Target { winner: PlayerId::A, plies: 0 }),
+            Err(PuzzleError::Refuted { winner: PlayerId::A, plies: 0 }));
+
+        // With a B checker too tall to destroy in the way of the diagonal step, only the straight
+        // step wins
+        let blocked = Scenario::parse(&ONE_STEP.replace("checker b 6 3 1", "checker b 0 1 2")).unwrap();
+        let solution = super::validate(&blocked, Target { winner: PlayerId::A, plies: 1 }).unwrap();
+        assert_eq!(solution.keys, [(PlayerId::A, Intent::MoveChecker(Vec2::new(1, 0), Vec2::new(0, 0)))]);
+    }
+}
@@ -0,0 +1,559 @@
+/**
+ * Game records: the moves of a game written out in plain text, so games can be saved,
+ * shared, and studied. Moves may carry annotations, which survive reading and writing.
+ *
+ * One line per directive or move, words separated by spaces, # starts a comment line:
+ *
+ *   name <free text>
+ *   variant standard|grand
+ *   free_setup on|off
+ *   first a|b
+ *   result a|b|draw
+ *   dice <roll> <roll> ...
+ *   position <hash of the last position, in hex>
+ *   a|b move <x>,<y> <x>,<y> [annotations]
+ *   a|b fire <x>,<y> [annotations]
+ *   a|b place <x>,<y> [annotations]
+ *   a|b slide <x>,<y> up|down|left|right [annotations]
+ *   a|b setup <x>,<y> <height> [annotations]
+ *   a|b swap [annotations]
+ *
+ * Annotations follow the move, in any order: a glyph (!! ! !? ?! ? ??), an evaluation
+ * as eval <n>, and a comment running from ; to the end of the line.
+ *
+ * A variation is a line of moves played instead of the move just before it, written
+ * between a ( line and a ) line straight after that move. Variations may hold variations
+ * of their own.
+ *
+ * Records start from the variant's start position, or an empty board for free setup;
+ * scenario setups are not recorded. The dice line holds every die the game rolled, in
+ * order, so its fights can be replayed exactly, and the position line the hash of the
+ * position it ended in; dice lines add to each other.
+ */
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::board::Direction;
+use crate::config::Variant;
+use crate::game::{Game, GameOutcome, Intent, PlayerId};
+use crate::scenario::{arg, coordinates, height, number, player, switch};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordError {
+    // The file could not be read or written
+    #[error("Could not access game record: {0}")]
+    Io(#[from] io::Error),
+    // A line could not be understood, with its line number counting from 1
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+/**
+ * Glyph is a conventional mark of a move's quality.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Glyph {
+    // !!
+    Brilliant,
+    // !
+    Good,
+    // !?
+    Interesting,
+    // ?!
+    Dubious,
+    // ?
+    Mistake,
+    // ??
+    Blunder,
+}
+
+impl Glyph {
+    /**
+     * symbol
+     * ret - The glyph as written in a record.
+     */
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Glyph::Brilliant => "!!",
+            Glyph::Good => "!",
+            Glyph::Interesting => "!?",
+            Glyph::Dubious => "?!",
+            Glyph::Mistake => "?",
+            Glyph::Blunder => "??",
+        }
+    }
+
+    /**
+     * from_symbol
+     * symbol - Glyph as written in a record.
+     * ret - The glyph, or None if symbol is not one.
+     */
+    pub fn from_symbol(symbol: &str) -> Option<Glyph> {
+        [Glyph::Brilliant, Glyph::Good, Glyph::Interesting, Glyph::Dubious, Glyph::Mistake, Glyph::Blunder]
+            .into_iter()
+            .find(|glyph| glyph.symbol() == symbol)
+    }
+}
+
+/**
+ * GameResult is how a recorded game ended, without the rule that decided it.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    Win(PlayerId),
+    Draw,
+}
+
+impl From<GameOutcome> for GameResult {
+    fn from(outcome: GameOutcome) -> GameResult {
+        match outcome {
+            GameOutcome::Win { winner, .. } => GameResult::Win(winner),
+            GameOutcome::Draw(_) => GameResult::Draw,
+        }
+    }
+}
+
+/**
+ * RecordedMove is one move of a record, with its annotations.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedMove {
+    pub player: PlayerId,
+    pub intent: Intent,
+    pub glyph: Option<Glyph>,
+    // score of the position after the move, from the mover's point of view
+    pub evaluation: Option<i32>,
+    // one line of free text; line breaks are written as spaces
+    pub comment: Option<String>,
+    // other lines that could have been played instead of this move, each starting with
+    // its replacement for this move
+    pub variations: Vec<Vec<RecordedMove>>,
+}
+
+impl RecordedMove {
+    /**
+     * new
+     * Create an unannotated move.
+     * player - Id of player who made the move.
+     * intent - The move.
+     */
+    pub fn new(player: PlayerId, intent: Intent) -> RecordedMove {
+        RecordedMove { player, intent, glyph: None, evaluation: None, comment: None, variations: Vec::new() }
+    }
+}
+
+/**
+ * GameRecord is the moves of one game, in order.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    pub name: Option<String>,
+    pub variant: Variant,
+    // true if the game began with checkers placed by the players
+    pub free_setup: bool,
+    // player who made the first move
+    pub first: PlayerId,
+    // None while the game is unfinished, or if the result was not recorded
+    pub result: Option<GameResult>,
+    // every die rolled, in order, or empty if they were not recorded
+    pub dice: Vec<u32>,
+    // Zobrist hash of the position after the last move, or None if it was not recorded
+    pub position: Option<u64>,
+    pub moves: Vec<RecordedMove>,
+}
+
+impl GameRecord {
+    /**
+     * new
+     * Create a record with no moves.
+     * variant - Board the game is played on.
+     */
+    pub fn new(variant: Variant) -> GameRecord {
+        GameRecord {
+            name: None,
+            variant,
+            free_setup: false,
+            first: PlayerId::A,
+            result: None,
+            dice: Vec::new(),
+            position: None,
+            moves: Vec::new(),
+        }
+    }
+
+    /**
+     * from_game
+     * Record the moves played so far in a game, without annotations, and its result if
+     * it is over.
+     * game - Game to record.
+     */
+    pub fn from_game(game: &Game) -> GameRecord {
+        let mut record = GameRecord::new(game.config().variant);
+        record.free_setup = game.config().free_setup;
+        record.first = game.config().first_player;
+        record.result = game.check_for_outcome().map(GameResult::from);
+        record.dice = game.dice().to_vec();
+        record.position = Some(game.board.position_hash());
+        record.moves = game.history().iter().map(|(player, intent)| RecordedMove::new(*player, *intent)).collect();
+        record
+    }
+
+    /**
+     * load
+     * Read and parse a record file.
+     * path - File to read.
+     * ret - The record, or a RecordError if it could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GameRecord, RecordError> {
+        GameRecord::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * save
+     * Write the record to a file, replacing anything already there.
+     * path - File to write.
+     * ret - Ok, or RecordError::Io if the file could not be written.
+     */
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecordError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /**
+     * parse
+     * Parse the text of a record.
+     * text - Contents of the record.
+     * ret - The record, or a RecordError describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<GameRecord, RecordError> {
+        let mut record = GameRecord::new(Variant::Standard);
+        // the main line, then each variation being read, innermost last
+        let mut lines: Vec<Vec<RecordedMove>> = vec![Vec::new()];
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| RecordError::Parse { line: idx + 1, message };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[0] {
+                "name" => record.name = Some(line["name".len()..].trim().to_string()),
+                "variant" => {
+                    record.variant = match arg(&words, 1).map_err(err)? {
+                        "standard" => Variant::Standard,
+                        "grand" => Variant::Grand10x8,
+                        other => return Err(err(format!("unknown variant '{}'", other))),
+                    };
+                },
+                "free_setup" => record.free_setup = switch(arg(&words, 1).map_err(err)?).map_err(err)?,
+                "first" => record.first = player(arg(&words, 1).map_err(err)?).map_err(err)?,
+                "result" => {
+                    record.result = Some(match arg(&words, 1).map_err(err)? {
+                        "draw" => GameResult::Draw,
+                        side => GameResult::Win(player(side).map_err(err)?),
+                    });
+                },
+                "dice" => {
+                    for word in words[1..].iter() {
+                        let roll: u32 = number(word).map_err(err)?;
+                        if !(1..=6).contains(&roll) {
+                            return Err(err(format!("a die can not roll {}", roll)));
+                        }
+                        record.dice.push(roll);
+                    }
+                },
+                "position" => {
+                    let hash = arg(&words, 1).map_err(err)?;
+                    let hash = u64::from_str_radix(hash, 16).map_err(|_| err(format!("expected a hash, found '{}'", hash)))?;
+                    record.position = Some(hash);
+                },
+                "(" => {
+                    if lines.last().unwrap().is_empty() {
+                        return Err(err(String::from("variation has no move to replace")));
+                    }
+                    lines.push(Vec::new());
+                },
+                ")" => {
+                    let variation = lines.pop().unwrap();
+                    let Some(parent) = lines.last_mut().and_then(|line| line.last_mut()) else {
+                        return Err(err(String::from("')' without a variation to close")));
+                    };
+                    if variation.is_empty() {
+                        return Err(err(String::from("variation has no moves")));
+                    }
+                    parent.variations.push(variation);
+                },
+                _ => lines.last_mut().unwrap().push(parse_move(line).map_err(err)?),
+            }
+        }
+        if lines.len() > 1 {
+            return Err(RecordError::Parse { line: text.lines().count(), message: String::from("variation is not closed") });
+        }
+        record.moves = lines.pop().unwrap();
+        Ok(record)
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if let Some(name) = &self.name {
+            writeln!(formatter, "name {}", name)?;
+        }
+        let variant = match self.variant {
+            Variant::Standard => "standard",
+            Variant::Grand10x8 => "grand",
+        };
+        writeln!(formatter, "variant {}", variant)?;
+        if self.free_setup {
+            writeln!(formatter, "free_setup on")?;
+        }
+        if self.first == PlayerId::B {
+            writeln!(formatter, "first b")?;
+        }
+        match self.result {
+            Some(GameResult::Win(PlayerId::A)) => writeln!(formatter, "result a")?,
+            Some(GameResult::Win(PlayerId::B)) => writeln!(formatter, "result b")?,
+            Some(GameResult::Draw) => writeln!(formatter, "result draw")?,
+            None => (),
+        }
+        if !self.dice.is_empty() {
+            let rolls: Vec<String> = self.dice.iter().map(u32::to_string).collect();
+            writeln!(formatter, "dice {}", rolls.join(" "))?;
+        }
+        if let Some(position) = self.position {
+            writeln!(formatter, "position {:016x}", position)?;
+        }
+        write_line(formatter, &self.moves, 0)
+    }
+}
+
+/*
+ * Helper function writing a line of moves with their variations, each variation
+ * indented one step further than the line it branches from.
+ */
+fn write_line(formatter: &mut Formatter<'_>, moves: &[RecordedMove], depth: usize) -> Result<(), std::fmt::Error> {
+    let indent = "  ".repeat(depth);
+    for recorded in moves.iter() {
+        writeln!(formatter, "{}{}", indent, move_line(recorded))?;
+        for variation in recorded.variations.iter() {
+            writeln!(formatter, "{}(", indent)?;
+            write_line(formatter, variation, depth + 1)?;
+            writeln!(formatter, "{})", indent)?;
+        }
+    }
+    Ok(())
+}
+
+/*
+ * Helper function writing one move and its annotations as a line of a record.
+ */
+pub(crate) fn move_line(recorded: &RecordedMove) -> String {
+    let side = match recorded.player {
+        PlayerId::A => "a",
+        PlayerId::B => "b",
+    };
+    let mut line = format!("{} {}", side, notation(recorded.intent));
+    if let Some(glyph) = recorded.glyph {
+        line.push(' ');
+        line.push_str(glyph.symbol());
+    }
+    if let Some(evaluation) = recorded.evaluation {
+        line.push_str(&format!(" eval {}", evaluation));
+    }
+    if let Some(comment) = &recorded.comment {
+        line.push_str(" ; ");
+        line.push_str(&comment.replace(['\n', '\r'], " "));
+    }
+    line
+}
+
+/**
+ * notation
+ * Write a move the way records do, without the player.
+ * intent - Move to write.
+ * ret - The move, eg "slide 4,0 down".
+ */
+pub fn notation(intent: Intent) -> String {
+    match intent {
+        Intent::MoveChecker(from, to) => format!("move {},{} {},{}", from.x, from.y, to.x, to.y),
+        Intent::FireChecker(at) => format!("fire {},{}", at.x, at.y),
+        Intent::PlaceStone(at) => format!("place {},{}", at.x, at.y),
+        Intent::SlideStone(from, direction) => format!("slide {},{} {}", from.x, from.y, direction),
+        Intent::PlaceChecker(at, height) => format!("setup {},{} {}", at.x, at.y, height),
+        Intent::SwapSides => String::from("swap"),
+    }
+}
+
+/*
+ * Helper function reading one move line, with its annotations.
+ */
+pub(crate) fn parse_move(line: &str) -> Result<RecordedMove, String> {
+    let (play, comment) = match line.split_once(';') {
+        Some((play, comment)) => (play, Some(comment.trim().to_string())),
+        None => (line, None),
+    };
+    let words: Vec<&str> = play.split_whitespace().collect();
+    let mover = player(arg(&words, 0)?)?;
+    let (intent, used) = parse_intent(&words[1..])?;
+    let mut recorded = RecordedMove::new(mover, intent);
+    recorded.comment = comment;
+    let mut rest = words[1 + used..].iter();
+    while let Some(word) = rest.next() {
+        if *word == "eval" {
+            recorded.evaluation = Some(number(rest.next().ok_or_else(|| String::from("missing value"))?)?);
+        } else if let Some(glyph) = Glyph::from_symbol(word) {
+            recorded.glyph = Some(glyph);
+        } else {
+            return Err(format!("unexpected '{}' after move", word));
+        }
+    }
+    Ok(recorded)
+}
+
+/*
+ * Helper function reading a move written in notation, returning it with the number of
+ * words it took up.
+ */
+pub(crate) fn parse_intent(words: &[&str]) -> Result<(Intent, usize), String> {
+    let intent = match arg(words, 0)? {
+        "move" => Intent::MoveChecker(coordinates(arg(words, 1)?)?, coordinates(arg(words, 2)?)?),
+        "fire" => Intent::FireChecker(coordinates(arg(words, 1)?)?),
+        "place" => Intent::PlaceStone(coordinates(arg(words, 1)?)?),
+        "slide" => {
+            let direction = match arg(words, 2)? {
+                "up" => Direction::Up,
+                "down" => Direction::Down,
+                "left" => Direction::Left,
+                "right" => Direction::Right,
+                other => return Err(format!("unknown direction '{}'", other)),
+            };
+            Intent::SlideStone(coordinates(arg(words, 1)?)?, direction)
+        },
+        "setup" => Intent::PlaceChecker(coordinates(arg(words, 1)?)?, height(arg(words, 2)?)?),
+        "swap" => Intent::SwapSides,
+        other => return Err(format!("unknown move '{}'", other)),
+    };
+    let used = match intent {
+        Intent::SwapSides => 1,
+        Intent::FireChecker(_) | Intent::PlaceStone(_) => 2,
+        _ => 3,
+    };
+    Ok((intent, used))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::CheckerHeight;
+    use crate::vec::Vec2;
+
+    const ANNOTATED: &str = "
+        # a short game
+        name Opening study
+        variant standard
+        free_setup on
+        result b
+        a move 6,2 5,2
+        b place 4,0 !? ; keeps the centre open
+        a fire 1,2 ?? eval -3 ; a wasted attack
+        b slide 4,0 down eval 2 !
+        a setup 3,3 2
+        b swap
+    ";
+
+    #[test]
+    fn parse() {
+        let record = GameRecord::parse(ANNOTATED).unwrap();
+        assert_eq!(record.name.as_deref(), Some("Opening study"));
+        assert_eq!(record.variant, Variant::Standard);
+        assert!(record.free_setup);
+        assert_eq!(record.result, Some(GameResult::Win(PlayerId::B)));
+        let intents: Vec<Intent> = record.moves.iter().map(|recorded| recorded.intent).collect();
+        assert_eq!(intents, vec![
+            Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2)),
+            Intent::PlaceStone(Vec2::new(4, 0)),
+            Intent::FireChecker(Vec2::new(1, 2)),
+            Intent::SlideStone(Vec2::new(4, 0), Direction::Down),
+            Intent::PlaceChecker(Vec2::new(3, 3), CheckerHeight::TWO),
+            Intent::SwapSides,
+        ]);
+        assert_eq!(record.moves[0], RecordedMove::new(PlayerId::A, intents[0]));
+        assert_eq!(record.moves[1].glyph, Some(Glyph::Interesting));
+        assert_eq!(record.moves[1].comment.as_deref(), Some("keeps the centre open"));
+        assert_eq!((record.moves[2].glyph, record.moves[2].evaluation), (Some(Glyph::Blunder), Some(-3)));
+        assert_eq!((record.moves[3].glyph, record.moves[3].evaluation), (Some(Glyph::Good), Some(2)));
+        assert_eq!(record.moves[5].player, PlayerId::B);
+    }
+
+    #[test]
+    fn round_trip() {
+        let record = GameRecord::parse(ANNOTATED).unwrap();
+        let written = record.to_string();
+        assert_eq!(GameRecord::parse(&written).unwrap(), record);
+        assert!(written.contains("b place 4,0 !? ; keeps the centre open\n"));
+
+        let mut record = GameRecord::new(Variant::Grand10x8);
+        let mut recorded = RecordedMove::new(PlayerId::B, Intent::FireChecker(Vec2::new(2, 2)));
+        recorded.comment = Some(String::from("two\nlines; with # marks"));
+        record.moves.push(recorded);
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed.variant, Variant::Grand10x8);
+        assert!(!parsed.free_setup);
+        assert_eq!((parsed.first, parsed.result), (PlayerId::A, None));
+        record.first = PlayerId::B;
+        assert_eq!(GameRecord::parse(&record.to_string()).unwrap().first, PlayerId::B);
+        (record.dice, record.position) = (vec![6, 1, 3], Some(0xbeef));
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!((parsed.dice, parsed.position), (vec![6, 1, 3], Some(0xbeef)));
+        assert!(GameRecord::parse("dice 3 7").is_err());
+        record.result = Some(GameResult::Draw);
+        assert_eq!(GameRecord::parse(&record.to_string()).unwrap().result, Some(GameResult::Draw));
+        assert_eq!(parsed.moves[0].comment.as_deref(), Some("two lines; with # marks"));
+    }
+
+    #[test]
+    fn variations() {
+        let text = "
+            a move 6,2 5,2
+            (
+              a place 4,0 ; quieter
+              b place 3,0
+              (
+                b fire 5,2
+              )
+            )
+            (
+              a swap
+            )
+            b place 2,2
+        ";
+        let record = GameRecord::parse(text).unwrap();
+        assert_eq!(record.moves.len(), 2);
+        let variations = &record.moves[0].variations;
+        assert_eq!(variations.len(), 2);
+        assert_eq!(variations[0].len(), 2);
+        assert_eq!(variations[0][0].comment.as_deref(), Some("quieter"));
+        assert_eq!(variations[0][1].variations, vec![vec![RecordedMove::new(PlayerId::B, Intent::FireChecker(Vec2::new(5, 2)))]]);
+        assert_eq!(variations[1], vec![RecordedMove::new(PlayerId::A, Intent::SwapSides)]);
+        assert!(record.moves[1].variations.is_empty());
+        assert_eq!(GameRecord::parse(&record.to_string()).unwrap(), record);
+
+        assert!(matches!(GameRecord::parse("(\na swap\n)"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(GameRecord::parse("a swap\n)"), Err(RecordError::Parse { line: 2, .. })));
+        assert!(matches!(GameRecord::parse("a swap\n(\n)"), Err(RecordError::Parse { line: 3, .. })));
+        assert!(matches!(GameRecord::parse("a swap\n(\nb swap"), Err(RecordError::Parse { line: 3, .. })));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(GameRecord::parse("variant huge"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(GameRecord::parse("a teleport 1,1"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(GameRecord::parse("\nc move 1,1 2,2"), Err(RecordError::Parse { line: 2, .. })));
+        assert!(matches!(GameRecord::parse("a move 1,1 2,2 !!!"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(GameRecord::parse("a slide 1,1 sideways"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(GameRecord::parse("a fire 1,1 eval"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(GameRecord::load("no/such/record.txt"), Err(RecordError::Io(_))));
+    }
+}
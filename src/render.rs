@@ -0,0 +1,7 @@
+/**
+ * Renderers drawing boards for places other than the terminal, which uses Board's Display.
+ */
+pub mod html;
+pub mod svg;
+#[cfg(feature = "evcxr")]
+pub mod notebook;
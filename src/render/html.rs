@@ -0,0 +1,141 @@
+/**
+ * HTML rendering of a board: a snippet with its own stylesheet, laid out with a CSS grid of
+ * stone corners and checker squares, so pages and reports can show a position without any
+ * script or image. Every class is prefixed with ironclad-, so the snippet can be dropped
+ * into a page, and several boards into one page, without clashing with anything else.
+ */
+use std::fmt::Write;
+
+use crate::board::{Board, Terrain};
+use crate::game::PlayerId;
+use crate::vec::Vec2;
+
+// size of a checker square and of the gap a corner takes up between squares, in pixels
+const SQUARE_PIXELS: usize = 40;
+const CORNER_PIXELS: usize = 12;
+
+const STYLE: &str = "<style>
+.ironclad-board { display: inline-grid; font: bold 20px sans-serif; background: #d8c8a0; }
+.ironclad-square { display: flex; align-items: center; justify-content: center; background: #efe4c8; }
+.ironclad-mud { background: #a08a60; }
+.ironclad-hill { background: #b8d890; }
+.ironclad-checker { border-radius: 50%; width: 80%; height: 80%; display: flex; align-items: center; justify-content: center; }
+.ironclad-corner { display: flex; align-items: center; justify-content: center; }
+.ironclad-stone { border-radius: 50%; width: 100%; height: 100%; }
+.ironclad-a { background: #f8f8f8; color: #202020; border: 1px solid #202020; }
+.ironclad-b { background: #202020; color: #f8f8f8; border: 1px solid #202020; }
+</style>
+";
+
+/**
+ * render
+ * Draw a board as HTML, with the stylesheet it needs. Checkers show their height, and
+ * squares, checkers and stones carry their owner, terrain and position in their classes
+ * and titles.
+ * board - Board to draw.
+ * ret - The HTML snippet.
+ */
+pub fn render(board: &Board) -> String {
+    let track = |count: usize, size: usize| {
+        let mut columns = vec![format!("{}px", CORNER_PIXELS)];
+        for _ in 0..count {
+            columns.push(format!("{}px {}px", size, CORNER_PIXELS));
+        }
+        columns.join(" ")
+    };
+    let mut html = String::from(STYLE);
+    writeln!(
+        html,
+        "<div class=\"ironclad-board\" style=\"grid-template-columns: {}; grid-template-rows: {};\">",
+        track(board.width(), SQUARE_PIXELS),
+        track(board.height(), SQUARE_PIXELS),
+    ).expect("writing to a String");
+    for yi in 0..=board.height() as i32 {
+        for xi in 0..=board.width() as i32 {
+            corner(&mut html, board, Vec2::new(xi, yi));
+            if xi < board.width() as i32 {
+                html.push_str("<span></span>\n");
+            }
+        }
+        if yi >= board.height() as i32 {
+            continue;
+        }
+        for xi in 0..board.width() as i32 {
+            html.push_str("<span></span>\n");
+            square(&mut html, board, Vec2::new(xi, yi));
+        }
+        html.push_str("<span></span>\n");
+    }
+    html.push_str("</div>\n");
+    html
+}
+
+/* Helper function writing the grid cell of the stone corner at @pos. */
+fn corner(html: &mut String, board: &Board, pos: Vec2) {
+    match board.stone_at(pos).ok().and_then(|stone| stone.owner) {
+        Some(owner) => writeln!(
+            html,
+            "<span class=\"ironclad-corner\"><span class=\"ironclad-stone ironclad-{}\" title=\"stone {} at {}\"></span></span>",
+            side(owner), side(owner), pos,
+        ),
+        None => writeln!(html, "<span class=\"ironclad-corner\"></span>"),
+    }.expect("writing to a String");
+}
+
+/* Helper function writing the grid cell of the checker square at @pos. */
+fn square(html: &mut String, board: &Board, pos: Vec2) {
+    let terrain = match board.terrain_at(pos) {
+        Terrain::Plain => "",
+        Terrain::Mud => " ironclad-mud",
+        Terrain::Hill => " ironclad-hill",
+    };
+    write!(html, "<span class=\"ironclad-square{}\">", terrain).expect("writing to a String");
+    if let Ok(checker) = board.checker_at(pos) {
+        if let Some(owner) = checker.owner {
+            write!(
+                html,
+                "<span class=\"ironclad-checker ironclad-{}\" title=\"checker {} at {}, height {}\">{}</span>",
+                side(owner), side(owner), pos, checker.height.get(), checker.height.get(),
+            ).expect("writing to a String");
+        }
+    }
+    html.push_str("</span>\n");
+}
+
+/* Helper function naming a side as the classes do. */
+fn side(player: PlayerId) -> &'static str {
+    match player {
+        PlayerId::A => "a",
+        PlayerId::B => "b",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Stone;
+
+    #[test]
+    fn render() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(0, 0), Stone::new(PlayerId::B)).unwrap();
+        board.set_terrain(Vec2::new(3, 3), Terrain::Hill).unwrap();
+        let html = super::render(&board);
+
+        assert!(html.starts_with("<style>"));
+        assert_eq!(html.matches("<div").count(), html.matches("</div>").count());
+        assert_eq!(html.matches("<span").count(), html.matches("</span>").count());
+        // One cell for every corner, square and gap between them, filling the grid
+        let (columns, rows) = (2 * board.width() + 1, 2 * board.height() + 1);
+        let cells = html.lines().filter(|line| line.starts_with("<span")).count();
+        assert_eq!(cells, columns * rows);
+        assert_eq!(html.matches("class=\"ironclad-square").count(), board.width() * board.height());
+        assert_eq!(html.matches("class=\"ironclad-corner\"").count(), (board.width() + 1) * (board.height() + 1));
+
+        assert_eq!(html.matches("ironclad-stone ironclad-b").count(), 1);
+        assert!(html.contains(&format!("title=\"stone b at {}\"", Vec2::new(0, 0))));
+        assert_eq!(html.matches("class=\"ironclad-checker ").count(), board.checkers().len());
+        assert_eq!(html.matches("ironclad-hill\"").count(), 1);
+        assert!(!html.contains("<script"));
+    }
+}
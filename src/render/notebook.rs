@@ -0,0 +1,78 @@
+/**
+ * Rich display in evcxr, the Rust kernel for Jupyter notebooks. evcxr shows a value by
+ * calling its evcxr_display method if it has one, which prints the value between markers
+ * naming its MIME type, so Boards and Games show as diagrams drawn by the HTML renderer.
+ * Enabled by the evcxr feature.
+ */
+use crate::board::Board;
+use crate::game::{Game, PlayerId};
+use crate::render::html;
+
+impl Board {
+    /**
+     * evcxr_display
+     * Show the board as a diagram in a notebook.
+     */
+    pub fn evcxr_display(&self) {
+        println!("{}", content(&html::render(self)));
+    }
+}
+
+impl Game<'_> {
+    /**
+     * evcxr_display
+     * Show the game in a notebook: the board as a diagram, then the players' stones and
+     * captures, and whose move it is or how the game ended.
+     */
+    pub fn evcxr_display(&self) {
+        println!("{}", content(&game_html(self)));
+    }
+}
+
+/* Helper function drawing @game as HTML, the board followed by its status lines. */
+fn game_html(game: &Game) -> String {
+    let view = game.view();
+    let mut lines = vec![view.status_line(PlayerId::A), view.status_line(PlayerId::B)];
+    lines.push(match game.check_for_outcome() {
+        Some(outcome) => outcome.to_string(),
+        None => format!("Player {} to move", game.side_to_move()),
+    });
+    let mut html = html::render(view.board);
+    for line in lines {
+        html.push_str(&format!("<p>{}</p>\n", escape(&line)));
+    }
+    html
+}
+
+/* Helper function wrapping @html in the markers evcxr reads rich output between. */
+fn content(html: &str) -> String {
+    format!("EVCXR_BEGIN_CONTENT text/html\n{}EVCXR_END_CONTENT", html)
+}
+
+/* Helper function escaping the characters HTML gives a meaning to in @text. */
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Replayed;
+    use crate::game::{Player, STARTING_STONES};
+
+    #[test]
+    fn game() {
+        let replayed = Replayed;
+        let (mut player_a, mut player_b) = (Player::new(PlayerId::A, STARTING_STONES, &replayed), Player::new(PlayerId::B, STARTING_STONES, &replayed));
+        let game = Game::new(&mut player_a, &mut player_b);
+        let html = game_html(&game);
+        assert!(html.starts_with(&html::render(&game.board)));
+        assert!(html.ends_with("<p>Player A to move</p>\n"));
+        assert_eq!(html.matches("<p>").count(), 3);
+
+        let shown = content(&html);
+        assert!(shown.starts_with("EVCXR_BEGIN_CONTENT text/html\n<style>"));
+        assert!(shown.ends_with("</p>\nEVCXR_END_CONTENT"));
+        assert_eq!(escape("a < b & c"), "a &lt; b &amp; c");
+    }
+}
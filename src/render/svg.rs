@@ -0,0 +1,144 @@
+/**
+ * SVG rendering of a board: a standalone image of the squares, checkers and stone corners,
+ * with chosen squares and corners highlighted, such as those of the last move. Every image
+ * is the same size for a board of the same size, so a sequence of them can be put together
+ * into an animation.
+ */
+use std::fmt::Write;
+
+use crate::board::{Board, Terrain};
+use crate::game::PlayerId;
+use crate::vec::Vec2;
+
+// size of a checker square, and the margin around the board the outer corners sit in, in pixels
+const SQUARE_PIXELS: i32 = 40;
+const MARGIN_PIXELS: i32 = 12;
+const HIGHLIGHT: &str = "#e8b020";
+
+/**
+ * Highlight is the squares and stone corners to draw highlighted.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Highlight {
+    pub squares: Vec<Vec2>,
+    pub corners: Vec<Vec2>,
+}
+
+/**
+ * render
+ * Draw a board as an SVG image. Checkers show their height.
+ * board - Board to draw.
+ * highlight - Squares and corners to highlight.
+ * ret - The SVG document.
+ */
+pub fn render(board: &Board, highlight: &Highlight) -> String {
+    let (width, height) = (board.width() as i32, board.height() as i32);
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-weight=\"bold\" font-size=\"20\">",
+        w = width * SQUARE_PIXELS + 2 * MARGIN_PIXELS,
+        h = height * SQUARE_PIXELS + 2 * MARGIN_PIXELS,
+    ).expect("writing to a String");
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#d8c8a0\"/>\n");
+    for yi in 0..height {
+        for xi in 0..width {
+            square(&mut svg, board, Vec2::new(xi, yi), highlight.squares.contains(&Vec2::new(xi, yi)));
+        }
+    }
+    for yi in 0..=height {
+        for xi in 0..=width {
+            corner(&mut svg, board, Vec2::new(xi, yi), highlight.corners.contains(&Vec2::new(xi, yi)));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/* Helper function drawing the square at @pos and the checker on it, outlined if @highlighted. */
+fn square(svg: &mut String, board: &Board, pos: Vec2, highlighted: bool) {
+    let fill = match board.terrain_at(pos) {
+        Terrain::Plain => "#efe4c8",
+        Terrain::Mud => "#a08a60",
+        Terrain::Hill => "#b8d890",
+    };
+    let (left, top) = corner_point(pos);
+    let stroke = if highlighted { format!(" stroke=\"{}\" stroke-width=\"4\"", HIGHLIGHT) } else { String::new() };
+    writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"{}\"{}/>",
+        left + 2, top + 2, fill, stroke, s = SQUARE_PIXELS - 4,
+    ).expect("writing to a String");
+    if let Ok(checker) = board.checker_at(pos) {
+        if let Some(owner) = checker.owner {
+            let (background, text) = colours(owner);
+            let (x, y) = (left + SQUARE_PIXELS / 2, top + SQUARE_PIXELS / 2);
+            writeln!(
+                svg,
+                "<circle class=\"checker\" cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"#202020\"/>",
+                x, y, SQUARE_PIXELS * 2 / 5, background,
+            ).expect("writing to a String");
+            writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                x, y, text, checker.height.get(),
+            ).expect("writing to a String");
+        }
+    }
+}
+
+/* Helper function drawing the stone on the corner at @pos, and a ring around it if @highlighted. */
+fn corner(svg: &mut String, board: &Board, pos: Vec2, highlighted: bool) {
+    let (x, y) = corner_point(pos);
+    if highlighted {
+        writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"3\"/>",
+            x, y, MARGIN_PIXELS - 2, HIGHLIGHT,
+        ).expect("writing to a String");
+    }
+    if let Some(owner) = board.stone_at(pos).ok().and_then(|stone| stone.owner) {
+        writeln!(
+            svg,
+            "<circle class=\"stone\" cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"#202020\"/>",
+            x, y, MARGIN_PIXELS / 2, colours(owner).0,
+        ).expect("writing to a String");
+    }
+}
+
+/* Helper function finding where the corner at @pos, the top left of the square at @pos, is drawn. */
+fn corner_point(pos: Vec2) -> (i32, i32) {
+    (MARGIN_PIXELS + pos.x * SQUARE_PIXELS, MARGIN_PIXELS + pos.y * SQUARE_PIXELS)
+}
+
+/* Helper function giving the fill and text colours of @player's pieces. */
+fn colours(player: PlayerId) -> (&'static str, &'static str) {
+    match player {
+        PlayerId::A => ("#f8f8f8", "#202020"),
+        PlayerId::B => ("#202020", "#f8f8f8"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Stone;
+
+    #[test]
+    fn render() {
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(0, 0), Stone::new(PlayerId::B)).unwrap();
+        let plain = super::render(&board, &Highlight::default());
+        assert!(plain.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(plain.ends_with("</svg>\n"));
+        assert_eq!(plain.matches("class=\"checker\"").count(), board.checkers().len());
+        assert_eq!(plain.matches("class=\"stone\"").count(), 1);
+        assert!(!plain.contains(HIGHLIGHT));
+
+        let highlight = Highlight { squares: vec![Vec2::new(1, 1)], corners: vec![Vec2::new(0, 0)] };
+        let marked = super::render(&board, &highlight);
+        assert_eq!(marked.matches(HIGHLIGHT).count(), 2);
+        // Highlights change only what is drawn, not the size of the image
+        assert_eq!(marked.lines().next(), plain.lines().next());
+    }
+}
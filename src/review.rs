@@ -0,0 +1,589 @@
+/**
+ * Review steps through a recorded game one move at a time, forward and back, showing
+ * the board after each ply. Reviews can branch off into variations: trying a different
+ * move from any position starts a variation there, or follows one already recorded, and
+ * the whole tree is kept in the record for saving.
+ *
+ * Moves are replayed on the board alone, starting as verify sets up its replays. The main
+ * line rolls the dice the record kept, so its fights go as they did in the game. Variations
+ * have no dice of their own, so their attacks roll dice seeded with REPLAY_SEED.
+ */
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::board::Board;
+use crate::game::{ApplyError, Intent, PlayerId};
+use crate::playback::{self, Playback};
+use crate::record::{notation, parse_move, GameRecord, RecordError, RecordedMove};
+use crate::verify::with_replay;
+
+// seed for the dice of every replay once the record's dice run out, so a line always
+// replays the same way
+pub(crate) const REPLAY_SEED: [u8; 32] = [0; 32];
+
+/**
+ * start_board
+ * Set up the board a record's main line is replayed on, in the game verify replays in,
+ * with the dice the record kept scripted and REPLAY_SEED rolling any after them.
+ * record - Game to replay.
+ * ret - The board before the first move.
+ */
+pub(crate) fn start_board(record: &GameRecord) -> Board {
+    let mut board = with_replay(record, |game| game.board.clone());
+    board.reseed(REPLAY_SEED);
+    board
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReviewError {
+    #[error(transparent)]
+    Record(#[from] RecordError),
+    // A recorded move could not be made, with the ply of the move counting from 1
+    #[error("Move {ply} ({notation}) could not be replayed: {source}")]
+    Replay { ply: usize, notation: String, source: ApplyError },
+    // A verified record did not replay as recorded, in this many ways
+    #[error("The record does not replay as recorded: {0} divergences found")]
+    Diverged(usize),
+}
+
+/**
+ * Review holds a record, the line of it being looked at, and the board after each
+ * move of that line.
+ */
+#[derive(Clone, Debug)]
+pub struct Review {
+    record: GameRecord,
+    // how to reach the line being looked at from the main line: at each step, the index of
+    // a move in the line so far, and which of that move's variations to follow instead
+    branches: Vec<(usize, usize)>,
+    // board before any move, then after each move of the current line in turn
+    positions: Vec<Board>,
+    // number of moves made to reach the current board, never before the start of the
+    // innermost variation
+    ply: usize,
+}
+
+impl Review {
+    /**
+     * new
+     * Replay a record and all its variations, ready to review the main line from the start.
+     * record - Game to review.
+     * ret - The review, or ReviewError::Replay if a move could not be made.
+     */
+    pub fn new(record: GameRecord) -> Result<Review, ReviewError> {
+        let mut review = Review { record, branches: Vec::new(), positions: Vec::new(), ply: 0 };
+        review.check_variations(&[], &review.record.moves)?;
+        review.positions = review.replay(&review.current_line())?;
+        Ok(review)
+    }
+
+    /**
+     * load
+     * Read a record file and replay it.
+     * path - File to read.
+     * ret - The review, or a ReviewError if the record could not be read or replayed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Review, ReviewError> {
+        Review::new(GameRecord::load(path)?)
+    }
+
+    /**
+     * record
+     * ret - The game being reviewed, with every variation explored so far.
+     */
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    /**
+     * ply
+     * ret - Number of moves made to reach the current board, 0 at the start.
+     */
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /**
+     * plies
+     * ret - Number of moves in the current line, which is its last ply.
+     */
+    pub fn plies(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    /**
+     * board
+     * ret - Board at the current ply.
+     */
+    pub fn board(&self) -> &Board {
+        &self.positions[self.ply]
+    }
+
+    /**
+     * last_move
+     * ret - Move that reached the current board, or None at the start.
+     */
+    pub fn last_move(&self) -> Option<&RecordedMove> {
+        self.current_line().get(self.ply.checked_sub(1)?).copied()
+    }
+
+    /**
+     * depth
+     * ret - Number of variations the current line is nested in, 0 on the main line.
+     */
+    pub fn depth(&self) -> usize {
+        self.branches.len()
+    }
+
+    /**
+     * forward
+     * Step to the board after the next move of the current line.
+     * ret - The new board, or None if already at the end.
+     */
+    pub fn forward(&mut self) -> Option<&Board> {
+        self.go_to(self.ply + 1)
+    }
+
+    /**
+     * back
+     * Step to the board before the last move. Stepping back past the start of a variation
+     * leaves it for the line it branched from.
+     * ret - The new board, or None if already at the start.
+     */
+    pub fn back(&mut self) -> Option<&Board> {
+        self.go_to(self.ply.checked_sub(1)?)
+    }
+
+    /**
+     * go_to
+     * Jump to the board at any ply of the current line, leaving any variation that starts
+     * after it.
+     * ply - Number of moves made, from 0 to plies().
+     * ret - The board at ply, or None if there is no such ply, leaving the review where it was.
+     */
+    pub fn go_to(&mut self, ply: usize) -> Option<&Board> {
+        if ply > self.plies() {
+            return None;
+        }
+        let depth = self.branches.len();
+        while !self.branches.is_empty() && ply < self.innermost_line().1 {
+            self.branches.pop();
+        }
+        if self.branches.len() != depth {
+            self.replay_current();
+        }
+        self.ply = ply;
+        Some(self.board())
+    }
+
+    /**
+     * alternatives
+     * ret - First moves of the variations that branch off here, replacing the next move.
+     */
+    pub fn alternatives(&self) -> Vec<&RecordedMove> {
+        let (line, start) = self.innermost_line();
+        match line.get(self.ply - start) {
+            Some(next) => next.variations.iter().map(|variation| &variation[0]).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /**
+     * enter_variation
+     * Follow one of the variations branching off here, onto its first move.
+     * idx - Index of the variation, as listed by alternatives.
+     * ret - Board after the variation's first move, or None if there is no such variation.
+     */
+    pub fn enter_variation(&mut self, idx: usize) -> Option<&Board> {
+        if idx >= self.alternatives().len() {
+            return None;
+        }
+        let (_, start) = self.innermost_line();
+        self.follow(self.ply - start, idx);
+        self.ply += 1;
+        Some(self.board())
+    }
+
+    /**
+     * leave_variation
+     * Go back to the line the current variation branched from, at the position the
+     * variation started from.
+     * ret - The board there, or None if already on the main line.
+     */
+    pub fn leave_variation(&mut self) -> Option<&Board> {
+        let (idx, _) = self.branches.pop()?;
+        let (_, start) = self.innermost_line();
+        self.replay_current();
+        self.ply = start + idx;
+        Some(self.board())
+    }
+
+    /**
+     * main_line
+     * Leave every variation, going back to the main line where the outermost one branched off.
+     * ret - The board there.
+     */
+    pub fn main_line(&mut self) -> &Board {
+        while self.leave_variation().is_some() {}
+        self.board()
+    }
+
+    /**
+     * play
+     * Try a move from the current board. If it is the next move of the line, or the first move
+     * of a variation branching off here, that line is followed. Otherwise a new variation is
+     * started with it, or, at the end of a line, the line is extended.
+     * player - Id of player making the move.
+     * intent - Move to make.
+     * ret - Board after the move, or the reason it could not be made, leaving the review as it was.
+     */
+    pub fn play(&mut self, player: PlayerId, intent: Intent) -> Result<&Board, ApplyError> {
+        let mut board = self.board().clone();
+        board.reseed(REPLAY_SEED);
+        board.apply(player, intent)?;
+        let same = |recorded: &RecordedMove| recorded.player == player && recorded.intent == intent;
+
+        let (line, start) = self.innermost_line();
+        let offset = self.ply - start;
+        match line.get(offset) {
+            Some(next) if same(next) => (),
+            Some(next) => {
+                let existing = next.variations.iter().position(|variation| same(&variation[0]));
+                let idx = match existing {
+                    Some(idx) => idx,
+                    None => {
+                        let next = &mut self.innermost_line_mut()[offset];
+                        next.variations.push(vec![RecordedMove::new(player, intent)]);
+                        next.variations.len() - 1
+                    },
+                };
+                self.follow(offset, idx);
+            },
+            None => {
+                self.innermost_line_mut().push(RecordedMove::new(player, intent));
+                self.replay_current();
+            },
+        }
+        self.ply += 1;
+        Ok(self.board())
+    }
+
+    /*
+     * Helper function switching to a variation of the move at @offset in the innermost line,
+     * keeping the ply where it is.
+     */
+    fn follow(&mut self, offset: usize, idx: usize) {
+        self.branches.push((offset, idx));
+        self.replay_current();
+    }
+
+    /*
+     * Helper function finding the innermost line being looked at, with the ply of its first move.
+     */
+    fn innermost_line(&self) -> (&Vec<RecordedMove>, usize) {
+        let mut line = &self.record.moves;
+        let mut start = 0;
+        for (offset, idx) in self.branches.iter() {
+            line = &line[*offset].variations[*idx];
+            start += offset;
+        }
+        (line, start)
+    }
+
+    fn innermost_line_mut(&mut self) -> &mut Vec<RecordedMove> {
+        let mut line = &mut self.record.moves;
+        for (offset, idx) in self.branches.iter() {
+            line = &mut line[*offset].variations[*idx];
+        }
+        line
+    }
+
+    /*
+     * Helper function listing every move of the current line from the start of the game.
+     */
+    fn current_line(&self) -> Vec<&RecordedMove> {
+        let mut moves = Vec::new();
+        let mut line = &self.record.moves;
+        for (offset, idx) in self.branches.iter() {
+            moves.extend(line[..*offset].iter());
+            line = &line[*offset].variations[*idx];
+        }
+        moves.extend(line.iter());
+        moves
+    }
+
+    /*
+     * Helper function replaying every variation branching off @line, where @before is the
+     * moves leading up to it, so bad variations are found when the record is loaded.
+     */
+    fn check_variations(&self, before: &[&RecordedMove], line: &[RecordedMove]) -> Result<(), ReviewError> {
+        let mut moves = before.to_vec();
+        for recorded in line.iter() {
+            for variation in recorded.variations.iter() {
+                let mut with_variation = moves.clone();
+                with_variation.extend(variation.iter());
+                self.replay(&with_variation)?;
+                self.check_variations(&moves, variation)?;
+            }
+            moves.push(recorded);
+        }
+        Ok(())
+    }
+
+    /*
+     * Helper function rebuilding the boards of the current line. Every line was replayed
+     * when it was loaded or played, so this cannot fail.
+     */
+    fn replay_current(&mut self) {
+        self.positions = self.replay(&self.current_line()).expect("checked line no longer replays");
+    }
+
+    /*
+     * Helper function making @moves from the start of the game. Moves of the main line roll
+     * the record's dice, and moves after a variation branches off roll seeded dice.
+     */
+    fn replay(&self, moves: &[&RecordedMove]) -> Result<Vec<Board>, ReviewError> {
+        // moves are references into the record, so the main line is where they are the same moves
+        let main_line = moves.iter().zip(self.record.moves.iter()).take_while(|(made, main)| std::ptr::eq(**made, *main)).count();
+        let mut board = start_board(&self.record);
+        let mut positions = vec![board.clone()];
+        for (idx, recorded) in moves.iter().enumerate() {
+            if idx == main_line {
+                board.script_dice(&[]);
+            }
+            board.apply(recorded.player, recorded.intent).map_err(|source| ReviewError::Replay {
+                ply: idx + 1,
+                notation: notation(recorded.intent),
+                source,
+            })?;
+            positions.push(board.clone());
+        }
+        Ok(positions)
+    }
+}
+
+/**
+ * run_console
+ * Step through a review from the console until the user quits.
+ * Commands: enter or n for the next move, p for the previous one, a number to jump to
+ * that ply, v and a number to follow a variation, u to leave a variation, m to go back
+ * to the main line, a move in record notation (eg "b place 3,0") to try it, w and a path
+ * to save the record with its variations, and q to quit.
+ * review - Review to step through.
+ * playback - How to animate each move stepped forward through, or None to show only the
+ * board after it.
+ */
+pub fn run_console(review: &mut Review, playback: Option<Playback>) {
+    let stdin = io::stdin();
+    let mut line = String::new();
+    loop {
+        println!("\n{}", review.board());
+        match review.last_move() {
+            Some(recorded) => println!("Ply {}/{}: {} {}", review.ply(), review.plies(), recorded.player, notation(recorded.intent)),
+            None => println!("Ply 0/{}: start", review.plies()),
+        }
+        if review.depth() > 0 {
+            println!("In a variation, {} deep", review.depth());
+        }
+        if let Some(comment) = review.last_move().and_then(|recorded| recorded.comment.as_ref()) {
+            println!("{}", comment);
+        }
+        for (idx, alternative) in review.alternatives().iter().enumerate() {
+            println!("  v{}: {} {}", idx, alternative.player, notation(alternative.intent));
+        }
+        print!("[n]ext, [p]revious, ply number, [v]ariation, [u]p, [m]ain line, move, [w]rite, or [q]uit: ");
+        io::stdout().flush().expect("Could not flush stdout");
+        line.clear();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let command = line.trim();
+        let moved = match command {
+            "" | "n" => {
+                let before = review.board().clone();
+                let moved = review.forward().is_some();
+                if let (true, Some(playback), Some(recorded)) = (moved, playback, review.last_move()) {
+                    let frames = playback::frames(&before, recorded.intent, review.board());
+                    playback.animate(&frames, &mut io::stdout()).expect("Could not write to stdout");
+                }
+                moved
+            },
+            "p" => review.back().is_some(),
+            "u" => review.leave_variation().is_some(),
+            "m" => {
+                review.main_line();
+                true
+            },
+            "q" => return,
+            _ if command.starts_with('v') => match command[1..].trim().parse() {
+                Ok(idx) => review.enter_variation(idx).is_some(),
+                Err(_) => false,
+            },
+            _ if command.starts_with("w ") => {
+                match review.record().save(command[2..].trim()) {
+                    Ok(()) => println!("Saved"),
+                    Err(err) => println!("{}", err),
+                }
+                true
+            },
+            _ if command.starts_with("a ") || command.starts_with("b ") => match parse_move(command) {
+                Ok(recorded) => match review.play(recorded.player, recorded.intent) {
+                    Ok(_) => true,
+                    Err(err) => {
+                        println!("{}", err);
+                        true
+                    },
+                },
+                Err(message) => {
+                    println!("{}", message);
+                    true
+                },
+            },
+            _ => match command.parse() {
+                Ok(ply) => review.go_to(ply).is_some(),
+                Err(_) => false,
+            },
+        };
+        if !moved {
+            println!("Cannot go there");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::MoveError;
+    use crate::test_support::fought_record;
+    use crate::vec::Vec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const GAME: &str = "
+        variant standard
+        a move 6,2 5,2
+        b place 4,0 ; opens the centre
+        b slide 4,0 down
+    ";
+
+    #[test]
+    fn step() {
+        let mut review = Review::new(GameRecord::parse(GAME).unwrap()).unwrap();
+        assert_eq!((review.ply(), review.plies()), (0, 3));
+        assert_eq!(review.board(), &Board::new());
+        assert!(review.back().is_none());
+        assert!(review.last_move().is_none());
+
+        let board = review.forward().unwrap();
+        assert_eq!(board.checker_at(Vec2::new(5, 2)).unwrap().owner, Some(PlayerId::A));
+        review.forward();
+        assert_eq!(review.last_move().unwrap().comment.as_deref(), Some("opens the centre"));
+        let board = review.forward().unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::B), vec![Vec2::new(4, 6)]);
+        assert!(review.forward().is_none());
+        assert_eq!(review.ply(), 3);
+
+        let board = review.back().unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::B), vec![Vec2::new(4, 0)]);
+        assert!(review.go_to(4).is_none());
+        assert_eq!(review.go_to(0).unwrap(), &Board::new());
+        assert_eq!(review.last_move().map(|recorded| recorded.intent), None);
+        review.go_to(1);
+        assert_eq!(review.last_move().unwrap().intent, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2)));
+    }
+
+    #[test]
+    fn replay_errors() {
+        let record = GameRecord::parse("a move 6,2 5,2\nb move 6,3 5,3").unwrap();
+        match Review::new(record) {
+            Err(ReviewError::Replay { ply: 2, notation, .. }) => assert_eq!(notation, "move 6,3 5,3"),
+            other => panic!("Expected move 2 to fail, got {:?}", other),
+        }
+        assert!(matches!(Review::load("no/such/record.txt"), Err(ReviewError::Record(RecordError::Io(_)))));
+    }
+
+    #[test]
+    fn variations() {
+        let text = "
+            a move 6,2 5,2
+            b place 4,0
+            (
+              b place 3,0
+              a place 2,0
+            )
+            a place 5,0
+        ";
+        let mut review = Review::new(GameRecord::parse(text).unwrap()).unwrap();
+        review.go_to(1);
+        let alternatives: Vec<Intent> = review.alternatives().iter().map(|recorded| recorded.intent).collect();
+        assert_eq!(alternatives, vec![Intent::PlaceStone(Vec2::new(3, 0))]);
+        assert!(review.enter_variation(1).is_none());
+
+        // Follow the recorded variation, then leave it where it branched off
+        let board = review.enter_variation(0).unwrap();
+        assert_eq!(board.stones_for_player(PlayerId::B), vec![Vec2::new(3, 0)]);
+        assert_eq!((review.depth(), review.ply(), review.plies()), (1, 2, 3));
+        review.forward();
+        assert_eq!(review.board().stones_for_player(PlayerId::A), vec![Vec2::new(2, 0)]);
+        assert_eq!(review.leave_variation().unwrap().stones_for_player(PlayerId::B), vec![]);
+        assert_eq!((review.depth(), review.ply(), review.plies()), (0, 1, 3));
+
+        // Playing a recorded move follows it; a new one starts a variation, nested in this one
+        review.play(PlayerId::B, Intent::PlaceStone(Vec2::new(3, 0))).unwrap();
+        assert_eq!(review.depth(), 1);
+        review.play(PlayerId::A, Intent::PlaceStone(Vec2::new(1, 0))).unwrap();
+        assert_eq!((review.depth(), review.ply()), (2, 3));
+        // and at the end of a line, extends it
+        review.play(PlayerId::B, Intent::PlaceStone(Vec2::new(0, 0))).unwrap();
+        assert_eq!((review.ply(), review.plies()), (4, 4));
+        let refused = review.play(PlayerId::B, Intent::PlaceStone(Vec2::new(0, 0)));
+        assert_eq!(refused.unwrap_err(), ApplyError::Move(MoveError::OccupiedError));
+        assert_eq!(review.plies(), 4);
+
+        // Stepping back before the start of a variation leaves it
+        review.go_to(2);
+        assert_eq!(review.depth(), 2);
+        review.back();
+        assert_eq!((review.depth(), review.ply()), (1, 1));
+        review.back();
+        assert_eq!((review.depth(), review.ply()), (0, 0));
+        review.go_to(1);
+        review.enter_variation(0);
+        review.main_line();
+        assert_eq!((review.depth(), review.ply()), (0, 1));
+        assert_eq!(review.forward().unwrap().stones_for_player(PlayerId::B), vec![Vec2::new(4, 0)]);
+
+        // The tree survives a round trip through the record format
+        let saved = review.record().to_string();
+        let expected = "variant standard\na move 6,2 5,2\nb place 4,0\n(\n  b place 3,0\n  a place 2,0\n  (\n    a place 1,0\n    b place 0,0\n  )\n)\na place 5,0\n";
+        assert_eq!(saved, expected);
+        assert_eq!(Review::new(GameRecord::parse(&saved).unwrap()).unwrap().record(), review.record());
+    }
+
+    #[test]
+    fn recorded_dice() {
+        let record = fought_record(&mut StdRng::seed_from_u64(7), 12);
+        let mut review = Review::new(record.clone()).unwrap();
+        let plies = review.plies();
+        assert_eq!(review.go_to(plies).map(Board::position_hash), record.position);
+
+        // Trying another move part way through rolls its own dice, and the main line still
+        // replays the recorded ones when it is come back to
+        let (first, second) = (&record.moves[0], &record.moves[1]);
+        let other = with_replay(&record, |game| {
+            game.try_apply_move(first.player, first.intent).unwrap();
+            game.move_request(second.player).all_moves().copied().find(|intent| *intent != second.intent)
+        });
+        review.go_to(1);
+        review.play(second.player, other.unwrap()).unwrap();
+        assert_eq!(review.depth(), 1);
+        review.main_line();
+        assert_eq!(review.go_to(plies).map(Board::position_hash), record.position);
+    }
+
+    #[test]
+    fn bad_variations() {
+        let record = GameRecord::parse("a move 6,2 5,2\n(\na move 1,2 2,2\n)").unwrap();
+        assert!(matches!(Review::new(record), Err(ReviewError::Replay { ply: 1, .. })));
+    }
+}
@@ -0,0 +1,347 @@
+/**
+ * Scenario files describe a game setup in plain text: the board, rule overrides, the
+ * starting position, and victory conditions. They are read into a GameConfig, so
+ * puzzles and challenge setups need no code changes.
+ *
+ * One directive per line, words separated by spaces, # starts a comment:
+ *
+ *   name <free text>
+ *   variant standard|grand
+ *   rule negation|circularity|orthogonal_checkers|elimination|pie_rule|deterministic_combat on|off
+ *   rule fire_range|actions_per_turn <n>
+ *   rule slide_limit <n>|none
+ *   repetition_limit <n>|none
+ *   free_setup on|off
+ *   first a|b
+ *   stones a|b <n>
+ *   checker a|b <x> <y> <height>
+ *   stone a|b <x> <y>
+ *   terrain <x> <y> plain|mud|hill
+ *   hill <turns> <x>,<y> [<x>,<y> ...]
+ *   move_limit <n> [margin <m>]
+ *   time_control <seconds> <increment seconds> <warning seconds>
+ *   glyphs classic|unicode
+ *   glyph a|b stone <char>
+ *   glyph a|b checker <height> <char>
+ *   glyph empty stone|checker <char>
+ *
+ * Any checker or stone line replaces the variant's starting layout with exactly the
+ * pieces listed.
+ */
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::board::{Board, Terrain};
+use crate::config::{GameConfig, GlyphTheme, HillConfig, StartPosition, TimeControl, Variant};
+use crate::game::{CheckerHeight, PlayerId};
+use crate::vec::Vec2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScenarioError {
+    // The file could not be read
+    #[error("Could not read scenario: {0}")]
+    Io(#[from] io::Error),
+    // A line could not be understood, with its line number counting from 1
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    // The scenario parsed, but its pieces do not fit on the board together
+    #[error("Invalid scenario: {0}")]
+    Invalid(String),
+}
+
+/**
+ * Scenario is a parsed scenario file.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub name: Option<String>,
+    pub config: GameConfig,
+}
+
+impl Scenario {
+    /**
+     * load
+     * Read and parse a scenario file.
+     * path - File to read.
+     * ret - The scenario, or a ScenarioError if it could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Scenario, ScenarioError> {
+        Scenario::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * parse
+     * Parse the text of a scenario file.
+     * text - Contents of the file.
+     * ret - The scenario, or a ScenarioError describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let mut name = None;
+        let mut config = GameConfig::default();
+        let mut position: Option<StartPosition> = None;
+
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |message: String| ScenarioError::Parse { line: idx + 1, message };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let args = &words[1..];
+            match words[0] {
+                "name" => name = Some(line["name".len()..].trim().to_string()),
+                "variant" => {
+                    config.variant = match arg(args, 0).map_err(err)? {
+                        "standard" => Variant::Standard,
+                        "grand" => Variant::Grand10x8,
+                        other => return Err(err(format!("unknown variant '{}'", other))),
+                    };
+                },
+                "rule" => {
+                    let value = arg(args, 1).map_err(err)?;
+                    let rules = &mut config.rules;
+                    match arg(args, 0).map_err(err)? {
+                        "negation" => rules.negation = switch(value).map_err(err)?,
+                        "circularity" => rules.circularity = switch(value).map_err(err)?,
+                        "orthogonal_checkers" => rules.orthogonal_checkers = switch(value).map_err(err)?,
+                        "elimination" => rules.elimination = switch(value).map_err(err)?,
+                        "pie_rule" => rules.pie_rule = switch(value).map_err(err)?,
+                        "deterministic_combat" => rules.deterministic_combat = switch(value).map_err(err)?,
+                        "fire_range" => rules.fire_range = number(value).map_err(err)?,
+                        "actions_per_turn" => rules.actions_per_turn = number(value).map_err(err)?,
+                        "slide_limit" => rules.slide_limit = optional(value).map_err(err)?,
+                        other => return Err(err(format!("unknown rule '{}'", other))),
+                    }
+                },
+                "repetition_limit" => config.repetition_limit = optional(arg(args, 0).map_err(err)?).map_err(err)?,
+                "free_setup" => config.free_setup = switch(arg(args, 0).map_err(err)?).map_err(err)?,
+                "first" => config.first_player = player(arg(args, 0).map_err(err)?).map_err(err)?,
+                "stones" => {
+                    let player = player(arg(args, 0).map_err(err)?).map_err(err)?;
+                    let stones = number(arg(args, 1).map_err(err)?).map_err(err)?;
+                    config = config.with_stone_pool(player, stones);
+                },
+                "checker" => {
+                    let owner = player(arg(args, 0).map_err(err)?).map_err(err)?;
+                    let pos = Vec2::new(number(arg(args, 1).map_err(err)?).map_err(err)?, number(arg(args, 2).map_err(err)?).map_err(err)?);
+                    let height = height(arg(args, 3).map_err(err)?).map_err(err)?;
+                    position.get_or_insert_with(StartPosition::default).checkers.push((owner, pos, height));
+                },
+                "stone" => {
+                    let owner = player(arg(args, 0).map_err(err)?).map_err(err)?;
+                    let pos = Vec2::new(number(arg(args, 1).map_err(err)?).map_err(err)?, number(arg(args, 2).map_err(err)?).map_err(err)?);
+                    position.get_or_insert_with(StartPosition::default).stones.push((owner, pos));
+                },
+                "terrain" => {
+                    let pos = Vec2::new(number(arg(args, 0).map_err(err)?).map_err(err)?, number(arg(args, 1).map_err(err)?).map_err(err)?);
+                    let terrain = match arg(args, 2).map_err(err)? {
+                        "plain" => Terrain::Plain,
+                        "mud" => Terrain::Mud,
+                        "hill" => Terrain::Hill,
+                        other => return Err(err(format!("unknown terrain '{}'", other))),
+                    };
+                    config.terrain.push((pos, terrain));
+                },
+                "hill" => {
+                    let turns = number(arg(args, 0).map_err(err)?).map_err(err)?;
+                    let mut squares = Vec::new();
+                    for square in args[1..].iter() {
+                        squares.push(coordinates(square).map_err(err)?);
+                    }
+                    if squares.is_empty() {
+                        return Err(err(String::from("hill needs at least one square")));
+                    }
+                    config.hill = Some(HillConfig { squares, turns });
+                },
+                "move_limit" => {
+                    config.move_limit = Some(number(arg(args, 0).map_err(err)?).map_err(err)?);
+                    if args.len() > 1 {
+                        if args[1] != "margin" {
+                            return Err(err(format!("expected 'margin', found '{}'", args[1])));
+                        }
+                        config.adjudication_margin = Some(number(arg(args, 2).map_err(err)?).map_err(err)?);
+                    }
+                },
+                "time_control" => {
+                    let mut seconds = [0; 3];
+                    for (idx, value) in seconds.iter_mut().enumerate() {
+                        *value = number(arg(args, idx).map_err(err)?).map_err(err)?;
+                    }
+                    config.time_control = Some(TimeControl {
+                        initial: Duration::from_secs(seconds[0]),
+                        increment: Duration::from_secs(seconds[1]),
+                        warning: Duration::from_secs(seconds[2]),
+                    });
+                },
+                "glyphs" => {
+                    let theme = arg(args, 0).map_err(err)?;
+                    config.glyphs = GlyphTheme::named(theme).ok_or_else(|| err(format!("unknown glyph theme '{}'", theme)))?;
+                },
+                "glyph" => {
+                    let glyphs = &mut config.glyphs;
+                    match (arg(args, 0).map_err(err)?, arg(args, 1).map_err(err)?) {
+                        ("empty", "stone") => glyphs.empty_stone = glyph(arg(args, 2).map_err(err)?).map_err(err)?,
+                        ("empty", "checker") => glyphs.empty_checker = glyph(arg(args, 2).map_err(err)?).map_err(err)?,
+                        (owner, "stone") => {
+                            glyphs.stones[player(owner).map_err(err)?.index()] = glyph(arg(args, 2).map_err(err)?).map_err(err)?;
+                        },
+                        (owner, "checker") => {
+                            let idx = height(arg(args, 2).map_err(err)?).map_err(err)?.get();
+                            glyphs.checkers[player(owner).map_err(err)?.index()][idx] = glyph(arg(args, 3).map_err(err)?).map_err(err)?;
+                        },
+                        (_, other) => return Err(err(format!("expected 'stone' or 'checker', found '{}'", other))),
+                    }
+                },
+                other => return Err(err(format!("unknown directive '{}'", other))),
+            }
+        }
+        config.start_position = position;
+        Scenario::validate(&config)?;
+        Ok(Scenario { name, config })
+    }
+
+    /*
+     * Helper function setting the scenario up on a board, so pieces that overlap or fall
+     * off the board are reported here rather than when the game starts.
+     */
+    fn validate(config: &GameConfig) -> Result<(), ScenarioError> {
+        let mut board = Board::for_variant(config.variant);
+        board.set_rules(config.rules);
+        for (pos, terrain) in config.terrain.iter() {
+            board.set_terrain(*pos, *terrain).map_err(|_| ScenarioError::Invalid(format!("terrain at {} is off the board", pos)))?;
+        }
+        if let Some(position) = &config.start_position {
+            board.set_position(position).map_err(|e| ScenarioError::Invalid(e.to_string()))?;
+        }
+        if let Some(hill) = &config.hill {
+            for pos in hill.squares.iter() {
+                board.checker_at(*pos).map_err(|_| ScenarioError::Invalid(format!("hill square {} is off the board", pos)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/*
+ * Helper functions reading one word of a directive, shared with game records.
+ */
+pub(crate) fn arg<'t>(args: &[&'t str], idx: usize) -> Result<&'t str, String> {
+    args.get(idx).copied().ok_or_else(|| String::from("missing value"))
+}
+
+pub(crate) fn number<T: FromStr>(word: &str) -> Result<T, String> {
+    word.parse().map_err(|_| format!("expected a number, found '{}'", word))
+}
+
+fn optional<T: FromStr>(word: &str) -> Result<Option<T>, String> {
+    if word == "none" {
+        return Ok(None);
+    }
+    number(word).map(Some)
+}
+
+pub(crate) fn switch(word: &str) -> Result<bool, String> {
+    match word {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("expected 'on' or 'off', found '{}'", word)),
+    }
+}
+
+pub(crate) fn player(word: &str) -> Result<PlayerId, String> {
+    match word {
+        "a" => Ok(PlayerId::A),
+        "b" => Ok(PlayerId::B),
+        _ => Err(format!("expected player 'a' or 'b', found '{}'", word)),
+    }
+}
+
+pub(crate) fn height(word: &str) -> Result<CheckerHeight, String> {
+    match CheckerHeight::new(number(word)?) {
+        Some(height) if !height.is_zero() => Ok(height),
+        _ => Err(format!("checker height must be 1 to {}, found {}", CheckerHeight::MAX, word)),
+    }
+}
+
+fn glyph(word: &str) -> Result<char, String> {
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(glyph), None) => Ok(glyph),
+        _ => Err(format!("expected a single character, found '{}'", word)),
+    }
+}
+
+pub(crate) fn coordinates(word: &str) -> Result<Vec2, String> {
+    let (x, y) = word.split_once(',').ok_or_else(|| format!("expected x,y, found '{}'", word))?;
+    Ok(Vec2::new(number(x)?, number(y)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "
+        name Last checker standing   # a small puzzle
+        variant standard
+        rule elimination on
+        rule slide_limit 2
+        repetition_limit none
+        first b
+        stones b 5
+        checker a 5 2 3
+        checker b 2 2 1
+        stone b 1 1
+        terrain 2 2 hill
+        move_limit 40 margin 10
+        time_control 300 2 30
+        glyphs unicode
+        glyph a checker 3 X
+        glyph empty checker ~
+    ";
+
+    #[test]
+    fn parse() {
+        let scenario = Scenario::parse(PUZZLE).unwrap();
+        assert_eq!(scenario.name.as_deref(), Some("Last checker standing"));
+        let config = scenario.config;
+        assert!(config.rules.elimination);
+        assert_eq!(config.rules.slide_limit, Some(2));
+        assert_eq!(config.repetition_limit, None);
+        assert_eq!(config.first_player, PlayerId::B);
+        assert_eq!(config.stones_for(PlayerId::B), 5);
+        assert_eq!(config.stones_for(PlayerId::A), Variant::Standard.stones());
+        let position = config.start_position.unwrap();
+        assert_eq!(position.checkers, vec![(PlayerId::A, Vec2::new(5, 2), CheckerHeight::THREE), (PlayerId::B, Vec2::new(2, 2), CheckerHeight::ONE)]);
+        assert_eq!(position.stones, vec![(PlayerId::B, Vec2::new(1, 1))]);
+        assert_eq!(config.terrain, vec![(Vec2::new(2, 2), Terrain::Hill)]);
+        assert_eq!((config.move_limit, config.adjudication_margin), (Some(40), Some(10)));
+        assert_eq!(config.glyphs.checker(PlayerId::A, CheckerHeight::THREE), 'X');
+        assert_eq!(config.glyphs.stone(PlayerId::B), GlyphTheme::unicode().stone(PlayerId::B));
+        assert_eq!(config.glyphs.empty_checker, '~');
+        let control = config.time_control.unwrap();
+        assert_eq!((control.initial, control.increment, control.warning), (Duration::from_secs(300), Duration::from_secs(2), Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        match Scenario::parse("variant standard\nrule negation maybe") {
+            Err(ScenarioError::Parse { line: 2, .. }) => (),
+            other => panic!("Expected a parse error on line 2, got {:?}", other),
+        }
+        assert!(matches!(Scenario::parse("teleport a"), Err(ScenarioError::Parse { line: 1, .. })));
+        assert!(matches!(Scenario::parse("checker a 5"), Err(ScenarioError::Parse { .. })));
+        assert!(matches!(Scenario::parse("checker a 5 2 4"), Err(ScenarioError::Parse { line: 1, .. })));
+        assert!(matches!(Scenario::parse("checker a 5 2 0"), Err(ScenarioError::Parse { line: 1, .. })));
+        // Stone on a corner of a checker's square breaks the rule of negation
+        assert!(matches!(Scenario::parse("checker a 1 1 1\nstone b 1 1"), Err(ScenarioError::Invalid(_))));
+        assert!(matches!(Scenario::parse("hill 3 9,9"), Err(ScenarioError::Invalid(_))));
+        assert!(matches!(Scenario::parse("time_control 300 2"), Err(ScenarioError::Parse { line: 1, .. })));
+        assert!(matches!(Scenario::parse("glyphs fancy"), Err(ScenarioError::Parse { line: 1, .. })));
+        assert!(matches!(Scenario::parse("glyph a stone ab"), Err(ScenarioError::Parse { line: 1, .. })));
+        assert!(matches!(Scenario::parse("glyph c checker 1 x"), Err(ScenarioError::Parse { line: 1, .. })));
+        assert!(matches!(Scenario::load("no/such/scenario.txt"), Err(ScenarioError::Io(_))));
+    }
+}
@@ -0,0 +1,488 @@
+/**
+ * Material scoring of a position, used to adjudicate games that hit the move limit
+ * and as a starting point for engines.
+ *
+ * Evaluator weights can be saved as plain text, one feature per line, # starting a comment
+ * line:
+ *
+ *   <feature> <weight>
+ *
+ * Features missing from the file keep their default weights.
+ */
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::board::Board;
+use crate::game::{other_player, DrawReason, GameOutcome, GameView, PlayerId, WinReason};
+use crate::grid::Grid;
+use crate::vec::Vec2;
+
+// value of each level of a checker's height
+pub const CHECKER_HEIGHT_VALUE: i32 = 10;
+// value of a stone on the board
+pub const BOARD_STONE_VALUE: i32 = 2;
+// value of a stone still in the pool
+pub const POOL_STONE_VALUE: i32 = 1;
+// smallest lead in progress, as a share of the way to a win, that wins an adjudication
+// when material does not decide it
+pub const PROGRESS_MARGIN: f32 = 0.25;
+// characters between the brackets of the progress bars shown under the board
+pub const PROGRESS_BAR_WIDTH: usize = 10;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WeightsError {
+    // The weights file could not be read or written
+    #[error("Could not access weights file: {0}")]
+    Io(#[from] io::Error),
+    // A line could not be understood, with its line number counting from 1
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+/**
+ * material
+ * Add up the value of one player's pieces.
+ * view - Position to score.
+ * player - Id of player to score.
+ * ret - Total value of the player's checkers, stones on the board, and stones in their pool.
+ */
+pub fn material(view: &GameView, player: PlayerId) -> i32 {
+    let checkers: i32 = view.board.checkers_for_player(player)
+        .iter()
+        .map(|pos| view.board.checker_at(*pos).unwrap().height.get() as i32 * CHECKER_HEIGHT_VALUE)
+        .sum();
+    let board_stones = view.board.stones_for_player(player).len() as i32 * BOARD_STONE_VALUE;
+    checkers + board_stones + view.stones_for(player) * POOL_STONE_VALUE
+}
+
+/**
+ * score
+ * Get a player's material advantage over their opponent.
+ * view - Position to score.
+ * player - Id of player to score for.
+ * ret - Positive if the player is ahead, negative if they are behind.
+ */
+pub fn score(view: &GameView, player: PlayerId) -> i32 {
+    material(view, player) - material(view, other_player(player))
+}
+
+/**
+ * adjudicate
+ * Decide a game that was stopped before it finished, on material, or on progress toward a
+ * win when neither player is far enough ahead on material.
+ * view - Final position.
+ * player - Id of either player.
+ * margin - Smallest material lead that counts as a win.
+ * ret - Win by adjudication for the player ahead on material, or else for the player ahead
+ * by PROGRESS_MARGIN in progress, or a draw at the move limit.
+ */
+pub fn adjudicate(view: &GameView, player: PlayerId, margin: i32) -> GameOutcome {
+    let lead = score(view, player);
+    let progress_lead = progress(view.board, player).share - progress(view.board, other_player(player)).share;
+    if lead >= margin && lead > 0 {
+        GameOutcome::Win { winner: player, reason: WinReason::Adjudication }
+    } else if -lead >= margin && lead < 0 {
+        GameOutcome::Win { winner: other_player(player), reason: WinReason::Adjudication }
+    } else if progress_lead >= PROGRESS_MARGIN {
+        GameOutcome::Win { winner: player, reason: WinReason::Adjudication }
+    } else if -progress_lead >= PROGRESS_MARGIN {
+        GameOutcome::Win { winner: other_player(player), reason: WinReason::Adjudication }
+    } else {
+        GameOutcome::Draw(DrawReason::MoveLimit)
+    }
+}
+
+/**
+ * Progress is how far a player has got toward either race to a win: a checker across the
+ * board, or a chain of stones from top to bottom.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    // columns the player's most advanced checker stands from their own edge; reaching the
+    // far column is width - 1
+    pub columns: u32,
+    // rows of corners spanned by the player's tallest connected group of stones; a chain
+    // spans height + 1
+    pub rows: u32,
+    // the further of the two races, as a share of the way to a win from 0 to 1
+    pub share: f32,
+}
+
+/**
+ * progress
+ * Measure how far a player has got toward a win.
+ * board - Position to measure.
+ * player - Id of player to measure for.
+ * ret - Columns covered by their checkers, rows spanned by their stones, and the share of
+ * the way to a win of whichever is further along.
+ */
+pub fn progress(board: &Board, player: PlayerId) -> Progress {
+    let columns = advancement_columns(board, player);
+    let rows = stone_rows(board, player);
+    let share = (columns as f32 / (board.width() - 1) as f32).max(rows as f32 / (board.height() + 1) as f32);
+    Progress { columns, rows, share }
+}
+
+/**
+ * progress_bar
+ * Draw a player's progress as a bar, for showing under the board.
+ * progress - Progress to draw.
+ * width - Number of characters between the brackets.
+ * ret - eg "[####------] 40%".
+ */
+pub fn progress_bar(progress: &Progress, width: usize) -> String {
+    let filled = (progress.share * width as f32).round() as usize;
+    format!("[{}{}] {:.0}%", "#".repeat(filled), "-".repeat(width - filled), progress.share * 100.0)
+}
+
+/**
+ * FeatureName is one of the things an Evaluator weighs up about a position.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FeatureName {
+    // checker heights and stones on the board, as in material, without the pools
+    Material,
+    // columns the checkers have advanced toward the far column
+    Advancement,
+    // how many fewer stones the player needs than the opponent to finish a stone chain
+    ChainProgress,
+    // expected damage the player's checkers could deal next turn, less what they could take
+    Safety,
+}
+
+impl FeatureName {
+    // every feature, in the order features measures them
+    pub const ALL: [FeatureName; 4] = [FeatureName::Material, FeatureName::Advancement, FeatureName::ChainProgress, FeatureName::Safety];
+}
+
+impl fmt::Display for FeatureName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            FeatureName::Material => "material",
+            FeatureName::Advancement => "advancement",
+            FeatureName::ChainProgress => "chain progress",
+            FeatureName::Safety => "safety",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/**
+ * features
+ * Measure each feature of a position, unweighted. Every feature is the player's measure less
+ * the opponent's, so positive values favour the player.
+ * board - Position to measure.
+ * player - Id of player to measure for.
+ * ret - Each feature with its value, in the order Material, Advancement, ChainProgress, Safety.
+ */
+pub fn features(board: &Board, player: PlayerId) -> Vec<(FeatureName, f32)> {
+    let opponent = other_player(player);
+    let material = board_material(board, player) - board_material(board, opponent);
+    let advancement = advancement(board, player) - advancement(board, opponent);
+    // a player cut off everywhere needs more stones than any chain could
+    let unreachable = board.height() as u32 + 2;
+    let gap = |player| board.chain_gap(player).unwrap_or(unreachable) as f32;
+    let chain_progress = gap(opponent) - gap(player);
+    let safety = exposure(board, opponent) - exposure(board, player);
+    vec![
+        (FeatureName::Material, material as f32),
+        (FeatureName::Advancement, advancement as f32),
+        (FeatureName::ChainProgress, chain_progress),
+        (FeatureName::Safety, safety),
+    ]
+}
+
+/**
+ * Evaluator scores positions as a weighted sum of their features, and can show how much
+ * each feature added to the score.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Evaluator {
+    pub material: f32,
+    pub advancement: f32,
+    pub chain_progress: f32,
+    pub safety: f32,
+}
+
+impl Default for Evaluator {
+    /* Weights in material points: a height lost to an attack costs as much as it is worth. */
+    fn default() -> Evaluator {
+        Evaluator {
+            material: 1.0,
+            advancement: 2.0,
+            chain_progress: 3.0,
+            safety: CHECKER_HEIGHT_VALUE as f32,
+        }
+    }
+}
+
+impl Evaluator {
+    /**
+     * weight
+     * feature - Feature to look up.
+     * ret - What one unit of the feature is worth.
+     */
+    pub fn weight(&self, feature: FeatureName) -> f32 {
+        match feature {
+            FeatureName::Material => self.material,
+            FeatureName::Advancement => self.advancement,
+            FeatureName::ChainProgress => self.chain_progress,
+            FeatureName::Safety => self.safety,
+        }
+    }
+
+    /**
+     * set_weight
+     * feature - Feature to change.
+     * weight - What one unit of the feature is now worth.
+     */
+    pub fn set_weight(&mut self, feature: FeatureName, weight: f32) {
+        match feature {
+            FeatureName::Material => self.material = weight,
+            FeatureName::Advancement => self.advancement = weight,
+            FeatureName::ChainProgress => self.chain_progress = weight,
+            FeatureName::Safety => self.safety = weight,
+        }
+    }
+
+    /**
+     * load
+     * Read weights saved by save.
+     * path - File to read.
+     * ret - The evaluator, or a WeightsError if the file could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Evaluator, WeightsError> {
+        Evaluator::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * save
+     * Write the weights to a file, replacing anything already there.
+     * path - File to write.
+     * ret - Ok, or WeightsError::Io if the file could not be written.
+     */
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), WeightsError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /**
+     * parse
+     * Parse saved weights.
+     * text - Contents of a weights file.
+     * ret - The evaluator, or WeightsError::Parse describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<Evaluator, WeightsError> {
+        let mut evaluator = Evaluator::default();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| WeightsError::Parse { line: idx + 1, message };
+            let (name, value) = line.rsplit_once(' ').ok_or_else(|| err(String::from("expected <feature> <weight>")))?;
+            let feature = FeatureName::ALL.into_iter()
+                .find(|feature| feature.to_string() == name.trim())
+                .ok_or_else(|| err(format!("unknown feature '{}'", name.trim())))?;
+            let weight = value.parse().map_err(|e: std::num::ParseFloatError| err(e.to_string()))?;
+            evaluator.set_weight(feature, weight);
+        }
+        Ok(evaluator)
+    }
+
+    /**
+     * explain
+     * Break an evaluation down into what each feature added to it.
+     * board - Position to evaluate.
+     * player - Id of player to evaluate for.
+     * ret - Each feature with its weighted value. These add up to evaluate.
+     */
+    pub fn explain(&self, board: &Board, player: PlayerId) -> Vec<(FeatureName, f32)> {
+        features(board, player)
+            .into_iter()
+            .map(|(feature, value)| (feature, value * self.weight(feature)))
+            .collect()
+    }
+
+    /**
+     * evaluate
+     * Score a position.
+     * board - Position to evaluate.
+     * player - Id of player to evaluate for.
+     * ret - Positive if the position favours the player, negative if it favours the opponent.
+     */
+    pub fn evaluate(&self, board: &Board, player: PlayerId) -> f32 {
+        self.explain(board, player).iter().map(|(_, value)| value).sum()
+    }
+}
+
+impl fmt::Display for Evaluator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for feature in FeatureName::ALL {
+            writeln!(f, "{} {}", feature, self.weight(feature))?;
+        }
+        Ok(())
+    }
+}
+
+/* Helper function adding up a player's checker heights and stones on the board. */
+fn board_material(board: &Board, player: PlayerId) -> i32 {
+    let checkers: i32 = board.checkers_for_player(player)
+        .iter()
+        .map(|pos| board.checker_at(*pos).unwrap().height.get() as i32 * CHECKER_HEIGHT_VALUE)
+        .sum();
+    checkers + board.stones_for_player(player).len() as i32 * BOARD_STONE_VALUE
+}
+
+/*
+ * Helper function adding up the columns a player's checkers have moved toward the far column:
+ * player A heads for column 0 and player B for the last column.
+ */
+fn advancement(board: &Board, player: PlayerId) -> i32 {
+    let last = board.width() as i32 - 1;
+    board.checkers_for_player(player)
+        .iter()
+        .map(|pos| match player {
+            PlayerId::A => last - pos.x,
+            PlayerId::B => pos.x,
+        })
+        .sum()
+}
+
+/* Helper function finding how many columns a player's most advanced checker stands from their own edge. */
+fn advancement_columns(board: &Board, player: PlayerId) -> u32 {
+    let last = board.width() as i32 - 1;
+    board.checkers_for_player(player)
+        .iter()
+        .map(|pos| match player {
+            PlayerId::A => last - pos.x,
+            PlayerId::B => pos.x,
+        })
+        .max()
+        .unwrap_or(0) as u32
+}
+
+/* Helper function finding the most rows of corners spanned by one connected group of a player's stones. */
+fn stone_rows(board: &Board, player: PlayerId) -> u32 {
+    let mut seen = Grid::new(board.width() + 1, board.height() + 1, false);
+    let mut tallest = 0;
+    for start in board.stones_for_player(player) {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let (mut top, mut bottom) = (start.y, start.y);
+        let mut group: Vec<Vec2> = vec![start];
+        while let Some(pos) = group.pop() {
+            (top, bottom) = (top.min(pos.y), bottom.max(pos.y));
+            for next in board.stone_neighbours(pos) {
+                if !seen[next] && board.stone_at(next).is_ok_and(|stone| stone.owner == Some(player)) {
+                    seen[next] = true;
+                    group.push(next);
+                }
+            }
+        }
+        tallest = tallest.max(bottom - top + 1);
+    }
+    tallest as u32
+}
+
+/* Helper function adding up the expected damage to a player's checkers from one attack on each. */
+fn exposure(board: &Board, player: PlayerId) -> f32 {
+    board.checkers_for_player(player)
+        .iter()
+        .map(|pos| board.attackers_by(*pos, other_player(player)) as f32 * board.hit_chance(*pos))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::game::{Checker, Stone, STARTING_STONES};
+    use crate::vec::Vec2;
+
+    #[test]
+    fn score() {
+        let mut board = Board::new();
+        let view = GameView { board: &board, stones: [STARTING_STONES; 2], captures: [0; 2], history: &[], dice: &[], clocks: None };
+        assert_eq!(super::score(&view, PlayerId::A), 0);
+        assert_eq!(material(&view, PlayerId::A), 12 * CHECKER_HEIGHT_VALUE + STARTING_STONES);
+
+        board.place_checker_at(Vec2::new(1, 2), Checker::empty()).unwrap();
+        let view = GameView { board: &board, stones: [STARTING_STONES, STARTING_STONES - 1], captures: [0; 2], history: &[], dice: &[], clocks: None };
+        assert_eq!(super::score(&view, PlayerId::A), CHECKER_HEIGHT_VALUE + POOL_STONE_VALUE);
+        assert_eq!(super::score(&view, PlayerId::B), -(CHECKER_HEIGHT_VALUE + POOL_STONE_VALUE));
+    }
+
+    #[test]
+    fn explain() {
+        let mut board = Board::new();
+        let evaluator = Evaluator::default();
+        // The start is symmetric
+        assert!(evaluator.explain(&board, PlayerId::A).iter().all(|(_, value)| *value == 0.0));
+
+        board.place_checker_at(Vec2::new(1, 2), Checker::empty()).unwrap();
+        board.place_stone_at(Vec2::new(7, 0), Stone::new(PlayerId::A)).unwrap();
+        let explained = evaluator.explain(&board, PlayerId::A);
+        let value = |feature| explained.iter().find(|(name, _)| *name == feature).unwrap().1;
+        let lost = Board::new().checker_at(Vec2::new(1, 2)).unwrap().height.get() as f32;
+        assert_eq!(value(FeatureName::Material), lost * CHECKER_HEIGHT_VALUE as f32 + BOARD_STONE_VALUE as f32);
+        assert_eq!(value(FeatureName::Advancement), evaluator.advancement);
+        assert_eq!(value(FeatureName::ChainProgress), evaluator.chain_progress);
+        let sum: f32 = explained.iter().map(|(_, value)| value).sum();
+        assert_eq!(evaluator.evaluate(&board, PlayerId::A), sum);
+        assert_eq!(evaluator.evaluate(&board, PlayerId::B), -sum);
+        assert_eq!(FeatureName::ChainProgress.to_string(), "chain progress");
+    }
+
+    #[test]
+    fn weights() {
+        let evaluator = Evaluator { material: 1.5, advancement: -0.25, chain_progress: 3.0, safety: 8.0 };
+        assert_eq!(Evaluator::parse(&evaluator.to_string()).unwrap(), evaluator);
+        let partial = Evaluator::parse("# tuned\nchain progress 4.5\n").unwrap();
+        assert_eq!(partial, Evaluator { chain_progress: 4.5, ..Evaluator::default() });
+        assert!(matches!(Evaluator::parse("material 1\nmobility 2"), Err(WeightsError::Parse { line: 2, .. })));
+        assert!(matches!(Evaluator::parse("safety high"), Err(WeightsError::Parse { line: 1, .. })));
+    }
+
+    #[test]
+    fn progress() {
+        let mut board = Board::new();
+        let start = super::progress(&board, PlayerId::A);
+        assert_eq!(start, super::progress(&board, PlayerId::B));
+        assert_eq!(start.rows, 0);
+
+        for y in 1..=3 {
+            board.place_stone_at(Vec2::new(4, y), Stone::new(PlayerId::B)).unwrap();
+        }
+        board.place_stone_at(Vec2::new(0, 6), Stone::new(PlayerId::B)).unwrap();
+        let chained = super::progress(&board, PlayerId::B);
+        assert_eq!(chained.rows, 3);
+        assert_eq!(chained.share, 3.0 / 7.0);
+        assert_eq!(progress_bar(&chained, 7), "[###----] 43%");
+        assert_eq!(progress_bar(&Progress { columns: 7, rows: 0, share: 1.0 }, 4), "[####] 100%");
+    }
+
+    #[test]
+    fn adjudicate() {
+        let mut board = Board::new();
+        board.place_checker_at(Vec2::new(1, 2), Checker::empty()).unwrap();
+        let view = GameView { board: &board, stones: [STARTING_STONES; 2], captures: [0; 2], history: &[], dice: &[], clocks: None };
+        let a_wins = GameOutcome::Win { winner: PlayerId::A, reason: WinReason::Adjudication };
+        assert_eq!(super::adjudicate(&view, PlayerId::A, 5), a_wins);
+        assert_eq!(super::adjudicate(&view, PlayerId::B, 5), a_wins);
+        assert_eq!(super::adjudicate(&view, PlayerId::B, 11), GameOutcome::Draw(DrawReason::MoveLimit));
+
+        // Level on material, a long chain of stones decides it
+        let mut board = Board::new();
+        for y in 0..4 {
+            board.place_stone_at(Vec2::new(4, y), Stone::new(PlayerId::B)).unwrap();
+        }
+        let view = GameView { board: &board, stones: [STARTING_STONES, STARTING_STONES - 4], captures: [0; 2], history: &[], dice: &[], clocks: None };
+        let b_wins = GameOutcome::Win { winner: PlayerId::B, reason: WinReason::Adjudication };
+        assert_eq!(super::adjudicate(&view, PlayerId::A, 5), b_wins);
+    }
+}
@@ -0,0 +1,101 @@
+/**
+ * Conflict rules for the experimental simultaneous-turn mode, where both players
+ * choose a move from the same position and the two moves are resolved together.
+ *
+ * Rules, applied in order:
+ * 1. If both moves would put a piece on the same square or corner (two checkers
+ *    stepping to the same square, two stones placed on or slid to the same corner),
+ *    the pieces bounce and neither move happens.
+ * 2. Movement (moving, placing, sliding) resolves before attacks, player A first.
+ *    A move that is no longer legal once the moves before it have been made is dropped.
+ * 3. Attacks resolve last. An attack on a checker that has moved away misses.
+ */
+use crate::board::Board;
+use crate::game::Intent;
+use crate::vec::Vec2;
+
+/**
+ * Target is where a move puts a piece. Checkers and stones live on different grids,
+ * so they only conflict with pieces of the same kind.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Checker(Vec2),
+    Stone(Vec2),
+}
+
+/**
+ * target_of
+ * Find where a move would put a piece, if anywhere.
+ * board - Position both moves were chosen from.
+ * intent - Move to look at.
+ * ret - Square or corner the piece ends on, or None for moves that do not place a piece.
+ */
+pub fn target_of(board: &Board, intent: Intent) -> Option<Target> {
+    match intent {
+        Intent::MoveChecker(_, to) => Some(Target::Checker(to)),
+        Intent::PlaceChecker(at, _) => Some(Target::Checker(at)),
+        Intent::PlaceStone(at) => Some(Target::Stone(at)),
+        Intent::SlideStone(from, direction) => board
+            .slide_stone_result(from, direction)
+            .ok()
+            .map(Target::Stone),
+        Intent::FireChecker(_) | Intent::SwapSides => None,
+    }
+}
+
+/**
+ * resolve
+ * Decide which of two simultaneous moves go ahead, and in which order.
+ * board - Position both moves were chosen from.
+ * intent_a - Move chosen by player A.
+ * intent_b - Move chosen by player B.
+ * ret - Moves to attempt, in order, as (player index, move). Index 0 is player A.
+ *       Each must still be checked for legality when its turn comes.
+ */
+pub fn resolve(board: &Board, intent_a: Intent, intent_b: Intent) -> Vec<(usize, Intent)> {
+    let target_a = target_of(board, intent_a);
+    if target_a.is_some() && target_a == target_of(board, intent_b) {
+        return Vec::new();
+    }
+    let mut order = vec![(0, intent_a), (1, intent_b)];
+    // stable, so player A still goes first within movement and within attacks
+    order.sort_by_key(|(_, intent)| matches!(intent, Intent::FireChecker(_)));
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Direction;
+    use crate::game::{PlayerId, Stone};
+
+    #[test]
+    fn same_target_bounces() {
+        let board = Board::new();
+        let a = Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2));
+        let b = Intent::MoveChecker(Vec2::new(1, 2), Vec2::new(2, 2));
+        assert_eq!(resolve(&board, a, b).len(), 2);
+
+        let b = Intent::PlaceStone(Vec2::new(4, 0));
+        assert!(resolve(&board, Intent::PlaceStone(Vec2::new(4, 0)), b).is_empty());
+        // Checkers and stones at the same coordinates do not conflict
+        assert_eq!(resolve(&board, Intent::MoveChecker(Vec2::new(5, 1), Vec2::new(4, 0)), b).len(), 2);
+
+        // A slide conflicts with wherever the stone would stop
+        let mut board = Board::new();
+        board.place_stone_at(Vec2::new(4, 0), Stone::new(PlayerId::A)).unwrap();
+        let slide = Intent::SlideStone(Vec2::new(4, 0), Direction::Down);
+        assert_eq!(target_of(&board, slide), Some(Target::Stone(Vec2::new(4, 6))));
+        assert!(resolve(&board, slide, Intent::PlaceStone(Vec2::new(4, 6))).is_empty());
+    }
+
+    #[test]
+    fn attacks_last() {
+        let board = Board::new();
+        let fire = Intent::FireChecker(Vec2::new(1, 2));
+        let place = Intent::PlaceStone(Vec2::new(4, 0));
+        assert_eq!(resolve(&board, fire, place), vec![(1, place), (0, fire)]);
+        assert_eq!(resolve(&board, place, fire), vec![(0, place), (1, fire)]);
+    }
+}
@@ -0,0 +1,247 @@
+/**
+ * Sequential probability ratio testing of engine changes: a new engine plays the old one in
+ * pairs of games from an opening suite until the results show, at the chosen error rates,
+ * that it is stronger by at least elo1 (accept the change) or by no more than elo0 (reject
+ * it), so clear cases finish after few games and close ones get as many as they need.
+ *
+ * The log-likelihood ratio uses the normal approximation over game scores, counting a draw
+ * as half a point, as engine testing frameworks commonly do.
+ */
+use rand::Rng;
+
+use crate::game::{Decide, PlayerId};
+use crate::matches::{play_pair, MatchError, MatchScore, OpeningSuite};
+
+/**
+ * Sprt is the hypotheses and error rates of a test.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    // Elo difference of the null hypothesis, that the change is no improvement
+    pub elo0: f64,
+    // Elo difference of the alternative hypothesis, that the change is an improvement
+    pub elo1: f64,
+    // chance of accepting a change that is no improvement
+    pub alpha: f64,
+    // chance of rejecting a change that is an improvement
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Sprt {
+        Sprt { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 }
+    }
+}
+
+/**
+ * SprtVerdict is what a test has shown so far.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SprtVerdict {
+    // The new engine is stronger by at least elo1
+    Accepted,
+    // The new engine is stronger by no more than elo0
+    Rejected,
+    // More games are needed
+    Inconclusive,
+}
+
+/**
+ * EloEstimate is the Elo difference the results suggest, with a 95% confidence interval.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/**
+ * SprtReport is the state of a test after its last game.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SprtReport {
+    // results for the new engine
+    pub score: MatchScore,
+    pub llr: f64,
+    pub verdict: SprtVerdict,
+    pub elo: EloEstimate,
+}
+
+impl Sprt {
+    /**
+     * bounds
+     * ret - The log-likelihood ratios at which the change is rejected and accepted, in that order.
+     */
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /**
+     * llr
+     * Work out the log-likelihood ratio of the alternative hypothesis against the null.
+     * score - Results for the new engine.
+     * ret - The ratio, or 0 while every game has had the same result, as there is no spread
+     * to judge by yet.
+     */
+    pub fn llr(&self, score: &MatchScore) -> f64 {
+        let Some((mean, variance)) = moments(score) else {
+            return 0.0;
+        };
+        if variance == 0.0 {
+            return 0.0;
+        }
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+        score.games() as f64 * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance)
+    }
+
+    /**
+     * verdict
+     * score - Results for the new engine.
+     * ret - What the results show so far.
+     */
+    pub fn verdict(&self, score: &MatchScore) -> SprtVerdict {
+        let llr = self.llr(score);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtVerdict::Accepted
+        } else if llr <= lower {
+            SprtVerdict::Rejected
+        } else {
+            SprtVerdict::Inconclusive
+        }
+    }
+
+    /**
+     * run
+     * Play the new engine against the old from the openings of a suite in turn, each twice with
+     * the sides swapped, until the test reaches a verdict or runs out of games.
+     * suite - Openings to play, repeated as often as needed.
+     * engines - The new engine and the old, in that order.
+     * max_pairs - Pairs of games after which the test stops, whatever it has shown.
+     * rng - Source of the seeds for the board's dice.
+     * ret - The state of the test when it stopped, or MatchError::Game if a game could not be
+     * played to the end.
+     */
+    pub fn run<R: Rng>(&self, suite: &OpeningSuite, engines: [&dyn Decide; 2], max_pairs: usize, rng: &mut R) -> Result<SprtReport, MatchError> {
+        let mut score = MatchScore::default();
+        for opening in suite.openings().iter().cycle().take(max_pairs) {
+            let games = play_pair(opening, engines, rng.gen())?;
+            score.record(&games[0].outcome, PlayerId::A);
+            score.record(&games[1].outcome, PlayerId::B);
+            if self.verdict(&score) != SprtVerdict::Inconclusive {
+                break;
+            }
+        }
+        Ok(self.report(&score))
+    }
+
+    /**
+     * report
+     * score - Results for the new engine.
+     * ret - The state of a test with those results.
+     */
+    pub fn report(&self, score: &MatchScore) -> SprtReport {
+        SprtReport { score: *score, llr: self.llr(score), verdict: self.verdict(score), elo: estimate(score) }
+    }
+}
+
+/**
+ * estimate
+ * Estimate the Elo difference between two engines from their results.
+ * score - Results for the first engine.
+ * ret - The estimate, infinite when one engine won every game, and NaN before any game.
+ */
+pub fn estimate(score: &MatchScore) -> EloEstimate {
+    let Some((mean, variance)) = moments(score) else {
+        return EloEstimate { elo: f64::NAN, lower: f64::NAN, upper: f64::NAN };
+    };
+    // 1.96 standard errors either side covers 95%
+    let margin = 1.96 * (variance / score.games() as f64).sqrt();
+    EloEstimate {
+        elo: elo_difference(mean),
+        lower: elo_difference((mean - margin).max(0.0)),
+        upper: elo_difference((mean + margin).min(1.0)),
+    }
+}
+
+/* Helper function finding the share of points an engine stronger by @elo expects to score. */
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/* Helper function finding the Elo difference at which an engine expects to score @mean. */
+fn elo_difference(mean: f64) -> f64 {
+    -400.0 * (1.0 / mean - 1.0).log10()
+}
+
+/* Helper function finding the mean and variance of the points per game in @score, or None before any game. */
+fn moments(score: &MatchScore) -> Option<(f64, f64)> {
+    let games = score.games() as f64;
+    if games == 0.0 {
+        return None;
+    }
+    let mean = score.points() as f64 / games;
+    let variance = (score.wins as f64 * (1.0 - mean).powi(2)
+        + score.draws as f64 * (0.5 - mean).powi(2)
+        + score.losses as f64 * mean.powi(2)) / games;
+    Some((mean, variance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Intent, MoveRequest};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn statistics() {
+        let sprt = Sprt::default();
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 1e-3 && (upper - 2.944).abs() < 1e-3);
+
+        let even = MatchScore { wins: 300, draws: 400, losses: 300 };
+        assert!(sprt.llr(&even) < 0.0);
+        let estimate = super::estimate(&even);
+        assert!(estimate.elo.abs() < 1e-9 && estimate.lower < -10.0 && estimate.upper > 10.0);
+
+        // 55% is about 35 Elo, plenty for a 10 Elo alternative over this many games
+        let better = MatchScore { wins: 400, draws: 300, losses: 300 };
+        assert_eq!(sprt.verdict(&better), SprtVerdict::Accepted);
+        assert!((super::estimate(&better).elo - 34.9).abs() < 0.1);
+        assert_eq!(sprt.verdict(&MatchScore { wins: 1, draws: 0, losses: 1 }), SprtVerdict::Inconclusive);
+        assert_eq!(sprt.llr(&MatchScore { wins: 5, draws: 0, losses: 0 }), 0.0);
+        assert!(super::estimate(&MatchScore::default()).elo.is_nan());
+    }
+
+    /* Engine that steps its checkers toward the far column whenever it can. */
+    struct Runner;
+
+    impl Decide for Runner {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            let goal = if request.player == PlayerId::A { 0 } else { 7 };
+            let progress = |intent: &Intent| match intent {
+                Intent::MoveChecker(_, to) => -(to.x - goal).abs(),
+                _ => i32::MIN,
+            };
+            *request.all_moves().max_by_key(|intent| progress(intent)).unwrap()
+        }
+    }
+
+    #[test]
+    fn run() {
+        // Whoever moves first wins the race, so identical engines split every pair
+        let suite = OpeningSuite::parse("rule elimination on; stones a 0; stones b 0; checker a 6 0 1; checker b 1 5 1").unwrap();
+        let sprt = Sprt { elo1: 100.0, ..Sprt::default() };
+        let mut rng = StdRng::seed_from_u64(5);
+        let report = sprt.run(&suite, [&Runner, &Runner], 1000, &mut rng).unwrap();
+        assert_eq!(report.verdict, SprtVerdict::Rejected);
+        assert!(report.score.games() < 200);
+        assert_eq!(report.score.wins, report.score.losses);
+        assert!(report.llr <= sprt.bounds().0);
+
+        let capped = sprt.run(&suite, [&Runner, &Runner], 3, &mut rng).unwrap();
+        assert_eq!((capped.verdict, capped.score.games()), (SprtVerdict::Inconclusive, 6));
+    }
+}
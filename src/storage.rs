@@ -0,0 +1,604 @@
+/**
+ * Storage for finished and in-progress games: each game's players, dates, record, clocks
+ * and final position, kept in a directory and looked up by id, player, date or result.
+ * Games in progress can be resumed from where they were saved.
+ *
+ * Every game is two files named by its id: <id>.record holds the game record, and
+ * <id>.meta holds one directive per line:
+ *
+ *   player a|b <name>
+ *   started <seconds since the Unix epoch>
+ *   updated <seconds since the Unix epoch>
+ *   position <hash of the last position, in hex>
+ *   time_control <milliseconds> <increment milliseconds> <warning milliseconds>
+ *   clocks <milliseconds left to a> <milliseconds left to b>
+ *
+ * The last two are only written for timed games.
+ *
+ * The directory also holds the leaderboard, in a file named leaderboard, which every stored
+ * game is counted in once it is finished.
+ *
+ * Games are shared between storages in archive files, which hold any number of games, each
+ * its metadata and record between marker lines. Lines before a game starting with # are
+ * comments.
+ *
+ *   game
+ *   <metadata directives>
+ *   record
+ *   <game record>
+ *   end
+ */
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::{GameConfig, TimeControl, Variant};
+use crate::game::{Game, PlayerId};
+use crate::leaderboard::{Leaderboard, LeaderboardError};
+use crate::record::{notation, GameRecord, GameResult, RecordError};
+use crate::review::ReviewError;
+use crate::scenario::{arg, number, player};
+
+const META_EXTENSION: &str = "meta";
+const RECORD_EXTENSION: &str = "record";
+const LEADERBOARD_FILE: &str = "leaderboard";
+const ARCHIVE_HEADER: &str = "# ironclad game archive\n";
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    // The storage directory or one of its files could not be read or written
+    #[error("Could not access game storage: {0}")]
+    Io(#[from] io::Error),
+    // A stored record could not be read
+    #[error(transparent)]
+    Record(#[from] RecordError),
+    // The leaderboard could not be read or written
+    #[error(transparent)]
+    Leaderboard(#[from] LeaderboardError),
+    // A metadata file could not be understood, with its line number counting from 1
+    #[error("Game {id}, line {line}: {message}")]
+    Parse { id: u64, line: usize, message: String },
+    // An archive could not be understood, with its line number counting from 1
+    #[error("Archive line {line}: {message}")]
+    Archive { line: usize, message: String },
+    // No game is stored under the id
+    #[error("No stored game with id {0}")]
+    NotFound(u64),
+    // A stored move could not be made again while resuming
+    #[error(transparent)]
+    Replay(#[from] ReviewError),
+    // Replaying the stored moves did not reach the stored position
+    #[error("Game {0} did not replay to its stored position")]
+    Position(u64),
+}
+
+/**
+ * StoredGame is one game in storage.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredGame {
+    pub id: u64,
+    // names of the players of side A and side B, in that order
+    pub players: [String; 2],
+    // seconds since the Unix epoch when the game was first stored, and last saved
+    pub started: u64,
+    pub updated: u64,
+    // Zobrist hash of the position after the last move of the record
+    pub position: u64,
+    pub record: GameRecord,
+    // the game's time control, and the time left to player A and player B when it was saved
+    pub time_control: Option<TimeControl>,
+    pub clocks: Option<[Duration; 2]>,
+}
+
+impl StoredGame {
+    /**
+     * finished
+     * ret - True if the record holds a result.
+     */
+    pub fn finished(&self) -> bool {
+        self.record.result.is_some()
+    }
+
+    /**
+     * config
+     * ret - Configuration to create a game with before resuming this one into it.
+     */
+    pub fn config(&self) -> GameConfig {
+        GameConfig {
+            free_setup: self.record.free_setup,
+            first_player: self.record.first,
+            time_control: self.time_control,
+            ..GameConfig::for_variant(self.record.variant)
+        }
+    }
+
+    /**
+     * resume
+     * Replay the stored moves into a new game, and set its clocks as they were saved.
+     * game - Game just created with this game's config.
+     * ret - Ok, or a StorageError if a move could not be replayed or the game did not reach
+     * the stored position.
+     */
+    pub fn resume(&self, game: &mut Game) -> Result<(), StorageError> {
+        // Fights go as they did with the dice the record kept
+        game.board.script_dice(&self.record.dice);
+        for (idx, recorded) in self.record.moves.iter().enumerate() {
+            game.try_apply_move(recorded.player, recorded.intent).map_err(|source| ReviewError::Replay {
+                ply: idx + 1,
+                notation: notation(recorded.intent),
+                source,
+            })?;
+        }
+        if game.board.position_hash() != self.position {
+            return Err(StorageError::Position(self.id));
+        }
+        if let Some(clocks) = self.clocks {
+            game.set_clocks(clocks);
+        }
+        Ok(())
+    }
+
+    /* Helper function creating a game with @record and nothing else known about it. */
+    fn blank(id: u64, record: GameRecord) -> StoredGame {
+        StoredGame {
+            id,
+            players: [String::new(), String::new()],
+            started: 0,
+            updated: 0,
+            position: 0,
+            record,
+            time_control: None,
+            clocks: None,
+        }
+    }
+
+    /*
+     * Helper function writing the game's metadata directives, one per line.
+     */
+    fn meta(&self) -> String {
+        let mut meta = format!(
+            "player a {}\nplayer b {}\nstarted {}\nupdated {}\nposition {:016x}\n",
+            self.players[0], self.players[1], self.started, self.updated, self.position,
+        );
+        if let Some(control) = self.time_control {
+            let [initial, increment, warning] = [control.initial, control.increment, control.warning].map(|time| time.as_millis());
+            meta.push_str(&format!("time_control {} {} {}\n", initial, increment, warning));
+        }
+        if let Some([a, b]) = self.clocks {
+            meta.push_str(&format!("clocks {} {}\n", a.as_millis(), b.as_millis()));
+        }
+        meta
+    }
+
+    /*
+     * Helper function reading one line of metadata into the game. Blank lines are skipped.
+     */
+    fn read_meta(&mut self, raw: &str) -> Result<(), String> {
+        let line = raw.trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let args = &words[1..];
+        match words[0] {
+            "player" => {
+                let side = player(arg(args, 0)?)?;
+                self.players[side.index()] = args[1..].join(" ");
+            },
+            "started" => self.started = number(arg(args, 0)?)?,
+            "updated" => self.updated = number(arg(args, 0)?)?,
+            "position" => self.position = u64::from_str_radix(arg(args, 0)?, 16).map_err(|e| e.to_string())?,
+            "time_control" => {
+                let mut times = [Duration::ZERO; 3];
+                for (idx, time) in times.iter_mut().enumerate() {
+                    *time = Duration::from_millis(number(arg(args, idx)?)?);
+                }
+                self.time_control = Some(TimeControl { initial: times[0], increment: times[1], warning: times[2] });
+            },
+            "clocks" => {
+                let mut clocks = [Duration::ZERO; 2];
+                for (idx, clock) in clocks.iter_mut().enumerate() {
+                    *clock = Duration::from_millis(number(arg(args, idx)?)?);
+                }
+                self.clocks = Some(clocks);
+            },
+            other => return Err(format!("unknown directive '{}'", other)),
+        }
+        Ok(())
+    }
+
+    /*
+     * Helper function copying the position, time control and clocks of @game.
+     */
+    fn take_state(&mut self, game: &Game) {
+        self.position = game.board.position_hash();
+        self.time_control = game.config().time_control;
+        self.clocks = game.clock(PlayerId::A).zip(game.clock(PlayerId::B)).map(|(a, b)| [a, b]);
+    }
+}
+
+/**
+ * Storage is a directory of stored games.
+ */
+#[derive(Clone, Debug)]
+pub struct Storage {
+    dir: PathBuf,
+}
+
+impl Storage {
+    /**
+     * open
+     * Open the storage in a directory, creating the directory if need be.
+     * dir - Directory the games are kept in.
+     * ret - The storage, or StorageError::Io if the directory could not be created.
+     */
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Storage, StorageError> {
+        fs::create_dir_all(&dir)?;
+        Ok(Storage { dir: dir.as_ref().to_path_buf() })
+    }
+
+    /**
+     * insert
+     * Store a new game under the next free id. Ids are never reused while the game is stored.
+     * players - Names of the players of side A and side B.
+     * game - The game so far.
+     * ret - Id of the stored game, or a StorageError if it could not be written.
+     */
+    pub fn insert(&self, players: [&str; 2], game: &Game) -> Result<u64, StorageError> {
+        let id = self.ids()?.last().map_or(1, |last| last + 1);
+        let now = now();
+        let mut stored = StoredGame::blank(id, GameRecord::from_game(game));
+        stored.players = players.map(String::from);
+        (stored.started, stored.updated) = (now, now);
+        stored.take_state(game);
+        self.write(&stored)?;
+        self.count_result(&stored)?;
+        Ok(id)
+    }
+
+    /**
+     * update
+     * Save more of a stored game, or its result, keeping its players and start date.
+     * The game is counted in the leaderboard when it is first saved finished.
+     * id - Id of the game.
+     * game - The game so far.
+     * ret - Ok, or StorageError::NotFound if no game has the id.
+     */
+    pub fn update(&self, id: u64, game: &Game) -> Result<(), StorageError> {
+        let mut stored = self.get(id)?;
+        let was_finished = stored.finished();
+        stored.record = GameRecord::from_game(game);
+        stored.take_state(game);
+        stored.updated = now();
+        self.write(&stored)?;
+        if !was_finished {
+            self.count_result(&stored)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * get
+     * Read one stored game.
+     * id - Id of the game.
+     * ret - The game, StorageError::NotFound if no game has the id, or another StorageError
+     * if its files could not be read.
+     */
+    pub fn get(&self, id: u64) -> Result<StoredGame, StorageError> {
+        let meta = match fs::read_to_string(self.path(id, META_EXTENSION)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(StorageError::NotFound(id)),
+            other => other?,
+        };
+        let record = GameRecord::load(self.path(id, RECORD_EXTENSION))?;
+        let mut game = StoredGame::blank(id, record);
+        for (idx, line) in meta.lines().enumerate() {
+            game.read_meta(line).map_err(|message| StorageError::Parse { id, line: idx + 1, message })?;
+        }
+        Ok(game)
+    }
+
+    /**
+     * games
+     * ret - Every stored game, by id, or a StorageError if one could not be read.
+     */
+    pub fn games(&self) -> Result<Vec<StoredGame>, StorageError> {
+        self.ids()?.into_iter().map(|id| self.get(id)).collect()
+    }
+
+    /**
+     * by_player
+     * Find the games a player took part in, on either side.
+     * name - Player's name, as stored.
+     * ret - Their games, by id.
+     */
+    pub fn by_player(&self, name: &str) -> Result<Vec<StoredGame>, StorageError> {
+        self.filter(|game| game.players.iter().any(|stored| stored == name))
+    }
+
+    /**
+     * started_between
+     * Find the games started in a span of time.
+     * from - First second of the span, since the Unix epoch.
+     * to - Second after the span ends.
+     * ret - The games, by id.
+     */
+    pub fn started_between(&self, from: u64, to: u64) -> Result<Vec<StoredGame>, StorageError> {
+        self.filter(|game| (from..to).contains(&game.started))
+    }
+
+    /**
+     * by_result
+     * Find the games with a result.
+     * result - Result to look for, or None for games still in progress.
+     * ret - The games, by id.
+     */
+    pub fn by_result(&self, result: Option<GameResult>) -> Result<Vec<StoredGame>, StorageError> {
+        self.filter(|game| game.record.result == result)
+    }
+
+    /**
+     * export
+     * Write stored games to one archive file, to be imported into another storage.
+     * ids - Ids of the games to write, in order.
+     * path - Archive file to write, replacing anything already there.
+     * ret - Ok, or a StorageError if a game could not be read or the archive written.
+     */
+    pub fn export<P: AsRef<Path>>(&self, ids: &[u64], path: P) -> Result<(), StorageError> {
+        let mut archive = String::from(ARCHIVE_HEADER);
+        for id in ids {
+            let game = self.get(*id)?;
+            archive.push_str(&format!("game\n{}record\n{}end\n", game.meta(), game.record));
+        }
+        fs::write(path, archive)?;
+        Ok(())
+    }
+
+    /**
+     * import
+     * Store every game of an archive under new ids, keeping their players, dates and clocks.
+     * Finished games are counted in the leaderboard, so an archive imported twice is counted
+     * twice.
+     * path - Archive file to read.
+     * ret - The new ids, in archive order, or a StorageError if the archive could not be read
+     * or understood. Nothing is stored from an archive that is not understood.
+     */
+    pub fn import<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u64>, StorageError> {
+        let games = parse_archive(&fs::read_to_string(path)?)?;
+        let first = self.ids()?.last().map_or(1, |last| last + 1);
+        let mut ids = Vec::new();
+        for (id, mut game) in (first..).zip(games) {
+            game.id = id;
+            self.write(&game)?;
+            self.count_result(&game)?;
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+
+    /**
+     * leaderboard
+     * ret - Standings from every finished stored game, or a StorageError if they could not
+     * be read.
+     */
+    pub fn leaderboard(&self) -> Result<Leaderboard, StorageError> {
+        match Leaderboard::load(self.dir.join(LEADERBOARD_FILE)) {
+            Err(LeaderboardError::Io(err)) if err.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+            other => Ok(other?),
+        }
+    }
+
+    /*
+     * Helper function counting @game in the leaderboard, if it is finished.
+     */
+    fn count_result(&self, game: &StoredGame) -> Result<(), StorageError> {
+        let Some(result) = game.record.result else {
+            return Ok(());
+        };
+        let mut leaderboard = self.leaderboard()?;
+        leaderboard.record([&game.players[0], &game.players[1]], result);
+        leaderboard.save(self.dir.join(LEADERBOARD_FILE))?;
+        Ok(())
+    }
+
+    /*
+     * Helper function reading every stored game that passes @keep.
+     */
+    fn filter<F: Fn(&StoredGame) -> bool>(&self, keep: F) -> Result<Vec<StoredGame>, StorageError> {
+        Ok(self.games()?.into_iter().filter(keep).collect())
+    }
+
+    /*
+     * Helper function listing the ids of the stored games, smallest first.
+     */
+    fn ids(&self) -> Result<Vec<u64>, StorageError> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == META_EXTENSION) {
+                if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse().ok()) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /*
+     * Helper function writing both files of a game, the record first so a game's metadata
+     * never points at a missing record.
+     */
+    fn write(&self, game: &StoredGame) -> Result<(), StorageError> {
+        game.record.save(self.path(game.id, RECORD_EXTENSION))?;
+        fs::write(self.path(game.id, META_EXTENSION), game.meta())?;
+        Ok(())
+    }
+
+    /* Helper function naming one of a game's files. */
+    fn path(&self, id: u64, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, extension))
+    }
+}
+
+/*
+ * Helper function reading the games of an archive, with no ids yet.
+ */
+fn parse_archive(text: &str) -> Result<Vec<StoredGame>, StorageError> {
+    let err = |line: usize, message: String| StorageError::Archive { line, message };
+    let unfinished = || err(text.lines().count(), String::from("game has no end line"));
+    let mut games = Vec::new();
+    // lines numbered from 1
+    let mut lines = text.lines().zip(1..);
+    while let Some((raw, number)) = lines.next() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line != "game" {
+            return Err(err(number, format!("expected 'game', found '{}'", line)));
+        }
+        let mut game = StoredGame::blank(0, GameRecord::new(Variant::Standard));
+        let record_start = loop {
+            let (line, number) = lines.next().ok_or_else(unfinished)?;
+            if line.trim() == "record" {
+                break number;
+            }
+            game.read_meta(line).map_err(|message| err(number, message))?;
+        };
+        let mut record = String::new();
+        loop {
+            let (line, _) = lines.next().ok_or_else(unfinished)?;
+            if line.trim() == "end" {
+                break;
+            }
+            record.push_str(line);
+            record.push('\n');
+        }
+        game.record = GameRecord::parse(&record).map_err(|error| match error {
+            RecordError::Parse { line, message } => err(record_start + line, message),
+            other => other.into(),
+        })?;
+        games.push(game);
+    }
+    Ok(games)
+}
+
+/* Helper function reading the clock, as seconds since the Unix epoch. */
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Decide, Intent, MoveRequest, Player, STARTING_STONES};
+
+    struct FirstOffered;
+
+    impl Decide for FirstOffered {
+        fn choose_move(&self, request: &MoveRequest) -> Intent {
+            *request.all_moves().next().unwrap()
+        }
+    }
+
+    #[test]
+    fn storage() {
+        let dir = std::env::temp_dir().join(format!("ironclad-storage-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let storage = Storage::open(&dir).unwrap();
+        assert!(storage.games().unwrap().is_empty());
+        assert!(storage.leaderboard().unwrap().standings().is_empty());
+
+        let control = TimeControl {
+            initial: Duration::from_secs(300),
+            increment: Duration::from_secs(2),
+            warning: Duration::from_secs(30),
+        };
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, GameConfig { time_control: Some(control), ..GameConfig::default() });
+        game.set_silent(true);
+        let first = storage.insert(["Ada", "Grace Hopper"], &game).unwrap();
+        game.play_turn().unwrap();
+        game.play_turn().unwrap();
+        game.set_clocks([Duration::from_millis(250_500), Duration::from_secs(20)]);
+        storage.update(first, &game).unwrap();
+
+        let stored = storage.get(first).unwrap();
+        assert_eq!(stored.players, [String::from("Ada"), String::from("Grace Hopper")]);
+        assert_eq!(stored.record, GameRecord::from_game(&game));
+        assert_eq!(stored.clocks, Some([Duration::from_millis(250_500), Duration::from_secs(20)]));
+        assert!(!stored.finished());
+
+        // Resuming reaches the same position, history and clocks
+        let mut resumed_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut resumed_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut resumed = Game::with_config(&mut resumed_a, &mut resumed_b, stored.config());
+        resumed.set_silent(true);
+        stored.resume(&mut resumed).unwrap();
+        assert_eq!(resumed.board.position_hash(), game.board.position_hash());
+        assert_eq!(resumed.history(), game.history());
+        assert_eq!(resumed.clock(PlayerId::B), Some(Duration::from_secs(20)));
+
+        // Finishing a game keeps who played it and when it started
+        let outcome = game.play().unwrap();
+        storage.update(first, &game).unwrap();
+        let finished = storage.get(first).unwrap();
+        assert!(finished.finished());
+        assert_eq!((finished.players.clone(), finished.started), (stored.players.clone(), stored.started));
+
+        // The result is counted in the leaderboard once, however often the game is saved
+        storage.update(first, &game).unwrap();
+        let leaderboard = storage.leaderboard().unwrap();
+        assert_eq!(leaderboard.standing("Ada").unwrap().games(), 1);
+        assert_eq!(leaderboard.standings().len(), 2);
+
+        let mut untimed_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut untimed_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let untimed = Game::new(&mut untimed_a, &mut untimed_b);
+        let second = storage.insert(["Grace Hopper", "Alan"], &untimed).unwrap();
+        assert_eq!((first, second), (1, 2));
+        assert_eq!(storage.get(second).unwrap().clocks, None);
+
+        assert_eq!(storage.by_player("Grace Hopper").unwrap().len(), 2);
+        assert_eq!(storage.by_player("Alan").unwrap()[0].id, second);
+        assert_eq!(storage.by_result(Some(GameResult::from(outcome))).unwrap()[0].id, first);
+        assert_eq!(storage.by_result(None).unwrap()[0].id, second);
+        assert_eq!(storage.started_between(stored.started, stored.started + 3600).unwrap().len(), 2);
+        assert!(storage.started_between(0, stored.started).unwrap().is_empty());
+        assert!(matches!(storage.get(99), Err(StorageError::NotFound(99))));
+        assert!(matches!(storage.update(99, &game), Err(StorageError::NotFound(99))));
+
+        // Archives carry games to another storage whole, under new ids
+        let archive = dir.join("archive");
+        storage.export(&[first, second], &archive).unwrap();
+        let other_dir = std::env::temp_dir().join(format!("ironclad-storage-import-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&other_dir);
+        let other = Storage::open(&other_dir).unwrap();
+        other.insert(["Alan", "Ada"], &untimed).unwrap();
+        assert_eq!(other.import(&archive).unwrap(), [2, 3]);
+        let imported = other.get(2).unwrap();
+        assert_eq!((imported.players, imported.record, imported.clocks), (finished.players, finished.record, finished.clocks));
+        assert_eq!(other.leaderboard().unwrap().standing("Grace Hopper").unwrap().games(), 1);
+        fs::write(&archive, "# games\ngame\nplayer a Ada\nrecord\nvariant standard\na jump 1,1\nend\n").unwrap();
+        assert!(matches!(other.import(&archive), Err(StorageError::Archive { line: 6, .. })));
+        fs::write(&archive, "game\nplayer a Ada\nrecord\n").unwrap();
+        assert!(matches!(other.import(&archive), Err(StorageError::Archive { line: 3, .. })));
+        assert_eq!(other.games().unwrap().len(), 3);
+        fs::remove_dir_all(&other_dir).unwrap();
+
+        // A stored game that no longer replays to its position is refused
+        fs::write(dir.join("2.meta"), "player a Ada\nposition 1234\n").unwrap();
+        let mut stale_a = Player::new(PlayerId::A, STARTING_STONES, &FirstOffered);
+        let mut stale_b = Player::new(PlayerId::B, STARTING_STONES, &FirstOffered);
+        let mut stale = Game::new(&mut stale_a, &mut stale_b);
+        assert!(matches!(storage.get(second).unwrap().resume(&mut stale), Err(StorageError::Position(2))));
+
+        fs::write(dir.join("3.meta"), "player a Ada\ncolour blue\n").unwrap();
+        fs::write(dir.join("3.record"), "").unwrap();
+        assert!(matches!(storage.get(3), Err(StorageError::Parse { id: 3, line: 2, .. })));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
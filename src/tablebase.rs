@@ -0,0 +1,400 @@
+/**
+ * Endgame tablebases: the exact result of every position with a few checkers left, found by
+ * retrograde analysis, so an engine can look endgames up instead of searching them.
+ *
+ * Tablebases cover positions with no stones on the board and empty stone pools, played with
+ * deterministic combat, so every move has one outcome, and with the elimination rule, so
+ * losing the last checker loses the game. Every position with from one up to the given
+ * number of checkers per side, each of height up to the given limit, is solved. The number
+ * of positions grows quickly: one or two checkers a side is practical.
+ *
+ * Tablebases are saved as plain text, one line per position:
+ *
+ *   <position hash in hex> a|b win|draw|loss <plies>
+ */
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use crate::board::Board;
+use crate::config::{GameConfig, Variant};
+use crate::game::{Checker, CheckerHeight, Decide, Game, GameOutcome, Intent, MoveRequest, Player, PlayerId};
+use crate::record::RecordError;
+use crate::scenario::player;
+use crate::vec::Vec2;
+
+/**
+ * Wdl is a position's result with best play, for the player to move.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+/**
+ * TablebaseEntry is what a tablebase knows about one position.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TablebaseEntry {
+    pub result: Wdl,
+    // moves until the game is decided, with the winner hurrying and the loser holding out;
+    // 0 for draws and for positions that are already decided
+    pub plies: u32,
+}
+
+/**
+ * Tablebase holds the solved positions for one board.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tablebase {
+    entries: HashMap<(u64, PlayerId), TablebaseEntry>,
+}
+
+/* Decider for the game used to generate moves, which is never asked for one. */
+struct Solver;
+
+impl Decide for Solver {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        *request.all_moves().next().expect("no moves offered")
+    }
+}
+
+impl Tablebase {
+    /**
+     * config
+     * Get the rules tablebase positions are played under.
+     * variant - Board to play on.
+     * ret - The variant's rules with deterministic combat, elimination, and empty pools.
+     */
+    pub fn config(variant: Variant) -> GameConfig {
+        let mut config = GameConfig::for_variant(variant);
+        config.rules.deterministic_combat = true;
+        config.rules.elimination = true;
+        config.stone_pools = vec![(PlayerId::A, 0), (PlayerId::B, 0)];
+        config
+    }
+
+    /**
+     * generate
+     * Solve every position with up to @checkers checkers per side, each at most @max_height
+     * high, by working back from decided positions.
+     * variant - Board to solve positions on.
+     * checkers - Most checkers of each side.
+     * max_height - Tallest checker.
+     * ret - The solved tablebase.
+     */
+    pub fn generate(variant: Variant, checkers: usize, max_height: CheckerHeight) -> Tablebase {
+        let (solver_a, solver_b) = (Solver, Solver);
+        let mut player_a = Player::new(PlayerId::A, 0, &solver_a);
+        let mut player_b = Player::new(PlayerId::B, 0, &solver_b);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, Tablebase::config(variant));
+        game.set_silent(true);
+        game.board.clear_checkers();
+
+        // Every position, for each side to move
+        let mut positions = Vec::new();
+        let squares: Vec<Vec2> = (0..game.board.height() as i32)
+            .flat_map(|y| (0..game.board.width() as i32).map(move |x| Vec2::new(x, y)))
+            .collect();
+        let mut boards = Vec::new();
+        place_all(&mut game.board.clone(), &squares, 0, [checkers; 2], [0; 2], max_height, &mut boards);
+        for board in boards {
+            positions.push((board.clone(), PlayerId::A));
+            positions.push((board, PlayerId::B));
+        }
+        let index: HashMap<(u64, PlayerId), usize> = positions.iter()
+            .enumerate()
+            .map(|(idx, (board, player))| ((board.position_hash(), *player), idx))
+            .collect();
+
+        // Decided positions and immediate wins are known; every other move links two positions
+        let mut known: Vec<Option<TablebaseEntry>> = vec![None; positions.len()];
+        let mut remaining = vec![0; positions.len()];
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); positions.len()];
+        // positions are taken in order of plies, so wins are found by the fastest route and
+        // losses by the slowest
+        let (mut queue, mut immediate) = (VecDeque::new(), Vec::new());
+        for (idx, (board, player)) in positions.iter().enumerate() {
+            game.board = board.clone();
+            if let Some(outcome) = game.check_for_outcome() {
+                known[idx] = Some(TablebaseEntry { result: result_for(outcome, *player), plies: 0 });
+                queue.push_back(idx);
+                continue;
+            }
+            let moves: Vec<Intent> = game.move_request(*player).all_moves().copied().collect();
+            for intent in moves {
+                let mut next = board.clone();
+                if next.apply(*player, intent).is_err() {
+                    continue;
+                }
+                let next_hash = next.position_hash();
+                game.board = next;
+                if let Some(GameOutcome::Win { winner, .. }) = game.check_for_outcome() {
+                    if winner == *player {
+                        known[idx] = Some(TablebaseEntry { result: Wdl::Win, plies: 1 });
+                        break;
+                    }
+                }
+                if let Some(next_idx) = index.get(&(next_hash, player.other())) {
+                    predecessors[*next_idx].push(idx);
+                    remaining[idx] += 1;
+                }
+            }
+            if known[idx].is_some() {
+                immediate.push(idx);
+            }
+        }
+        queue.extend(immediate);
+
+        // Work back: a move into a lost position wins, and a position whose moves all lead
+        // to won positions is lost
+        while let Some(idx) = queue.pop_front() {
+            let entry = known[idx].unwrap();
+            for &prev in predecessors[idx].iter() {
+                if known[prev].is_some() {
+                    continue;
+                }
+                match entry.result {
+                    Wdl::Loss => {
+                        known[prev] = Some(TablebaseEntry { result: Wdl::Win, plies: entry.plies + 1 });
+                        queue.push_back(prev);
+                    },
+                    Wdl::Win => {
+                        remaining[prev] -= 1;
+                        if remaining[prev] == 0 {
+                            known[prev] = Some(TablebaseEntry { result: Wdl::Loss, plies: entry.plies + 1 });
+                            queue.push_back(prev);
+                        }
+                    },
+                    Wdl::Draw => (),
+                }
+            }
+        }
+
+        // Whatever is left can be held forever
+        let entries = positions.iter()
+            .zip(known)
+            .map(|((board, player), entry)| {
+                ((board.position_hash(), *player), entry.unwrap_or(TablebaseEntry { result: Wdl::Draw, plies: 0 }))
+            })
+            .collect();
+        Tablebase { entries }
+    }
+
+    /**
+     * probe
+     * Look a position up.
+     * board - Position to look up, played under Tablebase::config.
+     * player - Id of player to move.
+     * ret - The position's result, or None if the tablebase does not cover it.
+     */
+    pub fn probe(&self, board: &Board, player: PlayerId) -> Option<TablebaseEntry> {
+        self.entries.get(&(board.position_hash(), player)).copied()
+    }
+
+    /**
+     * len
+     * ret - Number of positions solved, counting each side to move separately.
+     */
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /**
+     * is_empty
+     * ret - True if no positions are solved.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /**
+     * load
+     * Read a tablebase saved by save.
+     * path - File to read.
+     * ret - The tablebase, or a RecordError if it could not be read or parsed.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Tablebase, RecordError> {
+        Tablebase::parse(&fs::read_to_string(path)?)
+    }
+
+    /**
+     * save
+     * Write the tablebase to a file, replacing anything already there.
+     * path - File to write.
+     * ret - Ok, or RecordError::Io if the file could not be written.
+     */
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecordError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /**
+     * parse
+     * Parse a saved tablebase.
+     * text - Contents of a tablebase file.
+     * ret - The tablebase, or RecordError::Parse describing the first problem found.
+     */
+    pub fn parse(text: &str) -> Result<Tablebase, RecordError> {
+        let mut tablebase = Tablebase::default();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |message: String| RecordError::Parse { line: idx + 1, message };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() != 4 {
+                return Err(err(format!("expected 4 fields, found {}", words.len())));
+            }
+            let hash = u64::from_str_radix(words[0], 16).map_err(|e| err(e.to_string()))?;
+            let side = player(words[1]).map_err(err)?;
+            let result = match words[2] {
+                "win" => Wdl::Win,
+                "draw" => Wdl::Draw,
+                "loss" => Wdl::Loss,
+                other => return Err(err(format!("unknown result '{}'", other))),
+            };
+            let plies = words[3].parse().map_err(|e: std::num::ParseIntError| err(e.to_string()))?;
+            tablebase.entries.insert((hash, side), TablebaseEntry { result, plies });
+        }
+        Ok(tablebase)
+    }
+}
+
+impl Display for Tablebase {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mut keys: Vec<&(u64, PlayerId)> = self.entries.keys().collect();
+        keys.sort_by_key(|(hash, player)| (*hash, player.index()));
+        for key in keys {
+            let entry = self.entries[key];
+            let side = match key.1 {
+                PlayerId::A => "a",
+                PlayerId::B => "b",
+            };
+            let result = match entry.result {
+                Wdl::Win => "win",
+                Wdl::Draw => "draw",
+                Wdl::Loss => "loss",
+            };
+            writeln!(formatter, "{:016x} {} {} {}", key.0, side, result, entry.plies)?;
+        }
+        Ok(())
+    }
+}
+
+/* Helper function giving a decided game's result for @player. */
+fn result_for(outcome: GameOutcome, player: PlayerId) -> Wdl {
+    match outcome {
+        GameOutcome::Win { winner, .. } if winner == player => Wdl::Win,
+        GameOutcome::Win { .. } => Wdl::Loss,
+        GameOutcome::Draw(_) => Wdl::Draw,
+    }
+}
+
+/*
+ * Helper function collecting every way to add checkers to @board on @squares from index
+ * @from on, up to @left more per side, with at least one per side once @placed counts them.
+ */
+fn place_all(board: &mut Board, squares: &[Vec2], from: usize, left: [usize; 2], placed: [usize; 2], max_height: CheckerHeight, boards: &mut Vec<Board>) {
+    if placed[0] > 0 && placed[1] > 0 {
+        boards.push(board.clone());
+    }
+    for (offset, pos) in squares[from..].iter().enumerate() {
+        for player in [PlayerId::A, PlayerId::B] {
+            if left[player.index()] == 0 {
+                continue;
+            }
+            for height in 1..=max_height.get() {
+                board.place_checker_at(*pos, Checker::empty()).unwrap();
+                board.place_checker_at(*pos, Checker::new(CheckerHeight::new(height).unwrap(), player)).unwrap();
+                let (mut left, mut placed) = (left, placed);
+                left[player.index()] -= 1;
+                placed[player.index()] += 1;
+                place_all(board, squares, from + offset + 1, left, placed, max_height, boards);
+            }
+            board.place_checker_at(*pos, Checker::empty()).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate() {
+        let tablebase = Tablebase::generate(Variant::Standard, 1, CheckerHeight::ONE);
+        // One checker a side, on different squares, either side to move
+        assert_eq!(tablebase.len(), 48 * 47 * 2);
+
+        let (solver_a, solver_b) = (Solver, Solver);
+        let mut player_a = Player::new(PlayerId::A, 0, &solver_a);
+        let mut player_b = Player::new(PlayerId::B, 0, &solver_b);
+        let mut game = Game::with_config(&mut player_a, &mut player_b, Tablebase::config(Variant::Standard));
+        game.board.clear_checkers();
+        // Next to each other, the side to move destroys the other checker
+        game.board.place_checker_at(Vec2::new(4, 2), Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
+        game.board.place_checker_at(Vec2::new(3, 2), Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+        for player in [PlayerId::A, PlayerId::B] {
+            assert_eq!(tablebase.probe(&game.board, player), Some(TablebaseEntry { result: Wdl::Win, plies: 1 }));
+        }
+        // A checker already on its far column has won
+        game.board.place_checker_at(Vec2::new(4, 2), Checker::empty()).unwrap();
+        game.board.place_checker_at(Vec2::new(0, 5), Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
+        assert_eq!(tablebase.probe(&game.board, PlayerId::B), Some(TablebaseEntry { result: Wdl::Loss, plies: 0 }));
+
+        // Positions with taller checkers are not covered
+        game.board.place_checker_at(Vec2::new(0, 5), Checker::empty()).unwrap();
+        game.board.place_checker_at(Vec2::new(0, 5), Checker::new(CheckerHeight::TWO, PlayerId::A)).unwrap();
+        assert_eq!(tablebase.probe(&game.board, PlayerId::B), None);
+        game.board.clear_checkers();
+
+        // Every undecided result agrees with the results one move on, for A's checker on (5, 1)
+        let a_pos = Vec2::new(5, 1);
+        game.board.place_checker_at(a_pos, Checker::new(CheckerHeight::ONE, PlayerId::A)).unwrap();
+        let start = game.board.clone();
+        for b_pos in (1..6).flat_map(|y| (0..7).map(move |x| Vec2::new(x, y))).filter(|pos| *pos != a_pos) {
+            let mut board = start.clone();
+            board.place_checker_at(b_pos, Checker::new(CheckerHeight::ONE, PlayerId::B)).unwrap();
+            game.board = board.clone();
+            let entry = tablebase.probe(&board, PlayerId::A).unwrap();
+            let moves: Vec<Intent> = game.move_request(PlayerId::A).all_moves().copied().collect();
+            let replies: Vec<Option<TablebaseEntry>> = moves.iter()
+                .map(|intent| {
+                    let mut next = board.clone();
+                    next.apply(PlayerId::A, *intent).unwrap();
+                    game.board = next.clone();
+                    match game.check_for_outcome() {
+                        Some(_) => None,
+                        None => Some(tablebase.probe(&next, PlayerId::B).unwrap()),
+                    }
+                })
+                .collect();
+            let best = match entry.result {
+                Wdl::Win => replies.iter()
+                    .filter_map(|reply| reply.map_or(Some(1), |reply| (reply.result == Wdl::Loss).then_some(reply.plies + 1)))
+                    .min(),
+                Wdl::Loss => replies.iter().map(|reply| reply.unwrap().plies + 1).max(),
+                Wdl::Draw => {
+                    assert!(replies.iter().flatten().any(|reply| reply.result == Wdl::Draw));
+                    assert!(replies.iter().flatten().all(|reply| reply.result != Wdl::Loss));
+                    Some(0)
+                },
+            };
+            assert_eq!(best, Some(entry.plies), "{} against {}", a_pos, b_pos);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let tablebase = Tablebase::generate(Variant::Standard, 1, CheckerHeight::ONE);
+        let written = tablebase.to_string();
+        assert_eq!(Tablebase::parse(&written).unwrap(), tablebase);
+        assert!(matches!(Tablebase::parse("00 a win"), Err(RecordError::Parse { line: 1, .. })));
+        assert!(matches!(Tablebase::parse("00 c win 1"), Err(RecordError::Parse { line: 1, .. })));
+    }
+}
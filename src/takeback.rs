@@ -0,0 +1,306 @@
+/**
+ * Takebacks between the players of a network game. One player asks to take back their
+ * last turn, the server passes the request on to their opponent, and the turn is taken
+ * back through the Game API only if the opponent accepts. A request lapses, and counts as
+ * declined, if a move is played before it is answered.
+ *
+ * On the wire each message is one line, its fields separated by tabs:
+ *
+ *   takeback request a|b            client to server, for the side asking
+ *   takeback accept                 client to server, from the other side
+ *   takeback decline                client to server, from the other side
+ *   takeback requested a|b          server to both players
+ *   takeback taken a|b <moves>      server to both players
+ *   takeback declined a|b           server to both players
+ */
+use crate::auth::{AuthError, Profile, Seats};
+use crate::game::{Game, PlayerId};
+use crate::scenario::{number, player};
+
+const TAKEBACK_WORD: &str = "takeback";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TakebackError {
+    // The profile may not ask or answer for the side
+    #[error(transparent)]
+    Seat(#[from] AuthError),
+    // A request is already waiting for an answer, from the side that asked
+    #[error("Player {0} is already waiting for an answer")]
+    Pending(PlayerId),
+    // The side has not moved, so there is nothing to take back
+    #[error("Player {0} has nothing to take back")]
+    NothingToTakeBack(PlayerId),
+    // An answer arrived with no request waiting
+    #[error("No takeback has been asked for")]
+    NoRequest,
+    // A line is not a takeback message
+    #[error("Not a takeback message: {0}")]
+    Parse(String),
+}
+
+/**
+ * TakebackRequest is a takeback message from a client.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TakebackRequest {
+    // The side asks to take back its last turn
+    Ask(PlayerId),
+    Accept,
+    Decline,
+}
+
+/**
+ * TakebackNotice is what the server tells both players about a takeback.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TakebackNotice {
+    // The side asked to take back its last turn, and waits for the other to answer
+    Requested(PlayerId),
+    // The side's last turn, and the moves played since, were taken back
+    Taken { player: PlayerId, moves: usize },
+    // The other side declined, or the request lapsed
+    Declined(PlayerId),
+}
+
+impl TakebackRequest {
+    /**
+     * to_line
+     * ret - The request as the client sends it, without a line ending.
+     */
+    pub fn to_line(&self) -> String {
+        match self {
+            TakebackRequest::Ask(player) => format!("{}\trequest\t{}", TAKEBACK_WORD, side(*player)),
+            TakebackRequest::Accept => format!("{}\taccept", TAKEBACK_WORD),
+            TakebackRequest::Decline => format!("{}\tdecline", TAKEBACK_WORD),
+        }
+    }
+
+    /**
+     * parse_line
+     * Read a takeback message a client sent.
+     * line - Line received, with or without its line ending.
+     * ret - The request, or TakebackError::Parse if the line is not one.
+     */
+    pub fn parse_line(line: &str) -> Result<TakebackRequest, TakebackError> {
+        let fields = fields(line)?;
+        match fields.as_slice() {
+            ["request", side] => Ok(TakebackRequest::Ask(player(side).map_err(TakebackError::Parse)?)),
+            ["accept"] => Ok(TakebackRequest::Accept),
+            ["decline"] => Ok(TakebackRequest::Decline),
+            _ => Err(TakebackError::Parse(String::from(line.trim_end()))),
+        }
+    }
+}
+
+impl TakebackNotice {
+    /**
+     * to_line
+     * ret - The notice as the server sends it, without a line ending.
+     */
+    pub fn to_line(&self) -> String {
+        match self {
+            TakebackNotice::Requested(player) => format!("{}\trequested\t{}", TAKEBACK_WORD, side(*player)),
+            TakebackNotice::Taken { player, moves } => format!("{}\ttaken\t{}\t{}", TAKEBACK_WORD, side(*player), moves),
+            TakebackNotice::Declined(player) => format!("{}\tdeclined\t{}", TAKEBACK_WORD, side(*player)),
+        }
+    }
+
+    /**
+     * parse_line
+     * Read a takeback notice sent by the server.
+     * line - Line received, with or without its line ending.
+     * ret - The notice, or TakebackError::Parse if the line is not one.
+     */
+    pub fn parse_line(line: &str) -> Result<TakebackNotice, TakebackError> {
+        let fields = fields(line)?;
+        let notice = match fields.as_slice() {
+            ["requested", side] => player(side).map(TakebackNotice::Requested),
+            ["taken", side, moves] => player(side).and_then(|player| Ok(TakebackNotice::Taken { player, moves: number(moves)? })),
+            ["declined", side] => player(side).map(TakebackNotice::Declined),
+            _ => return Err(TakebackError::Parse(String::from(line.trim_end()))),
+        };
+        notice.map_err(TakebackError::Parse)
+    }
+}
+
+/**
+ * Negotiation is the takeback request of one game waiting for an answer, if any.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Negotiation {
+    // the side that asked, and the length of the game's history when it did
+    pending: Option<(PlayerId, usize)>,
+}
+
+impl Negotiation {
+    /**
+     * pending
+     * ret - The side waiting for an answer, or None.
+     */
+    pub fn pending(&self) -> Option<PlayerId> {
+        self.pending.map(|(player, _)| player)
+    }
+
+    /**
+     * handle
+     * Act on a takeback message from a player of the game.
+     * seats - Who plays the game.
+     * profile - Authenticated profile that sent the message.
+     * request - What they sent.
+     * game - Game the takeback is for.
+     * ret - The notice to send both players, or a TakebackError saying why the message is
+     * refused.
+     */
+    pub fn handle(&mut self, seats: &Seats, profile: &Profile, request: TakebackRequest, game: &mut Game) -> Result<TakebackNotice, TakebackError> {
+        match request {
+            TakebackRequest::Ask(player) => self.ask(seats, profile, player, game),
+            TakebackRequest::Accept => self.answer(seats, profile, true, game),
+            TakebackRequest::Decline => self.answer(seats, profile, false, game),
+        }
+    }
+
+    /**
+     * ask
+     * Ask the other side to agree to taking back a side's last turn.
+     * seats - Who plays the game.
+     * profile - Authenticated profile asking.
+     * player - Side whose last turn is to be taken back.
+     * game - Game the takeback is for.
+     * ret - TakebackNotice::Requested, or a TakebackError if the profile does not play the
+     * side, a request is already waiting, or the side has not moved.
+     */
+    pub fn ask(&mut self, seats: &Seats, profile: &Profile, player: PlayerId, game: &Game) -> Result<TakebackNotice, TakebackError> {
+        seats.check_move(profile, player)?;
+        if let Some(waiting) = self.pending() {
+            return Err(TakebackError::Pending(waiting));
+        }
+        if !game.history().iter().any(|(mover, _)| *mover == player) {
+            return Err(TakebackError::NothingToTakeBack(player));
+        }
+        self.pending = Some((player, game.history().len()));
+        Ok(TakebackNotice::Requested(player))
+    }
+
+    /**
+     * answer
+     * Answer the request waiting, taking the turn back if it is accepted. A request made
+     * before the last move was played is declined whatever the answer.
+     * seats - Who plays the game.
+     * profile - Authenticated profile answering, who must play the side that did not ask.
+     * accept - True to agree to the takeback.
+     * game - Game the takeback is for.
+     * ret - TakebackNotice::Taken or TakebackNotice::Declined, or a TakebackError if no
+     * request is waiting or the profile may not answer it.
+     */
+    pub fn answer(&mut self, seats: &Seats, profile: &Profile, accept: bool, game: &mut Game) -> Result<TakebackNotice, TakebackError> {
+        let Some((player, asked_at)) = self.pending else {
+            return Err(TakebackError::NoRequest);
+        };
+        seats.check_move(profile, player.other())?;
+        self.pending = None;
+        let moves = game.settle_takeback(player, accept && game.history().len() == asked_at);
+        if moves > 0 {
+            Ok(TakebackNotice::Taken { player, moves })
+        } else {
+            Ok(TakebackNotice::Declined(player))
+        }
+    }
+}
+
+/* Helper function writing a side as the wire does. */
+fn side(player: PlayerId) -> &'static str {
+    match player {
+        PlayerId::A => "a",
+        PlayerId::B => "b",
+    }
+}
+
+/* Helper function splitting a takeback line into the fields after its first word. */
+fn fields(line: &str) -> Result<Vec<&str>, TakebackError> {
+    let mut fields = line.trim_end_matches(['\r', '\n']).split('\t');
+    if fields.next() != Some(TAKEBACK_WORD) {
+        return Err(TakebackError::Parse(String::from(line.trim_end())));
+    }
+    Ok(fields.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Replayed;
+    use crate::game::{Player, STARTING_STONES};
+
+    fn profile(name: &str) -> Profile {
+        Profile { name: String::from(name) }
+    }
+
+    /* Helper function playing the first move offered to the side to move. */
+    fn play_first(game: &mut Game) {
+        let player = game.side_to_move();
+        let intent = *game.move_request(player).all_moves().next().unwrap();
+        game.try_apply_move(player, intent).unwrap();
+    }
+
+    #[test]
+    fn negotiate() {
+        let (ada, grace, alan) = (profile("Ada"), profile("Grace Hopper"), profile("Alan"));
+        let mut seats = Seats::default();
+        seats.sit(&ada, PlayerId::A).unwrap();
+        seats.sit(&grace, PlayerId::B).unwrap();
+        let (replayed_a, replayed_b) = (Replayed, Replayed);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &replayed_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &replayed_b);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.set_silent(true);
+        let mut negotiation = Negotiation::default();
+
+        assert!(matches!(negotiation.ask(&seats, &ada, PlayerId::A, &game), Err(TakebackError::NothingToTakeBack(PlayerId::A))));
+        play_first(&mut game);
+        assert!(matches!(negotiation.answer(&seats, &grace, true, &mut game), Err(TakebackError::NoRequest)));
+        assert!(matches!(negotiation.ask(&seats, &grace, PlayerId::A, &game), Err(TakebackError::Seat(AuthError::WrongSide { .. }))));
+
+        // A declined request leaves the game as it was
+        assert_eq!(negotiation.ask(&seats, &ada, PlayerId::A, &game).unwrap(), TakebackNotice::Requested(PlayerId::A));
+        assert!(matches!(negotiation.ask(&seats, &ada, PlayerId::A, &game), Err(TakebackError::Pending(PlayerId::A))));
+        assert!(matches!(negotiation.answer(&seats, &ada, true, &mut game), Err(TakebackError::Seat(AuthError::WrongSide { .. }))));
+        assert!(matches!(negotiation.answer(&seats, &alan, true, &mut game), Err(TakebackError::Seat(AuthError::NotParticipant(_)))));
+        assert_eq!(negotiation.handle(&seats, &grace, TakebackRequest::Decline, &mut game).unwrap(), TakebackNotice::Declined(PlayerId::A));
+        assert_eq!(negotiation.pending(), None);
+        assert_eq!(game.history().len(), 1);
+
+        // An accepted one takes back A's turn and B's reply
+        play_first(&mut game);
+        negotiation.handle(&seats, &ada, TakebackRequest::Ask(PlayerId::A), &mut game).unwrap();
+        assert_eq!(negotiation.answer(&seats, &grace, true, &mut game).unwrap(), TakebackNotice::Taken { player: PlayerId::A, moves: 2 });
+        assert!(game.history().is_empty());
+
+        // A request lapses once another move is played
+        play_first(&mut game);
+        negotiation.ask(&seats, &ada, PlayerId::A, &game).unwrap();
+        play_first(&mut game);
+        assert_eq!(negotiation.answer(&seats, &grace, true, &mut game).unwrap(), TakebackNotice::Declined(PlayerId::A));
+        assert_eq!(game.history().len(), 2);
+    }
+
+    #[test]
+    fn lines() {
+        for request in [TakebackRequest::Ask(PlayerId::B), TakebackRequest::Accept, TakebackRequest::Decline] {
+            assert_eq!(TakebackRequest::parse_line(&format!("{}\r\n", request.to_line())).unwrap(), request);
+        }
+        assert_eq!(TakebackRequest::Ask(PlayerId::A).to_line(), "takeback\trequest\ta");
+        assert!(matches!(TakebackRequest::parse_line("takeback\trequest\tc"), Err(TakebackError::Parse(_))));
+        assert!(matches!(TakebackRequest::parse_line("chat\taccept"), Err(TakebackError::Parse(_))));
+
+        let notices = [
+            TakebackNotice::Requested(PlayerId::A),
+            TakebackNotice::Taken { player: PlayerId::B, moves: 3 },
+            TakebackNotice::Declined(PlayerId::A),
+        ];
+        for notice in notices {
+            assert_eq!(TakebackNotice::parse_line(&format!("{}\n", notice.to_line())).unwrap(), notice);
+        }
+        assert_eq!(notices[1].to_line(), "takeback\ttaken\tb\t3");
+        assert!(matches!(TakebackNotice::parse_line("takeback\ttaken\tb\tall"), Err(TakebackError::Parse(_))));
+        assert!(matches!(TakebackNotice::parse_line("takeback\taccept"), Err(TakebackError::Parse(_))));
+    }
+}
@@ -0,0 +1,167 @@
+/**
+ * Generators of random positions and moves, for property tests and fuzzing here and in
+ * crates built on this one. Enabled by the test-support feature.
+ *
+ * Everything is driven by a caller-supplied random number generator, so a failing case
+ * can be replayed from its seed.
+ */
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::board::{Board, Direction};
+use crate::game::{CheckerHeight, Decide, Game, Intent, MoveRequest, Player, PlayerId, STARTING_STONES};
+use crate::record::GameRecord;
+use crate::vec::Vec2;
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+/* Decider for games that are driven from outside and never asked for a move. */
+struct Driven;
+
+impl Decide for Driven {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        *request.all_moves().next().expect("no moves offered")
+    }
+}
+
+/**
+ * random_board
+ * Play random legal moves from the standard start position.
+ * rng - Source of the moves, and of the seed for the board's dice.
+ * plies - Most moves to play. Fewer are played if the game ends first.
+ * ret - Position reached, with dice seeded from rng.
+ */
+pub fn random_board<R: Rng>(rng: &mut R, plies: usize) -> Board {
+    let (driven_a, driven_b) = (Driven, Driven);
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &driven_a);
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &driven_b);
+    let mut game = Game::new(&mut player_a, &mut player_b);
+    game.set_silent(true);
+    game.board.reseed(rng.gen());
+    for _ in 0..plies {
+        if game.check_for_outcome().is_some() {
+            break;
+        }
+        let player = game.side_to_move();
+        match random_legal_intent(rng, &game, player) {
+            Some(intent) => game.apply_move(player, intent),
+            None => break,
+        }
+    }
+    game.board.clone()
+}
+
+/**
+ * random_legal_intent
+ * Pick one of a player's legal moves uniformly.
+ * rng - Source of the choice.
+ * game - Game to move in.
+ * player - Id of player to move.
+ * ret - A legal move, or None if the player has none.
+ */
+pub fn random_legal_intent<R: Rng>(rng: &mut R, game: &Game, player: PlayerId) -> Option<Intent> {
+    let request = game.move_request(player);
+    let moves: Vec<&Intent> = request.all_moves().collect();
+    moves.choose(rng).map(|intent| **intent)
+}
+
+/**
+ * fought_record
+ * Record a game of random legal moves that attacks whenever it can, so it comes to blows
+ * quickly, for checking that replays roll the dice the record kept.
+ * rng - Source of the moves, and of the seed for the game's dice.
+ * dice - Dice to roll before stopping, unless the game ends first.
+ * ret - Record of the game, with its dice, final position and any result.
+ */
+pub fn fought_record<R: Rng>(rng: &mut R, dice: usize) -> GameRecord {
+    let (driven_a, driven_b) = (Driven, Driven);
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &driven_a);
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &driven_b);
+    let mut game = Game::new(&mut player_a, &mut player_b);
+    game.set_silent(true);
+    game.board.reseed(rng.gen());
+    while game.dice().len() < dice && game.check_for_outcome().is_none() {
+        let player = game.side_to_move();
+        let fire = game.move_request(player).all_moves().find(|intent| matches!(intent, Intent::FireChecker(_))).copied();
+        match fire.or_else(|| random_legal_intent(rng, &game, player)) {
+            Some(intent) => game.apply_move(player, intent),
+            None => break,
+        }
+    }
+    GameRecord::from_game(&game)
+}
+
+/**
+ * random_intent
+ * Make up a move of any kind, legal or not, with positions on the board or just off it.
+ * Meant for checking that bad moves are refused rather than panicking.
+ * rng - Source of the move.
+ * board - Board whose size the positions are drawn around.
+ * ret - Arbitrary move.
+ */
+pub fn random_intent<R: Rng>(rng: &mut R, board: &Board) -> Intent {
+    let pos = |rng: &mut R| Vec2::new(
+        rng.gen_range(-1..=board.width() as i32 + 1),
+        rng.gen_range(-1..=board.height() as i32 + 1),
+    );
+    match rng.gen_range(0..6) {
+        0 => {
+            let from = pos(rng);
+            Intent::MoveChecker(from, pos(rng))
+        },
+        1 => Intent::FireChecker(pos(rng)),
+        2 => Intent::PlaceStone(pos(rng)),
+        3 => Intent::SlideStone(pos(rng), *DIRECTIONS.choose(rng).unwrap()),
+        4 => {
+            let height = CheckerHeight::new(rng.gen_range(0..=CheckerHeight::MAX.get())).unwrap();
+            Intent::PlaceChecker(pos(rng), height)
+        },
+        _ => Intent::SwapSides,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn random_board() {
+        for seed in 0..20 {
+            let board = super::random_board(&mut StdRng::seed_from_u64(seed), 40);
+            assert_eq!(board, super::random_board(&mut StdRng::seed_from_u64(seed), 40));
+            assert_eq!(board.check_invariants(), Ok(()));
+            for player in [PlayerId::A, PlayerId::B] {
+                for pos in board.checkers_for_player(player) {
+                    let checker = board.checker_at(pos).unwrap();
+                    assert_eq!(checker.owner, Some(player));
+                    assert!(!checker.height.is_zero());
+                }
+                for pos in board.stones_for_player(player) {
+                    assert_eq!(board.stone_at(pos).unwrap().owner, Some(player));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn random_intents_are_refused_or_applied() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (driven_a, driven_b) = (Driven, Driven);
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &driven_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &driven_b);
+        let mut game = Game::new(&mut player_a, &mut player_b);
+        game.set_silent(true);
+        for _ in 0..500 {
+            if game.check_for_outcome().is_some() {
+                game.reset();
+            }
+            let player = game.side_to_move();
+            let intent = random_intent(&mut rng, &game.board);
+            let legal = game.move_request(player).all_moves().any(|offered| *offered == intent);
+            assert_eq!(game.try_apply_move(player, intent).is_ok(), legal, "{} by {}", intent, player);
+            assert_eq!(game.board.check_invariants(), Ok(()));
+        }
+    }
+}
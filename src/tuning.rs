@@ -0,0 +1,283 @@
+/**
+ * Texel-style tuning of evaluation weights: every position of a corpus of recorded games is
+ * labelled with the game's result, and the weights are adjusted until a logistic curve over
+ * the evaluation predicts those results as closely as it can.
+ *
+ * The prediction for a position is 1 / (1 + e^(-scale * evaluation)), for player A, and the
+ * error is the mean squared difference from the results, counting a win as 1, a draw as 0.5
+ * and a loss as 0. The scale is fitted to the starting weights first and then held, since
+ * scaling every weight alike would otherwise only change the scale.
+ */
+use crate::board::Board;
+use crate::game::PlayerId;
+use crate::record::{notation, GameRecord, GameResult};
+use crate::review::{start_board, ReviewError};
+use crate::scoring::{features, Evaluator, FeatureName};
+
+// smallest and largest scale tried when fitting the scale
+const MIN_SCALE: f64 = 1e-4;
+const MAX_SCALE: f64 = 10.0;
+// rounds of golden section search fitting the scale
+const SCALE_ROUNDS: usize = 60;
+
+/**
+ * TuningPosition is one position of the corpus, as its unweighted features and the result
+ * of its game, both for player A.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TuningPosition {
+    // feature values in the order of FeatureName::ALL
+    pub features: [f32; 4],
+    // 1 for a win, 0.5 for a draw and 0 for a loss
+    pub result: f32,
+}
+
+/**
+ * TuningSet is the positions weights are tuned against.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TuningSet {
+    positions: Vec<TuningPosition>,
+}
+
+impl TuningSet {
+    /**
+     * build
+     * Collect the positions of a corpus of records.
+     * records - Games to learn from.
+     * ret - The positions, or ReviewError::Replay if a record holds an illegal move.
+     */
+    pub fn build(records: &[GameRecord]) -> Result<TuningSet, ReviewError> {
+        let mut set = TuningSet::default();
+        for record in records {
+            set.add(record)?;
+        }
+        Ok(set)
+    }
+
+    /**
+     * add
+     * Collect the position after each move of one more record.
+     * record - Game to learn from. Records without a result and variations are ignored.
+     * ret - Ok, or ReviewError::Replay if the record holds an illegal move. Positions before
+     * the illegal move are still collected.
+     */
+    pub fn add(&mut self, record: &GameRecord) -> Result<(), ReviewError> {
+        let result = match record.result {
+            Some(GameResult::Win(PlayerId::A)) => 1.0,
+            Some(GameResult::Win(PlayerId::B)) => 0.0,
+            Some(GameResult::Draw) => 0.5,
+            None => return Ok(()),
+        };
+        let mut board = start_board(record);
+        for (idx, recorded) in record.moves.iter().enumerate() {
+            board.apply(recorded.player, recorded.intent).map_err(|source| ReviewError::Replay {
+                ply: idx + 1,
+                notation: notation(recorded.intent),
+                source,
+            })?;
+            self.push(&board, result);
+        }
+        Ok(())
+    }
+
+    /**
+     * push
+     * Add one position.
+     * board - The position.
+     * result - Result of its game for player A: 1 for a win, 0.5 for a draw and 0 for a loss.
+     */
+    pub fn push(&mut self, board: &Board, result: f32) {
+        let mut measured = [0.0; 4];
+        for (slot, (_, value)) in measured.iter_mut().zip(features(board, PlayerId::A)) {
+            *slot = value;
+        }
+        self.positions.push(TuningPosition { features: measured, result });
+    }
+
+    /**
+     * positions
+     * ret - Every position collected, in the order they were added.
+     */
+    pub fn positions(&self) -> &[TuningPosition] {
+        &self.positions
+    }
+
+    /**
+     * error
+     * Measure how well weights predict the results.
+     * evaluator - Weights to measure.
+     * scale - Scale of the logistic curve.
+     * ret - Mean squared difference between predictions and results, or 0 with no positions.
+     */
+    pub fn error(&self, evaluator: &Evaluator, scale: f64) -> f64 {
+        if self.positions.is_empty() {
+            return 0.0;
+        }
+        let weights = FeatureName::ALL.map(|feature| evaluator.weight(feature) as f64);
+        let total: f64 = self.positions.iter()
+            .map(|position| {
+                let evaluation: f64 = position.features.iter().zip(weights).map(|(value, weight)| *value as f64 * weight).sum();
+                let predicted = 1.0 / (1.0 + (-scale * evaluation).exp());
+                (position.result as f64 - predicted).powi(2)
+            })
+            .sum();
+        total / self.positions.len() as f64
+    }
+
+    /**
+     * fit_scale
+     * Find the scale of the logistic curve at which weights best predict the results.
+     * evaluator - Weights to fit the scale to.
+     * ret - The scale, between MIN_SCALE and MAX_SCALE.
+     */
+    pub fn fit_scale(&self, evaluator: &Evaluator) -> f64 {
+        // golden section search on the logarithm of the scale
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let error = |log_scale: f64| self.error(evaluator, log_scale.exp());
+        let (mut low, mut high) = (MIN_SCALE.ln(), MAX_SCALE.ln());
+        for _ in 0..SCALE_ROUNDS {
+            let left = high - ratio * (high - low);
+            let right = low + ratio * (high - low);
+            if error(left) < error(right) {
+                high = right;
+            } else {
+                low = left;
+            }
+        }
+        ((low + high) / 2.0).exp()
+    }
+}
+
+/**
+ * Tuner is how weights are searched for: each round tries moving every weight up and down by
+ * the step, keeping any move that lowers the error, and the step halves after a round with
+ * no improvement.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tuner {
+    // first change tried to each weight
+    pub step: f32,
+    // step below which tuning stops
+    pub min_step: f32,
+    // rounds after which tuning stops, improving or not
+    pub max_rounds: usize,
+}
+
+impl Default for Tuner {
+    fn default() -> Tuner {
+        Tuner { step: 1.0, min_step: 0.01, max_rounds: 1000 }
+    }
+}
+
+/**
+ * TuningReport is the outcome of tuning.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TuningReport {
+    pub evaluator: Evaluator,
+    // scale of the logistic curve, fitted to the starting weights
+    pub scale: f64,
+    // error of the starting weights and of the tuned ones
+    pub initial_error: f64,
+    pub error: f64,
+    pub rounds: usize,
+}
+
+impl Tuner {
+    /**
+     * tune
+     * Search for the weights that best predict the results of a set of positions.
+     * start - Weights to start from.
+     * set - Positions to tune against.
+     * ret - The tuned weights, with the errors before and after.
+     */
+    pub fn tune(&self, start: &Evaluator, set: &TuningSet) -> TuningReport {
+        let scale = set.fit_scale(start);
+        let initial_error = set.error(start, scale);
+        let (mut evaluator, mut error) = (*start, initial_error);
+        let mut step = self.step;
+        let mut rounds = 0;
+        while step >= self.min_step && rounds < self.max_rounds {
+            rounds += 1;
+            let mut improved = false;
+            for feature in FeatureName::ALL {
+                let weight = evaluator.weight(feature);
+                for candidate in [weight + step, weight - step] {
+                    let mut tried = evaluator;
+                    tried.set_weight(feature, candidate);
+                    let tried_error = set.error(&tried, scale);
+                    if tried_error < error {
+                        (evaluator, error, improved) = (tried, tried_error, true);
+                        break;
+                    }
+                }
+            }
+            if !improved {
+                step /= 2.0;
+            }
+        }
+        TuningReport { evaluator, scale, initial_error, error, rounds }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Checker, Intent};
+    use crate::test_support::fought_record;
+    use crate::vec::Vec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn build() {
+        let records: Vec<GameRecord> = [
+            "result a\na move 6,2 5,2\nb place 4,0",
+            "a place 4,4\nb place 4,0",
+        ].iter().map(|text| GameRecord::parse(text).unwrap()).collect();
+        let set = TuningSet::build(&records).unwrap();
+        assert_eq!(set.positions().len(), 2);
+        assert!(set.positions().iter().all(|position| position.result == 1.0));
+
+        let mut after = Board::new();
+        after.apply(PlayerId::A, Intent::MoveChecker(Vec2::new(6, 2), Vec2::new(5, 2))).unwrap();
+        assert_eq!(set.positions()[0].features[1], features(&after, PlayerId::A)[1].1);
+
+        let illegal = GameRecord::parse("result b\na move 6,2 5,2\na place 9,9").unwrap();
+        assert!(matches!(TuningSet::build(&[illegal]), Err(ReviewError::Replay { ply: 2, .. })));
+    }
+
+    #[test]
+    fn fought() {
+        let mut record = fought_record(&mut StdRng::seed_from_u64(7), 12);
+        record.result = Some(GameResult::Win(PlayerId::B));
+        // Every move of the game replays, attacks and all, giving a position each marked lost
+        let set = TuningSet::build(std::slice::from_ref(&record)).unwrap();
+        assert_eq!(set.positions().len(), record.moves.len());
+        assert!(set.positions().iter().all(|position| position.result == 0.0));
+    }
+
+    #[test]
+    fn tune() {
+        // Whoever has more material wins, whatever else the position holds
+        let mut set = TuningSet::default();
+        let start = Board::new();
+        let (a, b) = (start.checkers_for_player(PlayerId::A), start.checkers_for_player(PlayerId::B));
+        for (idx, pos) in [a[0], b[0], a[a.len() - 1], b[b.len() - 1]].into_iter().enumerate() {
+            let mut board = Board::new();
+            let owner = board.checker_at(pos).unwrap().owner;
+            board.place_checker_at(pos, Checker::empty()).unwrap();
+            let result = if owner == Some(PlayerId::A) { 0.0 } else { 1.0 };
+            for _ in 0..=idx {
+                set.push(&board, result);
+            }
+        }
+        let start = Evaluator { material: 0.1, advancement: 5.0, chain_progress: 5.0, safety: 5.0 };
+        let report = Tuner::default().tune(&start, &set);
+        assert!(report.error < report.initial_error);
+        assert!(report.evaluator.material > start.material);
+        assert!(report.scale >= MIN_SCALE && report.scale <= MAX_SCALE);
+        assert_eq!(TuningSet::default().error(&start, 1.0), 0.0);
+    }
+}
@@ -0,0 +1,154 @@
+/**
+ * Verification replays a record through a full game under today's rules, rolling the dice
+ * the record kept, and checks that it ends where the record says it did: in the recorded
+ * position, having used every recorded die, and with the recorded result. A rule change
+ * that alters how recorded games play out shows up as a divergence.
+ *
+ * Results decided off the board, by resignation, time or an arbiter, can not be replayed,
+ * so a recorded result is only checked when the replay reaches a result of its own.
+ */
+use std::fmt;
+
+use crate::analysis::Replayed;
+use crate::config::GameConfig;
+use crate::game::{ApplyError, Game, Player, PlayerId, STARTING_STONES};
+use crate::record::{notation, GameRecord, GameResult};
+
+/**
+ * Divergence is one way a replay differed from its record.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Divergence {
+    // A recorded move could not be made, with the ply of the move counting from 1; the
+    // replay stops there
+    Move { ply: usize, notation: String, source: ApplyError },
+    // The replay rolled a different number of dice than the record kept
+    Dice { recorded: usize, rolled: usize },
+    // The replay ended in another position
+    Position { recorded: u64, replayed: u64 },
+    // The replay reached another result
+    Result { recorded: Option<GameResult>, replayed: GameResult },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result = |result: &Option<GameResult>| match result {
+            Some(GameResult::Win(player)) => format!("player {} wins", player),
+            Some(GameResult::Draw) => String::from("draw"),
+            None => String::from("no result"),
+        };
+        match self {
+            Divergence::Move { ply, notation, source } => write!(f, "Move {} ({}) could not be replayed: {}", ply, notation, source),
+            Divergence::Dice { recorded, rolled } => write!(f, "The record kept {} dice, but the replay rolled {}", recorded, rolled),
+            Divergence::Position { recorded, replayed } => {
+                write!(f, "The replay ended in position {:016x}, not the recorded {:016x}", replayed, recorded)
+            },
+            Divergence::Result { recorded, replayed } => {
+                write!(f, "The replay ended in {}, not the recorded {}", result(&Some(*replayed)), result(recorded))
+            },
+        }
+    }
+}
+
+/**
+ * verify
+ * Replay a record's main line and compare where it ends with what the record says.
+ * record - Game to check. Its variations are ignored.
+ * ret - Every divergence found, none if the record replays as recorded.
+ */
+pub fn verify(record: &GameRecord) -> Vec<Divergence> {
+    with_replay(record, |game| diverges(record, game))
+}
+
+/**
+ * with_replay
+ * Set up a game to replay a record in as it was played: the record's variant, setup and
+ * first player, with the dice the record kept scripted for its attacks. Every replay of a
+ * recorded game starts here, so its fights go the way they went.
+ * record - Game to replay.
+ * action - Run on the silent game, which is at the start of the record.
+ * ret - What action returns.
+ */
+pub(crate) fn with_replay<T>(record: &GameRecord, action: impl FnOnce(&mut Game) -> T) -> T {
+    let config = GameConfig { free_setup: record.free_setup, first_player: record.first, ..GameConfig::for_variant(record.variant) };
+    let (replayed_a, replayed_b) = (Replayed, Replayed);
+    let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &replayed_a);
+    let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &replayed_b);
+    let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+    game.set_silent(true);
+    game.board.script_dice(&record.dice);
+    action(&mut game)
+}
+
+/* Helper function replaying the main line of @record in @game and listing how it diverged. */
+fn diverges(record: &GameRecord, game: &mut Game) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    for (idx, recorded) in record.moves.iter().enumerate() {
+        if let Err(source) = game.try_apply_move(recorded.player, recorded.intent) {
+            divergences.push(Divergence::Move { ply: idx + 1, notation: notation(recorded.intent), source });
+            return divergences;
+        }
+    }
+    if game.dice().len() != record.dice.len() {
+        divergences.push(Divergence::Dice { recorded: record.dice.len(), rolled: game.dice().len() });
+    }
+    let replayed = game.board.position_hash();
+    if let Some(recorded) = record.position.filter(|recorded| *recorded != replayed) {
+        divergences.push(Divergence::Position { recorded, replayed });
+    }
+    if let Some(replayed) = game.check_for_outcome().map(GameResult::from).filter(|replayed| record.result != Some(*replayed)) {
+        divergences.push(Divergence::Result { recorded: record.result, replayed });
+    }
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Intent;
+    use crate::test_support::fought_record;
+    use crate::vec::Vec2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn verify() {
+        let record = fought_record(&mut StdRng::seed_from_u64(7), 4);
+        assert!(record.dice.len() >= 4);
+        assert_eq!(super::verify(&record), vec![]);
+        assert_eq!(super::verify(&GameRecord::parse(&record.to_string()).unwrap()), vec![]);
+
+        let mut lost_dice = record.clone();
+        lost_dice.dice.clear();
+        // Rolled afresh, the fights go their own way, if the moves can still be made at all
+        assert!(!super::verify(&lost_dice).is_empty());
+
+        let mut moved = record.clone();
+        moved.position = Some(1);
+        let replayed = record.position.unwrap();
+        assert_eq!(super::verify(&moved), vec![Divergence::Position { recorded: 1, replayed }]);
+
+        let mut illegal = record.clone();
+        illegal.moves[0].intent = Intent::PlaceStone(Vec2::new(-1, 0));
+        assert!(matches!(super::verify(&illegal)[..], [Divergence::Move { ply: 1, .. }]));
+    }
+
+    #[test]
+    fn with_replay() {
+        let record = fought_record(&mut StdRng::seed_from_u64(7), 12);
+        assert!(record.dice.len() >= 12);
+        let end = |record: &GameRecord| super::with_replay(record, |game| {
+            for recorded in record.moves.iter() {
+                game.try_apply_move(recorded.player, recorded.intent).ok()?;
+            }
+            Some(game.board.position_hash())
+        });
+        // Every attack rolls the dice the record kept, so the game ends where it did
+        assert_eq!(end(&record), record.position);
+
+        // Rolled afresh, the fights go another way, if the moves can still be made at all
+        let mut lost_dice = record.clone();
+        lost_dice.dice.clear();
+        assert_ne!(end(&lost_dice), record.position);
+    }
+}
@@ -0,0 +1,228 @@
+/**
+ * Win probability from the features of a position: a logistic model over the same features
+ * the Evaluator weighs, fit on the outcomes of self-play games. Used to show how a game
+ * stands as a probability rather than a score, and for deciding when a game is lost.
+ *
+ * The model has no intercept. Features are measured as the player's less the opponent's,
+ * so the probabilities the two players get for a position always add up to one.
+ */
+use std::cell::RefCell;
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::board::Board;
+use crate::config::GameConfig;
+use crate::game::{Decide, Game, Intent, MoveRequest, Player, PlayerId, STARTING_STONES};
+use crate::matches::GameSummary;
+use crate::scoring::{self, Evaluator};
+
+// weights of the default model, from fit(&self_play(&mut StdRng::seed_from_u64(0), 200, 120), 20000, 0.001)
+const DEFAULT_WEIGHTS: [f32; 4] = [0.0061, 0.0875, 0.1827, -0.0564];
+
+/**
+ * Sample is one position from a finished game, seen from one player's side.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    // unweighted features of the position, in the order scoring::features gives them
+    pub features: Vec<f32>,
+    // whether the player went on to win
+    pub won: bool,
+}
+
+/**
+ * WinModel turns the features of a position into the chance the player wins from it.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct WinModel {
+    // one weight per feature, in the order scoring::features gives them
+    pub weights: Vec<f32>,
+}
+
+impl Default for WinModel {
+    fn default() -> WinModel {
+        WinModel { weights: DEFAULT_WEIGHTS.to_vec() }
+    }
+}
+
+impl WinModel {
+    /**
+     * fit
+     * Fit a model to samples by gradient descent on the log loss, starting from zero weights.
+     * samples - Positions with their outcomes.
+     * epochs - Number of passes over the samples.
+     * rate - Step size of each pass.
+     * ret - The fitted model. With no samples every weight stays zero.
+     */
+    pub fn fit(samples: &[Sample], epochs: usize, rate: f32) -> WinModel {
+        let features = samples.first().map_or(DEFAULT_WEIGHTS.len(), |sample| sample.features.len());
+        let mut model = WinModel { weights: vec![0.0; features] };
+        if samples.is_empty() {
+            return model;
+        }
+        for _ in 0..epochs {
+            let mut gradient = vec![0.0; features];
+            for sample in samples {
+                let error = model.predict(&sample.features) - f32::from(u8::from(sample.won));
+                for (slope, value) in gradient.iter_mut().zip(sample.features.iter()) {
+                    *slope += error * value;
+                }
+            }
+            for (weight, slope) in model.weights.iter_mut().zip(gradient) {
+                *weight -= rate * slope / samples.len() as f32;
+            }
+        }
+        model
+    }
+
+    /**
+     * probability
+     * Estimate the chance a player wins from a position.
+     * board - Position to judge.
+     * player - Id of player to judge for.
+     * ret - Probability from 0 to 1.
+     */
+    pub fn probability(&self, board: &Board, player: PlayerId) -> f32 {
+        let features: Vec<f32> = scoring::features(board, player).into_iter().map(|(_, value)| value).collect();
+        self.predict(&features)
+    }
+
+    /**
+     * predict
+     * Estimate the chance of a win from features already measured.
+     * features - Unweighted features, in the order scoring::features gives them.
+     * ret - Probability from 0 to 1.
+     */
+    pub fn predict(&self, features: &[f32]) -> f32 {
+        let logit: f32 = self.weights.iter().zip(features.iter()).map(|(weight, value)| weight * value).sum();
+        1.0 / (1.0 + (-logit).exp())
+    }
+}
+
+/**
+ * win_probability
+ * Estimate the chance a player wins from a position, with the default model.
+ * board - Position to judge.
+ * player - Id of player to judge for.
+ * ret - Probability from 0 to 1.
+ */
+pub fn win_probability(board: &Board, player: PlayerId) -> f32 {
+    WinModel::default().probability(board, player)
+}
+
+/* Decider for self-play: mostly the move the default Evaluator likes best, sometimes any move. */
+struct SelfPlay {
+    rng: RefCell<StdRng>,
+    evaluator: Evaluator,
+}
+
+impl Decide for SelfPlay {
+    fn choose_move(&self, request: &MoveRequest) -> Intent {
+        let moves: Vec<Intent> = request.all_moves().copied().collect();
+        let mut rng = self.rng.borrow_mut();
+        // one move in four is random, so games do not all go the same way
+        if rng.gen_range(0..4) == 0 {
+            return *moves.choose(&mut *rng).expect("no moves offered");
+        }
+        let player = request.player;
+        let score = |intent: &Intent| {
+            let mut board = request.view.board.clone();
+            match board.apply(player, *intent) {
+                Ok(()) => self.evaluator.evaluate(&board, player),
+                Err(_) => f32::MIN,
+            }
+        };
+        *moves.iter().max_by(|x, y| score(x).total_cmp(&score(y))).expect("no moves offered")
+    }
+}
+
+/**
+ * self_play
+ * Play games of the standard variant between two self-play deciders, and collect every
+ * position from both sides with the outcome for that side. Drawn games give no samples.
+ * rng - Source of the deciders' randomness and the board's dice.
+ * games - Number of games to play.
+ * move_limit - Moves after which a game is called a draw.
+ * ret - Samples for fit, and a summary of every game played to the end, drawn or not.
+ */
+pub fn self_play<R: Rng>(rng: &mut R, games: usize, move_limit: usize) -> (Vec<Sample>, Vec<GameSummary>) {
+    let (mut samples, mut summaries) = (Vec::new(), Vec::new());
+    for _ in 0..games {
+        let seeds: Vec<[u8; 32]> = (0..3).map(|_| rng.gen()).collect();
+        let decide_a = SelfPlay { rng: RefCell::new(StdRng::from_seed(seeds[1])), evaluator: Evaluator::default() };
+        let decide_b = SelfPlay { rng: RefCell::new(StdRng::from_seed(seeds[2])), evaluator: Evaluator::default() };
+        let mut player_a = Player::new(PlayerId::A, STARTING_STONES, &decide_a);
+        let mut player_b = Player::new(PlayerId::B, STARTING_STONES, &decide_b);
+        let config = GameConfig { move_limit: Some(move_limit), ..GameConfig::default() };
+        let mut game = Game::with_config(&mut player_a, &mut player_b, config);
+        game.set_silent(true);
+        game.board.reseed(seeds[0]);
+
+        let mut positions = Vec::new();
+        let started = Instant::now();
+        let outcome = loop {
+            match game.play_turn() {
+                Ok(None) => positions.push(scoring::features(&game.board, PlayerId::A)),
+                Ok(Some(outcome)) => break Some(outcome),
+                Err(_) => break None,
+            }
+        };
+        let Some(outcome) = outcome else {
+            continue;
+        };
+        let summary = GameSummary::of(&game, outcome, started, seeds);
+        let winner = summary.winner();
+        summaries.push(summary);
+        let Some(winner) = winner else {
+            continue;
+        };
+        for position in positions {
+            let features: Vec<f32> = position.into_iter().map(|(_, value)| value).collect();
+            // every feature changes sign when seen from the other side
+            samples.push(Sample { features: features.iter().map(|value| -value).collect(), won: winner == PlayerId::B });
+            samples.push(Sample { features, won: winner == PlayerId::A });
+        }
+    }
+    (samples, summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Checker;
+    use crate::vec::Vec2;
+
+    #[test]
+    fn win_probability() {
+        let mut board = Board::new();
+        assert_eq!(super::win_probability(&board, PlayerId::A), 0.5);
+        board.place_checker_at(Vec2::new(1, 2), Checker::empty()).unwrap();
+        let a = super::win_probability(&board, PlayerId::A);
+        assert!(a > 0.5);
+        assert!((a + super::win_probability(&board, PlayerId::B) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fit() {
+        let (samples, games) = self_play(&mut StdRng::seed_from_u64(1), 4, 60);
+        assert!(samples.chunks(2).all(|pair| pair[0].won != pair[1].won));
+        assert!(games.iter().all(|game| game.moves > 0 && game.seeds.len() == 3));
+        let model = WinModel::fit(&samples, 200, 0.001);
+        assert_eq!(model.weights.len(), scoring::features(&Board::new(), PlayerId::A).len());
+
+        // The fitted model is better than a coin toss on the games it was fit on
+        let loss = |model: &WinModel| samples.iter()
+            .map(|sample| {
+                let p = model.predict(&sample.features).clamp(1e-6, 1.0 - 1e-6);
+                if sample.won { -p.ln() } else { -(1.0 - p).ln() }
+            })
+            .sum::<f32>();
+        if !samples.is_empty() {
+            assert!(loss(&model) < loss(&WinModel::fit(&samples, 0, 0.0)));
+        }
+        assert_eq!(WinModel::fit(&[], 10, 0.1).weights, vec![0.0; 4]);
+    }
+}